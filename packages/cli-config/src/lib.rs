@@ -69,6 +69,7 @@ pub const APP_TITLE_ENV: &str = "DIOXUS_APP_TITLE";
 #[doc(hidden)]
 pub const OUT_DIR: &str = "DIOXUS_OUT_DIR";
 pub const SESSION_CACHE_DIR: &str = "DIOXUS_SESSION_CACHE_DIR";
+pub const STATIC_GENERATION_ENV: &str = "DIOXUS_STATIC_GENERATION";
 
 /// Reads an environment variable at runtime in debug mode or at compile time in
 /// release mode. When bundling in release mode, we will not be running under the
@@ -211,6 +212,14 @@ pub fn is_cli_enabled() -> bool {
     std::env::var(CLI_ENABLED_ENV).is_ok()
 }
 
+/// Check if the CLI is running the server to pre-render a static site.
+///
+/// This is read at runtime because the same server executable is used to pre-render the site and
+/// to serve the app.
+pub fn is_static_generation() -> bool {
+    std::env::var(STATIC_GENERATION_ENV).is_ok()
+}
+
 /// Get the path where the application will be served from.
 ///
 /// This is used by the router to format the URLs. For example, an app with a base path of `dogapp` will
@@ -817,10 +817,15 @@ impl AppBundle {
             return Ok(());
         }
         self.build.status_prerendering_routes();
+        let out_dir = match &self.build.build.ssg_out_dir {
+            Some(out_dir) => self.build.krate.crate_dir().join(out_dir),
+            None => self.build.krate.crate_dir().join("out"),
+        };
         pre_render_static_routes(
             &self
                 .server_exe()
                 .context("Failed to find server executable")?,
+            &out_dir,
         )
         .await?;
         Ok(())
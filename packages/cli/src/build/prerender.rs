@@ -2,14 +2,19 @@ use anyhow::Context;
 use dioxus_cli_config::{server_ip, server_port};
 use futures_util::stream::FuturesUnordered;
 use futures_util::StreamExt;
+use reqwest::StatusCode;
 use std::{
     net::{IpAddr, Ipv4Addr, SocketAddr},
-    path::Path,
-    time::Duration,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime},
 };
 use tokio::process::Command;
 
-pub(crate) async fn pre_render_static_routes(server_exe: &Path) -> anyhow::Result<()> {
+/// Render every static route of the app and write a self-contained static site to `out_dir`.
+pub(crate) async fn pre_render_static_routes(
+    server_exe: &Path,
+    out_dir: &Path,
+) -> anyhow::Result<()> {
     // Use the address passed in through environment variables or default to localhost:9999. We need
     // to default to a value that is different than the CLI default address to avoid conflicts
     let ip = server_ip().unwrap_or_else(|| IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)));
@@ -23,10 +28,15 @@ pub(crate) async fn pre_render_static_routes(server_exe: &Path) -> anyhow::Resul
 
     tracing::info!("Running SSG at http://{address}:{port}");
 
+    // Pages the app caches to the disk while we render are newer than this
+    let started = SystemTime::now();
+
     // Run the server executable
     let _child = Command::new(server_exe)
         .env(dioxus_cli_config::SERVER_PORT_ENV, port)
         .env(dioxus_cli_config::SERVER_IP_ENV, address)
+        // Render every page completely instead of streaming it
+        .env(dioxus_cli_config::STATIC_GENERATION_ENV, "true")
         .stdout(std::process::Stdio::piped())
         .stderr(std::process::Stdio::piped())
         .kill_on_drop(true)
@@ -89,28 +99,255 @@ pub(crate) async fn pre_render_static_routes(server_exe: &Path) -> anyhow::Resul
                     tracing::warn!("Route {route} has been rendering for 30 seconds");
                 }
             });
-            // Wait for the streaming response to completely finish before continuing. We don't use the html it returns directly
-            // because it may contain artifacts of intermediate streaming steps while the page is loading. The SSG app should write
-            // the final clean HTML to the disk automatically after the request completes.
-            let _html = request.text().await?;
+            // Wait for the response to completely finish before continuing. The html is only used if the SSG app doesn't
+            // write the final HTML to the disk after the request completes.
+            let status = request.status();
+            let html = request.text().await?;
 
             // Cancel the warning task if it hasn't already run
             warning_task.abort();
 
-            Ok::<_, reqwest::Error>(route)
+            Ok::<_, reqwest::Error>(RenderedPage {
+                route,
+                status,
+                html,
+            })
         })
         .collect::<FuturesUnordered<_>>();
 
+    let mut rendered = Vec::new();
     while let Some(route) = resolved_routes.next().await {
         match route {
-            Ok(route) => tracing::debug!("ssg success: {route:?}"),
+            Ok(page) => {
+                tracing::debug!("ssg rendered: {:?} ({})", page.route, page.status);
+                rendered.push(page);
+            }
             Err(err) => tracing::error!("ssg error: {err:?}"),
         }
     }
 
-    tracing::info!("SSG complete");
+    // Emit the generated sitemap.xml and robots.txt files next to the pre-rendered pages if the app serves them
+    let public_dir = server_exe
+        .parent()
+        .context("Server executable has no parent directory")?
+        .join("public");
+    for file_name in ["sitemap.xml", "robots.txt"] {
+        let response = reqwest_client
+            .get(format!("http://{address}:{port}/{file_name}"))
+            .send()
+            .await;
+        match response {
+            Ok(response) if response.status().is_success() => {
+                let contents = response.bytes().await?;
+                std::fs::write(public_dir.join(file_name), contents)
+                    .with_context(|| format!("Failed to write {file_name} for SSG"))?;
+                tracing::info!("Wrote {file_name} for SSG");
+            }
+            Ok(response) => {
+                tracing::debug!("Skipping {file_name} for SSG: {}", response.status())
+            }
+            Err(err) => tracing::debug!("Skipping {file_name} for SSG: {err}"),
+        }
+    }
+
+    // Render a route that doesn't exist for the 404 page. The app renders its not found page with a 404 status
+    let not_found = reqwest_client
+        .get(format!("http://{address}:{port}/__dioxus_ssg_not_found"))
+        .header("Accept", "text/html")
+        .send()
+        .await;
+    let not_found = match not_found {
+        Ok(response) => {
+            let status = response.status();
+            response.text().await.ok().map(|html| RenderedPage {
+                route: "/404.html".to_string(),
+                status,
+                html,
+            })
+        }
+        Err(err) => {
+            tracing::debug!("Skipping 404.html for SSG: {err}");
+            None
+        }
+    };
+
+    write_static_site(&public_dir, out_dir, started, &rendered, not_found)?;
+
+    tracing::info!("SSG complete. Wrote static site to {}", out_dir.display());
 
     drop(_child);
 
     Ok(())
 }
+
+/// The response of the server for a route
+struct RenderedPage {
+    route: String,
+    status: StatusCode,
+    html: String,
+}
+
+/// Write the public directory of the server and the html of every rendered route into `out_dir`. Pages
+/// are only written if the server rendered them successfully and `404.html` is only written if the
+/// server rendered the not found page with a 404 status.
+fn write_static_site(
+    public_dir: &Path,
+    out_dir: &Path,
+    started: SystemTime,
+    rendered: &[RenderedPage],
+    not_found: Option<RenderedPage>,
+) -> anyhow::Result<()> {
+    // Copy the hashed assets, sitemap and any pages the app cached to the disk
+    std::fs::create_dir_all(out_dir).with_context(|| {
+        format!(
+            "Failed to create SSG output directory {}",
+            out_dir.display()
+        )
+    })?;
+    dircpy::CopyBuilder::new(public_dir, out_dir)
+        .overwrite(true)
+        .run_par()
+        .context("Failed to copy the public directory to the SSG output directory")?;
+
+    for RenderedPage {
+        route,
+        status,
+        html,
+    } in rendered
+    {
+        if !status.is_success() {
+            tracing::error!("Skipping {route} for SSG: the server responded with {status}");
+            continue;
+        }
+
+        // Prefer the clean html the app cached during this run over the streamed response
+        let cached = route_html_path(public_dir, route);
+        let cached_this_run = std::fs::metadata(&cached)
+            .and_then(|metadata| metadata.modified())
+            .is_ok_and(|modified| modified >= started);
+        if cached_this_run {
+            continue;
+        }
+
+        let path = route_html_path(out_dir, route);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&path, html)
+            .with_context(|| format!("Failed to write {} for SSG", path.display()))?;
+    }
+
+    match not_found {
+        Some(page) if page.status == StatusCode::NOT_FOUND => {
+            std::fs::write(out_dir.join("404.html"), page.html)
+                .context("Failed to write 404.html for SSG")?;
+        }
+        Some(page) => tracing::warn!(
+            "Skipping 404.html for SSG: the server responded to a missing route with {}",
+            page.status
+        ),
+        None => {}
+    }
+
+    Ok(())
+}
+
+/// Get the path of the `index.html` file for a route in a static site directory
+fn route_html_path(dir: &Path, route: &str) -> PathBuf {
+    let route = route.split(['?', '#']).next().unwrap_or_default();
+    let mut path = dir.to_path_buf();
+    for segment in route.split('/') {
+        // Never let a route escape the output directory
+        if segment.is_empty() || segment == "." || segment == ".." {
+            continue;
+        }
+        path.push(segment);
+    }
+    path.join("index.html")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn page(route: &str, status: StatusCode, html: &str) -> RenderedPage {
+        RenderedPage {
+            route: route.to_string(),
+            status,
+            html: html.to_string(),
+        }
+    }
+
+    #[test]
+    fn route_html_paths() {
+        let dir = Path::new("/site");
+        for (route, path) in [
+            ("/", "/site/index.html"),
+            ("", "/site/index.html"),
+            ("/blog", "/site/blog/index.html"),
+            ("/blog/", "/site/blog/index.html"),
+            ("/blog/post-1", "/site/blog/post-1/index.html"),
+            ("//blog//post-1", "/site/blog/post-1/index.html"),
+            ("/search?query=dioxus", "/site/search/index.html"),
+            ("/docs#install", "/site/docs/index.html"),
+            ("/../../etc/passwd", "/site/etc/passwd/index.html"),
+            ("/./blog/../post", "/site/blog/post/index.html"),
+        ] {
+            assert_eq!(route_html_path(dir, route), Path::new(path), "{route}");
+        }
+    }
+
+    #[test]
+    fn only_successful_pages_are_written() {
+        let public = tempfile::tempdir().unwrap();
+        let out = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(public.path().join("assets")).unwrap();
+        std::fs::write(public.path().join("assets/main.css"), "body {}").unwrap();
+        // File times are coarser than the system clock, so start the run a moment earlier
+        let started = SystemTime::now() - Duration::from_secs(1);
+        // The app cached the clean html of this page while it was rendered
+        std::fs::create_dir_all(public.path().join("cached")).unwrap();
+        std::fs::write(public.path().join("cached/index.html"), "clean").unwrap();
+
+        let rendered = [
+            page("/", StatusCode::OK, "home"),
+            page("/cached", StatusCode::OK, "streamed"),
+            page("/broken", StatusCode::INTERNAL_SERVER_ERROR, "error"),
+            page("/missing", StatusCode::NOT_FOUND, "not found"),
+        ];
+        let not_found = page("/404.html", StatusCode::NOT_FOUND, "custom 404");
+        write_static_site(
+            public.path(),
+            out.path(),
+            started,
+            &rendered,
+            Some(not_found),
+        )
+        .unwrap();
+
+        let read = |path: &str| std::fs::read_to_string(out.path().join(path)).ok();
+        assert_eq!(read("assets/main.css").as_deref(), Some("body {}"));
+        assert_eq!(read("index.html").as_deref(), Some("home"));
+        assert_eq!(read("cached/index.html").as_deref(), Some("clean"));
+        assert_eq!(read("broken/index.html"), None);
+        assert_eq!(read("missing/index.html"), None);
+        assert_eq!(read("404.html").as_deref(), Some("custom 404"));
+    }
+
+    #[test]
+    fn not_found_page_needs_a_404_status() {
+        let public = tempfile::tempdir().unwrap();
+        let out = tempfile::tempdir().unwrap();
+        // The app rendered its home page for the missing route
+        let not_found = page("/404.html", StatusCode::OK, "home");
+        write_static_site(
+            public.path(),
+            out.path(),
+            SystemTime::now(),
+            &[],
+            Some(not_found),
+        )
+        .unwrap();
+        assert!(!out.path().join("404.html").exists());
+    }
+}
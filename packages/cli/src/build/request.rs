@@ -387,6 +387,14 @@ impl BuildRequest {
             features.extend(self.build.target_args.client_features.clone());
         }
 
+        // Lazy routes are only split into their own chunks if dioxus is built with the splitter enabled
+        if self.build.platform() == Platform::Web
+            && self.build.experimental_wasm_split
+            && !self.krate.has_dioxus_feature("wasm-split")
+        {
+            features.push("dioxus/wasm-split".to_string());
+        }
+
         features
     }
 
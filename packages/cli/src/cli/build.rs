@@ -38,6 +38,11 @@ pub(crate) struct BuildArgs {
     #[clap(long)]
     pub(crate) ssg: bool,
 
+    /// The directory the static site is written to when running with `--ssg`. The directory contains
+    /// an `index.html` for every route and can be deployed to any static host [default: "<crate dir>/out"]
+    #[clap(long)]
+    pub(crate) ssg_out_dir: Option<PathBuf>,
+
     /// Skip collecting assets from dependencies [default: false]
     #[clap(long)]
     #[serde(default)]
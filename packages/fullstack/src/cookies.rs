@@ -0,0 +1,270 @@
+//! Typed access to the cookies of the current request.
+
+use std::sync::Arc;
+
+use cookie::{Cookie, CookieJar, Key};
+use parking_lot::Mutex;
+
+use crate::prelude::DioxusServerContext;
+
+/// The cookie jar of a request. The jar is parsed from the request headers the first time it is
+/// used.
+pub(crate) type SharedCookieJar = Arc<Mutex<Option<CookieJar>>>;
+
+impl DioxusServerContext {
+    /// Get the cookies of the current request.
+    ///
+    /// Cookies that are added or removed are sent back to the client in the `Set-Cookie` headers of
+    /// the response. When the page is streamed, cookies need to be changed before the first part
+    /// of the page is sent, for example in a component outside of any suspense boundary.
+    ///
+    /// # Example
+    ///
+    /// ```rust, no_run
+    /// # use dioxus::prelude::*;
+    /// use dioxus::fullstack::cookie::Cookie;
+    ///
+    /// #[server]
+    /// async fn set_theme(theme: String) -> Result<(), ServerFnError> {
+    ///     let cookies = server_context().cookies();
+    ///     cookies.add(Cookie::build(("theme", theme)).path("/").permanent());
+    ///     Ok(())
+    /// }
+    ///
+    /// #[server]
+    /// async fn theme() -> Result<Option<String>, ServerFnError> {
+    ///     let cookies = server_context().cookies();
+    ///     Ok(cookies.get("theme").map(|cookie| cookie.value().to_string()))
+    /// }
+    /// ```
+    pub fn cookies(&self) -> Cookies {
+        Cookies {
+            context: self.clone(),
+        }
+    }
+
+    /// Write the cookies that changed during the request into the `Set-Cookie` headers of the
+    /// response parts
+    pub(crate) fn write_cookies(&self) {
+        let mut jar = self.cookies.lock();
+        let Some(jar) = jar.as_mut() else {
+            return;
+        };
+
+        let mut response = self.response_parts_mut();
+        for cookie in jar.delta() {
+            match http::HeaderValue::try_from(cookie.encoded().to_string()) {
+                Ok(value) => {
+                    response.headers.append(http::header::SET_COOKIE, value);
+                }
+                Err(err) => tracing::error!("Failed to write cookie {}: {err}", cookie.name()),
+            }
+        }
+        jar.reset_delta();
+    }
+}
+
+/// The cookies of a request returned from [`DioxusServerContext::cookies`].
+#[derive(Clone)]
+pub struct Cookies {
+    context: DioxusServerContext,
+}
+
+impl Cookies {
+    fn with_jar<O>(&self, f: impl FnOnce(&mut CookieJar) -> O) -> O {
+        let mut jar = self.context.cookies.lock();
+        let jar = jar.get_or_insert_with(|| {
+            let mut jar = CookieJar::new();
+            let request = self.context.request_parts();
+            for header in request.headers.get_all(http::header::COOKIE) {
+                let Ok(header) = header.to_str() else {
+                    continue;
+                };
+                for cookie in Cookie::split_parse_encoded(header).flatten() {
+                    jar.add_original(cookie.into_owned());
+                }
+            }
+            jar
+        });
+        f(jar)
+    }
+
+    /// Get a cookie by name. This includes cookies that were added during the request.
+    pub fn get(&self, name: &str) -> Option<Cookie<'static>> {
+        self.with_jar(|jar| jar.get(name).cloned())
+    }
+
+    /// Get every cookie in the jar.
+    pub fn all(&self) -> Vec<Cookie<'static>> {
+        self.with_jar(|jar| jar.iter().cloned().collect())
+    }
+
+    /// Add a cookie or replace the cookie with the same name.
+    pub fn add(&self, cookie: impl Into<Cookie<'static>>) {
+        self.with_jar(|jar| jar.add(cookie));
+    }
+
+    /// Remove a cookie. If the client sent the cookie, it is removed from the client as well. The
+    /// path and domain of the cookie need to match the cookie on the client.
+    pub fn remove(&self, cookie: impl Into<Cookie<'static>>) {
+        self.with_jar(|jar| jar.remove(cookie));
+    }
+
+    /// Get a view of the jar that signs cookies with a key. The client can read signed cookies, but
+    /// changes to them are detected and the cookie is ignored.
+    pub fn signed<'a>(&'a self, key: &'a Key) -> SignedCookies<'a> {
+        SignedCookies { cookies: self, key }
+    }
+
+    /// Get a view of the jar that encrypts cookies with a key. The client can neither read nor change
+    /// private cookies.
+    pub fn private<'a>(&'a self, key: &'a Key) -> PrivateCookies<'a> {
+        PrivateCookies { cookies: self, key }
+    }
+}
+
+/// Cookies that are signed with a key. Created with [`Cookies::signed`].
+pub struct SignedCookies<'a> {
+    cookies: &'a Cookies,
+    key: &'a Key,
+}
+
+impl SignedCookies<'_> {
+    /// Get a signed cookie by name. Returns `None` if the cookie doesn't exist or the signature
+    /// doesn't match.
+    pub fn get(&self, name: &str) -> Option<Cookie<'static>> {
+        self.cookies.with_jar(|jar| jar.signed(self.key).get(name))
+    }
+
+    /// Sign and add a cookie.
+    pub fn add(&self, cookie: impl Into<Cookie<'static>>) {
+        self.cookies
+            .with_jar(|jar| jar.signed_mut(self.key).add(cookie));
+    }
+
+    /// Remove a signed cookie.
+    pub fn remove(&self, cookie: impl Into<Cookie<'static>>) {
+        self.cookies
+            .with_jar(|jar| jar.signed_mut(self.key).remove(cookie));
+    }
+}
+
+/// Cookies that are encrypted with a key. Created with [`Cookies::private`].
+pub struct PrivateCookies<'a> {
+    cookies: &'a Cookies,
+    key: &'a Key,
+}
+
+impl PrivateCookies<'_> {
+    /// Get and decrypt a private cookie by name. Returns `None` if the cookie doesn't exist or
+    /// can't be decrypted.
+    pub fn get(&self, name: &str) -> Option<Cookie<'static>> {
+        self.cookies.with_jar(|jar| jar.private(self.key).get(name))
+    }
+
+    /// Encrypt and add a cookie.
+    pub fn add(&self, cookie: impl Into<Cookie<'static>>) {
+        self.cookies
+            .with_jar(|jar| jar.private_mut(self.key).add(cookie));
+    }
+
+    /// Remove a private cookie.
+    pub fn remove(&self, cookie: impl Into<Cookie<'static>>) {
+        self.cookies
+            .with_jar(|jar| jar.private_mut(self.key).remove(cookie));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use http::header::{COOKIE, SET_COOKIE};
+
+    fn context(cookies: &[&str]) -> DioxusServerContext {
+        let mut request = http::Request::get("/");
+        for cookie in cookies {
+            request = request.header(COOKIE, *cookie);
+        }
+        DioxusServerContext::new(request.body(()).unwrap().into_parts().0)
+    }
+
+    /// The cookies the response sets
+    fn set_cookies(context: &DioxusServerContext) -> Vec<Cookie<'static>> {
+        context.write_cookies();
+        context
+            .response_parts()
+            .headers
+            .get_all(SET_COOKIE)
+            .iter()
+            .map(|header| Cookie::parse_encoded(header.to_str().unwrap().to_string()).unwrap())
+            .collect()
+    }
+
+    /// Send a cookie the response set back with the next request
+    fn send_back(cookie: &Cookie) -> String {
+        cookie.stripped().encoded().to_string()
+    }
+
+    #[test]
+    fn request_cookies_are_read_and_changes_are_written() {
+        let context = context(&["theme=dark; lang=en"]);
+        let cookies = context.cookies();
+        assert_eq!(cookies.get("theme").unwrap().value(), "dark");
+        assert_eq!(cookies.all().len(), 2);
+        assert!(set_cookies(&context).is_empty());
+
+        cookies.add(("theme", "light"));
+        cookies.remove("lang");
+        assert_eq!(cookies.get("theme").unwrap().value(), "light");
+        assert!(cookies.get("lang").is_none());
+
+        let set = set_cookies(&context);
+        assert_eq!(set.len(), 2);
+        let theme = set.iter().find(|cookie| cookie.name() == "theme").unwrap();
+        assert_eq!(theme.value(), "light");
+        let lang = set.iter().find(|cookie| cookie.name() == "lang").unwrap();
+        assert_eq!(lang.max_age(), Some(cookie::time::Duration::ZERO));
+
+        // Changes are only written once
+        assert_eq!(set_cookies(&context).len(), 2);
+    }
+
+    #[test]
+    fn signed_cookies_detect_changes() {
+        let key = Key::generate();
+        let first = context(&[]);
+        first.cookies().signed(&key).add(("user", "1"));
+        let set = set_cookies(&first);
+        let signed = send_back(&set[0]);
+        // The client can read the value
+        assert!(signed.ends_with('1'));
+
+        let cookies = context(&[&signed]).cookies();
+        assert_eq!(cookies.signed(&key).get("user").unwrap().value(), "1");
+        assert!(cookies.signed(&Key::generate()).get("user").is_none());
+
+        let tampered = format!("{}2", signed.strip_suffix('1').unwrap());
+        let cookies = context(&[&tampered]).cookies();
+        assert!(cookies.signed(&key).get("user").is_none());
+        assert!(cookies.get("user").is_some());
+    }
+
+    #[test]
+    fn private_cookies_are_encrypted() {
+        let key = Key::generate();
+        let first = context(&[]);
+        first.cookies().private(&key).add(("token", "secret"));
+        let private = send_back(&set_cookies(&first)[0]);
+        assert!(!private.contains("secret"));
+
+        let cookies = context(&[&private]).cookies();
+        assert_eq!(
+            cookies.private(&key).get("token").unwrap().value(),
+            "secret"
+        );
+        assert!(cookies.private(&Key::generate()).get("token").is_none());
+
+        cookies.private(&key).remove("token");
+        assert!(cookies.private(&key).get("token").is_none());
+    }
+}
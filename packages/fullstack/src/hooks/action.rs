@@ -0,0 +1,341 @@
+use dioxus_lib::prelude::{dioxus_core::AttributeValue, *};
+use server_fn::{
+    client::Client,
+    codec::{Encoding, FromRes, PostUrl},
+    error::ServerFnErrorSerde,
+    request::ClientReq,
+    ServerFn, ServerFnError,
+};
+use std::marker::PhantomData;
+
+/// Bind a server function to a form with progressive enhancement.
+///
+/// The returned [`ServerAction`] is used as the `action` attribute of the form. The names of the form
+/// fields need to match the arguments of the server function.
+///
+/// - Before the page is hydrated or if javascript is disabled, the browser posts the form directly
+///   to the server function. The server runs the function and redirects back to the page. If the
+///   function fails, the error is added to the url of the redirect and [`ServerAction::error`]
+///   returns it while rendering the page.
+/// - Once the page is hydrated, the web renderer intercepts the submission and sends it with `fetch`
+///   without leaving the page. The action tracks whether the call is pending, the value it returned
+///   and the error it failed with.
+///
+/// Forms with their own `onsubmit` handler are not intercepted. Call [`ServerAction::submit`] from the
+/// handler instead. This is also required on desktop, mobile and liveview.
+///
+/// The server function needs to use the url encoded input (`input = server_fn::codec::PostUrl`)
+/// because that is the encoding browsers use for forms.
+///
+/// # Example
+///
+/// ```rust, no_run
+/// # use dioxus::prelude::*;
+/// #[server(input = server_fn::codec::PostUrl)]
+/// async fn add_todo(title: String) -> Result<usize, ServerFnError> {
+///     if title.is_empty() {
+///         return Err(ServerFnError::new("The title can't be empty"));
+///     }
+///     // Save the todo...
+///     Ok(1)
+/// }
+///
+/// fn App() -> Element {
+///     // The server function struct is named after the function in PascalCase
+///     let add = use_action::<AddTodo>();
+///
+///     rsx! {
+///         form {
+///             method: "post",
+///             action: add,
+///             input { name: "title" }
+///             button { disabled: add.pending(), "Add" }
+///         }
+///         if let Some(error) = add.error() {
+///             p { "Failed to add the todo: {error}" }
+///         }
+///         if let Some(id) = add.value() {
+///             p { "Added todo {id}" }
+///         }
+///     }
+/// }
+/// ```
+pub fn use_action<S>() -> ServerAction<S>
+where
+    S: ServerFn<InputEncoding = PostUrl> + 'static,
+    S::Output: 'static,
+    S::Error: Clone + 'static,
+{
+    let in_flight = use_signal(|| 0);
+    let value = use_signal(|| None);
+    // The error of a submission without javascript is read on the server and the client so the
+    // hydrated page matches the server render
+    let error = use_signal(redirected_error::<S>);
+
+    let action = ServerAction {
+        in_flight,
+        value,
+        error,
+        _marker: PhantomData,
+    };
+
+    #[cfg(all(feature = "web", not(feature = "server")))]
+    use_hook_with_cleanup(
+        move || FormInterception::register(action),
+        |interception| interception.unregister::<S>(),
+    );
+
+    action
+}
+
+/// A server function bound to a form with [`use_action`].
+pub struct ServerAction<S: ServerFn + 'static> {
+    in_flight: Signal<usize>,
+    value: Signal<Option<S::Output>>,
+    error: Signal<Option<ServerFnError<S::Error>>>,
+    _marker: PhantomData<fn() -> S>,
+}
+
+impl<S: ServerFn> Clone for ServerAction<S> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<S: ServerFn> Copy for ServerAction<S> {}
+
+impl<S> ServerAction<S>
+where
+    S: ServerFn<InputEncoding = PostUrl> + 'static,
+    S::Output: 'static,
+    S::Error: Clone + 'static,
+{
+    /// The url of the server function. This is used as the `action` of the form so the form works
+    /// without javascript.
+    pub fn url(&self) -> &'static str {
+        S::PATH
+    }
+
+    /// Submit the values of a form to the server function. Call this from the `onsubmit` handler of
+    /// the form. The form values are encoded the same way the browser encodes them when the form is
+    /// submitted without javascript.
+    pub fn submit(&self, event: FormEvent) {
+        let mut body = form_urlencoded::Serializer::new(String::new());
+        for (name, values) in event.values() {
+            for value in values.as_slice() {
+                body.append_pair(&name, value);
+            }
+        }
+        self.post(body.finish());
+    }
+
+    /// Call the server function with the url encoded values of a form.
+    fn post(&self, body: String) {
+        self.run(async move {
+            let req =
+                <<S::Client as Client<S::Error>>::Request as ClientReq<S::Error>>::try_new_post(
+                    S::PATH,
+                    S::OutputEncoding::CONTENT_TYPE,
+                    PostUrl::CONTENT_TYPE,
+                    body,
+                )?;
+            let res = S::Client::send(req).await?;
+            S::Output::from_res(res).await
+        });
+    }
+
+    /// Call the server function with typed arguments and track the result in the action.
+    pub fn dispatch(&self, args: S) {
+        self.run(args.run_on_client());
+    }
+
+    /// Returns true while a call to the server function is running.
+    pub fn pending(&self) -> bool {
+        (self.in_flight)() > 0
+    }
+
+    /// The value the server function returned the last time it succeeded.
+    pub fn value(&self) -> Option<S::Output>
+    where
+        S::Output: Clone,
+    {
+        self.value.cloned()
+    }
+
+    /// The error of the last call to the server function, if it failed.
+    pub fn error(&self) -> Option<ServerFnError<S::Error>> {
+        self.error.cloned()
+    }
+
+    fn run(
+        &self,
+        call: impl std::future::Future<Output = Result<S::Output, ServerFnError<S::Error>>> + 'static,
+    ) {
+        let mut in_flight = self.in_flight;
+        let mut value = self.value;
+        let mut error = self.error;
+        in_flight += 1;
+        spawn(async move {
+            match call.await {
+                Ok(output) => {
+                    value.set(Some(output));
+                    error.set(None);
+                }
+                Err(err) => error.set(Some(err)),
+            }
+            in_flight -= 1;
+        });
+    }
+}
+
+impl<S: ServerFn> IntoAttributeValue for ServerAction<S> {
+    fn into_value(self) -> AttributeValue {
+        AttributeValue::Text(S::PATH.to_string())
+    }
+}
+
+/// Submits the hydrated forms that post to a server function through its action.
+#[cfg(all(feature = "web", not(feature = "server")))]
+#[derive(Clone)]
+struct FormInterception(std::rc::Rc<wasm_bindgen::closure::Closure<dyn FnMut(String)>>);
+
+#[cfg(all(feature = "web", not(feature = "server")))]
+impl FormInterception {
+    fn register<S>(action: ServerAction<S>) -> Self
+    where
+        S: ServerFn<InputEncoding = PostUrl> + 'static,
+        S::Output: 'static,
+        S::Error: Clone + 'static,
+    {
+        use dioxus_lib::prelude::dioxus_core::Runtime;
+
+        // The callback is called from the submit listener, outside of the virtual dom
+        let runtime = Runtime::current().expect("use_action must be called in a component");
+        let scope = current_scope_id().expect("use_action must be called in a component");
+        let callback = wasm_bindgen::closure::Closure::new(move |body: String| {
+            runtime.on_scope(scope, || action.post(body))
+        });
+        dioxus_interpreter_js::minimal_bindings::registerFormAction(S::PATH, &callback);
+
+        Self(std::rc::Rc::new(callback))
+    }
+
+    fn unregister<S: ServerFn>(self) {
+        dioxus_interpreter_js::minimal_bindings::unregisterFormAction(S::PATH, &self.0);
+    }
+}
+
+/// Get the error a submission of the server function without javascript redirected back with.
+fn redirected_error<S: ServerFn>() -> Option<ServerFnError<S::Error>> {
+    let query = current_query()?;
+    let mut path = None;
+    let mut error = None;
+    for (key, value) in form_urlencoded::parse(query.as_bytes()) {
+        match &*key {
+            "__path" => path = Some(value),
+            "__err" => error = Some(value),
+            _ => {}
+        }
+    }
+
+    if path? != S::PATH {
+        return None;
+    }
+    error.map(|error| ServerFnError::de(&error))
+}
+
+/// The query of the url of the page that is being rendered
+fn current_query() -> Option<String> {
+    #[cfg(feature = "server")]
+    {
+        crate::prelude::server_context()
+            .request_parts()
+            .uri
+            .query()
+            .map(ToOwned::to_owned)
+    }
+    #[cfg(all(feature = "web", not(feature = "server")))]
+    {
+        let search = web_sys::window()?.location().search().ok()?;
+        Some(search.trim_start_matches('?').to_string())
+    }
+    #[cfg(not(any(feature = "server", feature = "web")))]
+    {
+        None
+    }
+}
+
+#[cfg(all(test, feature = "axum"))]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+    use axum::body::Body;
+    use http::{header, Request, StatusCode};
+    use tower::ServiceExt;
+
+    #[server(input = PostUrl)]
+    async fn add_todo(title: String) -> Result<usize, ServerFnError> {
+        if title.is_empty() {
+            return Err(ServerFnError::new("The title can't be empty"));
+        }
+        Ok(title.len())
+    }
+
+    fn todos() -> Element {
+        let add = use_action::<AddTodo>();
+        rsx! {
+            form { method: "post", action: add, input { name: "title" } }
+            if let Some(error) = add.error() {
+                p { "{error}" }
+            }
+        }
+    }
+
+    /// Post the form the way a browser without javascript does
+    async fn post_form(body: &'static str) -> String {
+        let request = Request::post(AddTodo::PATH)
+            .header(header::CONTENT_TYPE, PostUrl::CONTENT_TYPE)
+            .header(header::ACCEPT, "text/html,application/xhtml+xml")
+            .header(header::REFERER, "http://localhost/todos?list=1")
+            .body(Body::from(body))
+            .unwrap();
+        let response = axum::Router::new()
+            .register_server_functions()
+            .oneshot(request)
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::FOUND);
+        response.headers()[header::LOCATION]
+            .to_str()
+            .unwrap()
+            .to_string()
+    }
+
+    /// Render the page the browser is redirected to
+    fn render_page(uri: &str) -> String {
+        let (parts, _) = Request::get(uri).body(()).unwrap().into_parts();
+        with_server_context(DioxusServerContext::new(parts), || {
+            let mut dom = VirtualDom::new(todos);
+            dom.rebuild_in_place();
+            dioxus_ssr::render(&dom)
+        })
+    }
+
+    #[tokio::test]
+    async fn forms_without_javascript_redirect_back_to_the_page() {
+        let location = post_form("title=milk").await;
+        assert_eq!(location, "http://localhost/todos?list=1");
+        let page = render_page(&location);
+        assert!(page.contains(&format!(r#"action="{}""#, AddTodo::PATH)));
+        assert!(!page.contains("<p>"));
+
+        let location = post_form("title=").await;
+        assert!(location.starts_with("http://localhost/todos?list=1&__path="));
+        let page = render_page(&location);
+        assert!(page.contains("The title can&#x27;t be empty"), "{page}");
+
+        // Errors of other server functions are ignored
+        let other = location.replace("add_todo", "remove_todo");
+        assert!(!render_page(&other).contains("<p>"));
+    }
+}
@@ -0,0 +1,56 @@
+use dioxus_lib::prelude::use_hook;
+
+/// Attach a tag to the incremental render of the current page. Every page that was rendered with a
+/// tag can be removed from the incremental cache at once with `IncrementalRenderer::invalidate_tag`.
+/// This is useful to purge every page that displays an entity when the entity changes.
+///
+/// The tag is only used on the server while rendering. This hook does nothing on the client or if
+/// incremental rendering is disabled.
+///
+/// # Example
+/// ```rust
+/// use dioxus_lib::prelude::*;
+/// use dioxus_fullstack::prelude::*;
+///
+/// #[component]
+/// fn Product(id: u64) -> Element {
+///     use_cache_tag(format!("product:{id}"));
+///
+///     rsx! { "Product {id}" }
+/// }
+/// ```
+pub fn use_cache_tag(tag: impl Into<String>) {
+    #[cfg(feature = "server")]
+    {
+        let tag = tag.into();
+        use_hook(move || {
+            if let Some(tags) = dioxus_lib::prelude::try_consume_context::<CacheTags>() {
+                tags.push(tag);
+            }
+        })
+    }
+    #[cfg(not(feature = "server"))]
+    {
+        _ = tag;
+        use_hook(|| ())
+    }
+}
+
+/// The cache tags collected while rendering a page on the server.
+#[cfg(feature = "server")]
+#[derive(Clone, Default)]
+pub(crate) struct CacheTags(std::rc::Rc<std::cell::RefCell<Vec<String>>>);
+
+#[cfg(feature = "server")]
+impl CacheTags {
+    fn push(&self, tag: String) {
+        let mut tags = self.0.borrow_mut();
+        if !tags.contains(&tag) {
+            tags.push(tag);
+        }
+    }
+
+    pub(crate) fn take(&self) -> Vec<String> {
+        std::mem::take(&mut self.0.borrow_mut())
+    }
+}
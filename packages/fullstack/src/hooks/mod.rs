@@ -1,2 +1,11 @@
+pub mod action;
+pub mod cache_tag;
 pub mod server_cached;
 pub mod server_future;
+#[cfg(any(
+    feature = "web",
+    feature = "desktop",
+    feature = "mobile",
+    feature = "server"
+))]
+pub mod server_stream;
@@ -74,6 +74,10 @@ where
     #[cfg(feature = "server")]
     let caller = std::panic::Location::caller();
 
+    // With partial prerendering, futures outside of the dynamic regions are cached with the shell of the page
+    #[cfg(feature = "server")]
+    let shell_value = crate::partial_prerender::use_shell_value();
+
     // If this is the first run and we are on the web client, the data might be cached
     #[cfg(feature = "web")]
     let initial_web_result = use_hook(|| {
@@ -91,6 +95,9 @@ where
         #[cfg(feature = "web")]
         let initial_web_result = initial_web_result.clone();
 
+        #[cfg(feature = "server")]
+        let shell_value = shell_value.clone();
+
         #[allow(clippy::let_and_return)]
         async move {
             // If this is the first run and we are on the web client, the data might be cached
@@ -109,12 +116,22 @@ where
                 None => {}
             }
 
+            // If the shell of the page is served from the cache, the future already ran when the shell was rendered
+            #[cfg(feature = "server")]
+            if let Some(out) = shell_value.take_cached::<T>() {
+                serialize_context.insert(server_storage_entry, &out, caller);
+                return out;
+            }
+
             // Otherwise just run the future itself
             let out = user_fut.await;
 
             // If this is the first run and we are on the server, cache the data in the slot we reserved for it
             #[cfg(feature = "server")]
-            serialize_context.insert(server_storage_entry, &out, caller);
+            {
+                serialize_context.insert(server_storage_entry, &out, caller);
+                shell_value.record(&out);
+            }
 
             out
         }
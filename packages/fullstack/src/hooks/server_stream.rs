@@ -0,0 +1,139 @@
+use crate::server_stream::JsonStream;
+use dioxus_lib::prelude::*;
+use futures_util::StreamExt;
+use server_fn::ServerFnError;
+use std::future::Future;
+
+/// Opens a stream returned from a server function and appends every value to a signal as it
+/// arrives. Use this with server functions that have the [`JsonLines`](crate::prelude::JsonLines)
+/// or [`ServerSentEvents`](crate::prelude::ServerSentEvents) output encoding.
+///
+/// The stream is only opened on the client. During server side rendering the list of values stays
+/// empty because a stream that never ends would block the response. The closure that opens the
+/// stream is reactive: if it reads a signal, the stream is reopened and the values are cleared when
+/// the signal changes.
+///
+/// # Example
+///
+/// ```rust, no_run
+/// # use dioxus::prelude::*;
+/// #[server(output = JsonLines)]
+/// async fn countdown(from: u32) -> Result<JsonStream<u32>, ServerFnError> {
+///     Ok(futures_util::stream::iter((0..=from).rev()).into())
+/// }
+///
+/// fn App() -> Element {
+///     let count = use_server_stream(|| countdown(10));
+///
+///     rsx! {
+///         for value in count.items().iter() {
+///             p { "{value}" }
+///         }
+///         if let Some(error) = count.error() {
+///             p { "The stream failed: {error}" }
+///         }
+///         if count.is_finished() {
+///             p { "Done!" }
+///         }
+///     }
+/// }
+/// ```
+pub fn use_server_stream<T, F>(mut open: impl FnMut() -> F + 'static) -> UseServerStream<T>
+where
+    T: 'static,
+    F: Future<Output = Result<JsonStream<T>, ServerFnError>> + 'static,
+{
+    let mut items = use_signal(Vec::new);
+    let mut error = use_signal(|| None);
+    let mut finished = use_signal(|| false);
+
+    let task = use_future(move || {
+        // Streams are never opened while rendering on the server
+        #[cfg(feature = "server")]
+        let opened: Option<F> = {
+            let _ = &mut open;
+            None
+        };
+        #[cfg(not(feature = "server"))]
+        let opened = Some(open());
+
+        async move {
+            let Some(opened) = opened else {
+                return;
+            };
+
+            items.write().clear();
+            error.set(None);
+            finished.set(false);
+
+            match opened.await {
+                Ok(stream) => {
+                    let mut stream = std::pin::pin!(stream.into_inner());
+                    while let Some(value) = stream.next().await {
+                        match value {
+                            Ok(value) => items.write().push(value),
+                            Err(err) => {
+                                error.set(Some(err));
+                                break;
+                            }
+                        }
+                    }
+                }
+                Err(err) => error.set(Some(err)),
+            }
+            finished.set(true);
+        }
+    });
+
+    UseServerStream {
+        items,
+        error,
+        finished,
+        task,
+    }
+}
+
+/// The state of a stream opened with [`use_server_stream`].
+pub struct UseServerStream<T: 'static> {
+    items: Signal<Vec<T>>,
+    error: Signal<Option<ServerFnError>>,
+    finished: Signal<bool>,
+    task: UseFuture,
+}
+
+impl<T> Clone for UseServerStream<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for UseServerStream<T> {}
+
+impl<T> UseServerStream<T> {
+    /// The values the stream produced so far. Reading this subscribes the component to new values.
+    pub fn items(&self) -> ReadOnlySignal<Vec<T>> {
+        self.items.into()
+    }
+
+    /// The error that ended the stream, if any.
+    pub fn error(&self) -> Option<ServerFnError> {
+        self.error.cloned()
+    }
+
+    /// Returns true if the stream ended, either because the server closed it or because of an
+    /// error.
+    pub fn is_finished(&self) -> bool {
+        (self.finished)()
+    }
+
+    /// Close the stream and open it again. The values of the previous stream are cleared once the
+    /// new stream is opened.
+    pub fn restart(&mut self) {
+        self.task.restart();
+    }
+
+    /// Close the stream. The values it produced so far are kept.
+    pub fn cancel(&mut self) {
+        self.task.cancel();
+    }
+}
@@ -14,6 +14,14 @@ impl super::HTMLData {
         data
     }
 
+    /// Extract only the errors from a suspense boundary the server stopped waiting for. The data of the children is
+    /// left out, so the client runs their futures itself.
+    pub(crate) fn extract_errors_from_suspense_boundary(vdom: &VirtualDom, scope: ScopeId) -> Self {
+        let mut data = Self::default();
+        data.serialize_errors(vdom, scope);
+        data
+    }
+
     /// Get the errors from the suspense boundary
     fn serialize_errors(&mut self, vdom: &VirtualDom, scope: ScopeId) {
         // If there is an error boundary on the suspense boundary, grab the error from the context API
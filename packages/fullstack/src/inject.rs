@@ -0,0 +1,397 @@
+//! Typed dependency injection for server functions and server rendering.
+//!
+//! Values are created by async providers registered in [`Providers`]. A provider can depend on other
+//! provided values through the [`Injector`] it receives. Server functions get the values with
+//! `#[inject]` parameters of the type [`Inject`] or the [`Inject`] extractor.
+
+use std::{
+    any::{Any, TypeId},
+    collections::HashMap,
+    future::Future,
+    pin::Pin,
+    sync::Arc,
+};
+
+use parking_lot::Mutex;
+use server_fn::ServerFnError;
+use tokio::sync::OnceCell;
+
+use crate::prelude::{DioxusServerContext, FromServerContext};
+
+type BoxedValue = Arc<dyn Any + Send + Sync>;
+
+type ProviderFuture = Pin<Box<dyn Future<Output = Result<BoxedValue, ServerFnError>> + Send>>;
+
+type ProviderFn = Arc<dyn Fn(Injector) -> ProviderFuture + Send + Sync>;
+
+/// How long a provided value lives
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Lifetime {
+    /// The value is created once and shared between every request
+    Singleton,
+    /// The value is created once for every request
+    Request,
+}
+
+struct Provider {
+    lifetime: Lifetime,
+    create: ProviderFn,
+    /// The value of a singleton provider once it is created
+    singleton: OnceCell<BoxedValue>,
+}
+
+/// The providers that [`Inject`] resolves values from. Set them with
+/// [`ServeConfigBuilder::providers`](crate::ServeConfigBuilder::providers).
+///
+/// Every provider is an async function that gets an [`Injector`] to resolve its own dependencies and
+/// returns the value or an error. Values are created the first time they are injected.
+///
+/// ```rust, no_run
+/// # use dioxus::prelude::*;
+/// # fn app() -> Element { todo!() }
+/// use dioxus::fullstack::inject::Providers;
+///
+/// #[derive(Clone)]
+/// struct Pool;
+///
+/// #[derive(Clone)]
+/// struct CurrentUser(String);
+///
+/// dioxus::LaunchBuilder::new()
+///     .with_context(server_only! {
+///         ServeConfig::builder().providers(
+///             Providers::new()
+///                 // One pool is shared by the whole server
+///                 .singleton(|_| async { Ok(Pool) })
+///                 // The user is loaded once for every request
+///                 .request(|injector| async move {
+///                     let Pool = injector.get::<Pool>().await?;
+///                     let headers: http::HeaderMap = injector.server_context().extract().await?;
+///                     let name = headers.get("x-user").and_then(|name| name.to_str().ok());
+///                     Ok(CurrentUser(name.unwrap_or("guest").to_string()))
+///                 }),
+///         )
+///     })
+///     .launch(app);
+///
+/// #[server]
+/// async fn greet(#[inject] user: Inject<CurrentUser>) -> Result<String, ServerFnError> {
+///     let Inject(CurrentUser(name)) = user;
+///     Ok(format!("Hello {name}"))
+/// }
+/// ```
+#[derive(Clone, Default)]
+pub struct Providers {
+    providers: Arc<HashMap<TypeId, Provider>>,
+}
+
+impl Providers {
+    /// Create an empty set of providers
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Provide a value that is created once and shared between every request. Singletons can only
+    /// depend on other singletons.
+    pub fn singleton<T, F, Fut>(self, provider: F) -> Self
+    where
+        T: Clone + Send + Sync + 'static,
+        F: Fn(Injector) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<T, ServerFnError>> + Send + 'static,
+    {
+        self.with_provider(Lifetime::Singleton, provider)
+    }
+
+    /// Provide a value that is created once for every request. The value is shared between
+    /// everything that injects it while the request is handled.
+    pub fn request<T, F, Fut>(self, provider: F) -> Self
+    where
+        T: Clone + Send + Sync + 'static,
+        F: Fn(Injector) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<T, ServerFnError>> + Send + 'static,
+    {
+        self.with_provider(Lifetime::Request, provider)
+    }
+
+    fn with_provider<T, F, Fut>(mut self, lifetime: Lifetime, provider: F) -> Self
+    where
+        T: Clone + Send + Sync + 'static,
+        F: Fn(Injector) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<T, ServerFnError>> + Send + 'static,
+    {
+        let create: ProviderFn = Arc::new(move |injector| {
+            let future = provider(injector);
+            Box::pin(async move { Ok(Arc::new(future.await?) as BoxedValue) })
+        });
+        let providers = Arc::get_mut(&mut self.providers)
+            .expect("Providers can't be changed after they are added to the server");
+        providers.insert(
+            TypeId::of::<T>(),
+            Provider {
+                lifetime,
+                create,
+                singleton: OnceCell::new(),
+            },
+        );
+        self
+    }
+}
+
+/// The values request scoped providers created for the current request
+#[derive(Clone, Default)]
+struct RequestScope {
+    values: Arc<Mutex<HashMap<TypeId, Arc<OnceCell<BoxedValue>>>>>,
+}
+
+/// Resolves provided values. Providers get an injector to resolve their dependencies.
+#[derive(Clone)]
+pub struct Injector {
+    context: DioxusServerContext,
+    /// The types that are currently being created. Used to detect cycles between providers
+    resolving: Vec<TypeId>,
+    /// The lifetime of the provider this injector was passed to
+    lifetime: Option<Lifetime>,
+}
+
+impl Injector {
+    /// Create an injector for the current request
+    pub fn new(context: DioxusServerContext) -> Self {
+        Self {
+            context,
+            resolving: Vec::new(),
+            lifetime: None,
+        }
+    }
+
+    /// The server context of the request the value is created for
+    pub fn server_context(&self) -> &DioxusServerContext {
+        &self.context
+    }
+
+    /// Resolve a value. If no provider is registered for the type, the value is cloned from the
+    /// server context instead.
+    pub async fn get<T: Clone + Send + Sync + 'static>(&self) -> Result<T, InjectError> {
+        let value = self
+            .resolve(TypeId::of::<T>(), std::any::type_name::<T>())
+            .await?;
+        match value {
+            Some(value) => Ok(value
+                .downcast_ref::<T>()
+                .expect("Providers always create the type they are registered for")
+                .clone()),
+            None => self
+                .context
+                .get::<T>()
+                .ok_or(InjectError::NotProvided(std::any::type_name::<T>())),
+        }
+    }
+
+    async fn resolve(
+        &self,
+        type_id: TypeId,
+        type_name: &'static str,
+    ) -> Result<Option<BoxedValue>, InjectError> {
+        let Some(providers) = self.context.get::<Providers>() else {
+            return Ok(None);
+        };
+        let Some(provider) = providers.providers.get(&type_id) else {
+            return Ok(None);
+        };
+        if self.resolving.contains(&type_id) {
+            return Err(InjectError::Cycle(type_name));
+        }
+        if self.lifetime == Some(Lifetime::Singleton) && provider.lifetime == Lifetime::Request {
+            return Err(InjectError::RequestInSingleton(type_name));
+        }
+
+        let mut injector = self.clone();
+        injector.resolving.push(type_id);
+        injector.lifetime = Some(provider.lifetime);
+        let create = || async {
+            (provider.create)(injector)
+                .await
+                .map_err(|err| InjectError::Provider(type_name, err))
+        };
+        let value = match provider.lifetime {
+            Lifetime::Singleton => provider.singleton.get_or_try_init(create).await?.clone(),
+            Lifetime::Request => {
+                let scope = self.context.get_or_insert_with(RequestScope::default);
+                let cell = scope.values.lock().entry(type_id).or_default().clone();
+                cell.get_or_try_init(create).await?.clone()
+            }
+        };
+        Ok(Some(value))
+    }
+}
+
+/// An error that occurred while a value was injected
+#[derive(Debug, thiserror::Error)]
+pub enum InjectError {
+    /// No provider is registered for the type and it is not in the server context
+    #[error("`{0}` was not provided")]
+    NotProvided(&'static str),
+    /// The providers of the type depend on each other
+    #[error("the provider of `{0}` depends on itself")]
+    Cycle(&'static str),
+    /// A singleton provider tried to inject a request scoped value
+    #[error("the request scoped value `{0}` can't be injected into a singleton")]
+    RequestInSingleton(&'static str),
+    /// The provider of the type returned an error
+    #[error("failed to provide `{0}`: {1}")]
+    Provider(&'static str, ServerFnError),
+}
+
+/// Inject a value from the [`Providers`] of the server.
+///
+/// In a server function, `Inject` can be used as a parameter marked with `#[inject]`. The value is
+/// resolved on the server and the parameter is not sent from the client. `Inject` can also be extracted with
+/// [`extract`](crate::prelude::extract) anywhere the server context is available.
+///
+/// ```rust, no_run
+/// # use dioxus::prelude::*;
+/// #[derive(Clone)]
+/// struct Pool;
+///
+/// #[server]
+/// async fn count_users(#[inject] pool: Inject<Pool>) -> Result<usize, ServerFnError> {
+///     let Inject(_pool) = pool;
+///     Ok(0)
+/// }
+/// ```
+pub struct Inject<T>(pub T);
+
+impl<T: Clone + Send + Sync + 'static> Inject<T> {
+    /// Resolve the value for the current request
+    pub async fn resolve() -> Result<Self, InjectError> {
+        crate::prelude::extract().await
+    }
+}
+
+impl<T> std::ops::Deref for Inject<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> std::ops::DerefMut for Inject<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+}
+
+#[async_trait::async_trait]
+impl<T: Clone + Send + Sync + 'static> FromServerContext for Inject<T> {
+    type Rejection = InjectError;
+
+    async fn from_request(req: &DioxusServerContext) -> Result<Self, Self::Rejection> {
+        Injector::new(req.clone()).get().await.map(Self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[derive(Clone)]
+    struct Pool(usize);
+
+    #[derive(Clone)]
+    struct Transaction(usize);
+
+    #[derive(Clone)]
+    struct Ping;
+
+    #[derive(Clone)]
+    struct Pong;
+
+    fn request(providers: &Providers) -> DioxusServerContext {
+        let context = DioxusServerContext::default();
+        context.insert(providers.clone());
+        context
+    }
+
+    #[tokio::test]
+    async fn providers_resolve_dependencies_once_per_lifetime() {
+        let pools = Arc::new(AtomicUsize::new(0));
+        let transactions = Arc::new(AtomicUsize::new(0));
+        let providers = Providers::new()
+            .singleton({
+                let pools = pools.clone();
+                move |_| {
+                    let id = pools.fetch_add(1, Ordering::SeqCst);
+                    async move { Ok(Pool(id)) }
+                }
+            })
+            .request({
+                let transactions = transactions.clone();
+                move |injector| {
+                    let id = transactions.fetch_add(1, Ordering::SeqCst);
+                    async move {
+                        let Pool(pool) = injector.get().await?;
+                        Ok(Transaction(pool * 100 + id))
+                    }
+                }
+            });
+
+        for request_id in 0..2 {
+            let context = request(&providers);
+            for _ in 0..2 {
+                let Inject(Transaction(transaction)) = context.extract().await.unwrap();
+                assert_eq!(transaction, request_id);
+            }
+        }
+        assert_eq!(pools.load(Ordering::SeqCst), 1);
+        assert_eq!(transactions.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn invalid_dependencies_are_rejected() {
+        let providers = Providers::new()
+            .singleton(|injector| async move {
+                injector.get::<Transaction>().await?;
+                Ok(Pool(0))
+            })
+            .request(|_| async { Ok(Transaction(0)) })
+            .request(|injector| async move {
+                injector.get::<Pong>().await?;
+                Ok(Ping)
+            })
+            .request(|injector| async move {
+                injector.get::<Ping>().await?;
+                Ok(Pong)
+            });
+        let context = request(&providers);
+
+        let pool = context.extract::<_, Inject<Pool>>().await;
+        assert!(matches!(pool, Err(InjectError::Provider(_, _))));
+        let ping = context.extract::<_, Inject<Ping>>().await;
+        assert!(matches!(ping, Err(InjectError::Provider(_, _))));
+        let missing = context.extract::<_, Inject<String>>().await;
+        assert!(matches!(missing, Err(InjectError::NotProvided(_))));
+    }
+
+    #[crate::prelude::server]
+    async fn pool_id(#[inject] pool: Inject<Pool>) -> Result<usize, ServerFnError<String>> {
+        let Inject(Pool(id)) = pool;
+        Ok(id)
+    }
+
+    #[tokio::test]
+    async fn server_functions_resolve_injected_parameters() {
+        use crate::prelude::ProvideServerContext;
+
+        let providers = Providers::new().singleton(|_| async { Ok(Pool(7)) });
+        let id = ProvideServerContext::new(pool_id(), request(&providers)).await;
+        assert_eq!(id.unwrap(), 7);
+
+        // Values that can't be resolved fail with the error type of the server function
+        let missing = ProvideServerContext::new(pool_id(), request(&Providers::new())).await;
+        assert!(matches!(
+            missing,
+            Err(ServerFnError::<String>::ServerError(message)) if message.contains("was not provided")
+        ));
+    }
+}
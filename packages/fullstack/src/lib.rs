@@ -13,6 +13,22 @@ mod html_storage;
 pub mod server;
 
 mod hooks;
+mod partial_prerender;
+mod render_timeout;
+#[cfg(any(
+    feature = "web",
+    feature = "desktop",
+    feature = "mobile",
+    feature = "server"
+))]
+mod server_stream;
+#[cfg(any(
+    feature = "web",
+    feature = "desktop",
+    feature = "mobile",
+    feature = "server"
+))]
+mod websocket;
 
 pub mod document;
 #[cfg(feature = "server")]
@@ -28,10 +44,58 @@ pub use serve_config::*;
 #[cfg(feature = "server")]
 mod server_context;
 
+#[cfg(feature = "server")]
+mod revalidate;
+
+#[cfg(feature = "server")]
+mod cookies;
+#[cfg(feature = "server")]
+#[cfg_attr(docsrs, doc(cfg(feature = "server")))]
+pub use cookie;
+
+#[cfg(feature = "server")]
+#[cfg_attr(docsrs, doc(cfg(feature = "server")))]
+pub mod session;
+
+#[cfg(feature = "server")]
+#[cfg_attr(docsrs, doc(cfg(feature = "server")))]
+pub mod inject;
+
 /// A prelude of commonly used items in dioxus-fullstack.
 pub mod prelude {
     use crate::hooks;
-    pub use hooks::{server_cached::use_server_cached, server_future::use_server_future};
+    pub use crate::partial_prerender::Dynamic;
+    pub use crate::render_timeout::TimeoutBoundary;
+    pub use hooks::{
+        action::{use_action, ServerAction},
+        cache_tag::use_cache_tag,
+        server_cached::use_server_cached,
+        server_future::use_server_future,
+    };
+
+    #[cfg(any(
+        feature = "web",
+        feature = "desktop",
+        feature = "mobile",
+        feature = "server"
+    ))]
+    pub use crate::server_stream::{JsonLines, JsonStream, ServerSentEvents};
+    #[cfg(any(
+        feature = "web",
+        feature = "desktop",
+        feature = "mobile",
+        feature = "server"
+    ))]
+    pub use crate::websocket::{
+        Websocket, WebsocketArgs, WebsocketRequest, WebsocketResponse, WebsocketStream,
+    };
+    #[cfg(any(
+        feature = "web",
+        feature = "desktop",
+        feature = "mobile",
+        feature = "server"
+    ))]
+    pub use hooks::server_stream::{use_server_stream, UseServerStream};
 
     #[cfg(feature = "axum")]
     #[cfg_attr(docsrs, doc(cfg(feature = "axum")))]
@@ -56,6 +120,22 @@ pub mod prelude {
         FromServerContext, ProvideServerContext,
     };
 
+    #[cfg(feature = "server")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "server")))]
+    pub use crate::revalidate::{revalidate_path, revalidate_tag};
+
+    #[cfg(feature = "server")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "server")))]
+    pub use crate::render_timeout::TimedOutBoundary;
+
+    #[cfg(feature = "server")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "server")))]
+    pub use crate::cookies::{Cookies, PrivateCookies, SignedCookies};
+
+    #[cfg(feature = "server")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "server")))]
+    pub use crate::inject::Inject;
+
     #[cfg(feature = "server")]
     #[cfg_attr(docsrs, doc(cfg(feature = "server")))]
     pub use dioxus_isrg::{IncrementalRenderer, IncrementalRendererConfig};
@@ -0,0 +1,272 @@
+//! Partial prerendering caches the static shell of a page in the incremental cache and renders the
+//! [`Dynamic`] regions of the page for every request.
+//!
+//! The shell is rendered with every dynamic region suspended, so it contains the fallback of each
+//! region in place of its content. When a route is requested, the cached shell is sent immediately
+//! and the content of each dynamic region is streamed into the shell as it resolves with the same
+//! placeholders out of order streaming uses.
+//!
+//! The values of the server futures outside of the dynamic regions and the placeholder ids of the
+//! regions are cached with the shell. While the shell is cached, those futures take their values
+//! from the cache instead of running again and only the dynamic regions are rendered.
+
+use crate::prelude::use_server_future;
+use dioxus_lib::prelude::*;
+#[cfg(feature = "server")]
+use std::{cell::RefCell, rc::Rc};
+
+/// A region of the page that is rendered for every request when partial prerendering is enabled with
+/// [`ServeConfigBuilder::enable_partial_prerendering`](crate::ServeConfigBuilder::enable_partial_prerendering).
+///
+/// The rest of the page is cached as a static shell with the fallback in place of the region. Use
+/// this for personalized parts of an otherwise static page like the user menu or a shopping cart.
+/// The region works like a normal [`SuspenseBoundary`] if partial prerendering is disabled.
+///
+/// # Example
+///
+/// ```rust, no_run
+/// # use dioxus::prelude::*;
+/// #[server]
+/// async fn user_name() -> Result<String, ServerFnError> {
+///     Ok("Alice".to_string())
+/// }
+///
+/// fn App() -> Element {
+///     rsx! {
+///         h1 { "Welcome to the store" }
+///         Dynamic {
+///             fallback: |_| rsx! { "Loading your account..." },
+///             UserMenu {}
+///         }
+///     }
+/// }
+///
+/// fn UserMenu() -> Element {
+///     let name = use_server_future(user_name)?;
+///     rsx! { "Signed in as {name:?}" }
+/// }
+/// ```
+#[component]
+pub fn Dynamic(fallback: Callback<SuspenseContext, Element>, children: Element) -> Element {
+    use_context_provider(|| DynamicRegion);
+
+    rsx! {
+        SuspenseBoundary { fallback, DynamicHole { children } }
+    }
+}
+
+/// Marks the scope of a [`Dynamic`] region so the server can find the suspense boundary it renders
+#[derive(Clone, Copy)]
+struct DynamicRegion;
+
+/// Suspends the dynamic region while the shell is rendered on the server. The hole stores an empty
+/// value for hydration, so the client keeps the region suspended until the server streams it.
+#[component]
+fn DynamicHole(children: Element) -> Element {
+    #[cfg(feature = "server")]
+    let shell = use_hook(try_consume_context::<ShellRender>);
+
+    use_server_future(move || {
+        #[cfg(feature = "server")]
+        let shell = shell.clone();
+        async move {
+            #[cfg(feature = "server")]
+            if let Some(mut shell) = shell {
+                // Resolves once the shell is rendered
+                _ = shell.released.changed().await;
+            }
+        }
+    })?;
+
+    children
+}
+
+/// Provided while the shell of a page is rendered. Dynamic regions stay suspended until the sender
+/// of the channel is dropped.
+#[cfg(feature = "server")]
+#[derive(Clone)]
+pub(crate) struct ShellRender {
+    released: tokio::sync::watch::Receiver<()>,
+    /// The values of the server futures in the shell if it is served from the cache
+    cached_values: Option<Rc<RefCell<std::vec::IntoIter<Vec<u8>>>>>,
+}
+
+#[cfg(feature = "server")]
+impl ShellRender {
+    /// Create a new shell render context and the sender that releases the dynamic regions when it is dropped.
+    /// If the shell is served from the cache, the server futures outside of the dynamic regions take the
+    /// cached values in the order their hooks run.
+    pub(crate) fn new(
+        cached_values: Option<Vec<Vec<u8>>>,
+    ) -> (Self, tokio::sync::watch::Sender<()>) {
+        let (sender, released) = tokio::sync::watch::channel(());
+        let cached_values = cached_values.map(|values| Rc::new(RefCell::new(values.into_iter())));
+        (
+            Self {
+                released,
+                cached_values,
+            },
+            sender,
+        )
+    }
+}
+
+/// The part of a cached shell that is needed to stream the dynamic regions into it without rendering the
+/// shell again
+#[cfg(feature = "server")]
+#[derive(serde::Serialize, serde::Deserialize)]
+pub(crate) struct ShellData {
+    /// The serialized values of the server futures outside of the dynamic regions in the order their hooks run
+    pub(crate) values: Vec<Vec<u8>>,
+    /// The path of the placeholder of each dynamic region in document order
+    pub(crate) regions: Vec<Vec<usize>>,
+}
+
+#[cfg(feature = "server")]
+impl ShellData {
+    /// Encode the data so it can be stored in the html of the cached shell
+    pub(crate) fn encode(&self) -> String {
+        use base64::Engine;
+        let mut serialized = Vec::new();
+        ciborium::into_writer(self, &mut serialized).unwrap();
+        base64::engine::general_purpose::STANDARD.encode(serialized)
+    }
+
+    /// Decode data that was encoded with [`Self::encode`]
+    pub(crate) fn decode(data: &str) -> Option<Self> {
+        use base64::Engine;
+        let serialized = base64::engine::general_purpose::STANDARD
+            .decode(data)
+            .ok()?;
+        ciborium::from_reader(serialized.as_slice()).ok()
+    }
+}
+
+/// The value of a server future outside of the dynamic regions of a page rendered with partial prerendering
+#[cfg(feature = "server")]
+#[derive(Clone)]
+pub(crate) enum ShellValue {
+    /// Partial prerendering is disabled or the future is inside of a dynamic region
+    None,
+    /// The value from the cached shell. It is only used for the first run of the future
+    Cached(Rc<RefCell<Option<Vec<u8>>>>),
+    /// The slot the value is recorded in so it can be cached with the shell
+    Record { values: ShellValues, index: usize },
+}
+
+#[cfg(feature = "server")]
+impl ShellValue {
+    /// Take the value of the future from the cached shell
+    pub(crate) fn take_cached<T: serde::de::DeserializeOwned>(&self) -> Option<T> {
+        match self {
+            Self::Cached(value) => ciborium::from_reader(value.take()?.as_slice()).ok(),
+            _ => None,
+        }
+    }
+
+    /// Record the value of the future so it can be cached with the shell
+    pub(crate) fn record<T: serde::Serialize>(&self, value: &T) {
+        if let Self::Record { values, index } = self {
+            let mut serialized = Vec::new();
+            ciborium::into_writer(value, &mut serialized).unwrap();
+            values.0.borrow_mut()[*index] = Some(serialized);
+        }
+    }
+}
+
+/// The recorded values of the server futures of a scope in the order their hooks run
+#[cfg(feature = "server")]
+#[derive(Clone, Default)]
+pub(crate) struct ShellValues(Rc<RefCell<Vec<Option<Vec<u8>>>>>);
+
+/// Get the slot for the value of a server future in the shell of the page
+#[cfg(feature = "server")]
+pub(crate) fn use_shell_value() -> ShellValue {
+    use_hook(|| {
+        let Some(shell) = try_consume_context::<ShellRender>() else {
+            return ShellValue::None;
+        };
+        if try_consume_context::<DynamicRegion>().is_some() {
+            return ShellValue::None;
+        }
+        match &shell.cached_values {
+            Some(cached) => ShellValue::Cached(Rc::new(RefCell::new(cached.borrow_mut().next()))),
+            None => {
+                let values = has_context::<ShellValues>()
+                    .unwrap_or_else(|| provide_context(ShellValues::default()));
+                let index = {
+                    let mut values = values.0.borrow_mut();
+                    values.push(None);
+                    values.len() - 1
+                };
+                ShellValue::Record { values, index }
+            }
+        }
+    })
+}
+
+/// Collect the recorded values of the server futures in the shell in the order their hooks run when nothing is
+/// suspended, which is the order they are taken in when the shell is served from the cache. Returns `None` if
+/// any of the futures is not resolved.
+#[cfg(feature = "server")]
+pub(crate) fn shell_values(vdom: &VirtualDom) -> Option<Vec<Vec<u8>>> {
+    use dioxus_lib::prelude::dioxus_core::DynamicNode;
+
+    fn visit_scope(vdom: &VirtualDom, scope: ScopeId, values: &mut Vec<Vec<u8>>) -> Option<()> {
+        if is_dynamic_region(vdom, scope) {
+            return Some(());
+        }
+        let recorded = vdom.in_runtime(|| scope.has_context::<ShellValues>());
+        if let Some(recorded) = recorded {
+            for value in recorded.0.borrow().iter() {
+                values.push(value.clone()?);
+            }
+        }
+        let scope = vdom.get_scope(scope)?;
+        if let Some(suspense_boundary) =
+            SuspenseContext::downcast_suspense_boundary_from_scope(&vdom.runtime(), scope.id())
+        {
+            if let Some(node) = suspense_boundary.suspended_nodes() {
+                visit_vnode(vdom, &node, values)?;
+            }
+        }
+        if let Some(node) = scope.try_root_node() {
+            visit_vnode(vdom, node, values)?;
+        }
+        Some(())
+    }
+
+    fn visit_vnode(vdom: &VirtualDom, vnode: &VNode, values: &mut Vec<Vec<u8>>) -> Option<()> {
+        for (index, node) in vnode.dynamic_nodes.iter().enumerate() {
+            match node {
+                DynamicNode::Component(component) => {
+                    if let Some(scope) = component.mounted_scope(index, vnode, vdom) {
+                        visit_scope(vdom, scope.id(), values)?;
+                    }
+                }
+                DynamicNode::Fragment(nodes) => {
+                    for node in nodes {
+                        visit_vnode(vdom, node, values)?;
+                    }
+                }
+                _ => {}
+            }
+        }
+        Some(())
+    }
+
+    let mut values = Vec::new();
+    visit_scope(vdom, ScopeId::ROOT, &mut values)?;
+    Some(values)
+}
+
+/// Returns true if the scope is the suspense boundary of a [`Dynamic`] region
+#[cfg(feature = "server")]
+pub(crate) fn is_dynamic_region(vdom: &VirtualDom, scope: ScopeId) -> bool {
+    vdom.in_runtime(|| {
+        scope
+            .parent_scope()
+            .and_then(|parent| parent.has_context::<DynamicRegion>())
+            .is_some()
+    })
+}
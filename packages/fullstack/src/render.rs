@@ -1,22 +1,29 @@
 //! A shared pool of renderers for efficient server side rendering.
 use crate::document::ServerDocument;
+use crate::hooks::cache_tag::CacheTags;
 use crate::html_storage::serialize::SerializedHydrationData;
+use crate::partial_prerender::{is_dynamic_region, shell_values, ShellData, ShellRender};
+use crate::render_timeout::SuspenseTimeouts;
 use crate::streaming::{Mount, StreamingRenderer};
 use dioxus_cli_config::base_path;
 use dioxus_interpreter_js::INITIALIZE_STREAMING_JS;
 use dioxus_isrg::{CachedRender, IncrementalRendererError, RenderFreshness};
 use dioxus_lib::document::Document;
 use dioxus_ssr::Renderer;
-use futures_channel::mpsc::Sender;
 use futures_util::{Stream, StreamExt};
 use std::fmt::Write;
 use std::rc::Rc;
 use std::sync::Arc;
-use std::sync::RwLock;
-use std::{collections::HashMap, future::Future};
+use std::sync::{Mutex, RwLock};
+use std::{
+    collections::{HashMap, HashSet},
+    future::Future,
+    time::Instant,
+};
 use tokio::task::JoinHandle;
 
 use crate::{prelude::*, StreamingMode};
+use dioxus_lib::prelude::dioxus_core::DynamicNode;
 use dioxus_lib::prelude::*;
 
 /// A suspense boundary that is pending with a placeholder in the client
@@ -52,7 +59,9 @@ where
 
 struct SsrRendererPool {
     renderers: RwLock<Vec<Renderer>>,
-    incremental_cache: Option<RwLock<dioxus_isrg::IncrementalRenderer>>,
+    incremental_cache: Option<Arc<dioxus_isrg::IncrementalRenderer>>,
+    /// Stale routes that are currently being re-rendered in the background
+    revalidating: Arc<Mutex<HashSet<String>>>,
 }
 
 impl SsrRendererPool {
@@ -63,44 +72,42 @@ impl SsrRendererPool {
         let renderers = RwLock::new((0..initial_size).map(|_| pre_renderer()).collect());
         Self {
             renderers,
-            incremental_cache: incremental.map(|cache| RwLock::new(cache.build())),
+            incremental_cache: incremental.map(|cache| Arc::new(cache.build())),
+            revalidating: Default::default(),
         }
     }
 
-    /// Look for a cached route in the incremental cache and send it into the render channel if it exists
-    fn check_cached_route(
+    /// Look for a cached route in the incremental cache
+    async fn check_cached_route(
         &self,
         route: &str,
-        render_into: &mut Sender<Result<String, dioxus_isrg::IncrementalRendererError>>,
-    ) -> Option<RenderFreshness> {
+    ) -> Option<(
+        RenderFreshness,
+        Result<String, dioxus_isrg::IncrementalRendererError>,
+    )> {
         if let Some(incremental) = &self.incremental_cache {
-            if let Ok(mut incremental) = incremental.write() {
-                match incremental.get(route) {
-                    Ok(Some(cached_render)) => {
-                        let CachedRender {
-                            freshness,
-                            response,
-                            ..
-                        } = cached_render;
-                        _ = render_into.start_send(String::from_utf8(response.to_vec()).map_err(
-                            |err| dioxus_isrg::IncrementalRendererError::Other(Box::new(err)),
-                        ));
-                        return Some(freshness);
-                    }
-                    Err(e) => {
-                        tracing::error!(
-                            "Failed to get route \"{route}\" from incremental cache: {e}"
-                        );
-                    }
-                    _ => {}
+            match incremental.get(route).await {
+                Ok(Some(cached_render)) => {
+                    let CachedRender {
+                        freshness,
+                        response,
+                        ..
+                    } = cached_render;
+                    let html = String::from_utf8(response)
+                        .map_err(|err| dioxus_isrg::IncrementalRendererError::Other(Box::new(err)));
+                    return Some((freshness, html));
                 }
+                Err(e) => {
+                    tracing::error!("Failed to get route \"{route}\" from incremental cache: {e}");
+                }
+                _ => {}
             }
         }
         None
     }
 
-    /// Render a virtual dom into a stream. This method will return immediately and continue streaming the result in the background
-    /// The streaming is canceled when the stream the function returns is dropped
+    /// Render a virtual dom into a stream. This method returns once the headers of the response are final and continues streaming
+    /// the result in the background. The streaming is canceled when the stream the function returns is dropped
     async fn render_to(
         self: Arc<Self>,
         cfg: &ServeConfig,
@@ -145,18 +152,50 @@ impl SsrRendererPool {
             Result<String, dioxus_isrg::IncrementalRendererError>,
         >(1000);
 
-        // before we even spawn anything, we can check synchronously if we have the route cached
-        if let Some(freshness) = self.check_cached_route(&route, &mut into) {
-            return Ok((
-                freshness,
-                ReceiverWithDrop {
-                    receiver: rx,
-                    cancel_task: None,
-                },
-            ));
+        let partial_prerendering = cfg.partial_prerendering && self.incremental_cache.is_some();
+
+        // before we even spawn anything, we can check if we have the route cached
+        // If a cached response is served, this holds its freshness and whether the render should keep running after the response is dropped
+        let mut cached_freshness = None;
+        let mut revalidation_guard = None;
+        let mut cached_shell = None;
+        if let Some((freshness, cached)) = self.check_cached_route(&route).await {
+            // If the route is stale, serve the stale render and re-render the route in the background.
+            revalidation_guard = freshness
+                .is_stale()
+                .then(|| RevalidationGuard::new(&self.revalidating, &route))
+                .flatten();
+            // With partial prerendering, the cached page may be a shell with dynamic regions that are rendered for every request
+            let shell = match &cached {
+                Ok(html) if partial_prerendering => split_shell(html),
+                _ => None,
+            };
+            if let Some((before_regions, shell)) = shell {
+                _ = into.start_send(Ok(before_regions));
+                cached_shell = Some(shell);
+            } else {
+                _ = into.start_send(cached);
+                if revalidation_guard.is_none() {
+                    return Ok((
+                        freshness,
+                        ReceiverWithDrop {
+                            receiver: rx,
+                            cancel_task: None,
+                        },
+                    ));
+                }
+                // The background render only updates the cache, so the output of the stream is thrown away
+                into = futures_channel::mpsc::channel(1000).0;
+            }
+            cached_freshness = Some((freshness, revalidation_guard.is_some()));
         }
+        // The shell is cached again if it was missing or stale. Otherwise it is served from the cache without
+        // rendering it again
+        let cache_shell = cached_shell.is_none() || revalidation_guard.is_some();
+        let cached_shell = cached_shell.map(|shell| (shell, !cache_shell));
 
         let wrapper = FullstackHTMLTemplate { cfg: cfg.clone() };
+        let mut timeouts = SuspenseTimeouts::new(route.clone(), cfg);
 
         let server_context = server_context.clone();
         let mut renderer = self
@@ -169,10 +208,19 @@ impl SsrRendererPool {
         let myself = self.clone();
         let streaming_mode = cfg.streaming_mode;
 
+        // Components can change the cookies and session of the response until the part of the page before any streaming
+        // is rendered. The render signals when it gets there so the caller doesn't write the headers too early
+        let (headers_final, headers_ready) = futures_channel::oneshot::channel::<()>();
+        let response_is_rendered = cached_freshness.is_none() || cached_shell.is_some();
+
         let join_handle = spawn_platform(move || async move {
+            // Keep the route marked as revalidating until the render is finished
+            let _revalidation_guard = revalidation_guard;
             let mut virtual_dom = virtual_dom_factory();
             let document = std::rc::Rc::new(crate::document::server::ServerDocument::default());
             virtual_dom.provide_root_context(document.clone());
+            let cache_tags = CacheTags::default();
+            virtual_dom.provide_root_context(cache_tags.clone());
             // If there is a base path, trim the base path from the route and add the base path formatting to the
             // history provider
             let history;
@@ -187,16 +235,65 @@ impl SsrRendererPool {
             }
             virtual_dom.provide_root_context(Rc::new(history) as Rc<dyn dioxus_history::History>);
             virtual_dom.provide_root_context(document.clone() as std::rc::Rc<dyn Document>);
+            // With partial prerendering, dynamic regions stay suspended until the shell is rendered
+            let mut cached_shell = cached_shell;
+            let release_regions = partial_prerendering.then(|| {
+                let cached_values = cached_shell
+                    .as_mut()
+                    .filter(|(_, replay)| *replay)
+                    .map(|(shell, _)| std::mem::take(&mut shell.data.values));
+                let (shell, release_regions) = ShellRender::new(cached_values);
+                virtual_dom.provide_root_context(shell);
+                release_regions
+            });
 
             // poll the future, which may call server_context()
             with_server_context(server_context.clone(), || virtual_dom.rebuild_in_place());
 
+            if let Some(release_regions) = release_regions {
+                _ = headers_final.send(());
+                let stream = Arc::new(StreamingRenderer::new("", into));
+                let scope_to_mount_mapping = Arc::new(RwLock::new(HashMap::new()));
+                renderer.pre_render = true;
+                renderer.set_render_components(streaming_render_component_callback(
+                    stream.clone(),
+                    scope_to_mount_mapping.clone(),
+                ));
+                let partial = PartialRender {
+                    cache: cache_shell
+                        .then(|| self.incremental_cache.clone().map(|cache| (cache, route)))
+                        .flatten(),
+                    cache_tags,
+                    cached_shell,
+                    release_regions,
+                    timeouts,
+                };
+                if let Err(err) = render_partial(
+                    &mut renderer,
+                    &mut virtual_dom,
+                    &stream,
+                    &scope_to_mount_mapping,
+                    &wrapper,
+                    &server_context,
+                    partial,
+                )
+                .await
+                {
+                    stream.close_with_error(err);
+                    return;
+                }
+
+                renderer.reset_render_components();
+                myself.renderers.write().unwrap().push(renderer);
+                return;
+            }
+
             // If streaming is disabled, wait for the virtual dom to finish all suspense work
-            // before rendering anything
+            // before rendering anything. Boundaries that time out are sent as fallbacks after the page
             if streaming_mode == StreamingMode::Disabled {
-                ProvideServerContext::new(virtual_dom.wait_for_suspense(), server_context.clone())
-                    .await
+                wait_for_suspense_with_timeouts(&mut virtual_dom, &server_context, &timeouts).await;
             }
+            _ = headers_final.send(());
 
             let mut pre_body = String::new();
 
@@ -225,71 +322,45 @@ impl SsrRendererPool {
                 };
             }
 
-            // Render the initial frame with loading placeholders
-            let mut initial_frame = renderer.render(&virtual_dom);
+            if streaming_mode == StreamingMode::InOrder {
+                // Stream the page in document order as suspense resolves. This resolves all suspense unless a boundary
+                // times out, so the out of order loop below is skipped
+                if let Err(err) = render_in_order(
+                    &mut renderer,
+                    &mut virtual_dom,
+                    &stream,
+                    &scope_to_mount_mapping,
+                    &wrapper,
+                    &server_context,
+                    &timeouts,
+                )
+                .await
+                {
+                    throw_error!(err);
+                }
+            } else {
+                // Render the initial frame with loading placeholders
+                let mut initial_frame = renderer.render(&virtual_dom);
 
-            // Along with the initial frame, we render the html after the main element, but before the body tag closes. This should include the script that starts loading the wasm bundle.
-            if let Err(err) = wrapper.render_after_main(&mut initial_frame, &virtual_dom) {
-                throw_error!(err);
+                // Along with the initial frame, we render the html after the main element, but before the body tag closes. This should include the script that starts loading the wasm bundle.
+                if let Err(err) = wrapper.render_after_main(&mut initial_frame, &virtual_dom) {
+                    throw_error!(err);
+                }
+                stream.render(initial_frame);
             }
-            stream.render(initial_frame);
 
             // After the initial render, we need to resolve suspense
-            while virtual_dom.suspended_tasks_remaining() {
-                ProvideServerContext::new(
-                    virtual_dom.wait_for_suspense_work(),
-                    server_context.clone(),
-                )
-                .await;
-                let resolved_suspense_nodes = ProvideServerContext::new(
-                    virtual_dom.render_suspense_immediate(),
-                    server_context.clone(),
-                )
-                .await;
-
-                // Just rerender the resolved nodes
-                for scope in resolved_suspense_nodes {
-                    let pending_suspense_boundary = {
-                        let mut lock = scope_to_mount_mapping.write().unwrap();
-                        lock.remove(&scope)
-                    };
-                    // If the suspense boundary was immediately removed, it may not have a mount. We can just skip resolving it
-                    if let Some(pending_suspense_boundary) = pending_suspense_boundary {
-                        let mut resolved_chunk = String::new();
-                        // After we replace the placeholder in the dom with javascript, we need to send down the resolved data so that the client can hydrate the node
-                        let render_suspense = |into: &mut String| {
-                            renderer.reset_hydration();
-                            renderer.render_scope(into, &virtual_dom, scope)
-                        };
-                        let resolved_data = serialize_server_data(&virtual_dom, scope);
-                        if let Err(err) = stream.replace_placeholder(
-                            pending_suspense_boundary.mount,
-                            render_suspense,
-                            resolved_data,
-                            &mut resolved_chunk,
-                        ) {
-                            throw_error!(dioxus_isrg::IncrementalRendererError::RenderError(err));
-                        }
-
-                        stream.render(resolved_chunk);
-                        // Freeze the suspense boundary to prevent future reruns of any child nodes of the suspense boundary
-                        if let Some(suspense) =
-                            SuspenseContext::downcast_suspense_boundary_from_scope(
-                                &virtual_dom.runtime(),
-                                scope,
-                            )
-                        {
-                            suspense.freeze();
-                            // Go to every child suspense boundary and add an error boundary. Since we cannot rerun any nodes above the child suspense boundary,
-                            // we need to capture the errors and send them to the client as it resolves
-                            virtual_dom.in_runtime(|| {
-                                for &suspense_scope in pending_suspense_boundary.children.iter() {
-                                    start_capturing_errors(suspense_scope);
-                                }
-                            });
-                        }
-                    }
-                }
+            if let Err(err) = resolve_suspense_out_of_order(
+                &mut renderer,
+                &mut virtual_dom,
+                &stream,
+                &scope_to_mount_mapping,
+                &server_context,
+                &mut timeouts,
+            )
+            .await
+            {
+                throw_error!(err);
             }
 
             // After suspense is done, we render the html after the body
@@ -299,8 +370,13 @@ impl SsrRendererPool {
                 throw_error!(err);
             }
 
-            // If incremental rendering is enabled, add the new render to the cache without the streaming bits
-            if let Some(incremental) = &self.incremental_cache {
+            // If incremental rendering is enabled, add the new render to the cache without the streaming bits. Pages
+            // where a suspense boundary timed out are incomplete, so they are not cached
+            if let Some(incremental) = self
+                .incremental_cache
+                .as_ref()
+                .filter(|_| !timeouts.timed_out())
+            {
                 let mut cached_render = String::new();
                 if let Err(err) = wrapper.render_head(&mut cached_render, &virtual_dom) {
                     throw_error!(err);
@@ -314,8 +390,11 @@ impl SsrRendererPool {
                 }
                 cached_render.push_str(&post_streaming);
 
-                if let Ok(mut incremental) = incremental.write() {
-                    let _ = incremental.cache(route, cached_render);
+                if let Err(err) = incremental
+                    .cache_with_tags(route, cached_render, cache_tags.take())
+                    .await
+                {
+                    tracing::error!("Failed to cache route in the incremental cache: {err}");
                 }
             }
 
@@ -325,16 +404,557 @@ impl SsrRendererPool {
             myself.renderers.write().unwrap().push(renderer);
         });
 
+        // A background revalidation of a cached response doesn't change the response. If the render fails before the headers
+        // are final, the sender is dropped and the error is streamed instead
+        if response_is_rendered {
+            _ = headers_ready.await;
+        }
+
+        // If we are revalidating a stale render, let the background render finish even if the response is dropped
+        let (freshness, cancel_task) = match cached_freshness {
+            Some((freshness, true)) => (freshness, None),
+            Some((freshness, false)) => (freshness, Some(join_handle)),
+            None => (RenderFreshness::now(None), Some(join_handle)),
+        };
+
         Ok((
-            RenderFreshness::now(None),
+            freshness,
             ReceiverWithDrop {
                 receiver: rx,
-                cancel_task: Some(join_handle),
+                cancel_task,
             },
         ))
     }
 }
 
+/// Marks a route as revalidating until the guard is dropped so a stale route is only re-rendered once at a time
+struct RevalidationGuard {
+    revalidating: Arc<Mutex<HashSet<String>>>,
+    route: String,
+}
+
+impl RevalidationGuard {
+    /// Returns `None` if the route is already being revalidated
+    fn new(revalidating: &Arc<Mutex<HashSet<String>>>, route: &str) -> Option<Self> {
+        revalidating
+            .lock()
+            .unwrap()
+            .insert(route.to_string())
+            .then(|| Self {
+                revalidating: revalidating.clone(),
+                route: route.to_string(),
+            })
+    }
+}
+
+impl Drop for RevalidationGuard {
+    fn drop(&mut self) {
+        self.revalidating.lock().unwrap().remove(&self.route);
+    }
+}
+
+/// Resolve the pending suspense boundaries of a page and stream the resolved content into the placeholders that
+/// were rendered for them
+async fn resolve_suspense_out_of_order(
+    renderer: &mut Renderer,
+    virtual_dom: &mut VirtualDom,
+    stream: &StreamingRenderer<IncrementalRendererError>,
+    scope_to_mount_mapping: &RwLock<HashMap<ScopeId, PendingSuspenseBoundary>>,
+    server_context: &DioxusServerContext,
+    timeouts: &mut SuspenseTimeouts,
+) -> Result<(), IncrementalRendererError> {
+    loop {
+        // Send the fallback of boundaries that timed out without their data so the client loads them instead
+        let expired: Vec<_> = scope_to_mount_mapping
+            .read()
+            .unwrap()
+            .keys()
+            .copied()
+            .filter(|&scope| timeouts.expired(virtual_dom, scope))
+            .collect();
+        for scope in expired {
+            let Some(pending_suspense_boundary) =
+                scope_to_mount_mapping.write().unwrap().remove(&scope)
+            else {
+                continue;
+            };
+            let mut fallback_chunk = String::new();
+            let render_fallback = |into: &mut String| {
+                renderer.reset_hydration();
+                renderer.render_scope(into, virtual_dom, scope)
+            };
+            let errors = crate::html_storage::HTMLData::extract_errors_from_suspense_boundary(
+                virtual_dom,
+                scope,
+            );
+            if let Err(err) = stream.replace_placeholder(
+                pending_suspense_boundary.mount,
+                render_fallback,
+                errors.serialized(),
+                &mut fallback_chunk,
+            ) {
+                return Err(dioxus_isrg::IncrementalRendererError::RenderError(err));
+            }
+            stream.render(fallback_chunk);
+            timeouts.report(virtual_dom, scope);
+        }
+
+        // The tasks of boundaries that timed out may still be running, but nothing in the page is waiting for them
+        let nothing_mounted = scope_to_mount_mapping.read().unwrap().is_empty();
+        if !virtual_dom.suspended_tasks_remaining() || (timeouts.timed_out() && nothing_mounted) {
+            break;
+        }
+
+        let deadline = timeouts.next_deadline(
+            virtual_dom,
+            scope_to_mount_mapping
+                .read()
+                .unwrap()
+                .keys()
+                .copied()
+                .collect::<Vec<_>>(),
+        );
+        if !wait_for_suspense_work_until(virtual_dom, server_context, deadline).await {
+            continue;
+        }
+        let resolved_suspense_nodes = ProvideServerContext::new(
+            virtual_dom.render_suspense_immediate(),
+            server_context.clone(),
+        )
+        .await;
+
+        // Just rerender the resolved nodes
+        for scope in resolved_suspense_nodes {
+            let pending_suspense_boundary = {
+                let mut lock = scope_to_mount_mapping.write().unwrap();
+                lock.remove(&scope)
+            };
+            // If the suspense boundary was immediately removed, it may not have a mount. We can just skip resolving it
+            if let Some(pending_suspense_boundary) = pending_suspense_boundary {
+                let mut resolved_chunk = String::new();
+                // After we replace the placeholder in the dom with javascript, we need to send down the resolved data so that the client can hydrate the node
+                let render_suspense = |into: &mut String| {
+                    renderer.reset_hydration();
+                    renderer.render_scope(into, virtual_dom, scope)
+                };
+                let resolved_data = serialize_server_data(virtual_dom, scope);
+                if let Err(err) = stream.replace_placeholder(
+                    pending_suspense_boundary.mount,
+                    render_suspense,
+                    resolved_data,
+                    &mut resolved_chunk,
+                ) {
+                    return Err(dioxus_isrg::IncrementalRendererError::RenderError(err));
+                }
+
+                stream.render(resolved_chunk);
+                // Freeze the suspense boundary to prevent future reruns of any child nodes of the suspense boundary
+                if let Some(suspense) = SuspenseContext::downcast_suspense_boundary_from_scope(
+                    &virtual_dom.runtime(),
+                    scope,
+                ) {
+                    suspense.freeze();
+                    // Go to every child suspense boundary and add an error boundary. Since we cannot rerun any nodes above the child suspense boundary,
+                    // we need to capture the errors and send them to the client as it resolves
+                    virtual_dom.in_runtime(|| {
+                        for &suspense_scope in pending_suspense_boundary.children.iter() {
+                            start_capturing_errors(suspense_scope);
+                        }
+                    });
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Wait for suspense work until the deadline passes. Returns false if the deadline passed first
+async fn wait_for_suspense_work_until(
+    virtual_dom: &mut VirtualDom,
+    server_context: &DioxusServerContext,
+    deadline: Option<Instant>,
+) -> bool {
+    let work =
+        ProvideServerContext::new(virtual_dom.wait_for_suspense_work(), server_context.clone());
+    match deadline {
+        Some(deadline) => tokio::time::timeout_at(deadline.into(), work).await.is_ok(),
+        None => {
+            work.await;
+            true
+        }
+    }
+}
+
+/// Wait until every suspense boundary in the page resolves or times out
+async fn wait_for_suspense_with_timeouts(
+    virtual_dom: &mut VirtualDom,
+    server_context: &DioxusServerContext,
+    timeouts: &SuspenseTimeouts,
+) {
+    while virtual_dom.suspended_tasks_remaining() {
+        let pending = pending_suspense_boundaries(virtual_dom);
+        let waiting: Vec<_> = pending
+            .iter()
+            .copied()
+            .filter(|&scope| !timeouts.expired(virtual_dom, scope))
+            .collect();
+        // Suspended tasks outside of the page still need to finish before hydration
+        if waiting.is_empty() && !pending.is_empty() {
+            break;
+        }
+        let deadline = timeouts.next_deadline(virtual_dom, waiting);
+        if wait_for_suspense_work_until(virtual_dom, server_context, deadline).await {
+            ProvideServerContext::new(
+                virtual_dom.render_suspense_immediate(),
+                server_context.clone(),
+            )
+            .await;
+        }
+    }
+}
+
+/// Collect the suspense boundaries that are pending in the page in document order. Boundaries inside of a pending
+/// boundary are not part of the page yet, so they are skipped
+fn pending_suspense_boundaries(virtual_dom: &VirtualDom) -> Vec<ScopeId> {
+    fn visit_scope(vdom: &VirtualDom, scope: ScopeId, pending: &mut Vec<ScopeId>) {
+        let is_pending_suspense_boundary =
+            SuspenseContext::downcast_suspense_boundary_from_scope(&vdom.runtime(), scope)
+                .filter(|s| s.has_suspended_tasks())
+                .is_some();
+        if is_pending_suspense_boundary {
+            pending.push(scope);
+        } else if let Some(node) = vdom.get_scope(scope).and_then(|s| s.try_root_node()) {
+            visit_vnode(vdom, node, pending);
+        }
+    }
+
+    fn visit_vnode(vdom: &VirtualDom, vnode: &VNode, pending: &mut Vec<ScopeId>) {
+        for (index, node) in vnode.dynamic_nodes.iter().enumerate() {
+            match node {
+                DynamicNode::Component(component) => {
+                    if let Some(scope) = component.mounted_scope(index, vnode, vdom) {
+                        visit_scope(vdom, scope.id(), pending);
+                    }
+                }
+                DynamicNode::Fragment(nodes) => {
+                    for node in nodes {
+                        visit_vnode(vdom, node, pending);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    let mut pending = Vec::new();
+    visit_scope(virtual_dom, ScopeId::ROOT, &mut pending);
+    pending
+}
+
+/// The marker a cached shell contains where the dynamic regions of the page are streamed in. It is followed by the
+/// encoded [`ShellData`] of the shell and the end of the comment
+const DYNAMIC_REGIONS_MARKER: &str = "<!--dx-dynamic-regions:";
+
+/// A shell that was served from the cache
+struct CachedShell {
+    /// The html after the dynamic regions
+    after_regions: String,
+    data: ShellData,
+}
+
+/// Split a cached shell into the html before the dynamic regions are streamed in and the rest of the shell. Returns
+/// `None` if the page doesn't have any dynamic regions
+fn split_shell(html: &str) -> Option<(String, CachedShell)> {
+    let (before, rest) = html.split_once(DYNAMIC_REGIONS_MARKER)?;
+    let (data, after) = rest.split_once("-->")?;
+    let shell = CachedShell {
+        after_regions: after.to_string(),
+        data: ShellData::decode(data)?,
+    };
+    Some((before.to_string(), shell))
+}
+
+/// The state of a page rendered with partial prerendering
+struct PartialRender {
+    /// The cache and route the shell is cached under if it needs to be cached
+    cache: Option<(Arc<dioxus_isrg::IncrementalRenderer>, String)>,
+    cache_tags: CacheTags,
+    /// The shell if it was already sent from the cache and whether the page is rendered from the cached data
+    /// instead of rendering the shell again
+    cached_shell: Option<(CachedShell, bool)>,
+    /// Dropping this sender lets the dynamic regions render
+    release_regions: tokio::sync::watch::Sender<()>,
+    timeouts: SuspenseTimeouts,
+}
+
+/// Render a page with partial prerendering. Everything except the dynamic regions is resolved and rendered into
+/// a shell that is cached. The dynamic regions are then rendered for this request and streamed into the shell
+/// out of order.
+///
+/// If the shell is served from the cache, the server futures in it take the values the shell was cached with and
+/// the dynamic regions are streamed into the placeholders the shell was cached with, so the shell isn't rendered
+/// again. A stale shell is rendered again to update the cache.
+async fn render_partial(
+    renderer: &mut Renderer,
+    virtual_dom: &mut VirtualDom,
+    stream: &StreamingRenderer<IncrementalRendererError>,
+    scope_to_mount_mapping: &RwLock<HashMap<ScopeId, PendingSuspenseBoundary>>,
+    wrapper: &FullstackHTMLTemplate,
+    server_context: &DioxusServerContext,
+    partial: PartialRender,
+) -> Result<(), IncrementalRendererError> {
+    let PartialRender {
+        cache,
+        cache_tags,
+        cached_shell,
+        release_regions,
+        mut timeouts,
+    } = partial;
+    // Resolve everything except the dynamic regions which stay suspended. If the shell was already sent from the
+    // cache, the page must match it, so the timeouts only apply while the shell is rendered for the first time
+    let shell_sent = cached_shell.is_some();
+    let mut pending = pending_suspense_boundaries(virtual_dom);
+    loop {
+        let waiting: Vec<_> = pending
+            .iter()
+            .copied()
+            .filter(|&scope| !is_dynamic_region(virtual_dom, scope))
+            .filter(|&scope| shell_sent || !timeouts.expired(virtual_dom, scope))
+            .collect();
+        if waiting.is_empty() {
+            break;
+        }
+        let deadline = match shell_sent {
+            true => None,
+            false => timeouts.next_deadline(virtual_dom, waiting),
+        };
+        if wait_for_suspense_work_until(virtual_dom, server_context, deadline).await {
+            ProvideServerContext::new(
+                virtual_dom.render_suspense_immediate(),
+                server_context.clone(),
+            )
+            .await;
+        }
+        pending = pending_suspense_boundaries(virtual_dom);
+    }
+    let regions: Vec<_> = pending
+        .iter()
+        .copied()
+        .filter(|&scope| is_dynamic_region(virtual_dom, scope))
+        .collect();
+
+    let after_regions = match cached_shell {
+        Some((shell, true)) => {
+            // Mount the dynamic regions in the placeholders of the cached shell
+            if regions.len() != shell.data.regions.len() {
+                return Err(IncrementalRendererError::Other(
+                    "the dynamic regions of the page don't match the cached shell".into(),
+                ));
+            }
+            let mut scope_to_mount_mapping = scope_to_mount_mapping.write().unwrap();
+            for (&scope, path) in regions.iter().zip(&shell.data.regions) {
+                let mount = Mount::from_path(path).ok_or_else(|| {
+                    IncrementalRendererError::Other("the cached shell is invalid".into())
+                })?;
+                scope_to_mount_mapping.insert(
+                    scope,
+                    PendingSuspenseBoundary {
+                        mount,
+                        children: vec![],
+                    },
+                );
+                virtual_dom.in_runtime(|| start_capturing_errors(scope));
+            }
+            shell.after_regions
+        }
+        cached_shell => {
+            // A shell with boundaries that timed out is incomplete, so it is not cached
+            let shell_complete = pending.len() == regions.len();
+
+            // Render the shell with the fallback of each dynamic region. This mounts a placeholder for every region
+            let mut shell = String::new();
+            wrapper.render_head(&mut shell, virtual_dom)?;
+            renderer.reset_hydration();
+            renderer.render_to(&mut shell, virtual_dom)?;
+            wrapper.render_after_main(&mut shell, virtual_dom)?;
+            let mut after_body = String::new();
+            wrapper.render_after_body(&mut after_body)?;
+
+            if let Some((incremental, route)) = cache.filter(|_| shell_complete) {
+                cache_shell(
+                    virtual_dom,
+                    &incremental,
+                    route,
+                    &shell,
+                    &after_body,
+                    &regions,
+                    scope_to_mount_mapping,
+                    cache_tags,
+                )
+                .await;
+            }
+
+            match cached_shell {
+                Some((shell, _)) => shell.after_regions,
+                None => {
+                    stream.render(shell);
+                    after_body
+                }
+            }
+        }
+    };
+
+    // Let the dynamic regions render for this request and stream them into their placeholders
+    drop(release_regions);
+    resolve_suspense_out_of_order(
+        renderer,
+        virtual_dom,
+        stream,
+        scope_to_mount_mapping,
+        server_context,
+        &mut timeouts,
+    )
+    .await?;
+
+    stream.render(after_regions);
+
+    Ok(())
+}
+
+/// Cache a rendered shell along with the values of the server futures in it and the placeholders of the dynamic
+/// regions
+#[allow(clippy::too_many_arguments)]
+async fn cache_shell(
+    virtual_dom: &VirtualDom,
+    incremental: &dioxus_isrg::IncrementalRenderer,
+    route: String,
+    shell: &str,
+    after_body: &str,
+    regions: &[ScopeId],
+    scope_to_mount_mapping: &RwLock<HashMap<ScopeId, PendingSuspenseBoundary>>,
+    cache_tags: CacheTags,
+) {
+    let mut cached_shell = shell.to_string();
+    if !regions.is_empty() {
+        // Futures that are still running can't be replayed from the cache
+        let Some(values) = shell_values(virtual_dom) else {
+            return;
+        };
+        let scope_to_mount_mapping = scope_to_mount_mapping.read().unwrap();
+        let regions = regions
+            .iter()
+            .filter_map(|scope| scope_to_mount_mapping.get(scope))
+            .map(|boundary| boundary.mount.path())
+            .collect();
+        let data = ShellData { values, regions };
+        _ = write!(cached_shell, "{DYNAMIC_REGIONS_MARKER}{}-->", data.encode());
+    }
+    cached_shell.push_str(after_body);
+    if let Err(err) = incremental
+        .cache_with_tags(route, cached_shell, cache_tags.take())
+        .await
+    {
+        tracing::error!("Failed to cache route in the incremental cache: {err}");
+    }
+}
+
+/// A marker the in order renderer writes in place of suspense boundaries that are still pending
+const PENDING_SUSPENSE_MARKER: &str = "<!--dx-pending-suspense-->";
+
+/// Stream the body of the page in document order. Everything before the first pending suspense boundary is
+/// sent immediately and the rest of the page is sent as the suspense boundaries resolve.
+///
+/// The whole page is rendered again every time suspense resolves so the hydration ids match a page that was
+/// rendered in one pass, and the client can hydrate it the same way as a page rendered without streaming.
+///
+/// If a suspense boundary times out, the rest of the page is sent with placeholders for the pending boundaries and
+/// they are resolved out of order instead. If content that was already sent changes when a boundary resolves, the
+/// render fails because the client can't hydrate the page.
+async fn render_in_order(
+    renderer: &mut Renderer,
+    virtual_dom: &mut VirtualDom,
+    stream: &Arc<StreamingRenderer<IncrementalRendererError>>,
+    scope_to_mount_mapping: &Arc<RwLock<HashMap<ScopeId, PendingSuspenseBoundary>>>,
+    wrapper: &FullstackHTMLTemplate,
+    server_context: &DioxusServerContext,
+    timeouts: &SuspenseTimeouts,
+) -> Result<(), IncrementalRendererError> {
+    renderer.set_render_components(|renderer, to, vdom, scope| {
+        let is_pending_suspense_boundary =
+            SuspenseContext::downcast_suspense_boundary_from_scope(&vdom.runtime(), scope)
+                .filter(|s| s.has_suspended_tasks())
+                .is_some();
+        if is_pending_suspense_boundary {
+            to.write_str(PENDING_SUSPENSE_MARKER)
+        } else {
+            renderer.render_scope(to, vdom, scope)
+        }
+    });
+
+    let mut sent = String::new();
+    loop {
+        let mut frame = String::new();
+        renderer.render_to(&mut frame, virtual_dom)?;
+        if !frame.starts_with(&sent) {
+            // The part of the page the client already has is out of date. It can't be patched without a
+            // placeholder to replace, so fail the render instead of sending a page that can't hydrate
+            tracing::error!("Content before a pending suspense boundary changed while streaming in order. Only components after a suspense boundary may change when it resolves. Use out of order streaming if earlier content depends on the boundary");
+            return Err(IncrementalRendererError::Other(
+                "content before a pending suspense boundary changed while streaming in order"
+                    .into(),
+            ));
+        }
+
+        match frame.find(PENDING_SUSPENSE_MARKER) {
+            // Send everything up to the first pending suspense boundary and wait for more suspense to resolve
+            Some(ready) => {
+                if let Some(chunk) = frame.get(sent.len()..ready) {
+                    stream.render(chunk);
+                    sent.push_str(chunk);
+                }
+                let pending = pending_suspense_boundaries(virtual_dom);
+                if pending
+                    .iter()
+                    .any(|&scope| timeouts.expired(virtual_dom, scope))
+                {
+                    // Send the rest of the page with placeholders that the out of order loop fills in
+                    renderer.set_render_components(streaming_render_component_callback(
+                        stream.clone(),
+                        scope_to_mount_mapping.clone(),
+                    ));
+                    let mut frame = String::new();
+                    renderer.render_to(&mut frame, virtual_dom)?;
+                    let mut rest = frame.get(sent.len()..).unwrap_or_default().to_string();
+                    wrapper.render_after_main(&mut rest, virtual_dom)?;
+                    stream.render(rest);
+                    return Ok(());
+                }
+                let deadline = timeouts.next_deadline(virtual_dom, pending);
+                if wait_for_suspense_work_until(virtual_dom, server_context, deadline).await {
+                    ProvideServerContext::new(
+                        virtual_dom.render_suspense_immediate(),
+                        server_context.clone(),
+                    )
+                    .await;
+                }
+            }
+            // Suspended tasks that are not rendered don't block the page, but they still need to finish before hydration
+            None if virtual_dom.suspended_tasks_remaining() => {
+                ProvideServerContext::new(virtual_dom.wait_for_suspense(), server_context.clone())
+                    .await;
+            }
+            // Everything is resolved. Send the rest of the page along with the hydration data
+            None => {
+                let mut rest = frame.get(sent.len()..).unwrap_or_default().to_string();
+                wrapper.render_after_main(&mut rest, virtual_dom)?;
+                stream.render(rest);
+                return Ok(());
+            }
+        }
+    }
+}
+
 /// Create the streaming render component callback. It will keep track of what scopes are mounted to what pending
 /// suspense boundaries in the DOM.
 ///
@@ -435,6 +1055,11 @@ impl SSRState {
         }
     }
 
+    /// Get the incremental renderer used to cache routes or `None` if incremental rendering is disabled.
+    pub fn incremental_renderer(&self) -> Option<Arc<dioxus_isrg::IncrementalRenderer>> {
+        self.renderers.incremental_cache.clone()
+    }
+
     /// Render the application to HTML.
     pub async fn render<'a>(
         &'a self,
@@ -591,3 +1216,304 @@ fn pre_renderer() -> Renderer {
     renderer.pre_render = true;
     renderer
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn app() -> Element {
+        rsx! {
+            h1 { "Static" }
+            Dynamic { fallback: |_| rsx! { "Loading" }, Greeting {} }
+        }
+    }
+
+    #[component]
+    fn Greeting() -> Element {
+        let name = use_server_future(|| async { "Alice".to_string() })?;
+        rsx! { "Hello {name.cloned().unwrap_or_default()}" }
+    }
+
+    static TITLE_LOADS: std::sync::atomic::AtomicUsize = std::sync::atomic::AtomicUsize::new(0);
+    static VISITS: std::sync::atomic::AtomicUsize = std::sync::atomic::AtomicUsize::new(0);
+
+    fn shell_app() -> Element {
+        let title = use_server_future(|| async {
+            TITLE_LOADS.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            "Static".to_string()
+        })?;
+        rsx! {
+            h1 { "{title.cloned().unwrap_or_default()}" }
+            Dynamic { fallback: |_| rsx! { "Loading" }, Visitor {} }
+        }
+    }
+
+    #[component]
+    fn Visitor() -> Element {
+        let visit = use_server_future(|| async {
+            VISITS.fetch_add(1, std::sync::atomic::Ordering::SeqCst)
+        })?;
+        rsx! { "Visit {visit.cloned().unwrap_or_default()}" }
+    }
+
+    fn slow_app() -> Element {
+        rsx! {
+            h1 { "Product" }
+            TimeoutBoundary {
+                timeout: std::time::Duration::from_millis(50),
+                name: "recommendations",
+                fallback: |_| rsx! { "Loading recommendations" },
+                Recommendations {}
+            }
+        }
+    }
+
+    #[component]
+    fn Recommendations() -> Element {
+        let items = use_server_future(std::future::pending::<String>)?;
+        rsx! { "{items.cloned().unwrap_or_default()}" }
+    }
+
+    /// Decode the hydration data that follows `prefix` in a page
+    fn hydration_data(page: &str, prefix: &str) -> Vec<Option<Vec<u8>>> {
+        use base64::Engine;
+        let (_, data) = page.split_once(prefix).unwrap();
+        let (data, _) = data.split_once('"').unwrap();
+        let data = base64::engine::general_purpose::STANDARD
+            .decode(data)
+            .unwrap();
+        ciborium::from_reader(&data[..]).unwrap()
+    }
+
+    async fn render_route(state: &SSRState, cfg: &ServeConfig) -> Vec<String> {
+        render_app(state, cfg, shell_app).await
+    }
+
+    async fn render_app(state: &SSRState, cfg: &ServeConfig, app: fn() -> Element) -> Vec<String> {
+        let server_context = DioxusServerContext::default();
+        let (_, stream) = state
+            .render(
+                "/".to_string(),
+                cfg,
+                move || VirtualDom::new(app),
+                &server_context,
+            )
+            .await
+            .unwrap();
+        stream.map(|chunk| chunk.unwrap()).collect().await
+    }
+
+    #[tokio::test]
+    async fn partial_prerendering_streams_dynamic_regions_into_cached_shell() {
+        let static_dir = tempfile::tempdir().unwrap();
+        let cfg = ServeConfigBuilder::new()
+            .index_html(r#"<html><head></head><body><div id="main"></div></body></html>"#.into())
+            .incremental(
+                dioxus_isrg::IncrementalRendererConfig::new()
+                    .static_dir(static_dir.path())
+                    .clear_cache(true),
+            )
+            .enable_partial_prerendering()
+            .build()
+            .unwrap();
+        let state = SSRState::new(&cfg);
+
+        // The first render caches the shell. Every render streams the dynamic region into it
+        let first = render_route(&state, &cfg).await.concat();
+        let second = render_route(&state, &cfg).await.concat();
+        for (visit, page) in [&first, &second].into_iter().enumerate() {
+            assert!(page.contains("Static"));
+            assert!(page.contains("Loading"));
+            assert!(page.contains(r#"<div id="ds-0-r" hidden>"#));
+            assert!(page.contains(&format!("Visit {visit}")));
+            assert!(page.ends_with("</body></html>"));
+            assert!(!page.contains(DYNAMIC_REGIONS_MARKER));
+        }
+        // The cached shell is the same as the rendered shell
+        let shell = |page: &str| {
+            page.split(r#"<div id="ds-0-r""#)
+                .next()
+                .unwrap()
+                .to_string()
+        };
+        assert_eq!(shell(&first), shell(&second));
+
+        // The futures in the shell only run when the shell is rendered
+        assert_eq!(TITLE_LOADS.load(std::sync::atomic::Ordering::SeqCst), 1);
+
+        let cached = state
+            .incremental_renderer()
+            .unwrap()
+            .get("/")
+            .await
+            .unwrap();
+        let cached = String::from_utf8(cached.unwrap().response).unwrap();
+        assert!(cached.contains(DYNAMIC_REGIONS_MARKER));
+        assert!(!cached.contains("Visit"));
+        let (_, shell) = split_shell(&cached).unwrap();
+        assert_eq!(shell.data.values.len(), 1);
+        assert_eq!(shell.data.regions, [vec![0]]);
+    }
+
+    #[component]
+    fn Product(id: u64) -> Element {
+        use_cache_tag(format!("product:{id}"));
+        use_cache_tag("products");
+        rsx! { "Product {id}" }
+    }
+
+    #[tokio::test]
+    async fn cache_tags_invalidate_tagged_routes() {
+        let cache = Arc::new(dioxus_isrg::InMemoryCache::new(10));
+        let cfg = ServeConfigBuilder::new()
+            .index_html(r#"<html><head></head><body><div id="main"></div></body></html>"#.into())
+            .incremental(
+                dioxus_isrg::IncrementalRendererConfig::new()
+                    .cache_backends([cache.clone() as Arc<dyn dioxus_isrg::RenderCache>]),
+            )
+            .build()
+            .unwrap();
+        let state = SSRState::new(&cfg);
+
+        for id in [1, 2] {
+            let (_, stream) = state
+                .render(
+                    format!("/product/{id}"),
+                    &cfg,
+                    move || VirtualDom::new_with_props(Product, ProductProps { id }),
+                    &DioxusServerContext::default(),
+                )
+                .await
+                .unwrap();
+            let _: Vec<_> = stream.collect().await;
+        }
+        render_app(&state, &cfg, app).await;
+
+        let tags = |route: &'static str| {
+            let cache = cache.clone();
+            async move {
+                dioxus_isrg::RenderCache::get(&*cache, route)
+                    .await
+                    .unwrap()
+                    .map(|entry| entry.tags)
+            }
+        };
+        assert_eq!(tags("/product/1").await.unwrap(), ["product:1", "products"]);
+        assert_eq!(tags("/product/2").await.unwrap(), ["product:2", "products"]);
+        assert!(tags("/").await.unwrap().is_empty());
+
+        let renderer = state.incremental_renderer().unwrap();
+        renderer.invalidate_tag("product:1").await.unwrap();
+        assert!(tags("/product/1").await.is_none());
+        assert!(tags("/product/2").await.is_some());
+        assert!(tags("/").await.is_some());
+
+        renderer.invalidate_tag("products").await.unwrap();
+        assert!(tags("/product/2").await.is_none());
+        assert!(tags("/").await.is_some());
+    }
+
+    fn changing_prefix_app() -> Element {
+        let status = use_signal(|| "loading".to_string());
+        use_context_provider(|| status);
+        rsx! {
+            h1 { "Status: {status}" }
+            SuspenseBoundary { fallback: |_| rsx! { "Loading" }, UpdatesStatus {} }
+        }
+    }
+
+    #[component]
+    fn UpdatesStatus() -> Element {
+        let value = use_server_future(|| async {
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+            "ready".to_string()
+        })?;
+        let mut status = use_context::<Signal<String>>();
+        use_hook(move || status.set("ready".to_string()));
+        rsx! { "{value.cloned().unwrap_or_default()}" }
+    }
+
+    #[tokio::test]
+    async fn in_order_streaming_fails_if_sent_content_changes() {
+        let cfg = ServeConfigBuilder::new()
+            .index_html(r#"<html><head></head><body><div id="main"></div></body></html>"#.into())
+            .streaming_mode(StreamingMode::InOrder)
+            .build()
+            .unwrap();
+        let state = SSRState::new(&cfg);
+        let (_, stream) = state
+            .render(
+                "/".to_string(),
+                &cfg,
+                || VirtualDom::new(changing_prefix_app),
+                &DioxusServerContext::default(),
+            )
+            .await
+            .unwrap();
+        let chunks: Vec<_> = stream.collect().await;
+
+        let sent: String = chunks
+            .iter()
+            .filter_map(|chunk| chunk.as_ref().ok())
+            .cloned()
+            .collect();
+        assert!(sent.contains("Status: loading"));
+        assert!(!sent.contains("</body></html>"));
+        assert!(chunks.last().unwrap().is_err());
+
+        // The same page renders fine when the boundary is streamed out of order
+        let cfg = ServeConfigBuilder::new()
+            .index_html(r#"<html><head></head><body><div id="main"></div></body></html>"#.into())
+            .build()
+            .unwrap();
+        let state = SSRState::new(&cfg);
+        let page = render_app(&state, &cfg, changing_prefix_app).await.concat();
+        assert!(page.ends_with("</body></html>"));
+    }
+
+    #[tokio::test]
+    async fn timed_out_boundaries_send_their_fallback() {
+        for mode in [
+            StreamingMode::Disabled,
+            StreamingMode::InOrder,
+            StreamingMode::OutOfOrder,
+        ] {
+            let timed_out = Arc::new(Mutex::new(Vec::new()));
+            let cfg = ServeConfigBuilder::new()
+                .index_html(
+                    r#"<html><head></head><body><div id="main"></div></body></html>"#.into(),
+                )
+                .streaming_mode(mode)
+                .on_render_timeout({
+                    let timed_out = timed_out.clone();
+                    move |boundary| timed_out.lock().unwrap().push(boundary.clone())
+                })
+                .build()
+                .unwrap();
+            let state = SSRState::new(&cfg);
+
+            let page = render_app(&state, &cfg, slow_app).await.concat();
+            assert!(page.contains("Product"));
+            assert!(page.contains("Loading recommendations"));
+            assert!(page.contains("dx_hydrate([0]"));
+            assert!(page.ends_with("</body></html>"));
+
+            // The server future under the boundary never resolved, so its entry is sent without data and the
+            // client runs the future itself
+            let initial = hydration_data(&page, "initial_dioxus_hydration_data=\"");
+            let boundary = hydration_data(&page, "dx_hydrate([0], \"");
+            assert_eq!(initial.last(), Some(&None));
+            assert!(initial
+                .iter()
+                .chain(&boundary)
+                .flatten()
+                .all(|entry| ciborium::from_reader::<String, _>(&entry[..]).is_err()));
+
+            let timed_out = timed_out.lock().unwrap();
+            assert_eq!(timed_out.len(), 1);
+            assert_eq!(timed_out[0].route, "/");
+            assert_eq!(timed_out[0].name.as_deref(), Some("recommendations"));
+            assert!(timed_out[0].elapsed >= std::time::Duration::from_millis(50));
+        }
+    }
+}
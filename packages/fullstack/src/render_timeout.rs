@@ -0,0 +1,167 @@
+//! Timeouts for suspense boundaries while a page is rendered on the server.
+//!
+//! When a suspense boundary takes longer than its timeout, the server stops waiting for it and sends
+//! the fallback of the boundary without any hydration data. The client then runs the futures under the
+//! boundary itself, the same way it does when a component is first rendered on the client.
+
+use dioxus_lib::prelude::*;
+use std::time::Duration;
+
+/// A [`SuspenseBoundary`] the server stops waiting for after `timeout`.
+///
+/// If the children are still suspended when the timeout passes, the server sends the fallback and the
+/// client finishes loading the children. The timeout is measured from the start of the render. Every
+/// boundary is also limited by [`ServeConfigBuilder::render_timeout`](crate::ServeConfigBuilder::render_timeout)
+/// if it is set. On the client, this works like a normal [`SuspenseBoundary`].
+///
+/// # Example
+///
+/// ```rust, no_run
+/// # use dioxus::prelude::*;
+/// # use std::time::Duration;
+/// fn App() -> Element {
+///     rsx! {
+///         h1 { "Product" }
+///         TimeoutBoundary {
+///             timeout: Duration::from_millis(200),
+///             name: "recommendations",
+///             fallback: |_| rsx! { "Loading recommendations..." },
+///             Recommendations {}
+///         }
+///     }
+/// }
+///
+/// fn Recommendations() -> Element {
+///     let items = use_server_future(|| async {
+///         // A slow request to the recommendation service...
+///         vec!["Socks".to_string()]
+///     })?;
+///     rsx! { "{items:?}" }
+/// }
+/// ```
+#[component]
+pub fn TimeoutBoundary(
+    /// How long the server waits for the children before sending the fallback
+    timeout: Duration,
+    /// The name of the boundary that is passed to [`ServeConfigBuilder::on_render_timeout`](crate::ServeConfigBuilder::on_render_timeout)
+    #[props(into)]
+    name: Option<String>,
+    fallback: Callback<SuspenseContext, Element>,
+    children: Element,
+) -> Element {
+    #[cfg(feature = "server")]
+    use_context_provider(|| BoundaryTimeout {
+        timeout,
+        name: name.clone(),
+    });
+    #[cfg(not(feature = "server"))]
+    let _ = (timeout, name);
+
+    rsx! {
+        SuspenseBoundary { fallback, {children} }
+    }
+}
+
+/// The timeout of a [`TimeoutBoundary`] stored in the scope that renders the suspense boundary
+#[cfg(feature = "server")]
+#[derive(Clone)]
+struct BoundaryTimeout {
+    timeout: Duration,
+    name: Option<String>,
+}
+
+/// A suspense boundary the server stopped waiting for. This is passed to
+/// [`ServeConfigBuilder::on_render_timeout`](crate::ServeConfigBuilder::on_render_timeout).
+#[cfg(feature = "server")]
+#[derive(Clone, Debug)]
+pub struct TimedOutBoundary {
+    /// The route that was rendered
+    pub route: String,
+    /// The name of the [`TimeoutBoundary`] or `None` for other suspense boundaries
+    pub name: Option<String>,
+    /// How long the server waited for the boundary
+    pub elapsed: Duration,
+}
+
+/// Tracks the timeouts of the suspense boundaries of one render
+#[cfg(feature = "server")]
+pub(crate) struct SuspenseTimeouts {
+    route: String,
+    started: std::time::Instant,
+    render_timeout: Option<Duration>,
+    on_timeout: Option<crate::RenderTimeoutCallback>,
+    timed_out: bool,
+}
+
+#[cfg(feature = "server")]
+impl SuspenseTimeouts {
+    pub(crate) fn new(route: String, cfg: &crate::ServeConfig) -> Self {
+        Self {
+            route,
+            started: std::time::Instant::now(),
+            render_timeout: cfg.render_timeout,
+            on_timeout: cfg.on_render_timeout.clone(),
+            timed_out: false,
+        }
+    }
+
+    /// Returns true if any suspense boundary timed out during the render
+    pub(crate) fn timed_out(&self) -> bool {
+        self.timed_out
+    }
+
+    fn boundary_timeout(vdom: &VirtualDom, scope: ScopeId) -> Option<BoundaryTimeout> {
+        vdom.in_runtime(|| {
+            scope
+                .parent_scope()
+                .and_then(|parent| parent.has_context::<BoundaryTimeout>())
+        })
+    }
+
+    /// The time the server stops waiting for a suspense boundary
+    pub(crate) fn deadline(&self, vdom: &VirtualDom, scope: ScopeId) -> Option<std::time::Instant> {
+        let boundary = Self::boundary_timeout(vdom, scope).map(|boundary| boundary.timeout);
+        let timeout = match (boundary, self.render_timeout) {
+            (Some(boundary), Some(render)) => boundary.min(render),
+            (timeout, None) | (None, timeout) => timeout?,
+        };
+        Some(self.started + timeout)
+    }
+
+    /// The earliest deadline of a list of suspense boundaries
+    pub(crate) fn next_deadline(
+        &self,
+        vdom: &VirtualDom,
+        scopes: impl IntoIterator<Item = ScopeId>,
+    ) -> Option<std::time::Instant> {
+        scopes
+            .into_iter()
+            .filter_map(|scope| self.deadline(vdom, scope))
+            .min()
+    }
+
+    /// Returns true if the server should stop waiting for a suspense boundary
+    pub(crate) fn expired(&self, vdom: &VirtualDom, scope: ScopeId) -> bool {
+        self.deadline(vdom, scope)
+            .is_some_and(|deadline| deadline <= std::time::Instant::now())
+    }
+
+    /// Record that the server stopped waiting for a suspense boundary
+    pub(crate) fn report(&mut self, vdom: &VirtualDom, scope: ScopeId) {
+        self.timed_out = true;
+        let boundary = TimedOutBoundary {
+            route: self.route.clone(),
+            name: Self::boundary_timeout(vdom, scope).and_then(|boundary| boundary.name),
+            elapsed: self.started.elapsed(),
+        };
+        tracing::warn!(
+            "Stopped waiting for suspense boundary {} in route {} after {:?}. The client will finish loading it",
+            boundary.name.as_deref().unwrap_or("(unnamed)"),
+            boundary.route,
+            boundary.elapsed
+        );
+        if let Some(on_timeout) = &self.on_timeout {
+            on_timeout(&boundary);
+        }
+    }
+}
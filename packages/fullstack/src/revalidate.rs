@@ -0,0 +1,150 @@
+//! On-demand revalidation of incrementally rendered pages.
+
+use std::sync::Arc;
+
+use dioxus_isrg::IncrementalRenderer;
+use server_fn::ServerFnError;
+use subtle::ConstantTimeEq;
+
+use crate::prelude::server_context;
+
+/// The incremental renderer of the server. This is inserted into the server context so server
+/// functions can revalidate pages after a mutation.
+#[derive(Clone)]
+pub(crate) struct IncrementalRendererHandle(pub(crate) Arc<IncrementalRenderer>);
+
+/// The options for the revalidation endpoint set with [`ServeConfigBuilder::revalidation_endpoint`](crate::ServeConfigBuilder::revalidation_endpoint).
+#[derive(Clone)]
+pub(crate) struct RevalidationEndpoint {
+    pub(crate) path: String,
+    pub(crate) token: String,
+}
+
+impl RevalidationEndpoint {
+    /// Check the value of the `Authorization` header against the token of the endpoint
+    pub(crate) fn is_authorized(&self, authorization: Option<&str>) -> bool {
+        let Some(token) = authorization.and_then(|value| value.strip_prefix("Bearer ")) else {
+            return false;
+        };
+        // Compare in constant time so the time the comparison takes doesn't leak the token
+        token.as_bytes().ct_eq(self.token.as_bytes()).into()
+    }
+}
+
+/// Remove a route from the incremental cache so the next request renders it again. Call this from a
+/// server function after a mutation changes the content of a page.
+///
+/// This does nothing if incremental rendering is disabled or if it is called outside of a server
+/// function or a server render.
+///
+/// ```rust, no_run
+/// # use dioxus::prelude::*;
+/// #[server]
+/// async fn publish_post(id: u64) -> Result<(), ServerFnError> {
+///     // Save the post...
+///     revalidate_path(format!("/blog/{id}")).await?;
+///     revalidate_path("/blog").await
+/// }
+/// ```
+pub async fn revalidate_path(path: impl AsRef<str>) -> Result<(), ServerFnError> {
+    let Some(IncrementalRendererHandle(renderer)) = server_context().get() else {
+        return Ok(());
+    };
+    renderer
+        .invalidate(path.as_ref())
+        .await
+        .map_err(|err| ServerFnError::ServerError(err.to_string()))
+}
+
+/// Remove every route that was rendered with a cache tag from the incremental cache. Tags are
+/// attached to a page with [`use_cache_tag`](crate::prelude::use_cache_tag).
+///
+/// This does nothing if incremental rendering is disabled or if it is called outside of a server
+/// function or a server render.
+///
+/// ```rust, no_run
+/// # use dioxus::prelude::*;
+/// #[server]
+/// async fn update_product(id: u64, price: u64) -> Result<(), ServerFnError> {
+///     // Save the price...
+///     revalidate_tag(format!("product:{id}")).await
+/// }
+/// ```
+pub async fn revalidate_tag(tag: impl AsRef<str>) -> Result<(), ServerFnError> {
+    let Some(IncrementalRendererHandle(renderer)) = server_context().get() else {
+        return Ok(());
+    };
+    renderer
+        .invalidate_tag(tag.as_ref())
+        .await
+        .map_err(|err| ServerFnError::ServerError(err.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::{DioxusServerContext, ProvideServerContext};
+
+    #[test]
+    fn tokens_are_checked() {
+        let endpoint = RevalidationEndpoint {
+            path: "/api/revalidate".to_string(),
+            token: "secret".to_string(),
+        };
+        assert!(endpoint.is_authorized(Some("Bearer secret")));
+        assert!(!endpoint.is_authorized(Some("Bearer secrets")));
+        assert!(!endpoint.is_authorized(Some("Bearer secreT")));
+        assert!(!endpoint.is_authorized(Some("Bearer ")));
+        assert!(!endpoint.is_authorized(Some("secret")));
+        assert!(!endpoint.is_authorized(None));
+    }
+
+    #[test]
+    #[should_panic(expected = "must not be empty")]
+    fn empty_tokens_are_rejected() {
+        _ = crate::ServeConfigBuilder::new()
+            .index_html(String::new())
+            .revalidation_endpoint("/api/revalidate", "")
+            .build();
+    }
+
+    #[tokio::test]
+    async fn revalidate_paths_and_tags_from_server_functions() {
+        let renderer = Arc::new(
+            IncrementalRenderer::builder()
+                .cache_backends([Arc::new(dioxus_isrg::InMemoryCache::new(10)) as _])
+                .build(),
+        );
+        for (route, tag) in [("/a", "post"), ("/b", "post"), ("/c", "author")] {
+            renderer
+                .cache_with_tags(route.to_string(), route, [tag])
+                .await
+                .unwrap();
+        }
+        let cached = |route| {
+            let renderer = renderer.clone();
+            async move { renderer.get(route).await.unwrap().is_some() }
+        };
+
+        let context = DioxusServerContext::default();
+        context.insert(IncrementalRendererHandle(renderer.clone()));
+        ProvideServerContext::new(revalidate_path("/c"), context.clone())
+            .await
+            .unwrap();
+        assert!(!cached("/c").await);
+        assert!(cached("/a").await);
+
+        ProvideServerContext::new(revalidate_tag("post"), context)
+            .await
+            .unwrap();
+        assert!(!cached("/a").await);
+        assert!(!cached("/b").await);
+
+        // Revalidating without an incremental renderer does nothing
+        renderer.cache("/a".to_string(), "a").await.unwrap();
+        ProvideServerContext::new(revalidate_path("/a"), DioxusServerContext::default())
+            .await
+            .unwrap();
+        assert!(cached("/a").await);
+    }
+}
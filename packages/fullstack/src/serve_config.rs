@@ -1,16 +1,25 @@
 //! Configuration for how to serve a Dioxus application
 #![allow(non_snake_case)]
 
+use crate::inject::Providers;
+use crate::render_timeout::TimedOutBoundary;
+use crate::revalidate::RevalidationEndpoint;
+use crate::session::SessionConfig;
 use dioxus_lib::prelude::dioxus_core::LaunchConfig;
 use std::any::Any;
 use std::fs::File;
 use std::io::Read;
 use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::Duration;
 
 pub(crate) type ContextProviders =
     Arc<Vec<Box<dyn Fn() -> Box<dyn std::any::Any> + Send + Sync + 'static>>>;
 
+pub(crate) type TextFileGenerator = Arc<dyn Fn() -> String + Send + Sync + 'static>;
+
+pub(crate) type RenderTimeoutCallback = Arc<dyn Fn(&TimedOutBoundary) + Send + Sync + 'static>;
+
 /// A ServeConfig is used to configure how to serve a Dioxus application. It contains information about how to serve static assets, and what content to render with [`dioxus-ssr`].
 #[derive(Clone, Default)]
 pub struct ServeConfigBuilder {
@@ -21,6 +30,14 @@ pub struct ServeConfigBuilder {
     pub(crate) context_providers:
         Vec<Arc<dyn Fn() -> Box<dyn std::any::Any> + Send + Sync + 'static>>,
     pub(crate) streaming_mode: StreamingMode,
+    pub(crate) partial_prerendering: bool,
+    pub(crate) render_timeout: Option<Duration>,
+    pub(crate) on_render_timeout: Option<RenderTimeoutCallback>,
+    pub(crate) sitemap: Option<TextFileGenerator>,
+    pub(crate) robots_txt: Option<TextFileGenerator>,
+    pub(crate) revalidation: Option<RevalidationEndpoint>,
+    pub(crate) sessions: Option<SessionConfig>,
+    pub(crate) providers: Providers,
 }
 
 impl LaunchConfig for ServeConfigBuilder {}
@@ -35,6 +52,14 @@ impl ServeConfigBuilder {
             incremental: None,
             context_providers: Default::default(),
             streaming_mode: StreamingMode::default(),
+            partial_prerendering: false,
+            render_timeout: None,
+            on_render_timeout: None,
+            sitemap: None,
+            robots_txt: None,
+            revalidation: None,
+            sessions: None,
+            providers: Providers::default(),
         }
     }
 
@@ -302,7 +327,8 @@ impl ServeConfigBuilder {
         self
     }
 
-    /// Set the streaming mode for the server. By default, streaming is disabled.
+    /// Set the streaming mode for the server. By default, streaming is disabled. Streaming is always
+    /// disabled while the CLI pre-renders a static site, because the pages are written to files.
     ///
     /// ```rust, no_run
     /// # use dioxus::prelude::*;
@@ -336,8 +362,215 @@ impl ServeConfigBuilder {
         self
     }
 
+    /// Enable partial prerendering. The static shell of each route is cached with the
+    /// [incremental renderer](Self::incremental) while the [`Dynamic`](crate::prelude::Dynamic) regions of the
+    /// page are rendered for every request and streamed into the cached shell.
+    ///
+    /// Dynamic regions are streamed with the same placeholders as out of order streaming, so they require
+    /// javascript. Partial prerendering has no effect unless incremental rendering is enabled.
+    ///
+    /// ```rust, no_run
+    /// # use dioxus::prelude::*;
+    /// # fn app() -> Element { todo!() }
+    /// dioxus::LaunchBuilder::new()
+    ///     .with_context(server_only! {
+    ///         ServeConfig::builder()
+    ///             .incremental(IncrementalRendererConfig::default())
+    ///             .enable_partial_prerendering()
+    ///     })
+    ///     .launch(app);
+    /// ```
+    pub fn enable_partial_prerendering(mut self) -> Self {
+        self.partial_prerendering = true;
+        self
+    }
+
+    /// Set how long the server waits for suspense while rendering a page. When the timeout passes, the
+    /// server sends the fallback of every suspense boundary that is still pending and the client finishes
+    /// loading them. Individual boundaries can use a shorter timeout with [`TimeoutBoundary`](crate::prelude::TimeoutBoundary).
+    ///
+    /// Pages that timed out are not added to the [incremental cache](Self::incremental).
+    ///
+    /// ```rust, no_run
+    /// # use dioxus::prelude::*;
+    /// # fn app() -> Element { todo!() }
+    /// dioxus::LaunchBuilder::new()
+    ///     .with_context(server_only! {
+    ///         ServeConfig::builder().render_timeout(std::time::Duration::from_secs(2))
+    ///     })
+    ///     .launch(app);
+    /// ```
+    pub fn render_timeout(mut self, timeout: Duration) -> Self {
+        self.render_timeout = Some(timeout);
+        self
+    }
+
+    /// Call a function every time the server stops waiting for a suspense boundary because of a
+    /// [render timeout](Self::render_timeout) or a [`TimeoutBoundary`](crate::prelude::TimeoutBoundary).
+    /// Use this to record metrics about slow boundaries.
+    ///
+    /// ```rust, no_run
+    /// # use dioxus::prelude::*;
+    /// # fn app() -> Element { todo!() }
+    /// dioxus::LaunchBuilder::new()
+    ///     .with_context(server_only! {
+    ///         ServeConfig::builder()
+    ///             .render_timeout(std::time::Duration::from_secs(2))
+    ///             .on_render_timeout(|boundary| {
+    ///                 tracing::info!(
+    ///                     "{:?} in {} timed out after {:?}",
+    ///                     boundary.name,
+    ///                     boundary.route,
+    ///                     boundary.elapsed
+    ///                 );
+    ///             })
+    ///     })
+    ///     .launch(app);
+    /// ```
+    pub fn on_render_timeout(
+        mut self,
+        on_timeout: impl Fn(&TimedOutBoundary) + Send + Sync + 'static,
+    ) -> Self {
+        self.on_render_timeout = Some(Arc::new(on_timeout));
+        self
+    }
+
+    /// Serve a sitemap at `/sitemap.xml`. The function is called every time the sitemap is requested.
+    ///
+    /// If a `sitemap.xml` file already exists in the public directory (for example because it was
+    /// emitted by static generation), the file is served instead.
+    ///
+    /// ```rust, no_run
+    /// # use dioxus::prelude::*;
+    /// # fn app() -> Element { todo!() }
+    /// use dioxus::router::sitemap::Sitemap;
+    ///
+    /// #[derive(Routable, Clone, PartialEq)]
+    /// enum Route {
+    ///     #[route("/")]
+    ///     Home {},
+    /// }
+    /// # #[component]
+    /// # fn Home() -> Element { todo!() }
+    ///
+    /// let sitemap = Sitemap::<Route>::new("https://example.com");
+    /// dioxus::LaunchBuilder::new()
+    ///     .with_cfg(server_only! {
+    ///         ServeConfig::builder()
+    ///             .sitemap({
+    ///                 let sitemap = sitemap.clone();
+    ///                 move || sitemap.to_xml()
+    ///             })
+    ///             .robots_txt(move || sitemap.robots_txt())
+    ///     })
+    ///     .launch(app);
+    /// ```
+    pub fn sitemap(mut self, sitemap: impl Fn() -> String + Send + Sync + 'static) -> Self {
+        self.sitemap = Some(Arc::new(sitemap));
+        self
+    }
+
+    /// Serve a `robots.txt` file at `/robots.txt`. The function is called every time the file is requested.
+    ///
+    /// If a `robots.txt` file already exists in the public directory, the file is served instead.
+    pub fn robots_txt(mut self, robots_txt: impl Fn() -> String + Send + Sync + 'static) -> Self {
+        self.robots_txt = Some(Arc::new(robots_txt));
+        self
+    }
+
+    /// Add an endpoint at `path` that removes routes from the incremental cache so they are rendered
+    /// again on the next request. This lets external services like a CMS revalidate pages when their
+    /// content changes. The endpoint is only added if [incremental rendering](Self::incremental) is enabled.
+    ///
+    /// Requests must be sent with the `POST` method and an `Authorization: Bearer <token>` header. The body
+    /// is a JSON object with the routes and [cache tags](crate::prelude::use_cache_tag) to revalidate:
+    ///
+    /// ```json
+    /// { "paths": ["/blog/1", "/blog"], "tags": ["product:42"] }
+    /// ```
+    ///
+    /// To revalidate routes from inside a server function, use [`revalidate_path`](crate::prelude::revalidate_path)
+    /// and [`revalidate_tag`](crate::prelude::revalidate_tag) instead.
+    ///
+    /// ```rust, no_run
+    /// # use dioxus::prelude::*;
+    /// # fn app() -> Element { todo!() }
+    /// dioxus::LaunchBuilder::new()
+    ///     .with_context(server_only! {
+    ///         ServeConfig::builder()
+    ///             .incremental(IncrementalRendererConfig::default())
+    ///             .revalidation_endpoint("/api/revalidate", std::env::var("REVALIDATE_TOKEN").unwrap())
+    ///     })
+    ///     .launch(app);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// [`build`](Self::build) panics if the token is empty so a missing environment variable doesn't
+    /// open the endpoint to everyone.
+    pub fn revalidation_endpoint(
+        mut self,
+        path: impl Into<String>,
+        token: impl Into<String>,
+    ) -> Self {
+        self.revalidation = Some(RevalidationEndpoint {
+            path: path.into(),
+            token: token.into(),
+        });
+        self
+    }
+
+    /// Enable server side sessions. The session of each request is loaded before server functions
+    /// run and before the page is rendered and can be read with [`Session`](crate::session::Session).
+    ///
+    /// ```rust, no_run
+    /// # use dioxus::prelude::*;
+    /// # fn app() -> Element { todo!() }
+    /// use dioxus::fullstack::session::{MemorySessionStore, SessionConfig};
+    ///
+    /// dioxus::LaunchBuilder::new()
+    ///     .with_context(server_only! {
+    ///         ServeConfig::builder().sessions(SessionConfig::new(MemorySessionStore::default()))
+    ///     })
+    ///     .launch(app);
+    /// ```
+    pub fn sessions(mut self, sessions: SessionConfig) -> Self {
+        self.sessions = Some(sessions);
+        self
+    }
+
+    /// Set the providers that [`Inject`](crate::inject::Inject) resolves values from in server functions
+    /// and while the page is rendered.
+    ///
+    /// ```rust, no_run
+    /// # use dioxus::prelude::*;
+    /// # fn app() -> Element { todo!() }
+    /// use dioxus::fullstack::inject::Providers;
+    ///
+    /// #[derive(Clone)]
+    /// struct Pool;
+    ///
+    /// dioxus::LaunchBuilder::new()
+    ///     .with_context(server_only! {
+    ///         ServeConfig::builder().providers(Providers::new().singleton(|_| async { Ok(Pool) }))
+    ///     })
+    ///     .launch(app);
+    /// ```
+    pub fn providers(mut self, providers: Providers) -> Self {
+        self.providers = providers;
+        self
+    }
+
     /// Build the ServeConfig. This may fail if the index.html file is not found.
     pub fn build(self) -> Result<ServeConfig, UnableToLoadIndex> {
+        if let Some(revalidation) = &self.revalidation {
+            assert!(
+                !revalidation.token.trim().is_empty(),
+                "The token of the revalidation endpoint {} must not be empty",
+                revalidation.path
+            );
+        }
+
         // The CLI always bundles static assets into the exe/public directory
         let public_path = public_path();
 
@@ -367,7 +600,19 @@ impl ServeConfigBuilder {
             index,
             incremental: self.incremental,
             context_providers,
-            streaming_mode: self.streaming_mode,
+            streaming_mode: if dioxus_cli_config::is_static_generation() {
+                StreamingMode::Disabled
+            } else {
+                self.streaming_mode
+            },
+            partial_prerendering: self.partial_prerendering,
+            render_timeout: self.render_timeout,
+            on_render_timeout: self.on_render_timeout,
+            sitemap: self.sitemap,
+            robots_txt: self.robots_txt,
+            revalidation: self.revalidation,
+            sessions: self.sessions,
+            providers: self.providers,
         })
     }
 }
@@ -475,6 +720,15 @@ pub enum StreamingMode {
     /// Out of order streaming is enabled; server futures are resolved out of order and streamed to the client
     /// as they resolve
     OutOfOrder,
+    /// In order streaming is enabled; the page is streamed to the client in document order. Everything before
+    /// the first pending suspense boundary is sent immediately and the rest of the page is sent as suspense
+    /// resolves.
+    ///
+    /// Unlike out of order streaming, this works without javascript, so crawlers and clients with javascript
+    /// disabled see the page render progressively. The page is hydrated once everything is resolved. The head
+    /// of the page is sent before suspense resolves, so head elements like the title should be set outside of
+    /// suspended components.
+    InOrder,
 }
 
 /// Used to configure how to serve a Dioxus application. It contains information about how to serve static assets, and what content to render with [`dioxus-ssr`].
@@ -485,6 +739,14 @@ pub struct ServeConfig {
     pub(crate) incremental: Option<dioxus_isrg::IncrementalRendererConfig>,
     pub(crate) context_providers: ContextProviders,
     pub(crate) streaming_mode: StreamingMode,
+    pub(crate) partial_prerendering: bool,
+    pub(crate) render_timeout: Option<Duration>,
+    pub(crate) on_render_timeout: Option<RenderTimeoutCallback>,
+    pub(crate) sitemap: Option<TextFileGenerator>,
+    pub(crate) robots_txt: Option<TextFileGenerator>,
+    pub(crate) revalidation: Option<RevalidationEndpoint>,
+    pub(crate) sessions: Option<SessionConfig>,
+    pub(crate) providers: Providers,
 }
 
 impl LaunchConfig for ServeConfig {}
@@ -0,0 +1,188 @@
+//! Middleware for individual server functions.
+//!
+//! Any tower layer can be attached to a server function with `#[server(middleware = [..])]` or the
+//! `#[middleware(..)]` attribute. [`middleware_fn`] turns an async function into a layer that also
+//! receives the [`DioxusServerContext`] of the request.
+
+use std::{future::Future, pin::Pin, sync::Arc};
+
+use axum::body::Body;
+use http::{Request, Response};
+use parking_lot::Mutex;
+use server_fn::middleware::{BoxedService, Layer, Service};
+
+use crate::prelude::{server_context, DioxusServerContext};
+
+type InnerService = BoxedService<Request<Body>, Response<Body>>;
+
+/// Create a server function middleware from an async function. The function receives the server
+/// context of the request, the request itself and the rest of the middleware chain. It can return a
+/// response early or call [`Next::run`] to run the server function.
+///
+/// ```rust, no_run
+/// # use dioxus::prelude::*;
+/// use dioxus::fullstack::prelude::middleware::{middleware_fn, Next};
+/// use axum::{body::Body, http::{Request, Response}};
+///
+/// async fn log_requests(
+///     context: DioxusServerContext,
+///     request: Request<Body>,
+///     next: Next,
+/// ) -> Response<Body> {
+///     let path = context.request_parts().uri.path().to_string();
+///     let response = next.run(request).await;
+///     tracing::info!("{path} responded with {}", response.status());
+///     response
+/// }
+///
+/// #[server(middleware = [middleware_fn(log_requests)])]
+/// async fn double(value: u64) -> Result<u64, ServerFnError> {
+///     Ok(value * 2)
+/// }
+/// ```
+pub fn middleware_fn<F, Fut>(f: F) -> MiddlewareFn<F>
+where
+    F: Fn(DioxusServerContext, Request<Body>, Next) -> Fut + Clone + Send + Sync + 'static,
+    Fut: Future<Output = Response<Body>> + Send + 'static,
+{
+    MiddlewareFn { f }
+}
+
+/// A server function middleware created with [`middleware_fn`].
+#[derive(Clone)]
+pub struct MiddlewareFn<F> {
+    f: F,
+}
+
+impl<F, Fut> Layer<Request<Body>, Response<Body>> for MiddlewareFn<F>
+where
+    F: Fn(DioxusServerContext, Request<Body>, Next) -> Fut + Clone + Send + Sync + 'static,
+    Fut: Future<Output = Response<Body>> + Send + 'static,
+{
+    fn layer(&self, inner: InnerService) -> InnerService {
+        BoxedService::new(MiddlewareFnService {
+            f: self.f.clone(),
+            inner: Arc::new(Mutex::new(inner)),
+        })
+    }
+}
+
+struct MiddlewareFnService<F> {
+    f: F,
+    inner: Arc<Mutex<InnerService>>,
+}
+
+impl<F, Fut> Service<Request<Body>, Response<Body>> for MiddlewareFnService<F>
+where
+    F: Fn(DioxusServerContext, Request<Body>, Next) -> Fut + Send + 'static,
+    Fut: Future<Output = Response<Body>> + Send + 'static,
+{
+    fn run(&mut self, req: Request<Body>) -> Pin<Box<dyn Future<Output = Response<Body>> + Send>> {
+        let next = Next {
+            inner: self.inner.clone(),
+        };
+        Box::pin((self.f)(server_context(), req, next))
+    }
+}
+
+/// The rest of the middleware chain and the server function.
+pub struct Next {
+    inner: Arc<Mutex<InnerService>>,
+}
+
+impl Next {
+    /// Run the rest of the middleware chain and the server function with the request.
+    pub async fn run(self, req: Request<Body>) -> Response<Body> {
+        // Only hold the lock while the future is created, the inner service is shared between requests
+        let response = self.inner.lock().0.run(req);
+        response.await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+    use http::{header, StatusCode};
+    use tower::ServiceExt;
+
+    #[derive(Clone)]
+    struct UserId(u64);
+
+    async fn require_auth(
+        context: DioxusServerContext,
+        req: Request<Body>,
+        next: Next,
+    ) -> Response<Body> {
+        if !req.headers().contains_key(header::AUTHORIZATION) {
+            return Response::builder()
+                .status(StatusCode::UNAUTHORIZED)
+                .body(Body::empty())
+                .unwrap();
+        }
+        context.insert(UserId(7));
+        next.run(req).await
+    }
+
+    async fn tag_response(
+        _: DioxusServerContext,
+        req: Request<Body>,
+        next: Next,
+    ) -> Response<Body> {
+        let mut response = next.run(req).await;
+        response
+            .headers_mut()
+            .append("x-middleware", "outer".parse().unwrap());
+        response
+    }
+
+    #[server(middleware = [
+        middleware_fn(require_auth),
+        tower::util::MapResponseLayer::new(|mut response: Response<Body>| {
+            response.headers_mut().append("x-middleware", "inner".parse().unwrap());
+            response
+        }),
+        middleware_fn(tag_response),
+    ])]
+    async fn whoami() -> Result<u64, ServerFnError> {
+        let FromContext(UserId(id)) = extract().await?;
+        Ok(id)
+    }
+
+    #[tokio::test]
+    async fn middleware_runs_around_the_server_function() {
+        let call = |authorized: bool| {
+            let mut request =
+                Request::post(Whoami::PATH).header(header::CONTENT_TYPE, "application/json");
+            if authorized {
+                request = request.header(header::AUTHORIZATION, "Bearer token");
+            }
+            axum::Router::new()
+                .register_server_functions()
+                .oneshot(request.body(Body::from("{}")).unwrap())
+        };
+
+        let tags = |response: &Response<Body>| {
+            response
+                .headers()
+                .get_all("x-middleware")
+                .iter()
+                .map(|tag| tag.to_str().unwrap().to_string())
+                .collect::<Vec<_>>()
+        };
+
+        // Layers later in the list wrap the earlier ones, so they still see rejected requests
+        let response = call(false).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(tags(&response), ["inner", "outer"]);
+
+        // Context inserted by the middleware is visible to the server function
+        let response = call(true).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(tags(&response), ["inner", "outer"]);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        assert_eq!(&body[..], b"7");
+    }
+}
@@ -56,6 +56,7 @@
 //! ```
 
 pub mod launch;
+pub mod middleware;
 
 use axum::routing::*;
 use axum::{
@@ -245,41 +246,154 @@ where
         Cfg: TryInto<ServeConfig, Error = Error>,
         Error: std::error::Error,
     {
-        let cfg = cfg.try_into();
-        let context_providers = cfg
-            .as_ref()
-            .map(|cfg| cfg.context_providers.clone())
-            .unwrap_or_default();
+        let cfg = match cfg.try_into() {
+            Ok(cfg) => cfg,
+            Err(err) => {
+                tracing::trace!("Failed to create render handler. This is expected if you are only using fullstack for desktop/mobile server functions: {}", err);
+                return self
+                    .serve_static_assets()
+                    .register_server_functions_with_context(Default::default());
+            }
+        };
+        let ssr_state = SSRState::new(&cfg);
+
+        // Give server functions access to the incremental renderer so they can revalidate routes
+        let mut context_providers = cfg.context_providers.clone();
+        if let Some(renderer) = ssr_state.incremental_renderer() {
+            let handle = crate::revalidate::IncrementalRendererHandle(renderer);
+            context_providers = with_context_provider(&context_providers, handle);
+        }
+        // Load sessions before server functions run
+        if let Some(sessions) = cfg.sessions.clone() {
+            context_providers = with_context_provider(&context_providers, sessions);
+        }
+        context_providers = with_context_provider(&context_providers, cfg.providers.clone());
 
         // Add server functions and render index.html
         let server = self
             .serve_static_assets()
             .register_server_functions_with_context(context_providers);
+        let server = serve_generated_text_files(server, &cfg);
+        let server = serve_revalidation_endpoint(server, &cfg, &ssr_state);
+        server.fallback(
+            get(render_handler)
+                .with_state(RenderHandleState::new(cfg, app).with_ssr_state(ssr_state)),
+        )
+    }
+}
 
-        match cfg {
-            Ok(cfg) => {
-                let ssr_state = SSRState::new(&cfg);
-                server.fallback(
-                    get(render_handler)
-                        .with_state(RenderHandleState::new(cfg, app).with_ssr_state(ssr_state)),
-                )
-            }
-            Err(err) => {
-                tracing::trace!("Failed to create render handler. This is expected if you are only using fullstack for desktop/mobile server functions: {}", err);
-                server
-            }
+/// Add a context provider that clones a value to a list of context providers
+fn with_context_provider<T: Clone + Send + Sync + 'static>(
+    context_providers: &ContextProviders,
+    value: T,
+) -> ContextProviders {
+    let mut providers: Vec<Box<dyn Fn() -> Box<dyn std::any::Any> + Send + Sync>> = (0
+        ..context_providers.len())
+        .map(|index| {
+            let context_providers = context_providers.clone();
+            Box::new(move || context_providers[index]()) as Box<_>
+        })
+        .collect();
+    providers.push(Box::new(move || Box::new(value.clone())));
+    Arc::new(providers)
+}
+
+/// Serve the revalidation endpoint configured with [`ServeConfigBuilder::revalidation_endpoint`]
+/// if incremental rendering is enabled
+fn serve_revalidation_endpoint<S>(
+    router: Router<S>,
+    cfg: &ServeConfig,
+    ssr_state: &SSRState,
+) -> Router<S>
+where
+    S: Send + Sync + Clone + 'static,
+{
+    #[derive(serde::Deserialize)]
+    struct RevalidationRequest {
+        #[serde(default)]
+        paths: Vec<String>,
+        #[serde(default)]
+        tags: Vec<String>,
+    }
+
+    let Some(endpoint) = cfg.revalidation.clone() else {
+        return router;
+    };
+    let Some(renderer) = ssr_state.incremental_renderer() else {
+        tracing::warn!(
+            "The revalidation endpoint {} is disabled because incremental rendering is not enabled",
+            endpoint.path
+        );
+        return router;
+    };
+
+    let path = endpoint.path.clone();
+    router.route(
+        &path,
+        post(
+            move |headers: HeaderMap, axum::Json(request): axum::Json<RevalidationRequest>| async move {
+                let authorization = headers.get(AUTHORIZATION).and_then(|value| value.to_str().ok());
+                if !endpoint.is_authorized(authorization) {
+                    return Err(StatusCode::UNAUTHORIZED);
+                }
+
+                for path in &request.paths {
+                    if let Err(err) = renderer.invalidate(path).await {
+                        tracing::error!("Failed to revalidate route {path}: {err}");
+                        return Err(StatusCode::INTERNAL_SERVER_ERROR);
+                    }
+                }
+                for tag in &request.tags {
+                    if let Err(err) = renderer.invalidate_tag(tag).await {
+                        tracing::error!("Failed to revalidate tag {tag}: {err}");
+                        return Err(StatusCode::INTERNAL_SERVER_ERROR);
+                    }
+                }
+
+                Ok(StatusCode::NO_CONTENT)
+            },
+        ),
+    )
+}
+
+/// Serve the generated `sitemap.xml` and `robots.txt` files from the [`ServeConfig`] unless a
+/// static file with the same name exists in the public directory
+fn serve_generated_text_files<S>(mut router: Router<S>, cfg: &ServeConfig) -> Router<S>
+where
+    S: Send + Sync + Clone + 'static,
+{
+    let public_path = crate::public_path();
+    let files = [
+        ("sitemap.xml", "application/xml", &cfg.sitemap),
+        ("robots.txt", "text/plain", &cfg.robots_txt),
+    ];
+
+    for (file_name, content_type, generate) in files {
+        let Some(generate) = generate.clone() else {
+            continue;
+        };
+        if public_path.join(file_name).exists() {
+            tracing::trace!(
+                "Serving {file_name} from the public directory instead of generating it"
+            );
+            continue;
         }
+        router = router.route(
+            &format!("/{file_name}"),
+            get(move || async move { ([(CONTENT_TYPE, content_type)], generate()) }),
+        );
     }
+
+    router
 }
 
 fn apply_request_parts_to_response<B>(
     headers: hyper::header::HeaderMap,
     response: &mut axum::response::Response<B>,
 ) {
-    let mut_headers = response.headers_mut();
-    for (key, value) in headers.iter() {
-        mut_headers.insert(key, value.clone());
-    }
+    // Extending replaces headers with the same name but keeps every value of headers that appear
+    // multiple times like set-cookie
+    response.headers_mut().extend(headers);
 }
 
 fn add_server_context(server_context: &DioxusServerContext, context_providers: &ContextProviders) {
@@ -410,6 +524,14 @@ pub async fn render_handler(
     let server_context = DioxusServerContext::from_shared_parts(parts.clone());
     // Provide additional context from the render state
     add_server_context(&server_context, &state.config.context_providers);
+    if let Some(renderer) = ssr_state.incremental_renderer() {
+        server_context.insert(crate::revalidate::IncrementalRendererHandle(renderer));
+    }
+    if let Some(sessions) = &cfg.sessions {
+        server_context.insert(sessions.clone());
+    }
+    server_context.insert(cfg.providers.clone());
+    server_context.load_session().await;
 
     match ssr_state
         .render(url, cfg, build_virtual_dom, &server_context)
@@ -418,6 +540,7 @@ pub async fn render_handler(
         Ok((freshness, rx)) => {
             let mut response = axum::response::Html::from(Body::from_stream(rx)).into_response();
             freshness.write(response.headers_mut());
+            server_context.finish_response().await;
             let headers = server_context.response_parts().headers.clone();
             apply_request_parts_to_response(headers, &mut response);
             Ok(response)
@@ -458,6 +581,7 @@ async fn handle_server_fns_inner(
             let server_context = DioxusServerContext::new(parts);
             // Provide additional context from the render state
             add_server_context(&server_context, &additional_context);
+            server_context.load_session().await;
 
             // store Accepts and Referrer in case we need them for redirect (below)
             let accepts_html = req
@@ -485,6 +609,7 @@ async fn handle_server_fns_inner(
             }
 
             // apply the response parts from the server context to the response
+            server_context.finish_response().await;
             let mut res_options = server_context.response_parts_mut();
             res.headers_mut().extend(res_options.headers.drain());
 
@@ -532,3 +657,121 @@ async fn handle_server_fns_inner(
         future().await
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tower::ServiceExt;
+
+    #[tokio::test]
+    async fn revalidation_endpoint_requires_the_token() {
+        let cfg = ServeConfigBuilder::new()
+            .index_html(r#"<html><head></head><body><div id="main"></div></body></html>"#.into())
+            .incremental(
+                dioxus_isrg::IncrementalRendererConfig::new()
+                    .cache_backends([Arc::new(dioxus_isrg::InMemoryCache::new(10)) as _]),
+            )
+            .revalidation_endpoint("/api/revalidate", "secret")
+            .build()
+            .unwrap();
+        let ssr_state = SSRState::new(&cfg);
+        let renderer = ssr_state.incremental_renderer().unwrap();
+        for (route, tag) in [("/a", "post"), ("/b", "post"), ("/c", "author")] {
+            renderer
+                .cache_with_tags(route.to_string(), route, [tag])
+                .await
+                .unwrap();
+        }
+        let router: Router = serve_revalidation_endpoint(Router::new(), &cfg, &ssr_state);
+
+        let revalidate = |token: Option<&str>| {
+            let mut request =
+                Request::post("/api/revalidate").header(CONTENT_TYPE, "application/json");
+            if let Some(token) = token {
+                request = request.header(AUTHORIZATION, format!("Bearer {token}"));
+            }
+            let request = request
+                .body(Body::from(r#"{ "paths": ["/c"], "tags": ["post"] }"#))
+                .unwrap();
+            router.clone().oneshot(request)
+        };
+
+        for token in [None, Some("wrong"), Some("")] {
+            let response = revalidate(token).await.unwrap();
+            assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        }
+        assert!(renderer.get("/a").await.unwrap().is_some());
+
+        let response = revalidate(Some("secret")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+        for route in ["/a", "/b", "/c"] {
+            assert!(renderer.get(route).await.unwrap().is_none());
+        }
+    }
+
+    /// Counts the visits in the session and sets a cookie while the page renders
+    fn count_visits() -> Element {
+        use dioxus_lib::prelude::*;
+
+        let visits = use_hook(|| {
+            // A slow render must still finish before the handler writes the headers
+            std::thread::sleep(std::time::Duration::from_millis(50));
+            let context = server_context();
+            let session = context.session().unwrap();
+            let visits = session.get::<u32>("visits").unwrap_or(0) + 1;
+            session.insert("visits", visits).unwrap();
+            context.cookies().add(("theme", "dark"));
+            visits
+        });
+        rsx! { "Visits: {visits}" }
+    }
+
+    #[tokio::test]
+    async fn cookies_and_sessions_changed_while_rendering_are_sent() {
+        let cfg = ServeConfigBuilder::new()
+            .index_html(r#"<html><head></head><body><div id="main"></div></body></html>"#.into())
+            .sessions(crate::session::SessionConfig::new(
+                crate::session::MemorySessionStore::default(),
+            ))
+            .enable_out_of_order_streaming()
+            .build()
+            .unwrap();
+        let router: Router = Router::new()
+            .fallback(get(render_handler).with_state(RenderHandleState::new(cfg, count_visits)));
+
+        let visit = |cookie: Option<String>| {
+            let mut request = Request::get("/").header(ACCEPT, "text/html");
+            if let Some(cookie) = cookie {
+                request = request.header(COOKIE, cookie);
+            }
+            router.clone().oneshot(request.body(Body::empty()).unwrap())
+        };
+
+        let response = visit(None).await.unwrap();
+        let cookies: Vec<_> = response
+            .headers()
+            .get_all(SET_COOKIE)
+            .iter()
+            .map(|cookie| cookie::Cookie::parse(cookie.to_str().unwrap().to_string()).unwrap())
+            .collect();
+        assert!(cookies
+            .iter()
+            .any(|cookie| cookie.name_value() == ("theme", "dark")));
+        let session = cookies
+            .iter()
+            .find(|cookie| cookie.name() == "dioxus_session")
+            .expect("the session should be saved before the headers are sent");
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        assert!(String::from_utf8_lossy(&body).contains("Visits: 1"));
+
+        let response = visit(Some(format!("dioxus_session={}", session.value())))
+            .await
+            .unwrap();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        assert!(String::from_utf8_lossy(&body).contains("Visits: 2"));
+    }
+}
@@ -26,6 +26,7 @@ pub struct DioxusServerContext {
     shared_context: std::sync::Arc<RwLock<SendSyncAnyMap>>,
     response_parts: std::sync::Arc<RwLock<http::response::Parts>>,
     pub(crate) parts: Arc<RwLock<http::request::Parts>>,
+    pub(crate) cookies: crate::cookies::SharedCookieJar,
 }
 
 enum ContextType {
@@ -51,6 +52,7 @@ impl Default for DioxusServerContext {
                 http::response::Response::new(()).into_parts().0,
             )),
             parts: std::sync::Arc::new(RwLock::new(http::request::Request::new(()).into_parts().0)),
+            cookies: Default::default(),
         }
     }
 }
@@ -69,6 +71,7 @@ mod server_fn_impl {
                 response_parts: std::sync::Arc::new(RwLock::new(
                     http::response::Response::new(()).into_parts().0,
                 )),
+                cookies: Default::default(),
             }
         }
 
@@ -81,6 +84,7 @@ mod server_fn_impl {
                 response_parts: std::sync::Arc::new(RwLock::new(
                     http::response::Response::new(()).into_parts().0,
                 )),
+                cookies: Default::default(),
             }
         }
 
@@ -119,6 +123,19 @@ mod server_fn_impl {
                 .map(|v| v.downcast::<T>().unwrap())
         }
 
+        /// Clone a value from the shared server context or insert the value `init` returns if it is missing
+        pub(crate) fn get_or_insert_with<T: Any + Send + Sync + Clone + 'static>(
+            &self,
+            init: impl FnOnce() -> T,
+        ) -> T {
+            let mut shared_context = self.shared_context.write();
+            shared_context
+                .entry(TypeId::of::<T>())
+                .or_insert_with(|| ContextType::Value(Box::new(init())))
+                .downcast::<T>()
+                .unwrap()
+        }
+
         /// Insert a value into the shared server context
         pub fn insert<T: Any + Send + Sync + 'static>(&self, value: T) {
             self.insert_any(Box::new(value));
@@ -195,6 +212,22 @@ mod server_fn_impl {
             self.response_parts.write()
         }
 
+        /// Load the session of the request if sessions are enabled
+        pub(crate) async fn load_session(&self) {
+            if let Some(sessions) = self.get::<crate::session::SessionConfig>() {
+                sessions.load(self).await;
+            }
+        }
+
+        /// Save the session and write the cookies that changed into the response parts. This is
+        /// called before the headers of the response are sent.
+        pub(crate) async fn finish_response(&self) {
+            if let Some(sessions) = self.get::<crate::session::SessionConfig>() {
+                sessions.save(self).await;
+            }
+            self.write_cookies();
+        }
+
         /// Get the request parts
         ///
         #[doc = include_str!("../docs/request_origin.md")]
@@ -340,7 +373,7 @@ pub trait FromServerContext<I = ()>: Sized {
 }
 
 /// A type was not found in the server context
-pub struct NotFoundInServerContext<T: 'static>(std::marker::PhantomData<T>);
+pub struct NotFoundInServerContext<T: 'static>(pub(crate) std::marker::PhantomData<T>);
 
 impl<T: 'static> std::fmt::Debug for NotFoundInServerContext<T> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
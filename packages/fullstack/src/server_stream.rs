@@ -0,0 +1,233 @@
+//! Encodings for server functions that stream typed values to the client.
+
+use std::{fmt::Debug, pin::Pin};
+
+use bytes::Bytes;
+use futures_util::{Stream, StreamExt};
+use http::Method;
+use serde::{de::DeserializeOwned, Serialize};
+use server_fn::{
+    codec::{Encoding, FromRes, IntoRes},
+    error::NoCustomError,
+    response::{ClientRes, Res},
+    ServerFnError,
+};
+
+/// An output encoding for server functions that stream values as newline delimited JSON
+/// (`application/x-ndjson`).
+///
+/// A server function that uses this as its output encoding should return a [`JsonStream`].
+///
+/// ```rust, no_run
+/// # use dioxus::prelude::*;
+/// # use futures_util::StreamExt;
+/// #[server(output = JsonLines)]
+/// async fn countdown(from: u32) -> Result<JsonStream<u32>, ServerFnError> {
+///     Ok(futures_util::stream::iter((0..=from).rev()).into())
+/// }
+/// ```
+pub struct JsonLines;
+
+impl Encoding for JsonLines {
+    const CONTENT_TYPE: &'static str = "application/x-ndjson";
+    const METHOD: Method = Method::POST;
+}
+
+/// An output encoding for server functions that stream values as
+/// [server-sent events](https://html.spec.whatwg.org/multipage/server-sent-events.html) with a
+/// JSON payload (`text/event-stream`).
+///
+/// A server function that uses this as its output encoding should return a [`JsonStream`]. Prefer
+/// this encoding over [`JsonLines`] if the stream passes through proxies that buffer other content
+/// types.
+pub struct ServerSentEvents;
+
+impl Encoding for ServerSentEvents {
+    const CONTENT_TYPE: &'static str = "text/event-stream";
+    const METHOD: Method = Method::POST;
+}
+
+/// How the values of a [`JsonStream`] are framed in the body of the response.
+pub trait StreamFraming: Encoding {
+    /// The bytes that end every frame
+    const DELIMITER: &'static [u8];
+
+    /// Wrap the serialized value in a frame
+    fn encode(json: Vec<u8>) -> Bytes;
+
+    /// Get the serialized value out of a frame without the delimiter. Returns `None` if the frame
+    /// doesn't contain a value.
+    fn decode(frame: &[u8]) -> Option<&[u8]>;
+}
+
+impl StreamFraming for JsonLines {
+    const DELIMITER: &'static [u8] = b"\n";
+
+    fn encode(mut json: Vec<u8>) -> Bytes {
+        json.push(b'\n');
+        json.into()
+    }
+
+    fn decode(frame: &[u8]) -> Option<&[u8]> {
+        let frame = frame.strip_suffix(b"\r").unwrap_or(frame);
+        (!frame.is_empty()).then_some(frame)
+    }
+}
+
+impl StreamFraming for ServerSentEvents {
+    const DELIMITER: &'static [u8] = b"\n\n";
+
+    fn encode(json: Vec<u8>) -> Bytes {
+        let mut frame = Vec::with_capacity(json.len() + 8);
+        frame.extend_from_slice(b"data: ");
+        frame.extend_from_slice(&json);
+        frame.extend_from_slice(b"\n\n");
+        frame.into()
+    }
+
+    fn decode(frame: &[u8]) -> Option<&[u8]> {
+        // serde_json never writes raw newlines, so each event has exactly one data line
+        frame
+            .split(|byte| *byte == b'\n')
+            .find_map(|line| line.strip_prefix(b"data:"))
+            .map(|data| data.strip_prefix(b" ").unwrap_or(data))
+    }
+}
+
+/// A stream of values returned from a server function with the [`JsonLines`] or
+/// [`ServerSentEvents`] output encoding.
+///
+/// Each value is serialized as it is produced on the server and deserialized as soon as it arrives
+/// on the client. If the stream yields an error on the server, the response ends early and the
+/// client receives an error. Read the stream in a component with
+/// [`use_server_stream`](crate::prelude::use_server_stream).
+pub struct JsonStream<T, CustErr = NoCustomError>(
+    Pin<Box<dyn Stream<Item = Result<T, ServerFnError<CustErr>>> + Send>>,
+);
+
+impl<T, CustErr> Debug for JsonStream<T, CustErr> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("JsonStream").finish()
+    }
+}
+
+impl<T> JsonStream<T> {
+    /// Create a new `JsonStream` from a stream of results.
+    pub fn new(value: impl Stream<Item = Result<T, ServerFnError>> + Send + 'static) -> Self {
+        Self(Box::pin(value))
+    }
+}
+
+impl<T, CustErr> JsonStream<T, CustErr> {
+    /// Consumes the wrapper, returning the stream of values.
+    pub fn into_inner(self) -> impl Stream<Item = Result<T, ServerFnError<CustErr>>> + Send {
+        self.0
+    }
+}
+
+impl<S, T> From<S> for JsonStream<T>
+where
+    S: Stream<Item = T> + Send + 'static,
+    T: 'static,
+{
+    fn from(value: S) -> Self {
+        Self(Box::pin(value.map(Ok)))
+    }
+}
+
+impl<E, T, CustErr, Response> IntoRes<E, Response, CustErr> for JsonStream<T, CustErr>
+where
+    E: StreamFraming,
+    T: Serialize + 'static,
+    Response: Res<CustErr>,
+    CustErr: 'static,
+{
+    async fn into_res(self) -> Result<Response, ServerFnError<CustErr>> {
+        Response::try_from_stream(
+            E::CONTENT_TYPE,
+            self.into_inner().map(|value| {
+                let json = serde_json::to_vec(&value?)
+                    .map_err(|err| ServerFnError::Serialization(err.to_string()))?;
+                Ok(E::encode(json))
+            }),
+        )
+    }
+}
+
+impl<E, T, CustErr, Response> FromRes<E, Response, CustErr> for JsonStream<T>
+where
+    E: StreamFraming,
+    T: DeserializeOwned + Send + 'static,
+    Response: ClientRes<CustErr> + Send,
+{
+    async fn from_res(res: Response) -> Result<Self, ServerFnError<CustErr>> {
+        let mut buffer = Vec::new();
+        let stream = res.try_into_stream()?.flat_map(move |chunk| {
+            let values = match chunk {
+                Ok(chunk) => {
+                    buffer.extend_from_slice(&chunk);
+                    take_frames::<E>(&mut buffer)
+                        .into_iter()
+                        .filter_map(|frame| {
+                            E::decode(&frame).map(|json| {
+                                serde_json::from_slice(json)
+                                    .map_err(|err| ServerFnError::Deserialization(err.to_string()))
+                            })
+                        })
+                        .collect()
+                }
+                Err(err) => vec![Err(err)],
+            };
+            futures_util::stream::iter(values)
+        });
+        Ok(JsonStream::new(stream))
+    }
+}
+
+/// Remove every complete frame from the front of the buffer. Incomplete frames are kept until the
+/// rest of the frame arrives.
+fn take_frames<E: StreamFraming>(buffer: &mut Vec<u8>) -> Vec<Vec<u8>> {
+    let mut frames = Vec::new();
+    let mut start = 0;
+    while let Some(end) = buffer[start..]
+        .windows(E::DELIMITER.len())
+        .position(|window| window == E::DELIMITER)
+    {
+        frames.push(buffer[start..start + end].to_vec());
+        start += end + E::DELIMITER.len();
+    }
+    buffer.drain(..start);
+    frames
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frames_split_across_chunks() {
+        let mut buffer = b"1\n2".to_vec();
+        assert_eq!(take_frames::<JsonLines>(&mut buffer), vec![b"1".to_vec()]);
+        buffer.extend_from_slice(b"3\n\n");
+        assert_eq!(
+            take_frames::<JsonLines>(&mut buffer),
+            vec![b"23".to_vec(), Vec::new()]
+        );
+        assert!(buffer.is_empty());
+    }
+
+    #[test]
+    fn server_sent_events_round_trip() {
+        let frame = ServerSentEvents::encode(br#"{"a":1}"#.to_vec());
+        assert_eq!(&frame[..], b"data: {\"a\":1}\n\n");
+
+        let mut buffer = frame.to_vec();
+        let frames = take_frames::<ServerSentEvents>(&mut buffer);
+        assert_eq!(
+            ServerSentEvents::decode(&frames[0]),
+            Some(&br#"{"a":1}"#[..])
+        );
+        // Comments and keep-alive events don't contain a value
+        assert_eq!(ServerSentEvents::decode(b": keep-alive"), None);
+    }
+}
@@ -0,0 +1,484 @@
+//! Server side sessions backed by a pluggable store.
+
+use std::{
+    collections::HashMap,
+    future::Future,
+    pin::Pin,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use base64::Engine;
+use cookie::{Cookie, SameSite};
+use parking_lot::Mutex;
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::{
+    prelude::{DioxusServerContext, FromServerContext},
+    server_context::NotFoundInServerContext,
+};
+
+/// The values stored in a session.
+pub type SessionData = HashMap<String, serde_json::Value>;
+
+/// The future returned by the methods of a [`SessionStore`].
+pub type SessionFuture<'a, T> =
+    Pin<Box<dyn Future<Output = Result<T, SessionStoreError>> + Send + 'a>>;
+
+/// An error returned from a [`SessionStore`].
+#[derive(Debug, thiserror::Error)]
+#[error("session store error: {0}")]
+pub struct SessionStoreError(Box<dyn std::error::Error + Send + Sync>);
+
+impl SessionStoreError {
+    /// Create a new session store error from any error.
+    pub fn new(error: impl Into<Box<dyn std::error::Error + Send + Sync>>) -> Self {
+        Self(error.into())
+    }
+}
+
+/// A storage backend for sessions configured with [`SessionConfig`].
+///
+/// The store is shared between every request the server handles, so it only gets a shared
+/// reference and needs to handle synchronization internally. [`MemorySessionStore`] keeps sessions
+/// in memory. Implement this trait to keep sessions in a database that is shared between servers.
+pub trait SessionStore: Send + Sync + 'static {
+    /// Load the data of a session or `None` if the session doesn't exist or expired.
+    fn load<'a>(&'a self, id: &'a str) -> SessionFuture<'a, Option<SessionData>>;
+
+    /// Insert or replace the data of a session. The session should expire after `ttl`.
+    fn save<'a>(&'a self, id: &'a str, data: SessionData, ttl: Duration) -> SessionFuture<'a, ()>;
+
+    /// Remove a session.
+    fn delete<'a>(&'a self, id: &'a str) -> SessionFuture<'a, ()>;
+}
+
+/// A [`SessionStore`] that keeps sessions in the memory of the server. Sessions are lost when the
+/// server restarts and are not shared between servers.
+#[derive(Default)]
+pub struct MemorySessionStore {
+    sessions: Mutex<HashMap<String, (SessionData, Instant)>>,
+}
+
+impl SessionStore for MemorySessionStore {
+    fn load<'a>(&'a self, id: &'a str) -> SessionFuture<'a, Option<SessionData>> {
+        let mut sessions = self.sessions.lock();
+        let now = Instant::now();
+        sessions.retain(|_, (_, expires)| *expires > now);
+        let data = sessions.get(id).map(|(data, _)| data.clone());
+        Box::pin(async move { Ok(data) })
+    }
+
+    fn save<'a>(&'a self, id: &'a str, data: SessionData, ttl: Duration) -> SessionFuture<'a, ()> {
+        self.sessions
+            .lock()
+            .insert(id.to_string(), (data, Instant::now() + ttl));
+        Box::pin(async { Ok(()) })
+    }
+
+    fn delete<'a>(&'a self, id: &'a str) -> SessionFuture<'a, ()> {
+        self.sessions.lock().remove(id);
+        Box::pin(async { Ok(()) })
+    }
+}
+
+/// The options for sessions set with [`ServeConfigBuilder::sessions`](crate::ServeConfigBuilder::sessions).
+///
+/// The session id is stored in an http only cookie. The data of the session stays on the server
+/// in the [`SessionStore`].
+///
+/// ```rust, no_run
+/// # use dioxus::prelude::*;
+/// # fn app() -> Element { todo!() }
+/// use dioxus::fullstack::session::{MemorySessionStore, SessionConfig};
+///
+/// dioxus::LaunchBuilder::new()
+///     .with_context(server_only! {
+///         ServeConfig::builder().sessions(
+///             SessionConfig::new(MemorySessionStore::default())
+///                 .ttl(std::time::Duration::from_secs(60 * 60 * 24 * 7)),
+///         )
+///     })
+///     .launch(app);
+/// ```
+#[derive(Clone)]
+pub struct SessionConfig {
+    store: Arc<dyn SessionStore>,
+    cookie_name: String,
+    ttl: Duration,
+    secure: bool,
+    same_site: SameSite,
+}
+
+impl SessionConfig {
+    /// Create a new session config that keeps sessions in `store`. By default, the cookie is named
+    /// `dioxus_session`, sessions expire after a day and the cookie is only sent over https.
+    pub fn new(store: impl SessionStore) -> Self {
+        Self {
+            store: Arc::new(store),
+            cookie_name: "dioxus_session".to_string(),
+            ttl: Duration::from_secs(60 * 60 * 24),
+            secure: true,
+            same_site: SameSite::Lax,
+        }
+    }
+
+    /// Set the name of the cookie that stores the session id.
+    pub fn cookie_name(mut self, name: impl Into<String>) -> Self {
+        self.cookie_name = name.into();
+        self
+    }
+
+    /// Set how long a session lives after it was last changed.
+    pub fn ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    /// Set if the session cookie is only sent over https. Disable this for local development over
+    /// plain http.
+    pub fn secure(mut self, secure: bool) -> Self {
+        self.secure = secure;
+        self
+    }
+
+    /// Set the `SameSite` attribute of the session cookie. Defaults to `Lax`.
+    pub fn same_site(mut self, same_site: SameSite) -> Self {
+        self.same_site = same_site;
+        self
+    }
+
+    /// Load the session of the request into the server context
+    pub(crate) async fn load(&self, context: &DioxusServerContext) {
+        let id = context
+            .cookies()
+            .get(&self.cookie_name)
+            .map(|cookie| cookie.value().to_string());
+        let data = match &id {
+            Some(id) => match self.store.load(id).await {
+                Ok(data) => data,
+                Err(err) => {
+                    tracing::error!("Failed to load session: {err}");
+                    None
+                }
+            },
+            None => None,
+        };
+
+        let state = match data {
+            Some(data) => SessionState {
+                id,
+                data,
+                ..Default::default()
+            },
+            // The session expired or never existed. Never reuse an id the client picked
+            None => SessionState::default(),
+        };
+        context.insert(Session {
+            state: Arc::new(Mutex::new(state)),
+        });
+    }
+
+    /// Save the session of the request if it changed and update the session cookie
+    pub(crate) async fn save(&self, context: &DioxusServerContext) {
+        let Some(session) = context.get::<Session>() else {
+            return;
+        };
+        let (old_id, new_id, data) = {
+            let mut state = session.state.lock();
+            if !state.changed {
+                return;
+            }
+            state.changed = false;
+
+            let old_id = state.id.take();
+            let new_id = if state.destroyed {
+                None
+            } else if state.renew || old_id.is_none() {
+                Some(new_session_id())
+            } else {
+                old_id.clone()
+            };
+            state.renew = false;
+            state.id = new_id.clone();
+            (old_id, new_id, state.data.clone())
+        };
+
+        if let Some(old_id) = old_id.as_ref().filter(|old| Some(*old) != new_id.as_ref()) {
+            if let Err(err) = self.store.delete(old_id).await {
+                tracing::error!("Failed to delete session: {err}");
+            }
+        }
+
+        let cookies = context.cookies();
+        match new_id {
+            Some(id) => {
+                if let Err(err) = self.store.save(&id, data, self.ttl).await {
+                    tracing::error!("Failed to save session: {err}");
+                    return;
+                }
+                let max_age = cookie::time::Duration::try_from(self.ttl)
+                    .unwrap_or(cookie::time::Duration::MAX);
+                cookies.add(
+                    Cookie::build((self.cookie_name.clone(), id))
+                        .path("/")
+                        .http_only(true)
+                        .secure(self.secure)
+                        .same_site(self.same_site)
+                        .max_age(max_age),
+                );
+            }
+            None => cookies.remove(Cookie::build(self.cookie_name.clone()).path("/")),
+        }
+    }
+}
+
+fn new_session_id() -> String {
+    base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(rand::random::<[u8; 32]>())
+}
+
+#[derive(Default)]
+struct SessionState {
+    id: Option<String>,
+    data: SessionData,
+    changed: bool,
+    destroyed: bool,
+    renew: bool,
+}
+
+/// The session of the current request. Sessions are enabled with
+/// [`ServeConfigBuilder::sessions`](crate::ServeConfigBuilder::sessions).
+///
+/// The session is loaded before server functions run and before the page is rendered, so it can be
+/// read both from server functions and while rendering components on the server. Changes are
+/// saved when the response is sent. When the page is streamed, the session needs to be changed
+/// before the first part of the page is sent.
+///
+/// # Example
+///
+/// ```rust, no_run
+/// # use dioxus::prelude::*;
+/// use dioxus::fullstack::session::Session;
+///
+/// #[server]
+/// async fn login(user_id: u64) -> Result<(), ServerFnError> {
+///     let session: Session = extract().await?;
+///     // Give the session a new id when the user logs in so an old id can't be reused
+///     session.renew();
+///     session.insert("user_id", user_id)?;
+///     Ok(())
+/// }
+///
+/// #[server]
+/// async fn current_user() -> Result<Option<u64>, ServerFnError> {
+///     let session: Session = extract().await?;
+///     Ok(session.get("user_id"))
+/// }
+///
+/// #[server]
+/// async fn logout() -> Result<(), ServerFnError> {
+///     let session: Session = extract().await?;
+///     session.destroy();
+///     Ok(())
+/// }
+/// ```
+#[derive(Clone)]
+pub struct Session {
+    state: Arc<Mutex<SessionState>>,
+}
+
+impl Session {
+    /// The id of the session or `None` if the session is new and was not saved yet.
+    pub fn id(&self) -> Option<String> {
+        self.state.lock().id.clone()
+    }
+
+    /// Get a value from the session. Returns `None` if the value doesn't exist or can't be
+    /// deserialized into `T`.
+    pub fn get<T: DeserializeOwned>(&self, key: &str) -> Option<T> {
+        let state = self.state.lock();
+        let value = state.data.get(key)?;
+        serde_json::from_value(value.clone()).ok()
+    }
+
+    /// Insert or replace a value in the session.
+    pub fn insert(
+        &self,
+        key: impl Into<String>,
+        value: impl Serialize,
+    ) -> Result<(), serde_json::Error> {
+        let value = serde_json::to_value(value)?;
+        let mut state = self.state.lock();
+        state.data.insert(key.into(), value);
+        state.changed = true;
+        state.destroyed = false;
+        Ok(())
+    }
+
+    /// Remove a value from the session.
+    pub fn remove(&self, key: &str) {
+        let mut state = self.state.lock();
+        if state.data.remove(key).is_some() {
+            state.changed = true;
+        }
+    }
+
+    /// Remove every value from the session.
+    pub fn clear(&self) {
+        let mut state = self.state.lock();
+        state.data.clear();
+        state.changed = true;
+    }
+
+    /// Give the session a new id when it is saved. Call this when the user logs in to prevent
+    /// session fixation.
+    pub fn renew(&self) {
+        let mut state = self.state.lock();
+        state.renew = true;
+        state.changed = true;
+    }
+
+    /// Remove the session from the store and the session cookie from the client.
+    pub fn destroy(&self) {
+        let mut state = self.state.lock();
+        state.data.clear();
+        state.destroyed = true;
+        state.changed = true;
+    }
+}
+
+impl DioxusServerContext {
+    /// Get the session of the current request or `None` if sessions are not enabled with
+    /// [`ServeConfigBuilder::sessions`](crate::ServeConfigBuilder::sessions).
+    pub fn session(&self) -> Option<Session> {
+        self.get::<Session>()
+    }
+}
+
+#[async_trait::async_trait]
+impl FromServerContext for Session {
+    type Rejection = NotFoundInServerContext<Session>;
+
+    async fn from_request(req: &DioxusServerContext) -> Result<Self, Self::Rejection> {
+        req.session()
+            .ok_or(NotFoundInServerContext(std::marker::PhantomData))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use http::header::{COOKIE, SET_COOKIE};
+
+    /// Load the session of a request that sends the session cookie with `id`
+    async fn request(config: &SessionConfig, id: Option<&str>) -> DioxusServerContext {
+        let mut request = http::Request::get("/");
+        if let Some(id) = id {
+            request = request.header(COOKIE, format!("{}={id}", config.cookie_name));
+        }
+        let context = DioxusServerContext::new(request.body(()).unwrap().into_parts().0);
+        context.insert(config.clone());
+        context.load_session().await;
+        context
+    }
+
+    /// Save the session and return the session cookie the response sets
+    async fn respond(context: &DioxusServerContext) -> Option<Cookie<'static>> {
+        context.finish_response().await;
+        let response = context.response_parts();
+        let header = response.headers.get(SET_COOKIE)?;
+        Some(Cookie::parse(header.to_str().unwrap().to_string()).unwrap())
+    }
+
+    async fn stored(config: &SessionConfig, id: &str) -> Option<SessionData> {
+        config.store.load(id).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn sessions_are_saved_when_they_change() {
+        let config = SessionConfig::new(MemorySessionStore::default());
+        let context = request(&config, None).await;
+        assert!(context.session().unwrap().id().is_none());
+        assert!(respond(&context).await.is_none());
+
+        let context = request(&config, None).await;
+        context.session().unwrap().insert("user_id", 1).unwrap();
+        let cookie = respond(&context).await.unwrap();
+        assert_eq!(cookie.name(), "dioxus_session");
+        assert_eq!(cookie.http_only(), Some(true));
+        assert_eq!(cookie.secure(), Some(true));
+        assert_eq!(cookie.same_site(), Some(SameSite::Lax));
+        assert_eq!(cookie.path(), Some("/"));
+        assert_eq!(cookie.max_age(), Some(cookie::time::Duration::days(1)));
+
+        let context = request(&config, Some(cookie.value())).await;
+        let session = context.session().unwrap();
+        assert_eq!(session.id().as_deref(), Some(cookie.value()));
+        assert_eq!(session.get::<u64>("user_id"), Some(1));
+        // Reading the session doesn't save it again
+        assert!(respond(&context).await.is_none());
+    }
+
+    #[tokio::test]
+    async fn unknown_session_ids_are_not_reused() {
+        let config = SessionConfig::new(MemorySessionStore::default());
+        let context = request(&config, Some("picked-by-the-client")).await;
+        let session = context.session().unwrap();
+        assert!(session.id().is_none());
+        session.insert("user_id", 1).unwrap();
+        let cookie = respond(&context).await.unwrap();
+        assert_ne!(cookie.value(), "picked-by-the-client");
+        assert!(stored(&config, "picked-by-the-client").await.is_none());
+    }
+
+    #[tokio::test]
+    async fn renewed_sessions_get_a_new_id() {
+        let config = SessionConfig::new(MemorySessionStore::default());
+        let context = request(&config, None).await;
+        context.session().unwrap().insert("user_id", 1).unwrap();
+        let old = respond(&context).await.unwrap();
+
+        let context = request(&config, Some(old.value())).await;
+        context.session().unwrap().renew();
+        let new = respond(&context).await.unwrap();
+        assert_ne!(old.value(), new.value());
+        assert!(stored(&config, old.value()).await.is_none());
+        let data = stored(&config, new.value()).await.unwrap();
+        assert_eq!(data["user_id"], 1);
+    }
+
+    #[tokio::test]
+    async fn destroyed_sessions_are_removed() {
+        let config = SessionConfig::new(MemorySessionStore::default());
+        let context = request(&config, None).await;
+        context.session().unwrap().insert("user_id", 1).unwrap();
+        let id = respond(&context).await.unwrap().value().to_string();
+
+        let context = request(&config, Some(&id)).await;
+        context.session().unwrap().destroy();
+        let removal = respond(&context).await.unwrap();
+        assert_eq!(removal.name(), "dioxus_session");
+        assert_eq!(removal.value(), "");
+        assert_eq!(removal.max_age(), Some(cookie::time::Duration::ZERO));
+        assert!(stored(&config, &id).await.is_none());
+
+        let context = request(&config, Some(&id)).await;
+        assert!(context.session().unwrap().get::<u64>("user_id").is_none());
+    }
+
+    #[tokio::test]
+    async fn sessions_expire_after_the_ttl() {
+        let config =
+            SessionConfig::new(MemorySessionStore::default()).ttl(Duration::from_millis(20));
+        let context = request(&config, None).await;
+        context.session().unwrap().insert("user_id", 1).unwrap();
+        let id = respond(&context).await.unwrap().value().to_string();
+        assert!(stored(&config, &id).await.is_some());
+
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        let context = request(&config, Some(&id)).await;
+        let session = context.session().unwrap();
+        assert!(session.id().is_none());
+        assert!(session.get::<u64>("user_id").is_none());
+    }
+}
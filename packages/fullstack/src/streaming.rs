@@ -156,6 +156,29 @@ pub(crate) struct Mount {
     id: MountPath,
 }
 
+impl Mount {
+    /// The ids of the placeholder and every placeholder it is nested in, starting from the root
+    pub(crate) fn path(&self) -> Vec<usize> {
+        let mut path = vec![self.id.id];
+        let mut parent = self.id.parent.as_deref();
+        while let Some(mount) = parent {
+            path.push(mount.id);
+            parent = mount.parent.as_deref();
+        }
+        path.reverse();
+        path
+    }
+
+    /// Get the mount of a placeholder that was rendered in an earlier response from its [`Self::path`]
+    pub(crate) fn from_path(path: &[usize]) -> Option<Self> {
+        let (&id, parents) = path.split_last()?;
+        let parent = Self::from_path(parents).map(|parent| Arc::new(parent.id));
+        Some(Self {
+            id: MountPath { parent, id },
+        })
+    }
+}
+
 impl Display for Mount {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{}", self.id)
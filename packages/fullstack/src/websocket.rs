@@ -0,0 +1,750 @@
+//! Server functions that exchange typed messages with the client over a websocket.
+
+use std::{
+    fmt::Debug,
+    future::Future,
+    marker::PhantomData,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use bytes::Bytes;
+use futures_channel::{mpsc, oneshot};
+use futures_util::{future::ready, Stream, StreamExt};
+use http::Method;
+use serde::{de::DeserializeOwned, Serialize};
+use server_fn::{
+    client::Client,
+    codec::{Encoding, FromRes, IntoRes},
+    request::{BrowserMockReq, ClientReq},
+    response::{BrowserMockRes, ClientRes},
+    ServerFnError,
+};
+
+/// The protocol of a server function that keeps a websocket open to exchange messages with the
+/// client. `In` is the type of the messages the client sends and `Out` is the type of the
+/// messages the server sends. Messages are sent as JSON text frames.
+///
+/// Set it with the `protocol` argument of the server macro. The server function takes a single
+/// [`WebsocketStream`] with the messages from the client and returns a [`WebsocketStream`] with the
+/// messages for the client:
+///
+/// ```rust, no_run
+/// # use dioxus::prelude::*;
+/// # use futures_util::StreamExt;
+/// #[server(protocol = Websocket<String, String>)]
+/// async fn shout(input: WebsocketStream<String>) -> Result<WebsocketStream<String>, ServerFnError> {
+///     Ok(WebsocketStream::new(input.into_inner().map(|message| {
+///         message.map(|message| message.to_uppercase())
+///     })))
+/// }
+///
+/// # async fn run() -> Result<(), ServerFnError> {
+/// // On the client, the function opens the socket and returns the messages from the server
+/// let mut replies = shout(futures_util::stream::iter(["hello".to_string()]).into())
+///     .await?
+///     .into_inner();
+/// while let Some(reply) = replies.next().await {
+///     println!("{}", reply?);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+///
+/// The socket is closed once the server stops sending messages or the client drops the stream of
+/// replies. If the stream on the server yields an error, the socket is closed and the client
+/// receives the error as the last message.
+pub struct Websocket<In, Out>(PhantomData<fn() -> (In, Out)>);
+
+impl<In, Out> Encoding for Websocket<In, Out> {
+    const CONTENT_TYPE: &'static str = "application/json";
+    // The websocket handshake is always a GET request
+    const METHOD: Method = Method::GET;
+}
+
+/// Encodes the arguments of a server function with the [`Websocket`] protocol. The server macro
+/// wraps the arguments of every server function with the protocol in a type next to the function
+/// and implements the encodings for it with this trait.
+#[doc(hidden)]
+pub trait WebsocketArgs<T, Request, CustErr> {
+    /// Open the websocket on the client and send the messages from the arguments
+    fn into_req(args: T, path: &str, accepts: &str) -> Result<Request, ServerFnError<CustErr>>;
+
+    /// Accept the websocket on the server and read the arguments from the messages of the client
+    fn from_req(req: Request) -> impl Future<Output = Result<T, ServerFnError<CustErr>>> + Send;
+}
+
+/// A request that can carry the messages of a server function with the [`Websocket`] protocol
+#[doc(hidden)]
+pub trait WebsocketTransport<CustErr>: Sized {
+    /// Create the request that opens the websocket on the client
+    fn connect(
+        path: &str,
+        accepts: &str,
+        frames: impl Stream<Item = Bytes> + Send + 'static,
+    ) -> Result<Self, ServerFnError<CustErr>>;
+
+    /// Accept the websocket on the server and return the messages the client sends
+    fn accept(self) -> impl Future<Output = Result<Frames, ServerFnError<CustErr>>> + Send;
+}
+
+/// The raw messages sent over the websocket of a server function
+type Frames = mpsc::UnboundedReceiver<Result<Bytes, ServerFnError>>;
+
+/// A stream of messages sent over a server function with the [`Websocket`] protocol.
+pub struct WebsocketStream<T>(Pin<Box<dyn Stream<Item = Result<T, ServerFnError>> + Send>>);
+
+impl<T> Debug for WebsocketStream<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("WebsocketStream").finish()
+    }
+}
+
+impl<T> WebsocketStream<T> {
+    /// Create a new `WebsocketStream` from a stream of results.
+    pub fn new(value: impl Stream<Item = Result<T, ServerFnError>> + Send + 'static) -> Self {
+        Self(Box::pin(value))
+    }
+
+    /// Consumes the wrapper, returning the stream of messages.
+    pub fn into_inner(self) -> impl Stream<Item = Result<T, ServerFnError>> + Send {
+        self.0
+    }
+}
+
+impl<S, T> From<S> for WebsocketStream<T>
+where
+    S: Stream<Item = T> + Send + 'static,
+    T: 'static,
+{
+    fn from(value: S) -> Self {
+        Self(Box::pin(value.map(Ok)))
+    }
+}
+
+impl<T: Serialize + 'static> WebsocketStream<T> {
+    /// Serialize every message until the stream ends or yields an error
+    fn into_frames(self) -> impl Stream<Item = Result<Bytes, ServerFnError>> + Send {
+        let mut failed = false;
+        self.0.filter_map(move |message| {
+            let frame = match message {
+                _ if failed => None,
+                Ok(message) => match serde_json::to_vec(&message) {
+                    Ok(json) => Some(Ok(Bytes::from(json))),
+                    Err(err) => Some(Err(ServerFnError::Serialization(err.to_string()))),
+                },
+                Err(err) => Some(Err(err)),
+            };
+            failed |= matches!(frame, Some(Err(_)));
+            ready(frame)
+        })
+    }
+}
+
+impl<T: DeserializeOwned + 'static> WebsocketStream<T> {
+    fn from_frames(
+        frames: impl Stream<Item = Result<Bytes, ServerFnError>> + Send + 'static,
+    ) -> Self {
+        Self::new(frames.map(|frame| {
+            serde_json::from_slice(&frame?)
+                .map_err(|err| ServerFnError::Deserialization(err.to_string()))
+        }))
+    }
+}
+
+impl<T, In, Out, Request, CustErr> WebsocketArgs<T, Request, CustErr> for Websocket<In, Out>
+where
+    T: Into<WebsocketStream<In>> + From<WebsocketStream<In>>,
+    In: Serialize + DeserializeOwned + Send + 'static,
+    Request: WebsocketTransport<CustErr> + Send,
+{
+    fn into_req(args: T, path: &str, accepts: &str) -> Result<Request, ServerFnError<CustErr>> {
+        // The client stops sending messages if its stream yields an error
+        let frames = args
+            .into()
+            .into_frames()
+            .take_while(|frame| ready(frame.is_ok()))
+            .filter_map(|frame| ready(frame.ok()));
+        Request::connect(path, accepts, frames)
+    }
+
+    async fn from_req(req: Request) -> Result<T, ServerFnError<CustErr>> {
+        let frames = req.accept().await?;
+        Ok(T::from(WebsocketStream::from_frames(frames)))
+    }
+}
+
+impl<In, Out, CustErr> FromRes<Websocket<In, Out>, WebsocketResponse, CustErr>
+    for WebsocketStream<Out>
+where
+    Out: DeserializeOwned + Send + 'static,
+{
+    async fn from_res(res: WebsocketResponse) -> Result<Self, ServerFnError<CustErr>> {
+        Ok(Self::from_frames(res))
+    }
+}
+
+// The server request and response types are mocked when the server feature is disabled
+impl<CustErr> WebsocketTransport<CustErr> for BrowserMockReq {
+    fn connect(
+        _: &str,
+        _: &str,
+        _: impl Stream<Item = Bytes> + Send + 'static,
+    ) -> Result<Self, ServerFnError<CustErr>> {
+        unreachable!("websocket server functions are only called from the client")
+    }
+
+    async fn accept(self) -> Result<Frames, ServerFnError<CustErr>> {
+        unreachable!("websocket server functions only run on the server")
+    }
+}
+
+impl<In, Out, CustErr> IntoRes<Websocket<In, Out>, BrowserMockRes, CustErr>
+    for WebsocketStream<Out>
+{
+    async fn into_res(self) -> Result<BrowserMockRes, ServerFnError<CustErr>> {
+        unreachable!("websocket server functions only run on the server")
+    }
+}
+
+/// The request for a server function with the [`Websocket`] protocol. It holds the messages the
+/// client sends once the socket is open.
+#[doc(hidden)]
+pub struct WebsocketRequest {
+    path: String,
+    messages: Pin<Box<dyn Stream<Item = Bytes> + Send>>,
+}
+
+impl<CustErr> ClientReq<CustErr> for WebsocketRequest {
+    type FormData = ();
+
+    fn try_new_get(_: &str, _: &str, _: &str, _: &str) -> Result<Self, ServerFnError<CustErr>> {
+        Err(only_streams())
+    }
+
+    fn try_new_post(_: &str, _: &str, _: &str, _: String) -> Result<Self, ServerFnError<CustErr>> {
+        Err(only_streams())
+    }
+
+    fn try_new_post_bytes(
+        _: &str,
+        _: &str,
+        _: &str,
+        _: Bytes,
+    ) -> Result<Self, ServerFnError<CustErr>> {
+        Err(only_streams())
+    }
+
+    fn try_new_post_form_data(
+        _: &str,
+        _: &str,
+        _: &str,
+        _: Self::FormData,
+    ) -> Result<Self, ServerFnError<CustErr>> {
+        Err(only_streams())
+    }
+
+    fn try_new_multipart(
+        _: &str,
+        _: &str,
+        _: Self::FormData,
+    ) -> Result<Self, ServerFnError<CustErr>> {
+        Err(only_streams())
+    }
+
+    fn try_new_streaming(
+        path: &str,
+        _: &str,
+        _: &str,
+        body: impl Stream<Item = Bytes> + Send + 'static,
+    ) -> Result<Self, ServerFnError<CustErr>> {
+        Ok(Self {
+            path: path.to_string(),
+            messages: Box::pin(body),
+        })
+    }
+}
+
+impl<CustErr> WebsocketTransport<CustErr> for WebsocketRequest {
+    fn connect(
+        path: &str,
+        _: &str,
+        frames: impl Stream<Item = Bytes> + Send + 'static,
+    ) -> Result<Self, ServerFnError<CustErr>> {
+        Ok(Self {
+            path: path.to_string(),
+            messages: Box::pin(frames),
+        })
+    }
+
+    async fn accept(self) -> Result<Frames, ServerFnError<CustErr>> {
+        unreachable!("websocket server functions only run on the server")
+    }
+}
+
+fn only_streams<CustErr>() -> ServerFnError<CustErr> {
+    ServerFnError::Request("websocket server functions can only send a stream of messages".into())
+}
+
+/// The messages the server sends over a websocket opened by a server function with the
+/// [`Websocket`] protocol. The socket is closed when this is dropped.
+#[doc(hidden)]
+pub struct WebsocketResponse {
+    messages: mpsc::UnboundedReceiver<Result<Bytes, ServerFnError>>,
+    _close: oneshot::Sender<()>,
+}
+
+impl Stream for WebsocketResponse {
+    type Item = Result<Bytes, ServerFnError>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.messages.poll_next_unpin(cx)
+    }
+}
+
+impl<CustErr> ClientRes<CustErr> for WebsocketResponse {
+    async fn try_into_string(self) -> Result<String, ServerFnError<CustErr>> {
+        Err(ServerFnError::Response(
+            "websocket server functions only receive a stream of messages".into(),
+        ))
+    }
+
+    async fn try_into_bytes(self) -> Result<Bytes, ServerFnError<CustErr>> {
+        Err(ServerFnError::Response(
+            "websocket server functions only receive a stream of messages".into(),
+        ))
+    }
+
+    fn try_into_stream(
+        self,
+    ) -> Result<
+        impl Stream<Item = Result<Bytes, ServerFnError>> + Send + Sync + 'static,
+        ServerFnError<CustErr>,
+    > {
+        Ok(self)
+    }
+
+    fn status(&self) -> u16 {
+        http::StatusCode::SWITCHING_PROTOCOLS.as_u16()
+    }
+
+    fn status_text(&self) -> String {
+        http::StatusCode::SWITCHING_PROTOCOLS.to_string()
+    }
+
+    fn location(&self) -> String {
+        String::new()
+    }
+
+    fn has_redirect(&self) -> bool {
+        false
+    }
+}
+
+impl<In, Out, CustErr> Client<CustErr> for Websocket<In, Out> {
+    type Request = WebsocketRequest;
+    type Response = WebsocketResponse;
+
+    fn send(
+        req: Self::Request,
+    ) -> impl std::future::Future<Output = Result<Self::Response, ServerFnError<CustErr>>> + Send
+    {
+        #[cfg(all(target_arch = "wasm32", feature = "web"))]
+        {
+            send_wrapper::SendWrapper::new(browser::connect(req))
+        }
+        #[cfg(all(
+            not(target_arch = "wasm32"),
+            any(feature = "desktop", feature = "mobile", all(test, feature = "server"))
+        ))]
+        {
+            native::connect(req)
+        }
+        #[cfg(not(any(
+            all(target_arch = "wasm32", feature = "web"),
+            all(
+                not(target_arch = "wasm32"),
+                any(feature = "desktop", feature = "mobile", all(test, feature = "server"))
+            )
+        )))]
+        {
+            _ = (req.path, req.messages);
+            async {
+                Err(ServerFnError::Request(
+                    "websocket server functions can only be called from the web, desktop or mobile client"
+                        .into(),
+                ))
+            }
+        }
+    }
+}
+
+/// Get the url of the websocket for a server function
+#[allow(unused)]
+fn websocket_url(path: &str, origin: &str) -> String {
+    let origin = match origin.split_once("://") {
+        Some(("https", rest)) => format!("wss://{rest}"),
+        Some(("http", rest)) => format!("ws://{rest}"),
+        _ => origin.to_string(),
+    };
+    format!("{}{path}", origin.trim_end_matches('/'))
+}
+
+#[cfg(all(target_arch = "wasm32", feature = "web"))]
+mod browser {
+    use super::*;
+    use wasm_bindgen::{closure::Closure, JsCast};
+    use web_sys::{BinaryType, CloseEvent, MessageEvent, WebSocket};
+
+    pub(super) async fn connect<CustErr>(
+        req: WebsocketRequest,
+    ) -> Result<WebsocketResponse, ServerFnError<CustErr>> {
+        let origin = match server_fn::client::get_server_url() {
+            "" => web_sys::window()
+                .and_then(|window| window.location().origin().ok())
+                .unwrap_or_default(),
+            url => url.to_string(),
+        };
+        let socket = WebSocket::new(&websocket_url(&req.path, &origin))
+            .map_err(|err| ServerFnError::Request(format!("{err:?}")))?;
+        socket.set_binary_type(BinaryType::Arraybuffer);
+
+        let (messages_tx, messages) = mpsc::unbounded();
+        let (opened_tx, opened) = oneshot::channel();
+        let mut opened_tx = Some(opened_tx);
+
+        let on_message = Closure::<dyn FnMut(MessageEvent)>::new({
+            let messages_tx = messages_tx.clone();
+            move |event: MessageEvent| {
+                let data = event.data();
+                let frame = match data.as_string() {
+                    Some(text) => Bytes::from(text),
+                    None => Bytes::from(js_sys::Uint8Array::new(&data).to_vec()),
+                };
+                _ = messages_tx.unbounded_send(Ok(frame));
+            }
+        });
+        let on_open = Closure::<dyn FnMut()>::new(move || {
+            if let Some(opened_tx) = opened_tx.take() {
+                _ = opened_tx.send(());
+            }
+        });
+        let on_close = Closure::<dyn FnMut(CloseEvent)>::new(move |event: CloseEvent| {
+            if !event.was_clean() || event.code() == CLOSE_ERROR {
+                _ = messages_tx.unbounded_send(Err(ServerFnError::ServerError(event.reason())));
+            }
+            messages_tx.close_channel();
+        });
+        socket.set_onmessage(Some(on_message.as_ref().unchecked_ref()));
+        socket.set_onopen(Some(on_open.as_ref().unchecked_ref()));
+        socket.set_onclose(Some(on_close.as_ref().unchecked_ref()));
+
+        // The socket closes before it opens if the handshake fails
+        if opened.await.is_err() {
+            return Err(ServerFnError::Request(format!(
+                "failed to open a websocket to {}",
+                req.path
+            )));
+        }
+
+        let (close, closed) = oneshot::channel();
+        wasm_bindgen_futures::spawn_local(async move {
+            // Keep the callbacks alive as long as the socket is open
+            let _callbacks = (on_message, on_open, on_close);
+            let send = req.messages.for_each(|frame| {
+                _ = match std::str::from_utf8(&frame) {
+                    Ok(text) => socket.send_with_str(text),
+                    Err(_) => socket.send_with_u8_array(&frame),
+                };
+                ready(())
+            });
+            // Keep the socket open after the client stops sending until the replies are dropped
+            futures_util::future::join(send, closed).await;
+            _ = socket.close();
+        });
+
+        Ok(WebsocketResponse {
+            messages,
+            _close: close,
+        })
+    }
+}
+
+#[cfg(all(
+    not(target_arch = "wasm32"),
+    any(feature = "desktop", feature = "mobile", all(test, feature = "server"))
+))]
+mod native {
+    use super::*;
+    use futures_util::SinkExt;
+    use tokio_tungstenite::tungstenite::{protocol::frame::coding::CloseCode, Message};
+
+    pub(super) async fn connect<CustErr>(
+        req: WebsocketRequest,
+    ) -> Result<WebsocketResponse, ServerFnError<CustErr>> {
+        let url = websocket_url(&req.path, server_fn::client::get_server_url());
+        let (socket, _) = tokio_tungstenite::connect_async(url)
+            .await
+            .map_err(|err| ServerFnError::Request(err.to_string()))?;
+        let (mut sink, mut stream) = socket.split();
+
+        let (messages_tx, messages) = mpsc::unbounded();
+        let (close, closed) = oneshot::channel::<()>();
+        tokio::spawn(async move {
+            let receive = async {
+                while let Some(message) = stream.next().await {
+                    let frame = match message {
+                        Ok(Message::Text(text)) => Ok(Bytes::from(text)),
+                        Ok(Message::Binary(binary)) => Ok(Bytes::from(binary)),
+                        Ok(Message::Close(Some(frame))) if frame.code == CloseCode::Error => {
+                            Err(ServerFnError::ServerError(frame.reason.into_owned()))
+                        }
+                        Ok(Message::Close(_)) => break,
+                        Ok(_) => continue,
+                        Err(err) => Err(ServerFnError::Response(err.to_string())),
+                    };
+                    let failed = frame.is_err();
+                    if messages_tx.unbounded_send(frame).is_err() || failed {
+                        break;
+                    }
+                }
+            };
+            let send = async {
+                let mut frames = req.messages;
+                while let Some(frame) = frames.next().await {
+                    let message = match String::from_utf8(frame.to_vec()) {
+                        Ok(text) => Message::Text(text),
+                        Err(err) => Message::Binary(err.into_bytes()),
+                    };
+                    if sink.send(message).await.is_err() {
+                        return;
+                    }
+                }
+                // Keep the socket open until the replies are dropped
+                _ = closed.await;
+                _ = sink.close().await;
+            };
+            // Stop once the server closes the socket or the client drops the replies
+            futures_util::future::select(Box::pin(receive), Box::pin(send)).await;
+        });
+
+        Ok(WebsocketResponse {
+            messages,
+            _close: close,
+        })
+    }
+}
+
+/// The close code the server sends if the stream of messages yields an error
+#[allow(unused)]
+const CLOSE_ERROR: u16 = 1011;
+
+#[cfg(feature = "axum")]
+mod server {
+    use super::*;
+    use crate::prelude::server_context;
+    use axum::{
+        body::Body,
+        extract::{
+            ws::{CloseFrame, Message, WebSocket, WebSocketUpgrade},
+            FromRequestParts,
+        },
+    };
+    use futures_util::{stream::BoxStream, SinkExt};
+    use std::sync::{Arc, Mutex};
+
+    type Replies = BoxStream<'static, Result<Bytes, ServerFnError>>;
+
+    /// The handshake response of a websocket that is waiting for the server function to return
+    /// the stream of messages for the client
+    #[derive(Clone)]
+    struct PendingSocket(Arc<Mutex<Option<Handshake>>>);
+
+    /// The upgrade response and the channel the server function's messages are sent through
+    type Handshake = (http::Response<Body>, oneshot::Sender<Replies>);
+
+    impl<CustErr> WebsocketTransport<CustErr> for http::Request<Body> {
+        fn connect(
+            _: &str,
+            _: &str,
+            _: impl Stream<Item = Bytes> + Send + 'static,
+        ) -> Result<Self, ServerFnError<CustErr>> {
+            unreachable!("websocket server functions are only called from the client")
+        }
+
+        async fn accept(self) -> Result<Frames, ServerFnError<CustErr>> {
+            let (mut parts, _) = self.into_parts();
+            let upgrade = WebSocketUpgrade::from_request_parts(&mut parts, &())
+                .await
+                .map_err(|err| ServerFnError::Args(err.body_text()))?;
+
+            let (messages_tx, messages) = mpsc::unbounded();
+            let (replies_tx, replies) = oneshot::channel();
+            let response =
+                upgrade.on_upgrade(move |socket| run_socket(socket, messages_tx, replies));
+            server_context().insert(PendingSocket(Arc::new(Mutex::new(Some((
+                response, replies_tx,
+            ))))));
+
+            Ok(messages)
+        }
+    }
+
+    impl<In, Out, CustErr> IntoRes<Websocket<In, Out>, http::Response<Body>, CustErr>
+        for WebsocketStream<Out>
+    where
+        Out: Serialize + Send + 'static,
+    {
+        async fn into_res(self) -> Result<http::Response<Body>, ServerFnError<CustErr>> {
+            let pending = server_context()
+                .get::<PendingSocket>()
+                .and_then(|PendingSocket(pending)| pending.lock().unwrap().take());
+            let Some((response, replies)) = pending else {
+                return Err(ServerFnError::ServerError(
+                    "the websocket of the server function was already opened".into(),
+                ));
+            };
+            _ = replies.send(Box::pin(self.into_frames()));
+            Ok(response)
+        }
+    }
+
+    /// Forward the messages from the socket to the server function and the replies of the server
+    /// function to the socket
+    async fn run_socket(
+        socket: WebSocket,
+        messages: mpsc::UnboundedSender<Result<Bytes, ServerFnError>>,
+        replies: oneshot::Receiver<Replies>,
+    ) {
+        let (mut sink, mut stream) = socket.split();
+        let receive = async move {
+            while let Some(message) = stream.next().await {
+                let frame = match message {
+                    Ok(Message::Text(text)) => Ok(Bytes::from(text)),
+                    Ok(Message::Binary(binary)) => Ok(Bytes::from(binary)),
+                    Ok(Message::Close(_)) => break,
+                    Ok(_) => continue,
+                    Err(err) => Err(ServerFnError::Request(err.to_string())),
+                };
+                let failed = frame.is_err();
+                if messages.unbounded_send(frame).is_err() || failed {
+                    break;
+                }
+            }
+        };
+        let send = async move {
+            let Ok(mut replies) = replies.await else {
+                return;
+            };
+            while let Some(frame) = replies.next().await {
+                let message = match frame {
+                    Ok(frame) => match String::from_utf8(frame.to_vec()) {
+                        Ok(text) => Message::Text(text),
+                        Err(err) => Message::Binary(err.into_bytes()),
+                    },
+                    Err(err) => {
+                        _ = sink
+                            .send(Message::Close(Some(CloseFrame {
+                                code: CLOSE_ERROR,
+                                reason: err.to_string().into(),
+                            })))
+                            .await;
+                        return;
+                    }
+                };
+                if sink.send(message).await.is_err() {
+                    return;
+                }
+            }
+            _ = sink.close().await;
+        };
+        // Stop once the server function stops sending or the client disconnects
+        futures_util::future::select(Box::pin(receive), Box::pin(send)).await;
+    }
+}
+
+#[cfg(all(test, feature = "axum"))]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+    use server_fn::ServerFn;
+
+    #[server(protocol = Websocket<String, String>)]
+    async fn shout(
+        input: WebsocketStream<String>,
+    ) -> Result<WebsocketStream<String>, ServerFnError> {
+        Ok(WebsocketStream::new(input.into_inner().map(|message| {
+            message.map(|message| message.to_uppercase())
+        })))
+    }
+
+    #[server(protocol = Websocket<u32, u32>)]
+    async fn count_until_error(
+        input: WebsocketStream<u32>,
+    ) -> Result<WebsocketStream<u32>, ServerFnError> {
+        _ = input;
+        Ok(WebsocketStream::new(futures_util::stream::iter([
+            Ok(1),
+            Ok(2),
+            Err(ServerFnError::new("counting failed")),
+            Ok(3),
+        ])))
+    }
+
+    #[tokio::test]
+    async fn messages_round_trip_over_the_socket() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        server_fn::client::set_server_url(Box::leak(url.into_boxed_str()));
+        let router = axum::Router::new().register_server_functions();
+        tokio::spawn(async move { axum::serve(listener, router).await });
+
+        // The client keeps sending while the server replies
+        let (messages, receiver) = mpsc::unbounded::<String>();
+        let mut replies = ShoutProtocolArgs(Shout {
+            input: receiver.into(),
+        })
+        .run_on_client()
+        .await
+        .unwrap()
+        .into_inner();
+        for message in ["hello", "websocket"] {
+            messages.unbounded_send(message.to_string()).unwrap();
+            let reply = replies.next().await.unwrap().unwrap();
+            assert_eq!(reply, message.to_uppercase());
+        }
+        drop(messages);
+        drop(replies);
+
+        // Errors close the socket and are the last message the client receives
+        let replies: Vec<_> = CountUntilErrorProtocolArgs(CountUntilError {
+            input: futures_util::stream::pending().into(),
+        })
+        .run_on_client()
+        .await
+        .unwrap()
+        .into_inner()
+        .collect()
+        .await;
+        assert_eq!(replies.len(), 3);
+        assert_eq!(replies[0].as_ref().unwrap(), &1);
+        assert_eq!(replies[1].as_ref().unwrap(), &2);
+        assert!(replies[2]
+            .as_ref()
+            .unwrap_err()
+            .to_string()
+            .contains("counting failed"));
+    }
+
+    #[test]
+    fn websocket_urls_use_the_server_origin() {
+        assert_eq!(
+            websocket_url("/api/shout", "https://example.com/"),
+            "wss://example.com/api/shout"
+        );
+        assert_eq!(
+            websocket_url("/api/shout", "http://127.0.0.1:8080"),
+            "ws://127.0.0.1:8080/api/shout"
+        );
+    }
+}
@@ -39,6 +39,18 @@ pub mod minimal_bindings {
 
         /// Roll up all the values from the node into a JS object that we can deserialize
         pub fn collectFormValues(node: JsValue) -> JsValue;
+
+        /// Submit forms with the given action through the callback with their url encoded values
+        pub fn registerFormAction(
+            action: &str,
+            callback: &wasm_bindgen::closure::Closure<dyn FnMut(String)>,
+        );
+
+        /// Stop submitting forms with the given action through the callback
+        pub fn unregisterFormAction(
+            action: &str,
+            callback: &wasm_bindgen::closure::Closure<dyn FnMut(String)>,
+        );
     }
 
     #[wasm_bindgen(module = "/src/js/hydrate.js")]
@@ -1,2 +1,2 @@
 export { setAttributeInner } from "./set_attribute";
-export { retrieveFormValues } from "./form";
+export { retrieveFormValues, registerFormAction, unregisterFormAction } from "./form";
@@ -56,3 +56,49 @@ export function retrieveSelectValue(target: HTMLSelectElement): string[] {
   }
   return values;
 }
+
+// Forms whose action is a server function bound with `use_action`. Once the page is hydrated, submitting
+// one of these forms calls the callback with the url encoded values instead of navigating to the action
+const formActions: Map<string, (body: string) => void> = new Map();
+
+export function registerFormAction(action: string, callback: (body: string) => void) {
+  if (formActions.size === 0) {
+    document.addEventListener("submit", interceptFormAction);
+  }
+  formActions.set(action, callback);
+}
+
+export function unregisterFormAction(action: string, callback: (body: string) => void) {
+  // Another form may have registered the same action since
+  if (formActions.get(action) === callback) {
+    formActions.delete(action);
+  }
+  if (formActions.size === 0) {
+    document.removeEventListener("submit", interceptFormAction);
+  }
+}
+
+function interceptFormAction(event: SubmitEvent) {
+  const form = event.target;
+  // If an onsubmit handler already took care of the form, leave it alone
+  if (event.defaultPrevented || !(form instanceof HTMLFormElement)) {
+    return;
+  }
+
+  // The button that submitted the form can override the action
+  const action = event.submitter?.getAttribute("formaction") ?? form.getAttribute("action");
+  const callback = action && formActions.get(action);
+  if (!callback) {
+    return;
+  }
+  event.preventDefault();
+
+  // Encode the values the same way the browser does when it posts the form
+  const body = new URLSearchParams();
+  new FormData(form).forEach((value, key) => {
+    if (typeof value === "string") {
+      body.append(key, value);
+    }
+  });
+  callback(body.toString());
+}
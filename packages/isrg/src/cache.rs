@@ -0,0 +1,102 @@
+use std::{future::Future, pin::Pin};
+
+use chrono::{DateTime, Utc};
+
+use crate::IncrementalRendererError;
+
+/// The future returned by the methods of a [`RenderCache`].
+pub type CacheFuture<'a, T> =
+    Pin<Box<dyn Future<Output = Result<T, IncrementalRendererError>> + Send + 'a>>;
+
+/// A rendered route stored in a [`RenderCache`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CacheEntry {
+    /// The time the route was rendered
+    pub timestamp: DateTime<Utc>,
+    /// The tags attached to the render. Invalidating any of these tags removes the entry from the cache.
+    pub tags: Vec<String>,
+    /// The rendered html
+    pub html: Vec<u8>,
+}
+
+/// A storage backend for the [`IncrementalRenderer`](crate::IncrementalRenderer).
+///
+/// The renderer keeps a chain of caches. Lookups go through the chain in order and the first hit is
+/// copied into every cache before it, so fast local caches should come first and slower shared
+/// caches last. Writes and invalidations go to every cache in the chain.
+///
+/// Caches are shared between every request the server handles, so they only get a shared reference
+/// and need to handle synchronization internally.
+///
+/// ```rust
+/// use dioxus_isrg::{CacheEntry, CacheFuture, IncrementalRenderer, RenderCache};
+/// use std::{collections::HashMap, sync::{Arc, Mutex}};
+///
+/// // A fake cache that stores renders in a hash map. A real implementation could talk to a
+/// // cache shared between all of your servers instead.
+/// #[derive(Default)]
+/// struct HashMapCache(Mutex<HashMap<String, CacheEntry>>);
+///
+/// impl RenderCache for HashMapCache {
+///     fn get<'a>(&'a self, route: &'a str) -> CacheFuture<'a, Option<CacheEntry>> {
+///         Box::pin(async move { Ok(self.0.lock().unwrap().get(route).cloned()) })
+///     }
+///
+///     fn put<'a>(&'a self, route: &'a str, entry: CacheEntry) -> CacheFuture<'a, ()> {
+///         Box::pin(async move {
+///             self.0.lock().unwrap().insert(route.to_string(), entry);
+///             Ok(())
+///         })
+///     }
+///
+///     fn invalidate<'a>(&'a self, route: &'a str) -> CacheFuture<'a, ()> {
+///         Box::pin(async move {
+///             self.0.lock().unwrap().remove(route);
+///             Ok(())
+///         })
+///     }
+///
+///     fn invalidate_tag<'a>(&'a self, tag: &'a str) -> CacheFuture<'a, ()> {
+///         Box::pin(async move {
+///             self.0
+///                 .lock()
+///                 .unwrap()
+///                 .retain(|_, entry| !entry.tags.iter().any(|t| t == tag));
+///             Ok(())
+///         })
+///     }
+///
+///     fn clear(&self) -> CacheFuture<'_, ()> {
+///         Box::pin(async move {
+///             self.0.lock().unwrap().clear();
+///             Ok(())
+///         })
+///     }
+/// }
+///
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// let cache = Arc::new(HashMapCache::default());
+/// let renderer = IncrementalRenderer::builder()
+///     .cache_backends([cache.clone() as Arc<dyn RenderCache>])
+///     .build();
+///
+/// renderer.cache("/index".to_string(), "Hello world").await.unwrap();
+/// assert_eq!(cache.0.lock().unwrap()["/index"].html, b"Hello world");
+/// # });
+/// ```
+pub trait RenderCache: Send + Sync {
+    /// Get the cached render of a route or `None` if the route is not cached.
+    fn get<'a>(&'a self, route: &'a str) -> CacheFuture<'a, Option<CacheEntry>>;
+
+    /// Insert or replace the cached render of a route.
+    fn put<'a>(&'a self, route: &'a str, entry: CacheEntry) -> CacheFuture<'a, ()>;
+
+    /// Remove a route from the cache.
+    fn invalidate<'a>(&'a self, route: &'a str) -> CacheFuture<'a, ()>;
+
+    /// Remove every route that was cached with the tag `tag`.
+    fn invalidate_tag<'a>(&'a self, tag: &'a str) -> CacheFuture<'a, ()>;
+
+    /// Remove every route from the cache.
+    fn clear(&self) -> CacheFuture<'_, ()>;
+}
@@ -4,10 +4,11 @@
 use crate::fs_cache::PathMapFn;
 
 use crate::memory_cache::InMemoryCache;
-use crate::IncrementalRenderer;
+use crate::{IncrementalRenderer, RenderCache};
 
 use std::{
     path::{Path, PathBuf},
+    sync::Arc,
     time::Duration,
 };
 
@@ -17,8 +18,10 @@ pub struct IncrementalRendererConfig {
     static_dir: PathBuf,
     memory_cache_limit: usize,
     invalidate_after: Option<Duration>,
-    clear_cache: bool,
+    stale_while_revalidate: Option<Duration>,
+    clear_cache: Option<bool>,
     pre_render: bool,
+    cache_backends: Option<Vec<Arc<dyn RenderCache>>>,
 
     #[cfg(not(target_arch = "wasm32"))]
     map_path: Option<PathMapFn>,
@@ -37,16 +40,22 @@ impl IncrementalRendererConfig {
             static_dir: PathBuf::from("./static"),
             memory_cache_limit: 10000,
             invalidate_after: None,
-            clear_cache: true,
+            stale_while_revalidate: None,
+            clear_cache: None,
             pre_render: false,
+            cache_backends: None,
             #[cfg(not(target_arch = "wasm32"))]
             map_path: None,
         }
     }
 
-    /// Clear the cache on startup (default: true)
+    /// Clear the cache on startup (default: true for the default caches, false if custom
+    /// [`cache_backends`](Self::cache_backends) are set)
+    ///
+    /// Custom backends are often shared between several servers, so clearing them on startup would
+    /// wipe the cache every time a server restarts.
     pub fn clear_cache(mut self, clear_cache: bool) -> Self {
-        self.clear_cache = clear_cache;
+        self.clear_cache = Some(clear_cache);
         self
     }
 
@@ -76,29 +85,60 @@ impl IncrementalRendererConfig {
         self
     }
 
+    /// Keep serving routes for `stale_while_revalidate` after they are older than the
+    /// [`invalidate_after`](Self::invalidate_after) time. The stale route is returned immediately with
+    /// [`RenderFreshness::is_stale`](crate::RenderFreshness::is_stale) set so the server can re-render
+    /// it in the background.
+    ///
+    /// This only has an effect if `invalidate_after` is set.
+    pub fn stale_while_revalidate(mut self, stale_while_revalidate: Duration) -> Self {
+        self.stale_while_revalidate = Some(stale_while_revalidate);
+        self
+    }
+
     /// Set whether to include hydration ids in the pre-rendered html.
     pub fn pre_render(mut self, pre_render: bool) -> Self {
         self.pre_render = pre_render;
         self
     }
 
+    /// Replace the default in memory and file system caches with a chain of custom cache backends.
+    ///
+    /// Backends are checked in order when looking up a route, so faster caches should come first.
+    /// The `static_dir`, `map_path` and `memory_cache_limit` options only configure the default caches
+    /// and are ignored if custom backends are set.
+    pub fn cache_backends(
+        mut self,
+        backends: impl IntoIterator<Item = Arc<dyn RenderCache>>,
+    ) -> Self {
+        self.cache_backends = Some(backends.into_iter().collect());
+        self
+    }
+
     /// Build the incremental renderer.
     pub fn build(self) -> IncrementalRenderer {
-        let mut renderer = IncrementalRenderer {
-            #[cfg(not(target_arch = "wasm32"))]
-            file_system_cache: crate::fs_cache::FileSystemCache::new(
-                self.static_dir.clone(),
-                self.map_path,
-                self.invalidate_after,
-            ),
-            memory_cache: InMemoryCache::new(self.memory_cache_limit, self.invalidate_after),
+        let stale_while_revalidate = self.invalidate_after.and(self.stale_while_revalidate);
+        let clear_on_start = self.clear_cache.unwrap_or(self.cache_backends.is_none());
+        let caches = self.cache_backends.unwrap_or_else(|| {
+            vec![
+                Arc::new(InMemoryCache::new(self.memory_cache_limit)) as Arc<dyn RenderCache>,
+                #[cfg(not(target_arch = "wasm32"))]
+                Arc::new(crate::fs_cache::FileSystemCache::from_parts(
+                    self.static_dir.clone(),
+                    self.map_path,
+                    // Keep stale files around until they can't be served anymore
+                    self.invalidate_after
+                        .map(|after| after + stale_while_revalidate.unwrap_or_default()),
+                )),
+            ]
+        });
+
+        IncrementalRenderer {
+            caches,
             invalidate_after: self.invalidate_after,
-        };
-
-        if self.clear_cache {
-            renderer.invalidate_all();
+            stale_while_revalidate,
+            clear_on_start,
+            cleared: Default::default(),
         }
-
-        renderer
     }
 }
@@ -9,6 +9,8 @@ pub struct RenderFreshness {
     age: u64,
     /// The maximum age of the rendered response
     max_age: Option<u64>,
+    /// How long a response may be served after it exceeds the maximum age while it is re-rendered
+    stale_while_revalidate: Option<u64>,
     /// The time the response was rendered
     timestamp: DateTime<Utc>,
 }
@@ -19,6 +21,7 @@ impl RenderFreshness {
         Self {
             age,
             max_age: Some(max_age),
+            stale_while_revalidate: None,
             timestamp,
         }
     }
@@ -28,6 +31,7 @@ impl RenderFreshness {
         Self {
             age,
             max_age: None,
+            stale_while_revalidate: None,
             timestamp,
         }
     }
@@ -40,6 +44,7 @@ impl RenderFreshness {
                 .num_seconds()
                 .unsigned_abs(),
             max_age: max_age.map(|d| d.as_secs()),
+            stale_while_revalidate: None,
             timestamp,
         }
     }
@@ -49,10 +54,20 @@ impl RenderFreshness {
         Self {
             age: 0,
             max_age: max_age.map(|d| d.as_secs()),
+            stale_while_revalidate: None,
             timestamp: Utc::now(),
         }
     }
 
+    /// Allow the response to be served for `stale_while_revalidate` after it exceeds the maximum age
+    pub(crate) fn with_stale_while_revalidate(
+        mut self,
+        stale_while_revalidate: Option<Duration>,
+    ) -> Self {
+        self.stale_while_revalidate = stale_while_revalidate.map(|d| d.as_secs());
+        self
+    }
+
     /// Get the age of the rendered response in seconds
     pub fn age(&self) -> u64 {
         self.age
@@ -63,6 +78,18 @@ impl RenderFreshness {
         self.max_age
     }
 
+    /// Get how long the response may be served in seconds after it exceeds the maximum age while
+    /// a new version is rendered in the background
+    pub fn stale_while_revalidate(&self) -> Option<u64> {
+        self.stale_while_revalidate
+    }
+
+    /// Returns true if the response is older than the maximum age. Stale responses are only
+    /// returned from the cache if stale-while-revalidate is enabled, and should be re-rendered.
+    pub fn is_stale(&self) -> bool {
+        self.max_age.is_some_and(|max_age| self.age > max_age)
+    }
+
     /// Get the time the response was rendered
     pub fn timestamp(&self) -> DateTime<Utc> {
         self.timestamp
@@ -73,9 +100,15 @@ impl RenderFreshness {
         let age = self.age();
         headers.insert(http::header::AGE, age.into());
         if let Some(max_age) = self.max_age() {
+            let cache_control = match self.stale_while_revalidate() {
+                Some(stale_while_revalidate) => {
+                    format!("max-age={max_age}, stale-while-revalidate={stale_while_revalidate}")
+                }
+                None => format!("max-age={}", max_age),
+            };
             headers.insert(
                 http::header::CACHE_CONTROL,
-                http::HeaderValue::from_str(&format!("max-age={}", max_age)).unwrap(),
+                http::HeaderValue::from_str(&cache_control).unwrap(),
             );
         }
     }
@@ -2,19 +2,33 @@
 
 use chrono::{DateTime, Utc};
 
-use super::{IncrementalRendererError, RenderFreshness};
-use std::{path::PathBuf, sync::Arc, time::SystemTime};
+use crate::cache::{CacheEntry, CacheFuture, RenderCache};
+use rustc_hash::{FxHashMap, FxHashSet};
+use std::{
+    path::PathBuf,
+    sync::{Arc, Mutex},
+};
 
 pub(crate) type PathMapFn = Arc<dyn Fn(&str) -> PathBuf + Send + Sync>;
 
-pub(crate) struct FileSystemCache {
+/// A [`RenderCache`] that stores rendered routes as html files in a static directory.
+///
+/// Cache tags are tracked in memory, so invalidating a tag only removes routes that were cached
+/// since the server started.
+pub struct FileSystemCache {
     static_dir: PathBuf,
     map_path: PathMapFn,
     invalidate_after: Option<std::time::Duration>,
+    tags: Mutex<FxHashMap<String, FxHashSet<String>>>,
 }
 
 impl FileSystemCache {
-    pub fn new(
+    /// Create a new file system cache that stores routes in `static_dir`.
+    pub fn new(static_dir: impl Into<PathBuf>) -> Self {
+        Self::from_parts(static_dir.into(), None, None)
+    }
+
+    pub(crate) fn from_parts(
         static_dir: PathBuf,
         map_path: Option<PathMapFn>,
         invalidate_after: Option<std::time::Duration>,
@@ -32,94 +46,77 @@ impl FileSystemCache {
                 })
             }),
             invalidate_after,
+            tags: Default::default(),
         }
     }
 
-    pub fn put(
-        &mut self,
-        route: String,
-        timestamp: DateTime<Utc>,
-        data: Vec<u8>,
-    ) -> Result<(), IncrementalRendererError> {
-        use std::io::Write;
-        let file_path = self.route_as_path(&route, timestamp);
-        if let Some(parent) = file_path.parent() {
-            if !parent.exists() {
-                std::fs::create_dir_all(parent)?;
-            }
-        }
-        let file = std::fs::File::create(file_path)?;
-        let mut file = std::io::BufWriter::new(file);
-        file.write_all(&data)?;
-        Ok(())
-    }
-
-    pub fn clear(&mut self) {
-        // clear the static directory
-        let _ = std::fs::remove_dir_all(&self.static_dir);
+    /// Set a mapping from the route to the file path. This will override the default mapping to a folder in the static directory.
+    /// The function should return the path to the folder to store the index.html file in.
+    pub fn map_path<F: Fn(&str) -> PathBuf + Send + Sync + 'static>(mut self, map_path: F) -> Self {
+        self.map_path = Arc::new(map_path);
+        self
     }
 
-    pub fn invalidate(&mut self, route: &str) {
-        let file_path = self.find_file(route).unwrap().full_path;
-        if let Err(err) = std::fs::remove_file(file_path) {
-            tracing::error!("Failed to remove file: {}", err);
-        }
+    /// Store the time each route was rendered in the file name and delete files that are older than
+    /// `invalidate_after`.
+    pub fn invalidate_after(mut self, invalidate_after: std::time::Duration) -> Self {
+        self.invalidate_after = Some(invalidate_after);
+        self
     }
 
-    pub fn get(
-        &self,
-        route: &str,
-    ) -> Result<Option<(RenderFreshness, Vec<u8>)>, IncrementalRendererError> {
-        if let Some(file_path) = self.find_file(route) {
-            if let Some(freshness) = file_path.freshness(self.invalidate_after) {
-                if let Ok(file) = std::fs::File::open(file_path.full_path) {
-                    let mut file = std::io::BufReader::new(file);
-                    let mut cache_hit = Vec::new();
-                    std::io::copy(&mut file, &mut cache_hit)?;
-                    tracing::trace!("file cache hit {:?}", route);
-                    return Ok(Some((freshness, cache_hit)));
-                }
-            }
-        }
-
-        Ok(None)
-    }
-
-    fn find_file(&self, route: &str) -> Option<ValidCachedPath> {
+    async fn find_file(&self, route: &str) -> Option<ValidCachedPath> {
         let mut file_path = (self.map_path)(route);
         if let Some(deadline) = self.invalidate_after {
             // find the first file that matches the route and is a html file
             file_path.push("index");
-            if let Ok(dir) = std::fs::read_dir(file_path) {
-                for entry in dir.flatten() {
-                    if let Some(cached_path) = ValidCachedPath::try_from_path(entry.path()) {
-                        if let Ok(elapsed) = cached_path.timestamp.elapsed() {
-                            if elapsed < deadline {
-                                // The timestamp is valid, return the file
-                                return Some(cached_path);
-                            }
-                        }
-                        // if the timestamp is invalid or passed, delete the file
-                        if let Err(err) = std::fs::remove_file(entry.path()) {
-                            tracing::error!("Failed to remove file: {}", err);
+            let mut dir = tokio::fs::read_dir(file_path).await.ok()?;
+            while let Ok(Some(entry)) = dir.next_entry().await {
+                if let Some(cached_path) = ValidCachedPath::try_from_path(entry.path()) {
+                    if let Ok(elapsed) = cached_path.timestamp.elapsed() {
+                        if elapsed < deadline {
+                            // The timestamp is valid, return the file
+                            return Some(cached_path);
                         }
                     }
+                    // if the timestamp is invalid or passed, delete the file
+                    if let Err(err) = tokio::fs::remove_file(entry.path()).await {
+                        tracing::error!("Failed to remove file: {}", err);
+                    }
                 }
-                None
-            } else {
-                None
             }
+            None
         } else {
             file_path.push("index.html");
-            file_path.exists().then_some({
-                ValidCachedPath {
-                    full_path: file_path,
-                    timestamp: SystemTime::now(),
-                }
+            let timestamp = tokio::fs::metadata(&file_path)
+                .await
+                .and_then(|meta| meta.modified())
+                .ok()?;
+            Some(ValidCachedPath {
+                full_path: file_path,
+                timestamp,
             })
         }
     }
 
+    async fn remove_route(&self, route: &str) {
+        let Some(file) = self.find_file(route).await else {
+            return;
+        };
+        if let Err(err) = tokio::fs::remove_file(file.full_path).await {
+            tracing::error!("Failed to remove file: {}", err);
+        }
+    }
+
+    fn tags_of(&self, route: &str) -> Vec<String> {
+        self.tags
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .iter()
+            .filter(|(_, routes)| routes.contains(route))
+            .map(|(tag, _)| tag.clone())
+            .collect()
+    }
+
     fn route_as_path(&self, route: &str, timestamp: DateTime<Utc>) -> PathBuf {
         let mut file_path = (self.map_path)(route);
         if self.track_timestamps() {
@@ -137,6 +134,74 @@ impl FileSystemCache {
     }
 }
 
+impl RenderCache for FileSystemCache {
+    fn get<'a>(&'a self, route: &'a str) -> CacheFuture<'a, Option<CacheEntry>> {
+        Box::pin(async move {
+            let Some(file_path) = self.find_file(route).await else {
+                return Ok(None);
+            };
+            let Ok(html) = tokio::fs::read(&file_path.full_path).await else {
+                return Ok(None);
+            };
+            tracing::trace!("file cache hit {:?}", route);
+            Ok(Some(CacheEntry {
+                timestamp: file_path.timestamp.into(),
+                tags: self.tags_of(route),
+                html,
+            }))
+        })
+    }
+
+    fn put<'a>(&'a self, route: &'a str, entry: CacheEntry) -> CacheFuture<'a, ()> {
+        Box::pin(async move {
+            let file_path = self.route_as_path(route, entry.timestamp);
+            if let Some(parent) = file_path.parent() {
+                tokio::fs::create_dir_all(parent).await?;
+            }
+            tokio::fs::write(file_path, &entry.html).await?;
+
+            let mut tags = self.tags.lock().unwrap_or_else(|err| err.into_inner());
+            for tag in entry.tags {
+                tags.entry(tag).or_default().insert(route.to_string());
+            }
+            Ok(())
+        })
+    }
+
+    fn invalidate<'a>(&'a self, route: &'a str) -> CacheFuture<'a, ()> {
+        Box::pin(async move {
+            self.remove_route(route).await;
+            Ok(())
+        })
+    }
+
+    fn invalidate_tag<'a>(&'a self, tag: &'a str) -> CacheFuture<'a, ()> {
+        Box::pin(async move {
+            let routes = self
+                .tags
+                .lock()
+                .unwrap_or_else(|err| err.into_inner())
+                .remove(tag);
+            for route in routes.into_iter().flatten() {
+                self.remove_route(&route).await;
+            }
+            Ok(())
+        })
+    }
+
+    fn clear(&self) -> CacheFuture<'_, ()> {
+        Box::pin(async move {
+            // clear the static directory
+            let _ = tokio::fs::remove_dir_all(&self.static_dir).await;
+            self.tags
+                .lock()
+                .unwrap_or_else(|err| err.into_inner())
+                .clear();
+            Ok(())
+        })
+    }
+}
+
 pub(crate) struct ValidCachedPath {
     pub(crate) full_path: PathBuf,
     pub(crate) timestamp: std::time::SystemTime,
@@ -154,12 +219,6 @@ impl ValidCachedPath {
             timestamp,
         })
     }
-
-    pub fn freshness(&self, max_age: Option<std::time::Duration>) -> Option<RenderFreshness> {
-        let age = self.timestamp.elapsed().ok()?.as_secs();
-        let max_age = max_age.map(|max_age| max_age.as_secs());
-        Some(RenderFreshness::new(age, max_age?, self.timestamp.into()))
-    }
 }
 
 fn decode_timestamp(timestamp: &str) -> Option<std::time::SystemTime> {
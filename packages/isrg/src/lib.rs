@@ -2,36 +2,40 @@
 
 #![allow(non_snake_case)]
 
+mod cache;
 mod config;
 mod freshness;
 #[cfg(not(target_arch = "wasm32"))]
 mod fs_cache;
 mod memory_cache;
 
-use std::time::Duration;
+use std::{sync::Arc, time::Duration};
 
+pub use cache::*;
 use chrono::Utc;
 pub use config::*;
 pub use freshness::*;
-
-use self::memory_cache::InMemoryCache;
+#[cfg(not(target_arch = "wasm32"))]
+pub use fs_cache::FileSystemCache;
+pub use memory_cache::InMemoryCache;
 
 /// A render that was cached from a previous render.
-pub struct CachedRender<'a> {
+pub struct CachedRender {
     /// The route that was rendered
     pub route: String,
     /// The freshness information for the rendered response
     pub freshness: RenderFreshness,
     /// The rendered response
-    pub response: &'a [u8],
+    pub response: Vec<u8>,
 }
 
 /// An incremental renderer.
 pub struct IncrementalRenderer {
-    pub(crate) memory_cache: InMemoryCache,
-    #[cfg(not(target_arch = "wasm32"))]
-    pub(crate) file_system_cache: fs_cache::FileSystemCache,
+    caches: Vec<Arc<dyn RenderCache>>,
     invalidate_after: Option<Duration>,
+    stale_while_revalidate: Option<Duration>,
+    clear_on_start: bool,
+    cleared: tokio::sync::OnceCell<()>,
 }
 
 impl IncrementalRenderer {
@@ -41,108 +45,173 @@ impl IncrementalRenderer {
     }
 
     /// Remove a route from the cache.
-    pub fn invalidate(&mut self, route: &str) {
-        self.memory_cache.invalidate(route);
-        #[cfg(not(target_arch = "wasm32"))]
-        self.file_system_cache.invalidate(route);
+    pub async fn invalidate(&self, route: &str) -> Result<(), IncrementalRendererError> {
+        self.clear_if_pending().await?;
+        for cache in &self.caches {
+            cache.invalidate(route).await?;
+        }
+        Ok(())
+    }
+
+    /// Remove every route that was cached with the tag `tag`. Tags are attached to a route when it is
+    /// cached with [`IncrementalRenderer::cache_with_tags`].
+    ///
+    /// ```rust
+    /// # use dioxus_isrg::IncrementalRenderer;
+    /// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+    /// # let renderer = IncrementalRenderer::builder().build();
+    /// renderer
+    ///     .cache_with_tags("/products/42".to_string(), "Product 42", ["product:42"])
+    ///     .await
+    ///     .unwrap();
+    /// renderer.invalidate_tag("product:42").await.unwrap();
+    /// assert!(renderer.get("/products/42").await.unwrap().is_none());
+    /// # });
+    /// ```
+    pub async fn invalidate_tag(&self, tag: &str) -> Result<(), IncrementalRendererError> {
+        self.clear_if_pending().await?;
+        for cache in &self.caches {
+            cache.invalidate_tag(tag).await?;
+        }
+        Ok(())
     }
 
     /// Remove all routes from the cache.
-    pub fn invalidate_all(&mut self) {
-        self.memory_cache.clear();
-        #[cfg(not(target_arch = "wasm32"))]
-        self.file_system_cache.clear();
+    pub async fn invalidate_all(&self) -> Result<(), IncrementalRendererError> {
+        self.clear_if_pending().await?;
+        self.clear_caches().await
+    }
+
+    async fn clear_caches(&self) -> Result<(), IncrementalRendererError> {
+        for cache in &self.caches {
+            cache.clear().await?;
+        }
+        Ok(())
     }
 
     /// Cache a rendered response.
     ///
     /// ```rust
     /// # use dioxus_isrg::IncrementalRenderer;
-    /// # let mut renderer = IncrementalRenderer::builder().build();
+    /// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+    /// # let renderer = IncrementalRenderer::builder().build();
     /// let route = "/index".to_string();
     /// let response = b"<html><body>Hello world</body></html>";
-    /// renderer.cache(route, response).unwrap();
+    /// renderer.cache(route, response).await.unwrap();
+    /// # });
     /// ```
-    pub fn cache(
-        &mut self,
+    pub async fn cache(
+        &self,
+        route: String,
+        html: impl Into<Vec<u8>>,
+    ) -> Result<RenderFreshness, IncrementalRendererError> {
+        self.cache_with_tags(route, html, std::iter::empty::<String>())
+            .await
+    }
+
+    /// Cache a rendered response with a list of tags. Every route with a tag can be removed from the
+    /// cache at once with [`IncrementalRenderer::invalidate_tag`].
+    pub async fn cache_with_tags(
+        &self,
         route: String,
         html: impl Into<Vec<u8>>,
+        tags: impl IntoIterator<Item = impl Into<String>>,
     ) -> Result<RenderFreshness, IncrementalRendererError> {
+        self.clear_if_pending().await?;
         let timestamp = Utc::now();
-        let html = html.into();
-        #[cfg(not(target_arch = "wasm32"))]
-        self.file_system_cache
-            .put(route.clone(), timestamp, html.clone())?;
-        self.memory_cache.put(route, timestamp, html);
-        Ok(RenderFreshness::created_at(
+        let entry = CacheEntry {
             timestamp,
-            self.invalidate_after,
-        ))
+            tags: tags.into_iter().map(Into::into).collect(),
+            html: html.into(),
+        };
+        for cache in &self.caches {
+            cache.put(&route, entry.clone()).await?;
+        }
+        Ok(
+            RenderFreshness::created_at(timestamp, self.invalidate_after)
+                .with_stale_while_revalidate(self.stale_while_revalidate),
+        )
     }
 
     /// Try to get a cached response for a route.
     ///
     /// ```rust
     /// # use dioxus_isrg::IncrementalRenderer;
-    /// # let mut renderer = IncrementalRenderer::builder().build();
+    /// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+    /// # let renderer = IncrementalRenderer::builder().build();
     /// # let route = "/index".to_string();
     /// # let response = b"<html><body>Hello world</body></html>";
-    /// # renderer.cache(route, response).unwrap();
+    /// # renderer.cache(route, response).await.unwrap();
     /// let route = "/index";
-    /// let response = renderer.get(route).unwrap();
+    /// let response = renderer.get(route).await.unwrap();
     /// assert_eq!(response.unwrap().response, b"<html><body>Hello world</body></html>");
+    /// # });
     /// ```
     ///
-    /// If the route is not cached, `None` is returned.
+    /// If the route is not cached, `None` is returned. If stale-while-revalidate is enabled, routes
+    /// that are older than the invalidation time are still returned for a while with
+    /// [`RenderFreshness::is_stale`] set. The caller should re-render those routes.
     ///
     /// ```rust
     /// # use dioxus_isrg::IncrementalRenderer;
-    /// # let mut renderer = IncrementalRenderer::builder().build();
+    /// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+    /// # let renderer = IncrementalRenderer::builder().build();
     /// let route = "/index";
-    /// let response = renderer.get(route).unwrap();
+    /// let response = renderer.get(route).await.unwrap();
     /// assert!(response.is_none());
+    /// # });
     /// ```
-    pub fn get<'a>(
-        &'a mut self,
-        route: &str,
-    ) -> Result<Option<CachedRender<'a>>, IncrementalRendererError> {
-        let Self {
-            memory_cache,
-            #[cfg(not(target_arch = "wasm32"))]
-            file_system_cache,
-            ..
-        } = self;
-
-        #[allow(unused)]
-        enum FsGetError {
-            NotPresent,
-            Error(IncrementalRendererError),
-        }
+    pub async fn get(&self, route: &str) -> Result<Option<CachedRender>, IncrementalRendererError> {
+        self.clear_if_pending().await?;
 
-        // The borrow checker prevents us from simply using a match/if and returning early. Instead we need to use the more complex closure API
-        // non lexical lifetimes will make this possible (it works with polonius)
-        let or_insert = || {
-            // check the file cache
-            #[cfg(not(target_arch = "wasm32"))]
-            return match file_system_cache.get(route) {
-                Ok(Some((freshness, bytes))) => Ok((freshness.timestamp(), bytes)),
-                Ok(None) => Err(FsGetError::NotPresent),
-                Err(e) => Err(FsGetError::Error(e)),
+        for (index, cache) in self.caches.iter().enumerate() {
+            let Some(entry) = cache.get(route).await? else {
+                continue;
             };
 
-            #[allow(unreachable_code)]
-            Err(FsGetError::NotPresent)
-        };
+            let age = Utc::now()
+                .signed_duration_since(entry.timestamp)
+                .to_std()
+                .unwrap_or_default();
+            let freshness = match self.invalidate_after {
+                Some(invalidate_after) => {
+                    // The cache entry is out of date and can't be served while it is re-rendered, so we need to remove it.
+                    let serve_until =
+                        invalidate_after + self.stale_while_revalidate.unwrap_or_default();
+                    if age > serve_until {
+                        tracing::trace!("cache entry for {route} out of date");
+                        self.invalidate(route).await?;
+                        return Ok(None);
+                    }
+                    RenderFreshness::new(age.as_secs(), invalidate_after.as_secs(), entry.timestamp)
+                        .with_stale_while_revalidate(self.stale_while_revalidate)
+                }
+                None => RenderFreshness::new_age(age.as_secs(), entry.timestamp),
+            };
 
-        match memory_cache.try_get_or_insert(route, or_insert) {
-            Ok(Some((freshness, bytes))) => Ok(Some(CachedRender {
+            // Copy the render into the faster caches that missed
+            for cache in &self.caches[..index] {
+                cache.put(route, entry.clone()).await?;
+            }
+
+            return Ok(Some(CachedRender {
                 route: route.to_string(),
                 freshness,
-                response: bytes,
-            })),
-            Err(FsGetError::NotPresent) | Ok(None) => Ok(None),
-            Err(FsGetError::Error(e)) => Err(e),
+                response: entry.html,
+            }));
+        }
+
+        Ok(None)
+    }
+
+    /// The cache is cleared lazily on first use because [`RenderCache`]s are async and the renderer
+    /// is built synchronously. Every other operation waits for the clear to finish so writes that
+    /// happen while the cache is being cleared are not lost.
+    async fn clear_if_pending(&self) -> Result<(), IncrementalRendererError> {
+        if self.clear_on_start {
+            self.cleared.get_or_try_init(|| self.clear_caches()).await?;
         }
+        Ok(())
     }
 }
 
@@ -2,88 +2,70 @@
 
 #![allow(non_snake_case)]
 
-use chrono::offset::Utc;
-use chrono::DateTime;
 use rustc_hash::FxHasher;
-use std::{hash::BuildHasherDefault, num::NonZeroUsize};
+use std::{hash::BuildHasherDefault, num::NonZeroUsize, sync::Mutex};
 
-use super::freshness::RenderFreshness;
+use crate::cache::{CacheEntry, CacheFuture, RenderCache};
 
-pub(crate) struct InMemoryCache {
-    #[allow(clippy::type_complexity)]
-    lru: Option<lru::LruCache<String, (DateTime<Utc>, Vec<u8>), BuildHasherDefault<FxHasher>>>,
-    invalidate_after: Option<std::time::Duration>,
+type Lru = lru::LruCache<String, CacheEntry, BuildHasherDefault<FxHasher>>;
+
+/// A [`RenderCache`] that keeps the most recently used routes in memory.
+pub struct InMemoryCache {
+    lru: Option<Mutex<Lru>>,
 }
 
 impl InMemoryCache {
-    pub fn new(memory_cache_limit: usize, invalidate_after: Option<std::time::Duration>) -> Self {
+    /// Create a new memory cache that holds up to `memory_cache_limit` routes. If the limit is zero,
+    /// nothing is cached.
+    pub fn new(memory_cache_limit: usize) -> Self {
         Self {
             lru: NonZeroUsize::new(memory_cache_limit)
-                .map(|limit| lru::LruCache::with_hasher(limit, Default::default())),
-            invalidate_after,
+                .map(|limit| Mutex::new(lru::LruCache::with_hasher(limit, Default::default()))),
         }
     }
 
-    pub fn clear(&mut self) {
-        if let Some(cache) = &mut self.lru {
-            cache.clear();
-        }
+    fn with_lru<O>(&self, f: impl FnOnce(&mut Lru) -> O) -> Option<O> {
+        let lru = self.lru.as_ref()?;
+        let mut lru = lru.lock().unwrap_or_else(|err| err.into_inner());
+        Some(f(&mut lru))
     }
+}
 
-    pub fn put(&mut self, route: String, timestamp: DateTime<Utc>, data: Vec<u8>) {
-        if let Some(cache) = &mut self.lru {
-            cache.put(route, (timestamp, data));
+impl RenderCache for InMemoryCache {
+    fn get<'a>(&'a self, route: &'a str) -> CacheFuture<'a, Option<CacheEntry>> {
+        let entry = self.with_lru(|lru| lru.get(route).cloned()).flatten();
+        if entry.is_some() {
+            tracing::trace!("memory cache hit");
         }
+        Box::pin(async move { Ok(entry) })
     }
 
-    pub fn invalidate(&mut self, route: &str) {
-        if let Some(cache) = &mut self.lru {
-            cache.pop(route);
-        }
+    fn put<'a>(&'a self, route: &'a str, entry: CacheEntry) -> CacheFuture<'a, ()> {
+        self.with_lru(|lru| lru.put(route.to_string(), entry));
+        Box::pin(async { Ok(()) })
     }
 
-    pub fn try_get_or_insert<'a, F: FnOnce() -> Result<(DateTime<Utc>, Vec<u8>), E>, E>(
-        &'a mut self,
-        route: &str,
-        or_insert: F,
-    ) -> Result<Option<(RenderFreshness, &'a [u8])>, E> {
-        if let Some(memory_cache) = self.lru.as_mut() {
-            let (timestamp, _) = memory_cache.try_get_or_insert(route.to_string(), or_insert)?;
+    fn invalidate<'a>(&'a self, route: &'a str) -> CacheFuture<'a, ()> {
+        self.with_lru(|lru| lru.pop(route));
+        Box::pin(async { Ok(()) })
+    }
 
-            let now = Utc::now();
-            let elapsed = timestamp.signed_duration_since(now);
-            let age = elapsed.num_seconds();
-            // The cache entry is out of date, so we need to remove it.
-            if let Some(invalidate_after) = self.invalidate_after {
-                // If we can't convert to a std duration, the duration is negative and hasn't elapsed yet.
-                if let Ok(std_elapsed) = elapsed.to_std() {
-                    if std_elapsed > invalidate_after {
-                        tracing::trace!("memory cache out of date");
-                        memory_cache.pop(route);
-                        return Ok(None);
-                    }
-                }
+    fn invalidate_tag<'a>(&'a self, tag: &'a str) -> CacheFuture<'a, ()> {
+        self.with_lru(|lru| {
+            let tagged: Vec<String> = lru
+                .iter()
+                .filter(|(_, entry)| entry.tags.iter().any(|t| t == tag))
+                .map(|(route, _)| route.clone())
+                .collect();
+            for route in tagged {
+                lru.pop(&route);
             }
+        });
+        Box::pin(async { Ok(()) })
+    }
 
-            // We need to reborrow because we may have invalidated the lifetime if the route was removed.
-            // We know it wasn't because we returned... but rust doesn't understand that.
-            let (timestamp, cache_hit) = memory_cache.get(route).unwrap();
-
-            return match self.invalidate_after {
-                Some(invalidate_after) => {
-                    tracing::trace!("memory cache hit");
-                    let max_age = invalidate_after.as_secs();
-                    let freshness = RenderFreshness::new(age as u64, max_age, *timestamp);
-                    Ok(Some((freshness, cache_hit)))
-                }
-                None => {
-                    tracing::trace!("memory cache hit");
-                    let freshness = RenderFreshness::new_age(age as u64, *timestamp);
-                    Ok(Some((freshness, cache_hit)))
-                }
-            };
-        }
-
-        Ok(None)
+    fn clear(&self) -> CacheFuture<'_, ()> {
+        self.with_lru(|lru| lru.clear());
+        Box::pin(async { Ok(()) })
     }
 }
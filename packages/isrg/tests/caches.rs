@@ -0,0 +1,215 @@
+use chrono::Utc;
+use dioxus_isrg::{CacheEntry, FileSystemCache, InMemoryCache, IncrementalRenderer, RenderCache};
+use std::{sync::Arc, time::Duration};
+
+fn entry(html: &str, tags: &[&str]) -> CacheEntry {
+    CacheEntry {
+        timestamp: Utc::now(),
+        tags: tags.iter().map(|tag| tag.to_string()).collect(),
+        html: html.as_bytes().to_vec(),
+    }
+}
+
+fn aged(mut entry: CacheEntry, age: Duration) -> CacheEntry {
+    entry.timestamp -= chrono::Duration::from_std(age).unwrap();
+    entry
+}
+
+async fn html(cache: &dyn RenderCache, route: &str) -> Option<Vec<u8>> {
+    cache.get(route).await.unwrap().map(|entry| entry.html)
+}
+
+#[tokio::test]
+async fn memory_cache_get_put_invalidate() {
+    let cache = InMemoryCache::new(10);
+    assert!(cache.get("/").await.unwrap().is_none());
+
+    cache.put("/", entry("home", &[])).await.unwrap();
+    cache.put("/about", entry("about", &[])).await.unwrap();
+    assert_eq!(html(&cache, "/").await.as_deref(), Some(&b"home"[..]));
+
+    cache.put("/", entry("new home", &[])).await.unwrap();
+    assert_eq!(html(&cache, "/").await.as_deref(), Some(&b"new home"[..]));
+
+    cache.invalidate("/").await.unwrap();
+    assert!(html(&cache, "/").await.is_none());
+    assert!(html(&cache, "/about").await.is_some());
+
+    cache.clear().await.unwrap();
+    assert!(html(&cache, "/about").await.is_none());
+}
+
+#[tokio::test]
+async fn memory_cache_invalidate_tag() {
+    let cache = InMemoryCache::new(10);
+    cache.put("/a", entry("a", &["post"])).await.unwrap();
+    cache
+        .put("/b", entry("b", &["post", "author"]))
+        .await
+        .unwrap();
+    cache.put("/c", entry("c", &["author"])).await.unwrap();
+
+    cache.invalidate_tag("post").await.unwrap();
+    assert!(html(&cache, "/a").await.is_none());
+    assert!(html(&cache, "/b").await.is_none());
+    assert!(html(&cache, "/c").await.is_some());
+}
+
+#[tokio::test]
+async fn memory_cache_evicts_least_recently_used() {
+    let cache = InMemoryCache::new(2);
+    cache.put("/a", entry("a", &[])).await.unwrap();
+    cache.put("/b", entry("b", &[])).await.unwrap();
+    // Touch /a so /b is the least recently used route
+    cache.get("/a").await.unwrap();
+    cache.put("/c", entry("c", &[])).await.unwrap();
+
+    assert!(html(&cache, "/a").await.is_some());
+    assert!(html(&cache, "/b").await.is_none());
+    assert!(html(&cache, "/c").await.is_some());
+
+    let disabled = InMemoryCache::new(0);
+    disabled.put("/a", entry("a", &[])).await.unwrap();
+    assert!(html(&disabled, "/a").await.is_none());
+}
+
+#[tokio::test]
+async fn file_system_cache_get_put_invalidate() {
+    let dir = tempfile::tempdir().unwrap();
+    let cache = FileSystemCache::new(dir.path());
+    assert!(cache.get("/blog/post").await.unwrap().is_none());
+
+    cache
+        .put("/blog/post", entry("post", &["blog"]))
+        .await
+        .unwrap();
+    assert!(dir.path().join("blog/post/index.html").exists());
+    let cached = cache.get("/blog/post").await.unwrap().unwrap();
+    assert_eq!(cached.html, b"post");
+    assert_eq!(cached.tags, ["blog"]);
+
+    cache.invalidate("/blog/post").await.unwrap();
+    assert!(cache.get("/blog/post").await.unwrap().is_none());
+    assert!(!dir.path().join("blog/post/index.html").exists());
+
+    cache.put("/a", entry("a", &[])).await.unwrap();
+    cache.clear().await.unwrap();
+    assert!(cache.get("/a").await.unwrap().is_none());
+    assert!(!dir.path().exists());
+}
+
+#[tokio::test]
+async fn file_system_cache_invalidate_tag() {
+    let dir = tempfile::tempdir().unwrap();
+    let cache = FileSystemCache::new(dir.path());
+    cache.put("/a", entry("a", &["post"])).await.unwrap();
+    cache.put("/b", entry("b", &["author"])).await.unwrap();
+
+    cache.invalidate_tag("post").await.unwrap();
+    assert!(html(&cache, "/a").await.is_none());
+    assert!(html(&cache, "/b").await.is_some());
+}
+
+#[tokio::test]
+async fn file_system_cache_removes_stale_files() {
+    let dir = tempfile::tempdir().unwrap();
+    let cache = FileSystemCache::new(dir.path()).invalidate_after(Duration::from_secs(60));
+
+    cache
+        .put("/old", aged(entry("old", &[]), Duration::from_secs(120)))
+        .await
+        .unwrap();
+    cache.put("/new", entry("new", &[])).await.unwrap();
+
+    assert!(html(&cache, "/old").await.is_none());
+    assert_eq!(
+        std::fs::read_dir(dir.path().join("old/index"))
+            .unwrap()
+            .count(),
+        0,
+        "stale files should be deleted"
+    );
+    assert_eq!(html(&cache, "/new").await.as_deref(), Some(&b"new"[..]));
+}
+
+#[tokio::test]
+async fn renderer_serves_stale_routes_while_revalidating() {
+    let cache = Arc::new(InMemoryCache::new(10));
+    let renderer = IncrementalRenderer::builder()
+        .invalidate_after(Duration::from_secs(60))
+        .stale_while_revalidate(Duration::from_secs(60))
+        .cache_backends([cache.clone() as Arc<dyn RenderCache>])
+        .build();
+
+    cache.put("/fresh", entry("fresh", &[])).await.unwrap();
+    cache
+        .put("/stale", aged(entry("stale", &[]), Duration::from_secs(90)))
+        .await
+        .unwrap();
+    cache
+        .put(
+            "/expired",
+            aged(entry("expired", &[]), Duration::from_secs(150)),
+        )
+        .await
+        .unwrap();
+
+    let fresh = renderer.get("/fresh").await.unwrap().unwrap();
+    assert!(!fresh.freshness.is_stale());
+
+    let stale = renderer.get("/stale").await.unwrap().unwrap();
+    assert!(stale.freshness.is_stale());
+    assert_eq!(stale.response, b"stale");
+
+    assert!(renderer.get("/expired").await.unwrap().is_none());
+    assert!(
+        cache.get("/expired").await.unwrap().is_none(),
+        "expired routes should be removed from the cache"
+    );
+}
+
+#[tokio::test]
+async fn custom_backends_are_not_cleared_by_default() {
+    let cache = Arc::new(InMemoryCache::new(10));
+    cache.put("/", entry("shared", &[])).await.unwrap();
+
+    let renderer = IncrementalRenderer::builder()
+        .cache_backends([cache.clone() as Arc<dyn RenderCache>])
+        .build();
+    assert!(renderer.get("/").await.unwrap().is_some());
+
+    let renderer = IncrementalRenderer::builder()
+        .clear_cache(true)
+        .cache_backends([cache.clone() as Arc<dyn RenderCache>])
+        .build();
+    assert!(renderer.get("/").await.unwrap().is_none());
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn writes_during_the_startup_clear_are_kept() {
+    let dir = tempfile::tempdir().unwrap();
+    let renderer = Arc::new(
+        IncrementalRenderer::builder()
+            .static_dir(dir.path())
+            .clear_cache(true)
+            .build(),
+    );
+
+    let writers = (0..8).map(|i| {
+        let renderer = renderer.clone();
+        tokio::spawn(async move {
+            renderer
+                .cache(format!("/page/{i}"), format!("page {i}"))
+                .await
+                .unwrap();
+        })
+    });
+    for writer in writers.collect::<Vec<_>>() {
+        writer.await.unwrap();
+    }
+
+    for i in 0..8 {
+        let page = renderer.get(&format!("/page/{i}")).await.unwrap().unwrap();
+        assert_eq!(page.response, format!("page {i}").as_bytes());
+    }
+}
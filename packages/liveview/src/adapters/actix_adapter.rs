@@ -0,0 +1,156 @@
+use std::{
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use crate::{LiveViewError, LiveViewSocket};
+use actix_ws::{Message, MessageStream, Session};
+use futures_channel::mpsc::{self, UnboundedReceiver, UnboundedSender};
+use futures_util::{Sink, Stream, StreamExt};
+
+/// Convert an actix-web WebSocket into a `LiveViewSocket`.
+///
+/// This is required to launch a LiveView app using the actix-web framework. The message stream of actix-ws can't
+/// be sent between threads, so it is driven by a task on the current actix runtime. This function must be called
+/// from an actix-web handler.
+///
+/// ```rust, no_run
+/// use actix_web::{web, App, HttpRequest, HttpResponse, HttpServer};
+/// use dioxus::prelude::*;
+/// use dioxus_liveview::{actix_socket, interpreter_glue, LiveViewPool};
+///
+/// fn app() -> Element {
+///     rsx! { "hello from actix-web" }
+/// }
+///
+/// async fn index() -> HttpResponse {
+///     HttpResponse::Ok().body(format!(r#"<div id="main"></div>{}"#, interpreter_glue("/ws")))
+/// }
+///
+/// async fn ws(
+///     req: HttpRequest,
+///     body: web::Payload,
+///     pool: web::Data<LiveViewPool>,
+/// ) -> actix_web::Result<HttpResponse> {
+///     let (response, session, stream) = actix_ws::handle(&req, body)?;
+///     let pool = pool.get_ref().clone();
+///     actix_web::rt::spawn(async move {
+///         _ = pool.launch(actix_socket(session, stream), app).await;
+///     });
+///     Ok(response)
+/// }
+///
+/// # async fn serve() -> std::io::Result<()> {
+/// let pool = web::Data::new(LiveViewPool::new());
+/// HttpServer::new(move || {
+///     App::new()
+///         .app_data(pool.clone())
+///         .route("/", web::get().to(index))
+///         .route("/ws", web::get().to(ws))
+/// })
+/// .bind(("127.0.0.1", 3030))?
+/// .run()
+/// .await
+/// # }
+/// ```
+pub fn actix_socket(session: Session, stream: MessageStream) -> impl LiveViewSocket {
+    let (to_liveview, rx) = mpsc::unbounded();
+    let (tx, from_liveview) = mpsc::unbounded();
+    actix_web::rt::spawn(forward_messages(
+        session,
+        stream,
+        to_liveview,
+        from_liveview,
+    ));
+    ActixSocket { rx, tx }
+}
+
+/// Pass messages between the actix-ws session and the liveview until either side closes
+async fn forward_messages(
+    mut session: Session,
+    mut stream: MessageStream,
+    to_liveview: UnboundedSender<Result<Vec<u8>, LiveViewError>>,
+    mut from_liveview: UnboundedReceiver<Vec<u8>>,
+) {
+    loop {
+        tokio::select! {
+            message = stream.next() => {
+                let message = match message {
+                    Some(Ok(Message::Text(text))) => Ok(text.as_bytes().to_vec()),
+                    Some(Ok(Message::Binary(bytes))) => Ok(bytes.to_vec()),
+                    Some(Ok(Message::Ping(bytes))) => {
+                        if session.pong(&bytes).await.is_err() {
+                            return;
+                        }
+                        continue;
+                    }
+                    Some(Ok(Message::Close(reason))) => {
+                        _ = session.close(reason).await;
+                        return;
+                    }
+                    Some(Ok(_)) => continue,
+                    Some(Err(_)) => Err(LiveViewError::SendingFailed),
+                    None => return,
+                };
+                if to_liveview.unbounded_send(message).is_err() {
+                    _ = session.close(None).await;
+                    return;
+                }
+            }
+            message = from_liveview.next() => match message {
+                Some(message) => {
+                    if session.binary(message).await.is_err() {
+                        return;
+                    }
+                }
+                // The liveview dropped the socket
+                None => {
+                    _ = session.close(None).await;
+                    return;
+                }
+            }
+        }
+    }
+}
+
+/// The liveview side of an actix-ws session
+struct ActixSocket {
+    rx: UnboundedReceiver<Result<Vec<u8>, LiveViewError>>,
+    tx: UnboundedSender<Vec<u8>>,
+}
+
+impl Stream for ActixSocket {
+    type Item = Result<Vec<u8>, LiveViewError>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.rx.poll_next_unpin(cx)
+    }
+}
+
+impl Sink<Vec<u8>> for ActixSocket {
+    type Error = LiveViewError;
+
+    fn poll_ready(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Pin::new(&mut self.tx)
+            .poll_ready(cx)
+            .map_err(|_| LiveViewError::SendingFailed)
+    }
+
+    fn start_send(mut self: Pin<&mut Self>, item: Vec<u8>) -> Result<(), Self::Error> {
+        Pin::new(&mut self.tx)
+            .start_send(item)
+            .map_err(|_| LiveViewError::SendingFailed)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Pin::new(&mut self.tx)
+            .poll_flush(cx)
+            .map_err(|_| LiveViewError::SendingFailed)
+    }
+
+    fn poll_close(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Pin::new(&mut self.tx)
+            .poll_close(cx)
+            .map_err(|_| LiveViewError::SendingFailed)
+    }
+}
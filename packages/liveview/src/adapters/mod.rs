@@ -7,6 +7,26 @@ pub mod axum_adapter;
 #[cfg(feature = "axum")]
 pub use axum_adapter::*;
 
+#[cfg(feature = "actix")]
+pub mod actix_adapter;
+#[cfg(feature = "actix")]
+pub use actix_adapter::*;
+
+#[cfg(feature = "warp")]
+pub mod warp_adapter;
+#[cfg(feature = "warp")]
+pub use warp_adapter::*;
+
+#[cfg(feature = "salvo")]
+pub mod salvo_adapter;
+#[cfg(feature = "salvo")]
+pub use salvo_adapter::*;
+
+#[cfg(feature = "tungstenite")]
+pub mod tungstenite_adapter;
+#[cfg(feature = "tungstenite")]
+pub use tungstenite_adapter::*;
+
 /// A trait for servers that can be used to host a LiveView app.
 pub trait LiveviewRouter {
     /// Create a new router.
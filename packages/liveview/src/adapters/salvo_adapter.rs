@@ -0,0 +1,56 @@
+use crate::{LiveViewError, LiveViewSocket};
+use futures_util::{SinkExt, StreamExt};
+use salvo::websocket::{Message, WebSocket};
+
+/// Convert a salvo WebSocket into a `LiveViewSocket`.
+///
+/// This is required to launch a LiveView app using the salvo web framework.
+///
+/// ```rust, no_run
+/// use dioxus::prelude::*;
+/// use dioxus_liveview::{salvo_socket, LiveViewPool};
+/// use salvo::{prelude::*, websocket::WebSocketUpgrade};
+///
+/// fn app() -> Element {
+///     rsx! { "hello from salvo" }
+/// }
+///
+/// struct LiveView {
+///     pool: LiveViewPool,
+/// }
+///
+/// #[handler]
+/// impl LiveView {
+///     async fn handle(&self, req: &mut Request, res: &mut Response) -> Result<(), StatusError> {
+///         let pool = self.pool.clone();
+///         WebSocketUpgrade::new()
+///             .upgrade(req, res, |socket| async move {
+///                 _ = pool.launch(salvo_socket(socket), app).await;
+///             })
+///             .await
+///     }
+/// }
+///
+/// # async fn serve() {
+/// let router = Router::with_path("ws").goal(LiveView {
+///     pool: LiveViewPool::new(),
+/// });
+/// let acceptor = TcpListener::new("127.0.0.1:5800").bind().await;
+/// Server::new(acceptor).serve(router).await;
+/// # }
+/// ```
+pub fn salvo_socket(ws: WebSocket) -> impl LiveViewSocket {
+    ws.map(transform_rx)
+        .with(transform_tx)
+        .sink_map_err(|_| LiveViewError::SendingFailed)
+}
+
+fn transform_rx(message: Result<Message, salvo::Error>) -> Result<Vec<u8>, LiveViewError> {
+    Ok(message
+        .map_err(|_| LiveViewError::SendingFailed)?
+        .into_bytes())
+}
+
+async fn transform_tx(message: Vec<u8>) -> Result<Message, salvo::Error> {
+    Ok(Message::binary(message))
+}
@@ -0,0 +1,53 @@
+use crate::{LiveViewError, LiveViewSocket};
+use futures_util::{SinkExt, StreamExt};
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio_tungstenite::{
+    tungstenite::{Error, Message},
+    WebSocketStream,
+};
+
+/// Convert a tokio-tungstenite WebSocket into a `LiveViewSocket`.
+///
+/// This works with any websocket stream, so it can be used to launch a LiveView app from a raw TCP listener or
+/// from a connection hyper upgraded with `WebSocketStream::from_raw_socket`.
+///
+/// ```rust, no_run
+/// use dioxus::prelude::*;
+/// use dioxus_liveview::{tungstenite_socket, LiveViewPool};
+///
+/// fn app() -> Element {
+///     rsx! { "hello from tungstenite" }
+/// }
+///
+/// # async fn serve() {
+/// let pool = LiveViewPool::new();
+/// let listener = tokio::net::TcpListener::bind("127.0.0.1:3030").await.unwrap();
+/// while let Ok((stream, _)) = listener.accept().await {
+///     let pool = pool.clone();
+///     tokio::spawn(async move {
+///         let Ok(ws) = tokio_tungstenite::accept_async(stream).await else {
+///             return;
+///         };
+///         _ = pool.launch(tungstenite_socket(ws), app).await;
+///     });
+/// }
+/// # }
+/// ```
+pub fn tungstenite_socket<S>(ws: WebSocketStream<S>) -> impl LiveViewSocket
+where
+    S: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+{
+    ws.map(transform_rx)
+        .with(transform_tx)
+        .sink_map_err(|_| LiveViewError::SendingFailed)
+}
+
+fn transform_rx(message: Result<Message, Error>) -> Result<Vec<u8>, LiveViewError> {
+    Ok(message
+        .map_err(|_| LiveViewError::SendingFailed)?
+        .into_data())
+}
+
+async fn transform_tx(message: Vec<u8>) -> Result<Message, Error> {
+    Ok(Message::Binary(message))
+}
@@ -0,0 +1,47 @@
+use crate::{LiveViewError, LiveViewSocket};
+use futures_util::{SinkExt, StreamExt};
+use warp::ws::{Message, WebSocket};
+
+/// Convert a warp WebSocket into a `LiveViewSocket`.
+///
+/// This is required to launch a LiveView app using the warp web framework.
+///
+/// ```rust, no_run
+/// use dioxus::prelude::*;
+/// use dioxus_liveview::{interpreter_glue, warp_socket, LiveViewPool};
+/// use warp::Filter;
+///
+/// fn app() -> Element {
+///     rsx! { "hello from warp" }
+/// }
+///
+/// # async fn serve() {
+/// let pool = LiveViewPool::new();
+/// let index = warp::path::end()
+///     .map(|| warp::reply::html(format!(r#"<div id="main"></div>{}"#, interpreter_glue("/ws"))));
+/// let ws = warp::path("ws")
+///     .and(warp::ws())
+///     .map(move |ws: warp::ws::Ws| {
+///         let pool = pool.clone();
+///         ws.on_upgrade(|socket| async move {
+///             _ = pool.launch(warp_socket(socket), app).await;
+///         })
+///     });
+/// warp::serve(index.or(ws)).run(([127, 0, 0, 1], 3030)).await;
+/// # }
+/// ```
+pub fn warp_socket(ws: WebSocket) -> impl LiveViewSocket {
+    ws.map(transform_rx)
+        .with(transform_tx)
+        .sink_map_err(|_| LiveViewError::SendingFailed)
+}
+
+fn transform_rx(message: Result<Message, warp::Error>) -> Result<Vec<u8>, LiveViewError> {
+    Ok(message
+        .map_err(|_| LiveViewError::SendingFailed)?
+        .into_bytes())
+}
+
+async fn transform_tx(message: Vec<u8>) -> Result<Message, warp::Error> {
+    Ok(Message::binary(message))
+}
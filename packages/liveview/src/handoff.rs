@@ -0,0 +1,165 @@
+//! Hand off the state of sessions to another server when a [`LiveViewPool`](crate::LiveViewPool) drains.
+//!
+//! A [`VirtualDom`](dioxus_core::VirtualDom) can't move between processes, so components keep the state they
+//! need in signals created with [`use_handoff`]. When the pool drains, the state of every session is saved in a
+//! [`HandoffStore`] before the client is told to reconnect. The pool the client reconnects to takes the state from
+//! the store and starts a new session with it. The page reloads once and resumes the new session, so the state
+//! only survives if every server uses the same store. Without it the client starts over.
+
+use std::{cell::RefCell, collections::HashMap, future::Future, pin::Pin, rc::Rc, sync::Arc};
+
+use dioxus_core::prelude::{try_consume_context, use_drop, use_hook};
+use dioxus_signals::{Readable, Signal};
+use serde::{de::DeserializeOwned, Serialize};
+
+type SaveFn = dyn Fn(String, String) -> Pin<Box<dyn Future<Output = ()> + Send>> + Send + Sync;
+
+type TakeFn = dyn Fn(String) -> Pin<Box<dyn Future<Output = Option<String>> + Send>> + Send + Sync;
+
+/// Where the state of sessions is kept while their client moves to another server.
+///
+/// The store must be shared between every server behind the load balancer, for example a Redis or database
+/// table. The state is keyed by the session token of the client.
+///
+/// ```rust, no_run
+/// use dioxus_liveview::{HandoffStore, LiveViewPool};
+/// use std::{collections::HashMap, sync::{Arc, Mutex}};
+///
+/// // A real store would be shared between processes
+/// let states = Arc::new(Mutex::new(HashMap::new()));
+/// let pool = LiveViewPool::new().with_handoff(HandoffStore::new(
+///     {
+///         let states = states.clone();
+///         move |token, state| {
+///             states.lock().unwrap().insert(token, state);
+///             async {}
+///         }
+///     },
+///     move |token| {
+///         let state = states.lock().unwrap().remove(&token);
+///         async move { state }
+///     },
+/// ));
+/// ```
+#[derive(Clone)]
+pub struct HandoffStore {
+    save: Arc<SaveFn>,
+    take: Arc<TakeFn>,
+}
+
+impl HandoffStore {
+    /// Create a store from a function that saves the state of a session and a function that takes the saved
+    /// state of a session out of the store.
+    pub fn new<Save, SaveFut, Take, TakeFut>(save: Save, take: Take) -> Self
+    where
+        Save: Fn(String, String) -> SaveFut + Send + Sync + 'static,
+        SaveFut: Future<Output = ()> + Send + 'static,
+        Take: Fn(String) -> TakeFut + Send + Sync + 'static,
+        TakeFut: Future<Output = Option<String>> + Send + 'static,
+    {
+        Self {
+            save: Arc::new(move |token, state| Box::pin(save(token, state))),
+            take: Arc::new(move |token| Box::pin(take(token))),
+        }
+    }
+
+    pub(crate) async fn save(&self, token: &str, state: String) {
+        (self.save)(token.to_string(), state).await
+    }
+
+    pub(crate) async fn take(&self, token: &str) -> Option<String> {
+        (self.take)(token.to_string()).await
+    }
+}
+
+/// The signals components created with [`use_handoff`] in one session
+#[derive(Clone, Default)]
+pub(crate) struct HandoffContext {
+    inner: Rc<RefCell<HandoffState>>,
+}
+
+#[derive(Default)]
+struct HandoffState {
+    savers: HashMap<String, Rc<dyn Fn() -> String>>,
+    /// The state the session was restored from that was not taken by a component yet
+    restored: HashMap<String, String>,
+}
+
+impl HandoffContext {
+    /// Create the context of a session that was restored from the state another server saved
+    pub(crate) fn restore(state: Option<&str>) -> Self {
+        let restored = state
+            .and_then(|state| serde_json::from_str(state).ok())
+            .unwrap_or_default();
+        Self {
+            inner: Rc::new(RefCell::new(HandoffState {
+                savers: HashMap::new(),
+                restored,
+            })),
+        }
+    }
+
+    /// Serialize the value of every signal created with [`use_handoff`]
+    pub(crate) fn save(&self) -> String {
+        let savers: Vec<_> = self
+            .inner
+            .borrow()
+            .savers
+            .iter()
+            .map(|(key, saver)| (key.clone(), saver.clone()))
+            .collect();
+        let state: HashMap<_, _> = savers
+            .into_iter()
+            .map(|(key, saver)| (key, saver()))
+            .collect();
+        serde_json::to_string(&state).unwrap()
+    }
+}
+
+/// Create a signal that keeps its value when the session moves to another server while the
+/// [`LiveViewPool`](crate::LiveViewPool) drains.
+///
+/// The value is serialized when the pool drains. If the session was restored from another server, the signal
+/// starts with the value that was saved under the same `key`, otherwise it starts with the value of `init`. Keys
+/// must be unique in the app. Outside of a liveview session or without a [`HandoffStore`] this is a normal signal.
+///
+/// ```rust
+/// use dioxus::prelude::*;
+/// use dioxus_liveview::use_handoff;
+///
+/// fn Counter() -> Element {
+///     let mut count = use_handoff("counter", || 0);
+///
+///     rsx! {
+///         button { onclick: move |_| count += 1, "Clicked {count} times" }
+///     }
+/// }
+/// ```
+pub fn use_handoff<T>(key: &str, init: impl FnOnce() -> T) -> Signal<T>
+where
+    T: Serialize + DeserializeOwned + 'static,
+{
+    let signal = use_hook(|| {
+        let Some(context) = try_consume_context::<HandoffContext>() else {
+            return Signal::new(init());
+        };
+        let mut state = context.inner.borrow_mut();
+        let restored = state
+            .restored
+            .remove(key)
+            .and_then(|value| serde_json::from_str(&value).ok());
+        let signal = Signal::new(restored.unwrap_or_else(init));
+        let save = move || serde_json::to_string(&*signal.peek()).unwrap_or_default();
+        state.savers.insert(key.to_string(), Rc::new(save));
+        signal
+    });
+
+    let key = key.to_string();
+    use_drop(move || {
+        if let Some(context) = try_consume_context::<HandoffContext>() {
+            context.inner.borrow_mut().savers.remove(&key);
+        }
+    });
+
+    signal
+}
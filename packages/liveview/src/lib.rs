@@ -13,6 +13,12 @@ use dioxus_interpreter_js::NATIVE_JS;
 use futures_util::{SinkExt, StreamExt};
 pub use pool::*;
 mod config;
+mod protocol;
+pub use protocol::{Compression, ProtocolConfig};
+mod session;
+pub use session::{EvictionPolicy, PoolMetrics, SessionConfig};
+mod handoff;
+pub use handoff::{use_handoff, HandoffStore};
 mod document;
 mod events;
 mod history;
@@ -1,5 +1,113 @@
 const intercept_link_redirects = false;
 
+// The key the token of the liveview session is stored under so the session survives reloads
+const SESSION_KEY = "dioxus-liveview-session";
+
+// The encodings this client supports. Frames can only be decompressed if the browser supports DecompressionStream
+const ENCODINGS = ["binary-events"].concat(
+  typeof DecompressionStream === "undefined" ? [] : ["deflate"]
+);
+
+// The most strings that are sent as symbols over one connection
+const MAX_SYMBOLS = 4096;
+
+// Encodes events in the compact binary encoding the server reads in protocol.rs
+class EventEncoder {
+  constructor() {
+    this.symbols = new Map();
+    this.textEncoder = new TextEncoder();
+  }
+
+  // Returns null if the event can't be encoded
+  encode(body) {
+    if (typeof body.element !== "number") {
+      return null;
+    }
+    const known = this.symbols.size;
+    this.bytes = [1];
+    this.full = false;
+    this.symbol(body.name);
+    this.varint(body.element);
+    this.bytes.push(body.bubbles ? 1 : 0);
+    this.value(body.data);
+    if (this.full) {
+      // Forget the symbols of this event. The server never sees them
+      for (const [symbol, index] of this.symbols) {
+        if (index >= known) {
+          this.symbols.delete(symbol);
+        }
+      }
+      return null;
+    }
+    return Uint8Array.from(this.bytes);
+  }
+
+  varint(value) {
+    while (value >= 0x80) {
+      this.bytes.push((value & 0x7f) | 0x80);
+      value = Math.floor(value / 128);
+    }
+    this.bytes.push(value);
+  }
+
+  string(value) {
+    const bytes = this.textEncoder.encode(value);
+    this.varint(bytes.length);
+    for (const byte of bytes) {
+      this.bytes.push(byte);
+    }
+  }
+
+  symbol(value) {
+    const index = this.symbols.get(value);
+    if (index !== undefined) {
+      this.varint(index + 1);
+    } else if (this.symbols.size >= MAX_SYMBOLS) {
+      this.full = true;
+    } else {
+      this.symbols.set(value, this.symbols.size);
+      this.varint(0);
+      this.string(value);
+    }
+  }
+
+  value(value) {
+    if (typeof value === "boolean") {
+      this.bytes.push(value ? 2 : 1);
+    } else if (typeof value === "number") {
+      if (Number.isInteger(value) && Math.abs(value) < 2 ** 31) {
+        this.bytes.push(3);
+        this.varint(((value << 1) ^ (value >> 31)) >>> 0);
+      } else {
+        this.bytes.push(4);
+        const view = new DataView(new ArrayBuffer(8));
+        view.setFloat64(0, value, true);
+        for (const byte of new Uint8Array(view.buffer)) {
+          this.bytes.push(byte);
+        }
+      }
+    } else if (typeof value === "string") {
+      this.bytes.push(5);
+      this.string(value);
+    } else if (Array.isArray(value)) {
+      this.bytes.push(6);
+      this.varint(value.length);
+      value.forEach((item) => this.value(item));
+    } else if (value !== null && typeof value === "object") {
+      // Like JSON, skip properties that are undefined
+      const entries = Object.entries(value).filter(([, item]) => item !== undefined);
+      this.bytes.push(7);
+      this.varint(entries.length);
+      for (const [key, item] of entries) {
+        this.symbol(key);
+        this.value(item);
+      }
+    } else {
+      this.bytes.push(0);
+    }
+  }
+}
+
 function main() {
   let root = window.document.getElementById("main");
   if (root != null) {
@@ -13,55 +121,200 @@ class IPC {
     window.interpreter.initialize(root);
     window.interpreter.liveview = true;
     window.interpreter.ipc = this;
+
+    // Listeners can limit how often their events are sent to the server. Limiters are kept per limiting
+    // element, event name and target
+    this.limiters = new WeakMap();
+    // If the page has any element that limits an event, by event name. Cleared when the page changes
+    this.limitedEvents = new Map();
+    const sendSerializedEvent = window.interpreter.sendSerializedEvent.bind(
+      window.interpreter
+    );
+    window.interpreter.sendSerializedEvent = (body) =>
+      this.limitEvent(body, (body) => this.sendEvent(body, sendSerializedEvent));
+
+    // The number of edit frames this page applied in the current session
+    this.applied = 0;
+    this.session = window.sessionStorage.getItem(SESSION_KEY);
+    this.reconnectAttempts = 0;
+    this.connect();
+  }
+
+  connect() {
     const ws = new WebSocket(WS_ADDR);
     ws.binaryType = "arraybuffer";
+    let pingInterval = null;
+    // Compressed frames are decompressed asynchronously, so frames are handled in order through a queue
+    let frames = Promise.resolve();
+    // Events are sent as JSON until the server accepts the binary encoding
+    this.binaryEvents = false;
 
     function ping() {
       ws.send("__ping__");
     }
 
     ws.onopen = () => {
+      this.reconnectAttempts = 0;
       // we ping every 30 seconds to keep the websocket alive
-      setInterval(ping, 30000);
-      ws.send(window.interpreter.serializeIpcMessage("initialize"));
+      pingInterval = setInterval(ping, 30000);
+      // if we were connected before, ask the server to resume the session
+      ws.send(
+        window.interpreter.serializeIpcMessage("initialize", {
+          session: this.session,
+          applied: this.applied,
+          encodings: ENCODINGS,
+        })
+      );
     };
 
-    ws.onerror = (err) => {
-      // todo: retry the connection
+    ws.onclose = () => {
+      clearInterval(pingInterval);
+      // reconnect with an exponential backoff
+      const delay = Math.min(500 * 2 ** this.reconnectAttempts, 10000);
+      this.reconnectAttempts += 1;
+      setTimeout(() => this.connect(), delay);
     };
 
     ws.onmessage = (message) => {
-      const u8view = new Uint8Array(message.data);
-      const binaryFrame = u8view[0] == 1;
-      const messageData = message.data.slice(1);
-      // The first byte tells the shim if this is a binary of text frame
-      if (binaryFrame) {
-        // binary frame
-        window.interpreter.run_from_bytes(messageData);
-      } else {
-        // text frame
-
-        let decoder = new TextDecoder("utf-8");
-
-        // Using decode method to get string output
-        let str = decoder.decode(messageData);
-        // Ignore pongs
-        if (str != "__pong__") {
-          const event = JSON.parse(str);
-          switch (event.type) {
-            case "query":
-              Function("Eval", `"use strict";${event.data};`)();
-              break;
-          }
-        }
-      }
+      frames = frames.then(() => this.handleFrame(message.data));
     };
 
     this.ws = ws;
   }
 
+  async handleFrame(data) {
+    // The first byte tells the shim if this is a binary, text or compressed frame
+    if (new Uint8Array(data)[0] == 2) {
+      const decompressed = new Blob([data.slice(1)])
+        .stream()
+        .pipeThrough(new DecompressionStream("deflate"));
+      data = await new Response(decompressed).arrayBuffer();
+    }
+    const binaryFrame = new Uint8Array(data)[0] == 1;
+    const messageData = data.slice(1);
+    if (binaryFrame) {
+      // binary frame
+      window.interpreter.run_from_bytes(messageData);
+      this.applied += 1;
+      this.limitedEvents.clear();
+    } else {
+      // text frame
+
+      let decoder = new TextDecoder("utf-8");
+
+      // Using decode method to get string output
+      let str = decoder.decode(messageData);
+      // Ignore pongs
+      if (str != "__pong__") {
+        const event = JSON.parse(str);
+        switch (event.type) {
+          case "query":
+            Function("Eval", `"use strict";${event.data};`)();
+            this.limitedEvents.clear();
+            break;
+          case "protocol":
+            this.binaryEvents = event.data.encodings.includes("binary-events");
+            this.encoder = new EventEncoder();
+            break;
+          case "reconnect":
+            // The server drains and closes the socket. Reset the backoff so the client reconnects after the
+            // shortest delay and the load balancer can route it to another server
+            this.reconnectAttempts = 0;
+            break;
+          case "session":
+            this.session = event.data.token;
+            window.sessionStorage.setItem(SESSION_KEY, this.session);
+            // The old session could not be resumed. Reload to render the new session from scratch. A session
+            // restored from the handoff store of a drained server is new as well, so the reloaded page resumes it
+            // with the new token
+            if (!event.data.resumed && this.applied > 0) {
+              window.location.reload();
+            }
+            break;
+        }
+      }
+    }
+  }
+
+  // Send an event in the binary encoding if the server accepts it
+  sendEvent(body, sendJson) {
+    const encoded = this.binaryEvents ? this.encoder.encode(body) : null;
+    if (encoded) {
+      this.postMessage(encoded);
+    } else {
+      sendJson(body);
+    }
+  }
+
+  // Events are throttled or debounced by the closest element with a
+  // data-dioxus-throttle-{event} or data-dioxus-debounce-{event} attribute set to a number of milliseconds
+  limitEvent(body, send) {
+    const throttle = `data-dioxus-throttle-${body.name}`;
+    const debounce = `data-dioxus-debounce-${body.name}`;
+    const selector = `[${throttle}], [${debounce}]`;
+    // Most pages don't limit any events, so skip looking up the target of every event
+    let limitedEvent = this.limitedEvents.get(body.name);
+    if (limitedEvent === undefined) {
+      limitedEvent = document.querySelector(selector) !== null;
+      this.limitedEvents.set(body.name, limitedEvent);
+    }
+    const target = limitedEvent
+      ? document.querySelector(`[data-dioxus-id="${body.element}"]`)
+      : null;
+    const limited = target?.closest(selector);
+    if (!limited) {
+      send(body);
+      return;
+    }
+
+    // The limiting element may not have a listener or id, so limiters are keyed by the element itself. Events
+    // of different targets inside the element are limited separately
+    let limiters = this.limiters.get(limited);
+    if (!limiters) {
+      limiters = new Map();
+      this.limiters.set(limited, limiters);
+    }
+    const key = `${body.name}:${body.element}`;
+    let limiter = limiters.get(key);
+    if (!limiter) {
+      limiter = { timer: null, pending: null, last: 0 };
+      limiters.set(key, limiter);
+    }
+
+    // Debounced events are sent once no event happened for the delay
+    if (limited.hasAttribute(debounce)) {
+      const delay = parseInt(limited.getAttribute(debounce)) || 0;
+      clearTimeout(limiter.timer);
+      limiter.timer = setTimeout(() => {
+        limiters.delete(key);
+        send(body);
+      }, delay);
+      return;
+    }
+
+    // Throttled events are sent at most once per interval. The latest event in an interval is sent at its end
+    const interval = parseInt(limited.getAttribute(throttle)) || 0;
+    const now = Date.now();
+    if (limiter.timer === null && now - limiter.last >= interval) {
+      limiter.last = now;
+      send(body);
+      return;
+    }
+    limiter.pending = body;
+    if (limiter.timer === null) {
+      limiter.timer = setTimeout(() => {
+        limiter.timer = null;
+        limiter.last = Date.now();
+        send(limiter.pending);
+        limiter.pending = null;
+      }, interval - (now - limiter.last));
+    }
+  }
+
   postMessage(msg) {
-    this.ws.send(msg);
+    if (this.ws.readyState === WebSocket.OPEN) {
+      this.ws.send(msg);
+    }
   }
 }
 
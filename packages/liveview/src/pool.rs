@@ -2,20 +2,27 @@ use crate::{
     document::init_document,
     element::LiveviewElement,
     events::SerializedHtmlEventConverter,
+    handoff::{HandoffContext, HandoffStore},
+    protocol::{Protocol, ProtocolConfig},
     query::{QueryEngine, QueryResult},
+    session::{BoxedSocket, Connection, Journal, PoolMetrics, SessionConfig, SessionRegistry},
     LiveViewError,
 };
 use dioxus_core::prelude::*;
 use dioxus_html::{EventData, HtmlEvent, PlatformEventData};
 use dioxus_interpreter_js::MutationState;
-use futures_util::{pin_mut, SinkExt, StreamExt};
+use futures_util::{FutureExt, SinkExt, StreamExt};
 use serde::Serialize;
-use std::{any::Any, rc::Rc, time::Duration};
+use std::{any::Any, rc::Rc, sync::Arc, time::Duration};
+use tokio::sync::{oneshot, watch};
 use tokio_util::task::LocalPoolHandle;
 
 #[derive(Clone)]
 pub struct LiveViewPool {
     pub(crate) pool: LocalPoolHandle,
+    sessions: Arc<SessionRegistry>,
+    protocol: ProtocolConfig,
+    handoff: Option<HandoffStore>,
 }
 
 impl Default for LiveViewPool {
@@ -31,9 +38,54 @@ impl LiveViewPool {
 
         LiveViewPool {
             pool: LocalPoolHandle::new(16),
+            sessions: Arc::new(SessionRegistry::new(SessionConfig::default())),
+            protocol: ProtocolConfig::default(),
+            handoff: None,
         }
     }
 
+    /// Set how sessions are resumed after the websocket of a client disconnects. Sessions that are already
+    /// parked in the pool are dropped.
+    pub fn with_session_config(mut self, config: SessionConfig) -> Self {
+        self.sessions = Arc::new(SessionRegistry::new(config));
+        self
+    }
+
+    /// Set the encodings of the messages between the server and the clients.
+    pub fn with_protocol_config(mut self, config: ProtocolConfig) -> Self {
+        self.protocol = config;
+        self
+    }
+
+    /// Save the state of sessions in a store when the pool drains, and restore sessions of clients that
+    /// reconnect from another server. See [`use_handoff`](crate::use_handoff) for how components keep their state.
+    pub fn with_handoff(mut self, store: HandoffStore) -> Self {
+        self.handoff = Some(store);
+        self
+    }
+
+    /// Get a snapshot of the sessions in the pool
+    pub fn metrics(&self) -> PoolMetrics {
+        self.sessions.metrics()
+    }
+
+    /// Stop accepting new sessions and move the clients of the pool to other servers.
+    ///
+    /// Every session hands off its state to the [`HandoffStore`] if the pool has one and its client is told to
+    /// reconnect, so the load balancer can route it to another server. Clients that connect while the pool drains
+    /// are told to reconnect right away. Resolves once every session ended.
+    ///
+    /// ```rust, no_run
+    /// # async fn shutdown(pool: dioxus_liveview::LiveViewPool) {
+    /// // Stop routing new clients to this server first, then
+    /// pool.drain().await;
+    /// # }
+    /// ```
+    pub async fn drain(&self) {
+        self.sessions.drain();
+        self.sessions.wait_for_sessions().await;
+    }
+
     pub async fn launch(
         &self,
         ws: impl LiveViewSocket,
@@ -57,11 +109,57 @@ impl LiveViewPool {
         ws: impl LiveViewSocket,
         make_app: F,
     ) -> Result<(), LiveViewError> {
-        match self.pool.spawn_pinned(move || run(make_app(), ws)).await {
-            Ok(Ok(_)) => Ok(()),
-            Ok(Err(e)) => Err(e),
-            Err(_) => Err(LiveViewError::SendingFailed),
+        let mut socket: BoxedSocket = Box::pin(ws);
+        if self.sessions.is_draining() {
+            // Send the client to another server
+            let update = serde_json::to_string(&ClientUpdate::Reconnect).unwrap();
+            return socket.send(text_frame(&update)).await;
+        }
+
+        let Some(initialize) = read_initialize(&mut socket).await? else {
+            return Ok(());
+        };
+        let protocol = Protocol::negotiate(&self.protocol, &initialize.encodings);
+
+        // A client that reconnects sends the token of its session and how many edits it applied
+        let resume = initialize.session.as_ref().and_then(|token| {
+            self.sessions
+                .take(token, initialize.applied)
+                .map(|resume| (resume, initialize.applied))
+        });
+
+        // A client that comes from a server that drained may have left its state in the handoff store
+        let restored = match (&resume, &initialize.session, &self.handoff) {
+            (None, Some(token), Some(store)) => store.take(token).await,
+            _ => None,
+        };
+
+        let (done, finished) = oneshot::channel();
+        let mut connection = Connection {
+            socket,
+            applied: None,
+            protocol,
+            done,
+        };
+        if let Some((resume, applied)) = resume {
+            connection.applied = Some(applied);
+            match resume.send(connection) {
+                Ok(()) => return finished.await.unwrap_or(Err(LiveViewError::SendingFailed)),
+                // The session ended right before the client reconnected. Start a new session instead
+                Err(rejected) => {
+                    connection = Connection {
+                        applied: None,
+                        ..rejected
+                    }
+                }
+            }
         }
+
+        let sessions = self.sessions.clone();
+        let handoff = self.handoff.clone();
+        self.pool
+            .spawn_pinned(move || run_session(make_app(), connection, sessions, handoff, restored));
+        finished.await.unwrap_or(Err(LiveViewError::SendingFailed))
     }
 }
 
@@ -115,140 +213,425 @@ impl<S> LiveViewSocket for S where
 /// As long as your framework can provide a Sink and Stream of Bytes, you can use this function.
 ///
 /// You might need to transform the error types of the web backend into the LiveView error type.
-pub async fn run(mut vdom: VirtualDom, ws: impl LiveViewSocket) -> Result<(), LiveViewError> {
-    #[cfg(all(feature = "devtools", debug_assertions))]
-    let mut hot_reload_rx = {
-        let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
-        if let Some(endpoint) = dioxus_cli_config::devserver_ws_endpoint() {
-            dioxus_devtools::connect(endpoint, move |template| _ = tx.send(template));
-        }
-        rx
+///
+/// The VirtualDom is dropped when the socket closes. Use [`LiveViewPool`] to let clients resume their
+/// session after a reconnect.
+pub async fn run(vdom: VirtualDom, ws: impl LiveViewSocket) -> Result<(), LiveViewError> {
+    let mut socket: BoxedSocket = Box::pin(ws);
+    let Some(initialize) = read_initialize(&mut socket).await? else {
+        return Ok(());
     };
+    let protocol = Protocol::negotiate(&ProtocolConfig::default(), &initialize.encodings);
+    let sessions = SessionRegistry::new(SessionConfig::disabled().max_replay_bytes(0));
+    LiveViewSession::new(vdom, &sessions, None, None)
+        .connect(socket, None, None, protocol)
+        .await
+}
+
+/// Read the first message a client sends after it connects. Returns `None` if the socket closed.
+async fn read_initialize(socket: &mut BoxedSocket) -> Result<Option<Initialize>, LiveViewError> {
+    match socket.next().await {
+        Some(Ok(message)) => match serde_json::from_slice::<IpcMessage>(&message) {
+            Ok(IpcMessage::Initialize(initialize)) => Ok(Some(initialize)),
+            _ => Ok(Some(Initialize::default())),
+        },
+        Some(Err(err)) => Err(err),
+        None => Ok(None),
+    }
+}
+
+/// Run a session for every client that connects to it until it is not resumed within the grace period
+async fn run_session(
+    vdom: VirtualDom,
+    mut connection: Connection,
+    sessions: Arc<SessionRegistry>,
+    store: Option<HandoffStore>,
+    restored: Option<String>,
+) {
+    let _active = sessions.track_session();
+    let mut token = SessionRegistry::new_token();
+    let mut session = LiveViewSession::new(vdom, &sessions, store, restored.as_deref());
+    loop {
+        let result = {
+            let _connected = sessions.track_connection();
+            session
+                .connect(
+                    connection.socket,
+                    Some(&token),
+                    connection.applied,
+                    connection.protocol,
+                )
+                .await
+        };
+        // Park the session before the connection finishes, so the client can reconnect right away
+        let parked = sessions.park(&token, &session.journal);
+        _ = connection.done.send(result);
 
-    let mut mutations = MutationState::default();
-
-    // Create the a proxy for query engine
-    let (query_tx, mut query_rx) = tokio::sync::mpsc::unbounded_channel();
-    let query_engine = QueryEngine::new(query_tx);
-    vdom.runtime().on_scope(ScopeId::ROOT, || {
-        provide_context(query_engine.clone());
-        init_document();
-    });
-
-    // pin the futures so we can use select!
-    pin_mut!(ws);
-
-    if let Some(edits) = {
-        vdom.rebuild(&mut mutations);
-        take_edits(&mut mutations)
-    } {
-        // send the initial render to the client
-        ws.send(edits).await?;
+        let next = match parked {
+            Some(resumed) => sessions.wait_for_client(&token, resumed).await,
+            None => None,
+        };
+        match next {
+            // The token is kept in the session storage of the page, which is copied to tabs that duplicate it. A
+            // new token is sent with every resume, so only the tab that resumed the session can resume it again
+            Some(next) => {
+                connection = next;
+                token = SessionRegistry::new_token();
+            }
+            None => break,
+        }
     }
 
-    // desktop uses this wrapper struct thing around the actual event itself
-    // this is sorta driven by tao/wry
-    #[derive(serde::Deserialize, Debug)]
-    #[serde(tag = "method", content = "params")]
-    enum IpcMessage {
-        #[serde(rename = "user_event")]
-        Event(Box<HtmlEvent>),
-        #[serde(rename = "query")]
-        Query(QueryResult),
+    // A session that was parked or disconnected when the pool started to drain still needs to hand off its state
+    if sessions.is_draining() {
+        session.hand_off(&token).await;
     }
+}
 
-    loop {
+// desktop uses this wrapper struct thing around the actual event itself
+// this is sorta driven by tao/wry
+#[derive(serde::Deserialize, Debug)]
+#[serde(tag = "method", content = "params")]
+enum IpcMessage {
+    #[serde(rename = "user_event")]
+    Event(Box<HtmlEvent>),
+    #[serde(rename = "query")]
+    Query(QueryResult),
+    #[serde(rename = "initialize")]
+    Initialize(Initialize),
+}
+
+/// A message from the client
+enum ClientMessage {
+    /// The client keeps the websocket alive
+    Ping,
+    Ipc(IpcMessage),
+    /// A message that could not be parsed
+    Invalid,
+}
+
+impl ClientMessage {
+    fn parse(message: &[u8], protocol: &mut Protocol) -> Self {
+        if message == b"__ping__" {
+            return Self::Ping;
+        }
+        if let Some(event) = protocol.decode_event(message) {
+            return Self::Ipc(IpcMessage::Event(Box::new(event)));
+        }
+        serde_json::from_str(&String::from_utf8_lossy(message))
+            .map(Self::Ipc)
+            .unwrap_or(Self::Invalid)
+    }
+}
+
+/// The most messages that are taken from the websocket at once before they are handled
+const MAX_MESSAGE_BATCH: usize = 256;
+
+/// Events that only the latest of matters when many of them are queued. Listeners of these events may not
+/// run for every event the client sent while the server was busy. Input events are not coalesced because
+/// listeners often need every change, for example to handle each key the user typed.
+const COALESCED_EVENTS: &[&str] = &[
+    "mousemove",
+    "pointermove",
+    "touchmove",
+    "drag",
+    "dragover",
+    "scroll",
+];
+
+/// Drop events that are directly followed by an event of the same type on the same element if only the latest
+/// of them matters. Events are never reordered, so listeners still see the other events in the order they
+/// happened.
+fn coalesce_events(messages: impl IntoIterator<Item = ClientMessage>) -> Vec<ClientMessage> {
+    let mut coalesced: Vec<ClientMessage> = Vec::new();
+    for message in messages {
+        if let (
+            Some(ClientMessage::Ipc(IpcMessage::Event(last))),
+            ClientMessage::Ipc(IpcMessage::Event(next)),
+        ) = (coalesced.last(), &message)
+        {
+            if last.name == next.name
+                && last.element == next.element
+                && COALESCED_EVENTS.contains(&next.name.as_str())
+            {
+                coalesced.pop();
+            }
+        }
+        coalesced.push(message);
+    }
+    coalesced
+}
+
+/// The first message a client sends after it connects
+#[derive(serde::Deserialize, Debug, Default)]
+#[serde(default)]
+struct Initialize {
+    /// The token of the session the client was connected to before
+    session: Option<String>,
+    /// The number of edit frames the client applied in that session
+    applied: u64,
+    /// The encodings the client supports
+    encodings: Vec<String>,
+}
+
+/// A VirtualDom and the state of the client it renders to. The session outlives the websocket, so a client can
+/// reconnect to it.
+struct LiveViewSession {
+    vdom: VirtualDom,
+    mutations: MutationState,
+    query_engine: QueryEngine,
+    query_rx: tokio::sync::mpsc::UnboundedReceiver<String>,
+    #[cfg(all(feature = "devtools", debug_assertions))]
+    hot_reload_rx: tokio::sync::mpsc::UnboundedReceiver<dioxus_devtools::DevserverMsg>,
+    journal: Journal,
+    built: bool,
+    /// The encodings of the current connection
+    protocol: Protocol,
+    /// Changes to `true` when the pool starts to drain
+    draining: watch::Receiver<bool>,
+    handoff: HandoffContext,
+    store: Option<HandoffStore>,
+    handed_off: bool,
+}
+
+impl LiveViewSession {
+    fn new(
+        vdom: VirtualDom,
+        sessions: &SessionRegistry,
+        store: Option<HandoffStore>,
+        restored: Option<&str>,
+    ) -> Self {
         #[cfg(all(feature = "devtools", debug_assertions))]
-        let hot_reload_wait = hot_reload_rx.recv();
-        #[cfg(not(all(feature = "devtools", debug_assertions)))]
-        let hot_reload_wait: std::future::Pending<Option<()>> = std::future::pending();
-
-        tokio::select! {
-            // poll any futures or suspense
-            _ = vdom.wait_for_work() => {}
-
-            evt = ws.next() => {
-                match evt.as_ref().map(|o| o.as_deref()) {
-                    // respond with a pong every ping to keep the websocket alive
-                    Some(Ok(b"__ping__")) => {
-                        ws.send(text_frame("__pong__")).await?;
-                    }
-                    Some(Ok(evt)) => {
-                        if let Ok(message) = serde_json::from_str::<IpcMessage>(&String::from_utf8_lossy(evt)) {
-                            match message {
-                                IpcMessage::Event(evt) => {
-                                    // Intercept the mounted event and insert a custom element type
-                                    let event = if let EventData::Mounted = &evt.data {
-                                        let element = LiveviewElement::new(evt.element, query_engine.clone());
-                                        Event::new(
-                                            Rc::new(PlatformEventData::new(Box::new(element))) as Rc<dyn Any>,
-                                            evt.bubbles,
-                                        )
-                                    } else {
-                                        Event::new(
-                                            evt.data.into_any(),
-                                            evt.bubbles,
-                                        )
-                                    };
-                                    vdom.runtime().handle_event(
-                                        &evt.name,
-                                        event,
-                                        evt.element,
-                                    );
+        let hot_reload_rx = {
+            let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+            if let Some(endpoint) = dioxus_cli_config::devserver_ws_endpoint() {
+                dioxus_devtools::connect(endpoint, move |template| _ = tx.send(template));
+            }
+            rx
+        };
+
+        // Create the a proxy for query engine
+        let (query_tx, query_rx) = tokio::sync::mpsc::unbounded_channel();
+        let query_engine = QueryEngine::new(query_tx);
+        let handoff = HandoffContext::restore(restored);
+        vdom.runtime().on_scope(ScopeId::ROOT, || {
+            provide_context(query_engine.clone());
+            provide_context(handoff.clone());
+            init_document();
+        });
+
+        Self {
+            vdom,
+            mutations: MutationState::default(),
+            query_engine,
+            query_rx,
+            #[cfg(all(feature = "devtools", debug_assertions))]
+            hot_reload_rx,
+            journal: Journal::new(
+                sessions.config.max_replay_bytes,
+                sessions.replay_bytes.clone(),
+            ),
+            built: false,
+            protocol: Protocol::default(),
+            draining: sessions.draining(),
+            handoff,
+            store,
+            handed_off: false,
+        }
+    }
+
+    /// Send the edits that are queued to the client and record them in the journal
+    async fn flush(&mut self, ws: &mut BoxedSocket) -> Result<(), LiveViewError> {
+        if let Some(edits) = take_edits(&mut self.mutations) {
+            self.journal.record(&edits);
+            ws.send(self.protocol.encode_frame(edits)).await?;
+        }
+        Ok(())
+    }
+
+    /// Save the state of the session in the handoff store, so the server the client connects to next can restore it
+    async fn hand_off(&mut self, token: &str) {
+        if std::mem::replace(&mut self.handed_off, true) {
+            return;
+        }
+        if let Some(store) = &self.store {
+            let state = self.vdom.in_runtime(|| self.handoff.save());
+            store.save(token, state).await;
+        }
+    }
+
+    /// Run the listeners of an event the client sent
+    fn handle_event(&mut self, evt: HtmlEvent) {
+        // Intercept the mounted event and insert a custom element type
+        let event = if let EventData::Mounted = &evt.data {
+            let element = LiveviewElement::new(evt.element, self.query_engine.clone());
+            Event::new(
+                Rc::new(PlatformEventData::new(Box::new(element))) as Rc<dyn Any>,
+                evt.bubbles,
+            )
+        } else {
+            Event::new(evt.data.into_any(), evt.bubbles)
+        };
+        self.vdom
+            .runtime()
+            .handle_event(&evt.name, event, evt.element);
+    }
+
+    /// Handle a client until its websocket closes. If the client was connected to the session before, `applied`
+    /// is the number of edit frames it applied and it gets the frames it missed.
+    async fn connect(
+        &mut self,
+        mut ws: BoxedSocket,
+        token: Option<&str>,
+        applied: Option<u64>,
+        protocol: Protocol,
+    ) -> Result<(), LiveViewError> {
+        // Tell the client which encodings it can use before anything else is sent
+        self.protocol = protocol;
+        let encodings = self.protocol.encodings();
+        if !encodings.is_empty() {
+            let update = ClientUpdate::Protocol { encodings };
+            ws.send(text_frame(&serde_json::to_string(&update).unwrap()))
+                .await?;
+        }
+
+        if let Some(token) = token {
+            let session = ClientUpdate::Session {
+                token: token.to_string(),
+                resumed: applied.is_some(),
+            };
+            ws.send(text_frame(&serde_json::to_string(&session).unwrap()))
+                .await?;
+        }
+
+        if let Some(applied) = applied {
+            // Re-sync the client with the edits it missed
+            for frame in self.journal.frames_after(applied) {
+                ws.send(self.protocol.encode_frame(frame.clone())).await?;
+            }
+        }
+
+        if !self.built {
+            self.built = true;
+            self.vdom.rebuild(&mut self.mutations);
+            // send the initial render to the client
+            self.flush(&mut ws).await?;
+        }
+
+        loop {
+            #[cfg(all(feature = "devtools", debug_assertions))]
+            let hot_reload_wait = self.hot_reload_rx.recv();
+            #[cfg(not(all(feature = "devtools", debug_assertions)))]
+            let hot_reload_wait: std::future::Pending<Option<()>> = std::future::pending();
+
+            tokio::select! {
+                // poll any futures or suspense
+                _ = self.vdom.wait_for_work() => {}
+
+                evt = ws.next() => {
+                    match evt {
+                        Some(Ok(message)) => {
+                            // Take every message that already arrived, so bursts of events can be coalesced
+                            let mut messages = vec![message];
+                            let mut closed = false;
+                            while messages.len() < MAX_MESSAGE_BATCH {
+                                match ws.next().now_or_never() {
+                                    Some(Some(Ok(message))) => messages.push(message),
+                                    Some(Some(Err(_))) => {}
+                                    Some(None) => {
+                                        closed = true;
+                                        break;
+                                    }
+                                    None => break,
                                 }
-                                IpcMessage::Query(result) => {
-                                    query_engine.send(result);
-                                },
+                            }
+
+                            let messages: Vec<_> = messages
+                                .iter()
+                                .map(|message| ClientMessage::parse(message, &mut self.protocol))
+                                .collect();
+                            for message in coalesce_events(messages) {
+                                match message {
+                                    // respond with a pong every ping to keep the websocket alive
+                                    ClientMessage::Ping => {
+                                        ws.send(text_frame("__pong__")).await?;
+                                    }
+                                    ClientMessage::Ipc(IpcMessage::Event(evt)) => self.handle_event(*evt),
+                                    ClientMessage::Ipc(IpcMessage::Query(result)) => {
+                                        self.query_engine.send(result);
+                                    }
+                                    ClientMessage::Ipc(IpcMessage::Initialize(_)) | ClientMessage::Invalid => {}
+                                }
+                            }
+
+                            if closed {
+                                return Ok(());
                             }
                         }
+                        // log this I guess? when would we get an error here?
+                        Some(Err(_e)) => {}
+                        None => return Ok(()),
                     }
-                    // log this I guess? when would we get an error here?
-                    Some(Err(_e)) => {}
-                    None => return Ok(()),
                 }
-            }
 
-            // handle any new queries
-            Some(query) = query_rx.recv() => {
-                ws.send(text_frame(&serde_json::to_string(&ClientUpdate::Query(query)).unwrap())).await?;
-            }
+                // move the client to another server when the pool drains
+                _ = wait_for_drain(&mut self.draining) => {
+                    if let Some(token) = token {
+                        self.hand_off(token).await;
+                    }
+                    ws.send(text_frame(&serde_json::to_string(&ClientUpdate::Reconnect).unwrap())).await?;
+                    return Ok(());
+                }
 
-            Some(msg) = hot_reload_wait => {
-                #[cfg(all(feature = "devtools", debug_assertions))]
-                match msg{
-                    dioxus_devtools::DevserverMsg::HotReload(msg)=> {
-                        dioxus_devtools::apply_changes(&vdom, &msg);
+                // handle any new queries
+                Some(query) = self.query_rx.recv() => {
+                    let frame = text_frame(&serde_json::to_string(&ClientUpdate::Query(query)).unwrap());
+                    ws.send(self.protocol.encode_frame(frame)).await?;
+                }
+
+                Some(msg) = hot_reload_wait => {
+                    #[cfg(all(feature = "devtools", debug_assertions))]
+                    match msg{
+                        dioxus_devtools::DevserverMsg::HotReload(msg)=> {
+                            dioxus_devtools::apply_changes(&self.vdom, &msg);
+                        }
+                        dioxus_devtools::DevserverMsg::Shutdown => {
+                            std::process::exit(0);
+                        },
+                        dioxus_devtools::DevserverMsg::FullReloadCommand
+                        | dioxus_devtools::DevserverMsg::FullReloadStart
+                        | dioxus_devtools::DevserverMsg::FullReloadFailed => {
+                            // usually only web gets this message - what are we supposed to do?
+                            // Maybe we could just binary patch ourselves in place without losing window state?
+                        },
                     }
-                    dioxus_devtools::DevserverMsg::Shutdown => {
-                        std::process::exit(0);
-                    },
-                    dioxus_devtools::DevserverMsg::FullReloadCommand
-                    | dioxus_devtools::DevserverMsg::FullReloadStart
-                    | dioxus_devtools::DevserverMsg::FullReloadFailed => {
-                        // usually only web gets this message - what are we supposed to do?
-                        // Maybe we could just binary patch ourselves in place without losing window state?
-                    },
+                    #[cfg(not(all(feature = "devtools", debug_assertions)))]
+                    let () = msg;
                 }
-                #[cfg(not(all(feature = "devtools", debug_assertions)))]
-                let () = msg;
             }
-        }
 
-        // wait for suspense to resolve in a 10ms window
-        tokio::select! {
-            _ = tokio::time::sleep(Duration::from_millis(10)) => {}
-            _ = vdom.wait_for_suspense() => {}
-        }
+            // wait for suspense to resolve in a 10ms window
+            tokio::select! {
+                _ = tokio::time::sleep(Duration::from_millis(10)) => {}
+                _ = self.vdom.wait_for_suspense() => {}
+            }
 
-        // render the vdom
-        vdom.render_immediate(&mut mutations);
+            // render the vdom
+            self.vdom.render_immediate(&mut self.mutations);
 
-        if let Some(edits) = take_edits(&mut mutations) {
-            ws.send(edits).await?;
+            self.flush(&mut ws).await?;
         }
     }
 }
 
+/// Wait until the pool starts to drain
+async fn wait_for_drain(draining: &mut watch::Receiver<bool>) {
+    if draining.wait_for(|draining| *draining).await.is_err() {
+        // The pool is gone, so it will never drain
+        std::future::pending::<()>().await;
+    }
+}
+
 fn text_frame(text: &str) -> Vec<u8> {
     let mut bytes = vec![0];
     bytes.extend(text.as_bytes());
@@ -267,4 +650,79 @@ fn take_edits(mutations: &mut MutationState) -> Option<Vec<u8>> {
 enum ClientUpdate {
     #[serde(rename = "query")]
     Query(String),
+    #[serde(rename = "session")]
+    Session { token: String, resumed: bool },
+    #[serde(rename = "protocol")]
+    Protocol { encodings: Vec<&'static str> },
+    #[serde(rename = "reconnect")]
+    Reconnect,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dioxus_html::HasFormData;
+
+    fn event(name: &str, element: usize, value: &str) -> ClientMessage {
+        let message = serde_json::json!({
+            "method": "user_event",
+            "params": {
+                "name": name,
+                "element": element,
+                "bubbles": true,
+                "data": { "value": value },
+            },
+        });
+        ClientMessage::parse(message.to_string().as_bytes(), &mut Protocol::default())
+    }
+
+    fn summary(message: &ClientMessage) -> String {
+        match message {
+            ClientMessage::Ipc(IpcMessage::Event(evt)) => match &evt.data {
+                EventData::Form(data) => format!("{}@{}={}", evt.name, evt.element.0, data.value()),
+                EventData::Scroll(_) => format!("{}@{}", evt.name, evt.element.0),
+                _ => unreachable!(),
+            },
+            ClientMessage::Ping => "ping".to_string(),
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn only_the_latest_of_adjacent_events_is_handled() {
+        let messages = vec![
+            event("scroll", 1, ""),
+            event("scroll", 1, ""),
+            event("scroll", 2, ""),
+            event("change", 1, "ab"),
+            event("change", 1, "ab"),
+            event("scroll", 1, ""),
+            ClientMessage::parse(b"__ping__", &mut Protocol::default()),
+            event("scroll", 1, ""),
+        ];
+        let coalesced: Vec<_> = coalesce_events(messages).iter().map(summary).collect();
+        assert_eq!(
+            coalesced,
+            [
+                "scroll@1",
+                "scroll@2",
+                "change@1=ab",
+                "change@1=ab",
+                "scroll@1",
+                "ping",
+                "scroll@1",
+            ]
+        );
+    }
+
+    #[test]
+    fn every_input_event_is_handled() {
+        let messages = vec![
+            event("input", 1, "a"),
+            event("input", 1, "ab"),
+            event("input", 1, "abc"),
+        ];
+        let coalesced: Vec<_> = coalesce_events(messages).iter().map(summary).collect();
+        assert_eq!(coalesced, ["input@1=a", "input@1=ab", "input@1=abc"]);
+    }
 }
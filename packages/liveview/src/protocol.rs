@@ -0,0 +1,442 @@
+//! The encodings of the messages between the server and the client.
+//!
+//! The client offers the encodings it supports in its first message and the server replies with the encodings it
+//! picked. Events can be sent in a compact binary encoding instead of JSON and the frames the server sends can be
+//! compressed.
+//!
+//! Every frame the server sends starts with a byte that tells the client how to read it:
+//! - `0`: a text frame with a JSON message
+//! - `1`: a binary frame with edits
+//! - `2`: a frame compressed with zlib that contains one of the other frames
+//!
+//! A binary event starts with a `1` followed by the name of the event as a symbol, the id of the element as a varint,
+//! a byte for whether the event bubbles and the data of the event as a value. Values start with a tag:
+//! - `0`: null, `1`: false, `2`: true
+//! - `3`: an integer as a zigzag encoded varint
+//! - `4`: a little endian 64 bit float
+//! - `5`: a string as a varint length followed by the UTF-8 bytes
+//! - `6`: an array as a varint length followed by the values
+//! - `7`: an object as a varint length followed by pairs of a symbol key and a value
+//!
+//! Symbols are strings that are sent once per connection. A symbol is a varint that is either `0`, followed by a
+//! new string that gets the next index, or the index of a string that was sent before plus one.
+
+use std::io::Write;
+
+use dioxus_html::HtmlEvent;
+use flate2::{write::ZlibEncoder, Compression as Level};
+use serde_json::{Map, Number, Value};
+
+/// The encoding the client can use to send events in the compact binary encoding
+pub(crate) const BINARY_EVENTS: &str = "binary-events";
+/// The encoding the server can use to compress frames with zlib
+pub(crate) const DEFLATE: &str = "deflate";
+
+/// The tag of a frame that is compressed
+const COMPRESSED_FRAME: u8 = 2;
+/// The tag of an event in the binary encoding
+const BINARY_EVENT: u8 = 1;
+
+/// The most symbols a client can send over one connection. The client sends events as JSON once it used them all.
+const MAX_SYMBOLS: usize = 4096;
+/// How deep values in binary events can be nested
+const MAX_DEPTH: usize = 32;
+
+/// The options for the encodings of the messages between the server and the client.
+///
+/// Both encodings are only used if the client supports them. By default events are sent in the binary encoding
+/// and frames of at least 1 KiB are compressed with deflate.
+///
+/// ```rust, no_run
+/// use dioxus_liveview::{Compression, LiveViewPool, ProtocolConfig};
+///
+/// let pool = LiveViewPool::new().with_protocol_config(
+///     ProtocolConfig::default()
+///         .compression(Some(Compression::Deflate))
+///         .compression_threshold(4096),
+/// );
+/// ```
+#[derive(Clone, Debug)]
+pub struct ProtocolConfig {
+    binary_events: bool,
+    compression: Option<Compression>,
+    compression_threshold: usize,
+}
+
+impl Default for ProtocolConfig {
+    fn default() -> Self {
+        Self {
+            binary_events: true,
+            compression: Some(Compression::Deflate),
+            compression_threshold: 1024,
+        }
+    }
+}
+
+impl ProtocolConfig {
+    /// A config that always uses JSON events and uncompressed frames
+    pub fn uncompressed() -> Self {
+        Self::default().binary_events(false).compression(None)
+    }
+
+    /// Set if the client may send events in the binary encoding instead of JSON.
+    pub fn binary_events(mut self, binary_events: bool) -> Self {
+        self.binary_events = binary_events;
+        self
+    }
+
+    /// Set how the frames the server sends are compressed or `None` to never compress them.
+    pub fn compression(mut self, compression: Option<Compression>) -> Self {
+        self.compression = compression;
+        self
+    }
+
+    /// Set how many bytes a frame needs before it is compressed. Small frames rarely get smaller.
+    pub fn compression_threshold(mut self, compression_threshold: usize) -> Self {
+        self.compression_threshold = compression_threshold;
+        self
+    }
+}
+
+/// How the frames the server sends are compressed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Compression {
+    /// Compress frames with zlib. Browsers decompress the frames with `DecompressionStream`.
+    Deflate,
+}
+
+/// The encodings the server and a client agreed on for one connection
+#[derive(Default)]
+pub(crate) struct Protocol {
+    binary_events: bool,
+    /// The threshold of frames that are compressed if compression is enabled
+    compression: Option<usize>,
+    /// The symbols the client sent over this connection
+    symbols: Vec<String>,
+}
+
+impl Protocol {
+    /// Pick the encodings both the server and the client support
+    pub(crate) fn negotiate(config: &ProtocolConfig, offered: &[String]) -> Self {
+        let offers = |encoding: &str| offered.iter().any(|offer| offer == encoding);
+        Self {
+            binary_events: config.binary_events && offers(BINARY_EVENTS),
+            compression: match config.compression {
+                Some(Compression::Deflate) if offers(DEFLATE) => Some(config.compression_threshold),
+                _ => None,
+            },
+            symbols: Vec::new(),
+        }
+    }
+
+    /// The encodings that are used for this connection
+    pub(crate) fn encodings(&self) -> Vec<&'static str> {
+        let mut encodings = Vec::new();
+        if self.binary_events {
+            encodings.push(BINARY_EVENTS);
+        }
+        if self.compression.is_some() {
+            encodings.push(DEFLATE);
+        }
+        encodings
+    }
+
+    /// Compress a frame if it is large enough
+    pub(crate) fn encode_frame(&self, frame: Vec<u8>) -> Vec<u8> {
+        match self.compression {
+            Some(threshold) if frame.len() >= threshold => {
+                let mut encoder = ZlibEncoder::new(vec![COMPRESSED_FRAME], Level::fast());
+                encoder
+                    .write_all(&frame)
+                    .and_then(|_| encoder.finish())
+                    .unwrap_or(frame)
+            }
+            _ => frame,
+        }
+    }
+
+    /// Decode an event in the binary encoding. Returns `None` if the message is not a binary event or it is invalid.
+    pub(crate) fn decode_event(&mut self, message: &[u8]) -> Option<HtmlEvent> {
+        let (&BINARY_EVENT, message) = message.split_first()? else {
+            return None;
+        };
+        let mut reader = Reader {
+            bytes: message,
+            symbols: &mut self.symbols,
+        };
+        let name = reader.symbol()?;
+        let element = reader.varint()?;
+        let bubbles = reader.byte()? != 0;
+        let data = reader.value(0)?;
+        serde_json::from_value(serde_json::json!({
+            "name": name,
+            "element": element,
+            "bubbles": bubbles,
+            "data": data,
+        }))
+        .ok()
+    }
+}
+
+/// Reads the values of a binary event
+struct Reader<'a> {
+    bytes: &'a [u8],
+    symbols: &'a mut Vec<String>,
+}
+
+impl Reader<'_> {
+    fn byte(&mut self) -> Option<u8> {
+        let (&byte, rest) = self.bytes.split_first()?;
+        self.bytes = rest;
+        Some(byte)
+    }
+
+    fn take(&mut self, len: u64) -> Option<&[u8]> {
+        let len = usize::try_from(len).ok()?;
+        if len > self.bytes.len() {
+            return None;
+        }
+        let (taken, rest) = self.bytes.split_at(len);
+        self.bytes = rest;
+        Some(taken)
+    }
+
+    fn varint(&mut self) -> Option<u64> {
+        let mut value = 0u64;
+        for shift in (0..64).step_by(7) {
+            let byte = self.byte()?;
+            value |= u64::from(byte & 0x7f) << shift;
+            if byte & 0x80 == 0 {
+                return Some(value);
+            }
+        }
+        None
+    }
+
+    /// Read a length that can't be longer than the rest of the message
+    fn len(&mut self) -> Option<usize> {
+        let len = usize::try_from(self.varint()?).ok()?;
+        (len <= self.bytes.len()).then_some(len)
+    }
+
+    fn string(&mut self) -> Option<String> {
+        let len = self.varint()?;
+        String::from_utf8(self.take(len)?.to_vec()).ok()
+    }
+
+    fn symbol(&mut self) -> Option<String> {
+        match self.varint()? {
+            0 if self.symbols.len() < MAX_SYMBOLS => {
+                let symbol = self.string()?;
+                self.symbols.push(symbol.clone());
+                Some(symbol)
+            }
+            0 => None,
+            index => self.symbols.get(usize::try_from(index - 1).ok()?).cloned(),
+        }
+    }
+
+    fn value(&mut self, depth: usize) -> Option<Value> {
+        if depth > MAX_DEPTH {
+            return None;
+        }
+        let value = match self.byte()? {
+            0 => Value::Null,
+            1 => Value::Bool(false),
+            2 => Value::Bool(true),
+            3 => {
+                let zigzag = self.varint()?;
+                Value::from((zigzag >> 1) as i64 ^ -((zigzag & 1) as i64))
+            }
+            4 => {
+                let bytes = self.take(8)?.try_into().ok()?;
+                Number::from_f64(f64::from_le_bytes(bytes)).map_or(Value::Null, Value::Number)
+            }
+            5 => Value::String(self.string()?),
+            6 => {
+                let len = self.len()?;
+                let values = (0..len).map(|_| self.value(depth + 1));
+                Value::Array(values.collect::<Option<_>>()?)
+            }
+            7 => {
+                let len = self.len()?;
+                let mut object = Map::new();
+                for _ in 0..len {
+                    let key = self.symbol()?;
+                    object.insert(key, self.value(depth + 1)?);
+                }
+                Value::Object(object)
+            }
+            _ => return None,
+        };
+        Some(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dioxus_html::{EventData, HasFormData};
+    use std::io::Read;
+
+    /// Encodes events the same way the client does
+    #[derive(Default)]
+    struct Writer {
+        bytes: Vec<u8>,
+        symbols: Vec<String>,
+    }
+
+    impl Writer {
+        fn varint(&mut self, mut value: u64) {
+            while value >= 0x80 {
+                self.bytes.push(value as u8 | 0x80);
+                value >>= 7;
+            }
+            self.bytes.push(value as u8);
+        }
+
+        fn string(&mut self, string: &str) {
+            self.varint(string.len() as u64);
+            self.bytes.extend(string.as_bytes());
+        }
+
+        fn symbol(&mut self, symbol: &str) {
+            match self.symbols.iter().position(|known| known == symbol) {
+                Some(index) => self.varint(index as u64 + 1),
+                None => {
+                    self.symbols.push(symbol.to_string());
+                    self.varint(0);
+                    self.string(symbol);
+                }
+            }
+        }
+
+        fn value(&mut self, value: &Value) {
+            match value {
+                Value::Null => self.bytes.push(0),
+                Value::Bool(value) => self.bytes.push(1 + *value as u8),
+                Value::Number(number) => match number.as_i64() {
+                    Some(int) => {
+                        self.bytes.push(3);
+                        self.varint(((int << 1) ^ (int >> 63)) as u64);
+                    }
+                    None => {
+                        self.bytes.push(4);
+                        self.bytes.extend(number.as_f64().unwrap().to_le_bytes());
+                    }
+                },
+                Value::String(string) => {
+                    self.bytes.push(5);
+                    self.string(string);
+                }
+                Value::Array(values) => {
+                    self.bytes.push(6);
+                    self.varint(values.len() as u64);
+                    values.iter().for_each(|value| self.value(value));
+                }
+                Value::Object(object) => {
+                    self.bytes.push(7);
+                    self.varint(object.len() as u64);
+                    for (key, value) in object {
+                        self.symbol(key);
+                        self.value(value);
+                    }
+                }
+            }
+        }
+
+        fn event(&mut self, name: &str, element: u64, data: &Value) -> Vec<u8> {
+            self.bytes = vec![BINARY_EVENT];
+            self.symbol(name);
+            self.varint(element);
+            self.bytes.push(1);
+            self.value(data);
+            std::mem::take(&mut self.bytes)
+        }
+    }
+
+    fn mouse_data(x: i64) -> Value {
+        serde_json::json!({
+            "alt_key": false, "button": 0, "buttons": 0, "client_x": x, "client_y": 20,
+            "ctrl_key": false, "meta_key": false, "offset_x": x, "offset_y": 20,
+            "page_x": x, "page_y": 20, "screen_x": x, "screen_y": 20, "shift_key": false,
+        })
+    }
+
+    #[test]
+    fn binary_events_decode_like_json_events() {
+        let mut client = Writer::default();
+        let mut server = Protocol::default();
+
+        let input = client.event(
+            "input",
+            3,
+            &serde_json::json!({ "value": "héllo", "valid": true }),
+        );
+        let event = server.decode_event(&input).unwrap();
+        assert_eq!((event.name.as_str(), event.element.0), ("input", 3));
+        let EventData::Form(data) = &event.data else {
+            panic!("expected form data");
+        };
+        assert_eq!(data.value(), "héllo");
+
+        for x in [-5, 10, 70_000] {
+            let mousemove = client.event("mousemove", 4, &mouse_data(x));
+            let event = server.decode_event(&mousemove).unwrap();
+            assert_eq!(event.name, "mousemove");
+            assert!(matches!(event.data, EventData::Mouse(_)));
+        }
+
+        // Once the symbols are known, an event is a fraction of its JSON size
+        let json = serde_json::json!({
+            "method": "user_event",
+            "params": { "name": "mousemove", "element": 4, "bubbles": true, "data": mouse_data(10) },
+        });
+        let binary = client.event("mousemove", 4, &mouse_data(10));
+        assert!(binary.len() * 5 < json.to_string().len());
+    }
+
+    #[test]
+    fn invalid_binary_events_are_rejected() {
+        let mut server = Protocol::default();
+        let mut client = Writer::default();
+        let event = client.event("input", 3, &serde_json::json!({ "value": "a" }));
+        assert!(server.decode_event(&event[..event.len() - 1]).is_none());
+        // The client thinks the server knows the symbols, but the server doesn't
+        let mut server = Protocol::default();
+        let event = client.event("input", 3, &serde_json::json!({ "value": "a" }));
+        assert!(server.decode_event(&event).is_none());
+        assert!(server.decode_event(br#"{"method":"user_event"}"#).is_none());
+        assert!(server
+            .decode_event(&[BINARY_EVENT, 0, 200, 200, 200, 200])
+            .is_none());
+    }
+
+    #[test]
+    fn large_frames_are_compressed() {
+        let config = ProtocolConfig::default();
+        let protocol = Protocol::negotiate(&config, &[DEFLATE.to_string()]);
+        assert_eq!(protocol.encodings(), [DEFLATE]);
+
+        let small = vec![1, 2, 3];
+        assert_eq!(protocol.encode_frame(small.clone()), small);
+
+        let large: Vec<u8> = std::iter::once(1)
+            .chain((0..10_000).map(|i| (i % 7) as u8))
+            .collect();
+        let compressed = protocol.encode_frame(large.clone());
+        assert_eq!(compressed[0], COMPRESSED_FRAME);
+        assert!(compressed.len() < large.len() / 10);
+        let mut decompressed = Vec::new();
+        flate2::read::ZlibDecoder::new(&compressed[1..])
+            .read_to_end(&mut decompressed)
+            .unwrap();
+        assert_eq!(decompressed, large);
+
+        // Nothing is compressed if the client doesn't support it
+        let protocol = Protocol::negotiate(&config, &[]);
+        assert!(protocol.encodings().is_empty());
+        assert_eq!(protocol.encode_frame(large.clone()), large);
+    }
+}
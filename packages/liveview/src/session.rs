@@ -0,0 +1,399 @@
+//! Sessions let a client reconnect to its [`VirtualDom`](dioxus_core::VirtualDom) after the websocket drops.
+//!
+//! Every edit frame sent to the client is recorded in a [`Journal`]. When the websocket closes, the session is
+//! parked in the [`LiveViewPool`](crate::LiveViewPool) for a grace period. A client that reconnects with the
+//! session token tells the server how many frames it applied and the server replays the frames it missed.
+
+use std::{
+    collections::{HashMap, VecDeque},
+    fmt::Write,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+use futures_util::{Sink, Stream};
+use tokio::sync::{oneshot, watch, Notify};
+
+use crate::{protocol::Protocol, LiveViewError};
+
+/// The options for resuming sessions after the websocket of a client disconnects.
+///
+/// ```rust, no_run
+/// use dioxus_liveview::{EvictionPolicy, LiveViewPool, SessionConfig};
+/// use std::time::Duration;
+///
+/// let pool = LiveViewPool::new().with_session_config(
+///     SessionConfig::default()
+///         .grace_period(Duration::from_secs(60))
+///         .max_parked_sessions(1000)
+///         .eviction_policy(EvictionPolicy::EvictOldest),
+/// );
+/// ```
+#[derive(Clone, Debug)]
+pub struct SessionConfig {
+    grace_period: Duration,
+    max_parked_sessions: usize,
+    eviction_policy: EvictionPolicy,
+    pub(crate) max_replay_bytes: usize,
+}
+
+impl Default for SessionConfig {
+    /// Sessions are parked for 30 seconds, at most 10,000 sessions are parked at once and at most 4 MiB of
+    /// edits are kept for each session.
+    fn default() -> Self {
+        Self {
+            grace_period: Duration::from_secs(30),
+            max_parked_sessions: 10_000,
+            eviction_policy: EvictionPolicy::EvictOldest,
+            max_replay_bytes: 4 * 1024 * 1024,
+        }
+    }
+}
+
+impl SessionConfig {
+    /// A config that never parks sessions. The virtual dom is dropped as soon as the websocket closes.
+    pub fn disabled() -> Self {
+        Self::default().grace_period(Duration::ZERO)
+    }
+
+    /// Set how long a session is parked after the websocket closes before it is dropped.
+    pub fn grace_period(mut self, grace_period: Duration) -> Self {
+        self.grace_period = grace_period;
+        self
+    }
+
+    /// Set how many sessions can be parked at the same time.
+    pub fn max_parked_sessions(mut self, max_parked_sessions: usize) -> Self {
+        self.max_parked_sessions = max_parked_sessions;
+        self
+    }
+
+    /// Set what happens when a session is parked while [`Self::max_parked_sessions`] sessions are already
+    /// parked.
+    pub fn eviction_policy(mut self, eviction_policy: EvictionPolicy) -> Self {
+        self.eviction_policy = eviction_policy;
+        self
+    }
+
+    /// Set how many bytes of edits are kept for each session to replay them after a reconnect.
+    ///
+    /// A client that reloads the page needs every edit since the session started. Once a session sent more
+    /// edits than this, the oldest edits are dropped and only clients that still have the page open can resume
+    /// the session.
+    pub fn max_replay_bytes(mut self, max_replay_bytes: usize) -> Self {
+        self.max_replay_bytes = max_replay_bytes;
+        self
+    }
+}
+
+/// What happens when a session is parked while the maximum number of sessions are already parked.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EvictionPolicy {
+    /// Drop the session that was parked first to make room for the new session.
+    EvictOldest,
+    /// Drop the new session instead of parking it.
+    RejectNew,
+}
+
+/// The websocket of a client as a trait object so it can be handed to a parked session
+pub(crate) trait DynSocket:
+    Sink<Vec<u8>, Error = LiveViewError> + Stream<Item = Result<Vec<u8>, LiveViewError>> + Send
+{
+}
+
+impl<S> DynSocket for S where
+    S: Sink<Vec<u8>, Error = LiveViewError> + Stream<Item = Result<Vec<u8>, LiveViewError>> + Send
+{
+}
+
+pub(crate) type BoxedSocket = Pin<Box<dyn DynSocket>>;
+
+/// A client that connected to a session
+pub(crate) struct Connection {
+    pub(crate) socket: BoxedSocket,
+    /// The number of edit frames the client applied or `None` if this is a new client
+    pub(crate) applied: Option<u64>,
+    /// The encodings the client and the server agreed on
+    pub(crate) protocol: Protocol,
+    /// Receives the result of the connection once the websocket closes
+    pub(crate) done: oneshot::Sender<Result<(), LiveViewError>>,
+}
+
+/// The edit frames a session sent to the client. Frames are numbered from 1 in the order they are sent.
+pub(crate) struct Journal {
+    frames: VecDeque<Vec<u8>>,
+    /// The number of frames sent before the first frame that is still in the journal
+    replay_from: u64,
+    bytes: usize,
+    max_bytes: usize,
+    /// The bytes of every journal in the pool
+    pool_bytes: Arc<AtomicUsize>,
+}
+
+impl Journal {
+    pub(crate) fn new(max_bytes: usize, pool_bytes: Arc<AtomicUsize>) -> Self {
+        Self {
+            frames: VecDeque::new(),
+            replay_from: 0,
+            bytes: 0,
+            max_bytes,
+            pool_bytes,
+        }
+    }
+
+    /// The number of frames that were sent
+    pub(crate) fn sent(&self) -> u64 {
+        self.replay_from + self.frames.len() as u64
+    }
+
+    /// Record a frame that is sent to the client. The oldest frames are dropped if the journal is full.
+    pub(crate) fn record(&mut self, frame: &[u8]) {
+        let before = self.bytes;
+        self.frames.push_back(frame.to_vec());
+        self.bytes += frame.len();
+        while self.bytes > self.max_bytes {
+            let Some(dropped) = self.frames.pop_front() else {
+                break;
+            };
+            self.bytes -= dropped.len();
+            self.replay_from += 1;
+        }
+        self.pool_bytes.fetch_add(self.bytes, Ordering::Relaxed);
+        self.pool_bytes.fetch_sub(before, Ordering::Relaxed);
+    }
+
+    /// The frames a client that applied `applied` frames missed
+    pub(crate) fn frames_after(&self, applied: u64) -> impl Iterator<Item = &Vec<u8>> {
+        let skip = applied.saturating_sub(self.replay_from) as usize;
+        self.frames.iter().skip(skip)
+    }
+}
+
+impl Drop for Journal {
+    fn drop(&mut self) {
+        self.pool_bytes.fetch_sub(self.bytes, Ordering::Relaxed);
+    }
+}
+
+/// A snapshot of the sessions in a [`LiveViewPool`](crate::LiveViewPool).
+///
+/// The memory a [`VirtualDom`](dioxus_core::VirtualDom) uses can't be measured, so only the edits sessions keep
+/// to replay them after a reconnect are counted.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct PoolMetrics {
+    /// The number of sessions that are running, whether their client is connected or not
+    pub active_sessions: usize,
+    /// The number of sessions with a connected client
+    pub connected_sessions: usize,
+    /// The number of sessions that wait for their client to reconnect
+    pub parked_sessions: usize,
+    /// The bytes of edits the sessions keep to replay them after a reconnect
+    pub replay_bytes: usize,
+    /// If the pool is draining and doesn't accept new sessions
+    pub draining: bool,
+}
+
+impl PoolMetrics {
+    /// The average bytes of edits each session keeps to replay them after a reconnect
+    pub fn replay_bytes_per_session(&self) -> usize {
+        self.replay_bytes
+            .checked_div(self.active_sessions)
+            .unwrap_or_default()
+    }
+}
+
+/// A session that waits for its client to reconnect
+struct ParkedSession {
+    resume: oneshot::Sender<Connection>,
+    parked_at: Instant,
+    /// The range of applied frame counts the session can resume from
+    replay_from: u64,
+    sent: u64,
+}
+
+/// The sessions of a [`LiveViewPool`](crate::LiveViewPool) that are waiting for their client to reconnect
+pub(crate) struct SessionRegistry {
+    pub(crate) config: SessionConfig,
+    parked: Mutex<HashMap<String, ParkedSession>>,
+    active: AtomicUsize,
+    connected: AtomicUsize,
+    pub(crate) replay_bytes: Arc<AtomicUsize>,
+    /// Notified every time a session ends
+    ended: Notify,
+    draining: watch::Sender<bool>,
+}
+
+impl SessionRegistry {
+    pub(crate) fn new(config: SessionConfig) -> Self {
+        Self {
+            config,
+            parked: Default::default(),
+            active: AtomicUsize::new(0),
+            connected: AtomicUsize::new(0),
+            replay_bytes: Default::default(),
+            ended: Notify::new(),
+            draining: watch::Sender::new(false),
+        }
+    }
+
+    pub(crate) fn metrics(&self) -> PoolMetrics {
+        PoolMetrics {
+            active_sessions: self.active.load(Ordering::Relaxed),
+            connected_sessions: self.connected.load(Ordering::Relaxed),
+            parked_sessions: self.parked.lock().unwrap().len(),
+            replay_bytes: self.replay_bytes.load(Ordering::Relaxed),
+            draining: self.is_draining(),
+        }
+    }
+
+    /// Count a session as active until the guard is dropped
+    pub(crate) fn track_session(&self) -> Tracked<'_> {
+        Tracked::new(&self.active, Some(&self.ended))
+    }
+
+    /// Count a session as connected until the guard is dropped
+    pub(crate) fn track_connection(&self) -> Tracked<'_> {
+        Tracked::new(&self.connected, None)
+    }
+
+    pub(crate) fn is_draining(&self) -> bool {
+        *self.draining.borrow()
+    }
+
+    /// Subscribe to the drain of the pool
+    pub(crate) fn draining(&self) -> watch::Receiver<bool> {
+        self.draining.subscribe()
+    }
+
+    /// Stop parking sessions and drop the sessions that are parked. Running sessions are notified through
+    /// [`Self::draining`].
+    pub(crate) fn drain(&self) {
+        let mut parked = self.parked.lock().unwrap();
+        self.draining.send_replace(true);
+        parked.clear();
+    }
+
+    /// Wait until every session ended
+    pub(crate) async fn wait_for_sessions(&self) {
+        loop {
+            let ended = self.ended.notified();
+            tokio::pin!(ended);
+            ended.as_mut().enable();
+            if self.active.load(Ordering::Relaxed) == 0 {
+                return;
+            }
+            ended.await;
+        }
+    }
+
+    /// Create a new random session token
+    pub(crate) fn new_token() -> String {
+        rand::random::<[u8; 32]>()
+            .iter()
+            .fold(String::with_capacity(64), |mut token, byte| {
+                _ = write!(token, "{byte:02x}");
+                token
+            })
+    }
+
+    /// Take the parked session of a client that reconnects. Returns `None` if the session doesn't exist or the
+    /// client missed frames that are no longer in the journal. A session that can't be resumed is dropped.
+    pub(crate) fn take(&self, token: &str, applied: u64) -> Option<oneshot::Sender<Connection>> {
+        let parked = self.parked.lock().unwrap().remove(token)?;
+        (parked.replay_from..=parked.sent)
+            .contains(&applied)
+            .then_some(parked.resume)
+    }
+
+    /// Park a session so its client can reconnect. Returns `None` if the session was rejected.
+    pub(crate) fn park(
+        &self,
+        token: &str,
+        journal: &Journal,
+    ) -> Option<oneshot::Receiver<Connection>> {
+        if self.config.grace_period.is_zero() || self.config.max_parked_sessions == 0 {
+            return None;
+        }
+
+        let (resume, resumed) = oneshot::channel();
+        let mut parked = self.parked.lock().unwrap();
+        // The pool drains while the lock is held, so no session can be parked after the drain
+        if self.is_draining() {
+            return None;
+        }
+        if parked.len() >= self.config.max_parked_sessions {
+            match self.config.eviction_policy {
+                EvictionPolicy::RejectNew => return None,
+                EvictionPolicy::EvictOldest => {
+                    let oldest = parked
+                        .iter()
+                        .min_by_key(|(_, session)| session.parked_at)
+                        .map(|(token, _)| token.clone());
+                    if let Some(oldest) = oldest {
+                        tracing::debug!("Evicting the oldest parked liveview session");
+                        parked.remove(&oldest);
+                    }
+                }
+            }
+        }
+        parked.insert(
+            token.to_string(),
+            ParkedSession {
+                resume,
+                parked_at: Instant::now(),
+                replay_from: journal.replay_from,
+                sent: journal.sent(),
+            },
+        );
+        Some(resumed)
+    }
+
+    /// Wait for the client of a parked session to reconnect. Returns `None` if the session was evicted or the
+    /// grace period passed.
+    pub(crate) async fn wait_for_client(
+        &self,
+        token: &str,
+        mut resumed: oneshot::Receiver<Connection>,
+    ) -> Option<Connection> {
+        tokio::select! {
+            // The sender is dropped if the session is evicted
+            connection = &mut resumed => connection.ok(),
+            _ = tokio::time::sleep(self.config.grace_period) => {
+                // If the session was taken right before the grace period ended, the client is already on its way
+                let expired = self.parked.lock().unwrap().remove(token).is_some();
+                match expired {
+                    true => None,
+                    false => resumed.await.ok(),
+                }
+            }
+        }
+    }
+}
+
+/// Counts something while it is alive
+pub(crate) struct Tracked<'a> {
+    counter: &'a AtomicUsize,
+    /// Notified when the guard is dropped
+    dropped: Option<&'a Notify>,
+}
+
+impl<'a> Tracked<'a> {
+    fn new(counter: &'a AtomicUsize, dropped: Option<&'a Notify>) -> Self {
+        counter.fetch_add(1, Ordering::Relaxed);
+        Self { counter, dropped }
+    }
+}
+
+impl Drop for Tracked<'_> {
+    fn drop(&mut self) {
+        self.counter.fetch_sub(1, Ordering::Relaxed);
+        if let Some(dropped) = self.dropped {
+            dropped.notify_waiters();
+        }
+    }
+}
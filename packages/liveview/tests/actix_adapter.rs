@@ -0,0 +1,38 @@
+#![cfg(feature = "actix")]
+
+use actix_web::{web, App, HttpRequest, HttpResponse, HttpServer};
+use dioxus_liveview::{actix_socket, LiveViewPool};
+
+mod common;
+
+async fn ws(
+    req: HttpRequest,
+    body: web::Payload,
+    pool: web::Data<LiveViewPool>,
+) -> actix_web::Result<HttpResponse> {
+    let (response, session, stream) = actix_ws::handle(&req, body)?;
+    let pool = pool.get_ref().clone();
+    actix_web::rt::spawn(async move {
+        _ = pool
+            .launch(actix_socket(session, stream), common::app)
+            .await;
+    });
+    Ok(response)
+}
+
+#[actix_web::test]
+async fn actix_socket_renders_the_app() {
+    let pool = web::Data::new(LiveViewPool::new());
+    let server = HttpServer::new(move || {
+        App::new()
+            .app_data(pool.clone())
+            .route("/ws", web::get().to(ws))
+    })
+    .workers(1)
+    .bind(("127.0.0.1", 0))
+    .unwrap();
+    let addr = server.addrs()[0];
+    actix_web::rt::spawn(server.run());
+
+    common::assert_renders(&format!("ws://{addr}/ws")).await;
+}
@@ -0,0 +1,54 @@
+//! A websocket client that connects to a liveview server running in the test process
+
+use dioxus::prelude::*;
+use futures_util::{SinkExt, StreamExt};
+use tokio_tungstenite::tungstenite::Message;
+
+pub fn app() -> Element {
+    rsx! {
+        div { "Hello from liveview" }
+    }
+}
+
+/// Connect to the liveview websocket at `url` and check that the app is rendered
+pub async fn assert_renders(url: &str) {
+    let (mut ws, _) = tokio_tungstenite::connect_async(url).await.unwrap();
+    ws.send(Message::Text(
+        r#"{"method":"initialize","params":{"session":null,"applied":0}}"#.to_string(),
+    ))
+    .await
+    .unwrap();
+
+    // Text frames start with a 0 and the first one tells the client its session
+    let session = next_frame(&mut ws).await;
+    assert_eq!(session[0], 0);
+    let session: serde_json::Value = serde_json::from_slice(&session[1..]).unwrap();
+    assert_eq!(session["type"], "session");
+    assert_eq!(session["data"]["resumed"], false);
+
+    // Binary frames start with a 1 and contain the edits of the initial render
+    let edits = next_frame(&mut ws).await;
+    assert_eq!(edits[0], 1);
+    let text = String::from_utf8_lossy(&edits);
+    assert!(text.contains("Hello from liveview"));
+
+    // The socket answers pings the client uses to keep the connection alive. Queries the app sent to the
+    // client may arrive first
+    ws.send(Message::Text("__ping__".to_string()))
+        .await
+        .unwrap();
+    while next_frame(&mut ws).await != b"\0__pong__" {}
+}
+
+async fn next_frame<S>(ws: &mut tokio_tungstenite::WebSocketStream<S>) -> Vec<u8>
+where
+    S: tokio::io::AsyncRead + tokio::io::AsyncWrite + Unpin,
+{
+    loop {
+        match ws.next().await.unwrap().unwrap() {
+            Message::Binary(frame) => return frame,
+            Message::Text(text) => return text.into_bytes(),
+            _ => {}
+        }
+    }
+}
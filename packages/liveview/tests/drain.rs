@@ -0,0 +1,165 @@
+#![cfg(feature = "tungstenite")]
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use dioxus::prelude::*;
+use dioxus_liveview::{tungstenite_socket, use_handoff, HandoffStore, LiveViewPool};
+use futures_util::{SinkExt, StreamExt};
+use tokio::net::TcpStream;
+use tokio_tungstenite::{tungstenite::Message, MaybeTlsStream, WebSocketStream};
+
+type Client = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+/// Every session counts how many servers it ran on
+fn app() -> Element {
+    let mut servers = use_handoff("servers", || 0);
+    use_hook(move || servers += 1);
+
+    rsx! {
+        div { "Ran on {servers} servers" }
+    }
+}
+
+async fn serve(pool: LiveViewPool) -> String {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        while let Ok((stream, _)) = listener.accept().await {
+            let pool = pool.clone();
+            tokio::spawn(async move {
+                let ws = tokio_tungstenite::accept_async(stream).await.unwrap();
+                _ = pool.launch(tungstenite_socket(ws), app).await;
+            });
+        }
+    });
+    format!("ws://{addr}")
+}
+
+async fn connect(url: &str, session: Option<&str>, applied: u64) -> Client {
+    let (mut ws, _) = tokio_tungstenite::connect_async(url).await.unwrap();
+    let initialize = serde_json::json!({
+        "method": "initialize",
+        "params": { "session": session, "applied": applied },
+    });
+    ws.send(Message::Text(initialize.to_string()))
+        .await
+        .unwrap();
+    ws
+}
+
+async fn next_frame(ws: &mut Client) -> Vec<u8> {
+    loop {
+        match ws.next().await.unwrap().unwrap() {
+            Message::Binary(frame) => return frame,
+            Message::Text(text) => return text.into_bytes(),
+            _ => {}
+        }
+    }
+}
+
+/// Read frames until a text frame with an update of the type `kind` arrives
+async fn next_update(ws: &mut Client, kind: &str) -> serde_json::Value {
+    loop {
+        let frame = next_frame(ws).await;
+        if frame[0] != 0 {
+            continue;
+        }
+        if let Ok(update) = serde_json::from_slice::<serde_json::Value>(&frame[1..]) {
+            if update["type"] == kind {
+                return update;
+            }
+        }
+    }
+}
+
+/// Read frames until the edits contain `text`
+async fn wait_for_text(ws: &mut Client, text: &str) {
+    loop {
+        let frame = next_frame(ws).await;
+        if frame[0] == 1 && String::from_utf8_lossy(&frame).contains(text) {
+            return;
+        }
+    }
+}
+
+#[tokio::test]
+async fn drained_sessions_move_to_another_server() {
+    let states = Arc::new(Mutex::new(HashMap::new()));
+    let store = HandoffStore::new(
+        {
+            let states = states.clone();
+            move |token, state| {
+                states.lock().unwrap().insert(token, state);
+                async {}
+            }
+        },
+        {
+            let states = states.clone();
+            move |token| {
+                let state = states.lock().unwrap().remove(&token);
+                async move { state }
+            }
+        },
+    );
+    let draining = LiveViewPool::new().with_handoff(store.clone());
+    let next = LiveViewPool::new().with_handoff(store);
+    let draining_url = serve(draining.clone()).await;
+    let next_url = serve(next.clone()).await;
+
+    let mut client = connect(&draining_url, None, 0).await;
+    let session = next_update(&mut client, "session").await;
+    let token = session["data"]["token"].as_str().unwrap().to_string();
+    wait_for_text(&mut client, "Ran on 1 servers").await;
+
+    let metrics = draining.metrics();
+    assert_eq!(metrics.active_sessions, 1);
+    assert_eq!(metrics.connected_sessions, 1);
+    assert!(!metrics.draining);
+
+    // The client is told to reconnect after the session handed off its state
+    let drained = tokio::spawn({
+        let draining = draining.clone();
+        async move { draining.drain().await }
+    });
+    next_update(&mut client, "reconnect").await;
+    tokio::time::timeout(Duration::from_secs(5), drained)
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(states.lock().unwrap().contains_key(&token));
+
+    let metrics = draining.metrics();
+    assert_eq!(metrics.active_sessions, 0);
+    assert_eq!(metrics.connected_sessions, 0);
+    assert!(metrics.draining);
+
+    // The server the client reconnects to restores the session from the handoff store
+    let mut client = connect(&next_url, Some(&token), 1).await;
+    let session = next_update(&mut client, "session").await;
+    assert_eq!(session["data"]["resumed"], false);
+    wait_for_text(&mut client, "Ran on 2 servers").await;
+    assert!(states.lock().unwrap().is_empty());
+
+    // The restored session is new to the client, so the page reloads and resumes it with the token it was sent
+    let token = session["data"]["token"].as_str().unwrap().to_string();
+    client.close(None).await.unwrap();
+    tokio::time::timeout(Duration::from_secs(5), async {
+        while next.metrics().parked_sessions == 0 {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+    })
+    .await
+    .unwrap();
+    let mut client = connect(&next_url, Some(&token), 0).await;
+    let session = next_update(&mut client, "session").await;
+    assert_eq!(session["data"]["resumed"], true);
+    wait_for_text(&mut client, "Ran on 2 servers").await;
+
+    // Clients that connect to the draining server are sent away right away
+    let mut client = connect(&draining_url, None, 0).await;
+    next_update(&mut client, "reconnect").await;
+}
@@ -0,0 +1,35 @@
+#![cfg(feature = "salvo")]
+
+use dioxus_liveview::{salvo_socket, LiveViewPool};
+use salvo::{conn::tcp::TcpAcceptor, prelude::*, websocket::WebSocketUpgrade};
+
+mod common;
+
+struct LiveView {
+    pool: LiveViewPool,
+}
+
+#[handler]
+impl LiveView {
+    async fn handle(&self, req: &mut Request, res: &mut Response) -> Result<(), StatusError> {
+        let pool = self.pool.clone();
+        WebSocketUpgrade::new()
+            .upgrade(req, res, |socket| async move {
+                _ = pool.launch(salvo_socket(socket), common::app).await;
+            })
+            .await
+    }
+}
+
+#[tokio::test]
+async fn salvo_socket_renders_the_app() {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let acceptor = TcpAcceptor::try_from(listener).unwrap();
+    let router = Router::with_path("ws").goal(LiveView {
+        pool: LiveViewPool::new(),
+    });
+    tokio::spawn(Server::new(acceptor).serve(router));
+
+    common::assert_renders(&format!("ws://{addr}/ws")).await;
+}
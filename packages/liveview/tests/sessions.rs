@@ -0,0 +1,233 @@
+#![cfg(feature = "tungstenite")]
+
+use std::time::Duration;
+
+use dioxus::prelude::*;
+use dioxus_liveview::{
+    tungstenite_socket, EvictionPolicy, LiveViewPool, PoolMetrics, SessionConfig,
+};
+use futures_util::{SinkExt, StreamExt};
+use tokio::net::TcpStream;
+use tokio_tungstenite::{tungstenite::Message, MaybeTlsStream, WebSocketStream};
+
+type Client = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+/// Renders a new frame for every step after the initial render
+fn app() -> Element {
+    let mut count = use_signal(|| 0);
+    use_future(move || async move {
+        for _ in 0..3 {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            count += 1;
+        }
+    });
+
+    rsx! {
+        div { "Count {count}" }
+    }
+}
+
+async fn serve(pool: LiveViewPool) -> String {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        while let Ok((stream, _)) = listener.accept().await {
+            let pool = pool.clone();
+            tokio::spawn(async move {
+                let ws = tokio_tungstenite::accept_async(stream).await.unwrap();
+                _ = pool.launch(tungstenite_socket(ws), app).await;
+            });
+        }
+    });
+    format!("ws://{addr}")
+}
+
+async fn connect(url: &str, session: Option<&str>, applied: u64) -> Client {
+    let (mut ws, _) = tokio_tungstenite::connect_async(url).await.unwrap();
+    let initialize = serde_json::json!({
+        "method": "initialize",
+        "params": { "session": session, "applied": applied },
+    });
+    ws.send(Message::Text(initialize.to_string()))
+        .await
+        .unwrap();
+    ws
+}
+
+async fn next_frame(ws: &mut Client) -> Vec<u8> {
+    loop {
+        match ws.next().await.unwrap().unwrap() {
+            Message::Binary(frame) => return frame,
+            Message::Text(text) => return text.into_bytes(),
+            _ => {}
+        }
+    }
+}
+
+/// Read the session update the server sends after the client connects and return the token and if the
+/// session was resumed
+async fn session(ws: &mut Client) -> (String, bool) {
+    loop {
+        let frame = next_frame(ws).await;
+        if frame[0] != 0 {
+            continue;
+        }
+        if let Ok(update) = serde_json::from_slice::<serde_json::Value>(&frame[1..]) {
+            if update["type"] == "session" {
+                let token = update["data"]["token"].as_str().unwrap().to_string();
+                let resumed = update["data"]["resumed"].as_bool().unwrap();
+                return (token, resumed);
+            }
+        }
+    }
+}
+
+/// Read edit frames until the edits contain `text` and return every edit frame that was read
+async fn edits_until(ws: &mut Client, text: &str) -> Vec<Vec<u8>> {
+    let mut edits = Vec::new();
+    loop {
+        let frame = next_frame(ws).await;
+        if frame[0] != 1 {
+            continue;
+        }
+        let done = String::from_utf8_lossy(&frame).contains(text);
+        edits.push(frame);
+        if done {
+            return edits;
+        }
+    }
+}
+
+/// Close the websocket of a client and wait until the pool reaches the expected state
+async fn disconnect(mut ws: Client, pool: &LiveViewPool, until: impl Fn(PoolMetrics) -> bool) {
+    ws.close(None).await.unwrap();
+    wait_for(pool, until).await;
+}
+
+async fn wait_for(pool: &LiveViewPool, until: impl Fn(PoolMetrics) -> bool) {
+    tokio::time::timeout(Duration::from_secs(5), async {
+        while !until(pool.metrics()) {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+    })
+    .await
+    .unwrap();
+}
+
+/// Start a session and read every frame it renders. Returns the client, the token and the edit frames.
+async fn start_session(url: &str) -> (Client, String, Vec<Vec<u8>>) {
+    let mut client = connect(url, None, 0).await;
+    let (token, resumed) = session(&mut client).await;
+    assert!(!resumed);
+    let edits = edits_until(&mut client, "Count 3").await;
+    (client, token, edits)
+}
+
+#[tokio::test]
+async fn reconnecting_clients_get_the_frames_they_missed() {
+    let pool = LiveViewPool::new();
+    let url = serve(pool.clone()).await;
+
+    let (client, token, edits) = start_session(&url).await;
+    assert!(edits.len() > 1);
+    disconnect(client, &pool, |metrics| metrics.parked_sessions == 1).await;
+
+    // The client only applied the initial render, so every later frame is replayed
+    let mut client = connect(&url, Some(&token), 1).await;
+    let (resumed_token, resumed) = session(&mut client).await;
+    assert!(resumed);
+    for edit in &edits[1..] {
+        assert_eq!(&next_frame(&mut client).await, edit);
+    }
+    assert_eq!(pool.metrics().parked_sessions, 0);
+
+    // Resuming a session rotates its token, so a duplicated tab with the old token can't take the session over
+    assert_ne!(resumed_token, token);
+    disconnect(client, &pool, |metrics| metrics.parked_sessions == 1).await;
+    let mut duplicate = connect(&url, Some(&token), edits.len() as u64).await;
+    assert!(!session(&mut duplicate).await.1);
+    let mut client = connect(&url, Some(&resumed_token), edits.len() as u64).await;
+    assert!(session(&mut client).await.1);
+}
+
+#[tokio::test]
+async fn clients_that_missed_frames_outside_of_the_journal_start_a_new_session() {
+    // Every frame is dropped from the journal right after it is sent
+    let pool =
+        LiveViewPool::new().with_session_config(SessionConfig::default().max_replay_bytes(1));
+    let url = serve(pool.clone()).await;
+
+    let (client, token, edits) = start_session(&url).await;
+    disconnect(client, &pool, |metrics| metrics.parked_sessions == 1).await;
+
+    // The client claims to have applied more frames than the session sent
+    let mut client = connect(&url, Some(&token), edits.len() as u64 + 1).await;
+    assert!(!session(&mut client).await.1);
+    // A session that can't be resumed is dropped
+    wait_for(&pool, |metrics| metrics.active_sessions == 1).await;
+    disconnect(client, &pool, |metrics| metrics.parked_sessions == 1).await;
+
+    let (client, token, _) = start_session(&url).await;
+    disconnect(client, &pool, |metrics| metrics.parked_sessions == 2).await;
+
+    // The frames the client missed are no longer in the journal
+    let mut client = connect(&url, Some(&token), 0).await;
+    assert!(!session(&mut client).await.1);
+    edits_until(&mut client, "Count 3").await;
+    assert_eq!(pool.metrics().parked_sessions, 1);
+}
+
+#[tokio::test]
+async fn parked_sessions_are_dropped_after_the_grace_period() {
+    let pool = LiveViewPool::new()
+        .with_session_config(SessionConfig::default().grace_period(Duration::from_millis(100)));
+    let url = serve(pool.clone()).await;
+
+    let (client, token, edits) = start_session(&url).await;
+    disconnect(client, &pool, |metrics| metrics.parked_sessions == 1).await;
+    wait_for(&pool, |metrics| metrics.active_sessions == 0).await;
+    assert_eq!(pool.metrics().parked_sessions, 0);
+
+    let mut client = connect(&url, Some(&token), edits.len() as u64).await;
+    assert!(!session(&mut client).await.1);
+}
+
+/// Park two sessions in a pool that only has room for one and return if each of them could be resumed
+async fn resume_two_sessions(eviction_policy: EvictionPolicy) -> (bool, bool) {
+    let pool = LiveViewPool::new().with_session_config(
+        SessionConfig::default()
+            .max_parked_sessions(1)
+            .eviction_policy(eviction_policy),
+    );
+    let url = serve(pool.clone()).await;
+
+    let (first, first_token, first_edits) = start_session(&url).await;
+    let (second, second_token, second_edits) = start_session(&url).await;
+    disconnect(first, &pool, |metrics| metrics.parked_sessions == 1).await;
+    // Either the first or the second session is dropped when the second session is parked
+    disconnect(second, &pool, |metrics| {
+        metrics.active_sessions == 1 && metrics.connected_sessions == 0
+    })
+    .await;
+    assert_eq!(pool.metrics().parked_sessions, 1);
+
+    let mut first = connect(&url, Some(&first_token), first_edits.len() as u64).await;
+    let mut second = connect(&url, Some(&second_token), second_edits.len() as u64).await;
+    (session(&mut first).await.1, session(&mut second).await.1)
+}
+
+#[tokio::test]
+async fn the_oldest_parked_session_is_evicted() {
+    assert_eq!(
+        resume_two_sessions(EvictionPolicy::EvictOldest).await,
+        (false, true)
+    );
+}
+
+#[tokio::test]
+async fn new_sessions_are_rejected_when_the_pool_is_full() {
+    assert_eq!(
+        resume_two_sessions(EvictionPolicy::RejectNew).await,
+        (true, false)
+    );
+}
@@ -0,0 +1,23 @@
+#![cfg(feature = "tungstenite")]
+
+use dioxus_liveview::{tungstenite_socket, LiveViewPool};
+
+mod common;
+
+#[tokio::test]
+async fn tungstenite_socket_renders_the_app() {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let pool = LiveViewPool::new();
+    tokio::spawn(async move {
+        while let Ok((stream, _)) = listener.accept().await {
+            let pool = pool.clone();
+            tokio::spawn(async move {
+                let ws = tokio_tungstenite::accept_async(stream).await.unwrap();
+                _ = pool.launch(tungstenite_socket(ws), common::app).await;
+            });
+        }
+    });
+
+    common::assert_renders(&format!("ws://{addr}")).await;
+}
@@ -0,0 +1,23 @@
+#![cfg(feature = "warp")]
+
+use dioxus_liveview::{warp_socket, LiveViewPool};
+use warp::Filter;
+
+mod common;
+
+#[tokio::test]
+async fn warp_socket_renders_the_app() {
+    let pool = LiveViewPool::new();
+    let routes = warp::path("ws")
+        .and(warp::ws())
+        .map(move |ws: warp::ws::Ws| {
+            let pool = pool.clone();
+            ws.on_upgrade(|socket| async move {
+                _ = pool.launch(warp_socket(socket), common::app).await;
+            })
+        });
+    let (addr, server) = warp::serve(routes).bind_ephemeral(([127, 0, 0, 1], 0));
+    tokio::spawn(server);
+
+    common::assert_renders(&format!("ws://{addr}/ws")).await;
+}
@@ -68,3 +68,47 @@ test("document elements", async ({ page }) => {
   const main = page.locator("#main");
   await expect(main).toHaveCSS("font-family", "Roboto");
 });
+
+test("form actions", async ({ page }) => {
+  await page.goto("http://localhost:3333");
+  // Wait for the page to hydrate
+  const main = page.locator("#main");
+  await page.locator("button.increment-button").click();
+  await expect(main).toContainText("hello axum! 12346");
+
+  // Once hydrated, the form is submitted without leaving the page
+  const form = page.locator("#form-action");
+  const result = page.locator("#form-action-result");
+  await form.locator("input[name='title']").fill("milk");
+  await form.locator("button").click();
+  await expect(result).toContainText("Form added: milk");
+  await expect(main).toContainText("hello axum! 12346");
+  expect(page.url()).toBe("http://localhost:3333/");
+
+  await form.locator("input[name='title']").fill("");
+  await form.locator("button").click();
+  await expect(result).toContainText("Form error:");
+  await expect(result).toContainText("the title is empty");
+});
+
+test.describe("without javascript", () => {
+  test.use({ javaScriptEnabled: false });
+
+  test("form actions post to the server function", async ({ page }) => {
+    await page.goto("http://localhost:3333");
+    const form = page.locator("#form-action");
+    const result = page.locator("#form-action-result");
+
+    // The server redirects back to the page after running the server function
+    await form.locator("input[name='title']").fill("milk");
+    await form.locator("button").click();
+    await page.waitForURL("http://localhost:3333/");
+    await expect(result).not.toContainText("Form error:");
+
+    // Errors are added to the url of the redirect and rendered by the server
+    await form.locator("input[name='title']").fill("");
+    await form.locator("button").click();
+    await page.waitForURL(/__err=/);
+    await expect(result).toContainText("the title is empty");
+  });
+});
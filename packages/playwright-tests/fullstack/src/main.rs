@@ -3,6 +3,7 @@
 // - Server functions
 // - SSR
 // - Hydration
+// - Form actions
 
 #![allow(non_snake_case)]
 use dioxus::{prelude::*, CapturedError};
@@ -43,6 +44,7 @@ fn app() -> Element {
         OnMounted {}
         DefaultServerFnCodec {}
         DocumentElements {}
+        FormAction {}
     }
 }
 
@@ -153,3 +155,37 @@ fn DocumentElements() -> Element {
         document::Style { id: "style-head", "body {{ font-family: 'Roboto'; }}" }
     }
 }
+
+#[server(input = server_fn::codec::PostUrl)]
+async fn add_todo(title: String) -> Result<String, ServerFnError> {
+    assert_server_context_provided().await;
+    if title.is_empty() {
+        return Err(ServerFnError::new("the title is empty"));
+    }
+    Ok(title)
+}
+
+/// This component tests forms that post to a server function with and without javascript
+#[component]
+fn FormAction() -> Element {
+    let add = use_action::<AddTodo>();
+
+    rsx! {
+        form {
+            id: "form-action",
+            method: "post",
+            action: add,
+            input { name: "title" }
+            button { "Add" }
+        }
+        div {
+            id: "form-action-result",
+            if let Some(error) = add.error() {
+                "Form error: {error}"
+            }
+            if let Some(title) = add.value() {
+                "Form added: {title}"
+            }
+        }
+    }
+}
@@ -78,3 +78,14 @@ test("onmounted", async ({ page }) => {
   const mountedDiv = page.locator("div.onmounted-div");
   await expect(mountedDiv).toHaveText("onmounted was called 1 times");
 });
+
+test("limited events", async ({ page }) => {
+  await page.goto("http://127.0.0.1:3030");
+  const div = page.locator("div.limited-events-div");
+
+  // Every input in the debounced element and the input in the throttled element send their last event
+  await page.locator("textarea.debounced-first").fill("one");
+  await page.locator("textarea.debounced-second").fill("two");
+  await page.locator("textarea.throttled").fill("three");
+  await expect(div).toHaveText("one two three");
+});
@@ -21,6 +21,7 @@ fn app() -> Element {
         input { value: "hello input" }
         div { class: "style-div", color: "red", "colored text" }
         OnMounted {}
+        LimitedEvents {}
     }
 }
 
@@ -38,6 +39,24 @@ fn OnMounted() -> Element {
     }
 }
 
+#[component]
+fn LimitedEvents() -> Element {
+    let mut first = use_signal(String::new);
+    let mut second = use_signal(String::new);
+    let mut third = use_signal(String::new);
+    // The limiting elements have no listeners of their own
+    rsx! {
+        div { "data-dioxus-debounce-input": "100",
+            textarea { class: "debounced-first", oninput: move |evt| first.set(evt.value()) }
+            textarea { class: "debounced-second", oninput: move |evt| second.set(evt.value()) }
+        }
+        div { "data-dioxus-throttle-input": "100",
+            textarea { class: "throttled", oninput: move |evt| third.set(evt.value()) }
+        }
+        div { class: "limited-events-div", "{first} {second} {third}" }
+    }
+}
+
 #[tokio::main]
 async fn main() {
     let addr: std::net::SocketAddr = ([127, 0, 0, 1], 3030).into();
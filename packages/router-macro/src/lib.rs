@@ -24,6 +24,7 @@ mod redirect;
 mod route;
 mod route_tree;
 mod segment;
+mod sitemap;
 
 /// Derives the Routable trait for an enum of routes
 ///
@@ -91,11 +92,14 @@ mod segment;
 /// # fn IndexComponent() -> Element { VNode::empty() }
 /// ```
 ///
-/// # `#[route("path", component)]`
+/// # `#[route("path", component, lazy, outlet = "name", background = ..)]`
 ///
-/// The `#[route]` attribute is used to define a route. It takes up to 2 parameters:
+/// The `#[route]` attribute is used to define a route. It takes up to 5 parameters:
 /// - `path`: The path to the enum variant (relative to the parent nest)
 /// - (optional) `component`: The component to render when the route is matched. If not specified, the name of the variant is used
+/// - (optional) `lazy` or `lazy(fallback = Component)`: Split the component into its own wasm chunk that is only loaded when the route is rendered
+/// - (optional) `outlet = "name"`: Render the component in a named outlet of the innermost layout instead of the default outlet
+/// - (optional) `background = "/path"` or `background = field`: The route the default outlet renders behind a route with `outlet = "name"`
 ///
 /// Routes are the most basic attribute. They allow you to define a route and the component to render when the route is matched. The component must take all dynamic parameters of the route and all parent nests.
 /// The next variant will be tied to the component. If you link to that variant, the component will be rendered.
@@ -114,6 +118,63 @@ mod segment;
 /// # fn Index() -> Element { VNode::empty() }
 /// ```
 ///
+/// ## Route aliases and url builders
+///
+/// A variant can have multiple `#[route]` attributes. The variant is parsed from any of the paths,
+/// but it is always displayed with the first one which makes it the canonical url of the route.
+/// Aliases can only contain a path.
+///
+/// The derive also generates a `{Enum}Urls` type next to the enum with a typed url builder function
+/// for every variant, named after the variant in snake case. The function takes the fields of the
+/// variant in order. The builders live on their own type so they never collide with methods you
+/// define on the enum.
+///
+/// ```rust
+/// use dioxus::prelude::*;
+///
+/// #[derive(Clone, Debug, PartialEq, Routable)]
+/// enum Route {
+///     #[route("/blog/:id/:slug")]
+///     // Old links to /posts/.. still work, but are displayed as /blog/..
+///     #[route("/posts/:id/:slug")]
+///     BlogPost { id: usize, slug: String },
+/// }
+/// # #[component]
+/// # fn BlogPost(id: usize, slug: String) -> Element { VNode::empty() }
+///
+/// assert_eq!(RouteUrls::blog_post(1, "hello".to_string()), "/blog/1/hello");
+/// let route: Route = "/posts/1/hello".parse().unwrap();
+/// assert_eq!(route.to_string(), "/blog/1/hello");
+/// ```
+///
+/// ## Lazy routes
+///
+/// Routes marked with `lazy` are wrapped in a [`SuspenseBoundary`](dioxus::prelude::SuspenseBoundary).
+/// When bundle splitting is enabled in the CLI (`dx serve --experimental-wasm-split`), the CLI turns
+/// on the `wasm-split` feature of dioxus and the component of every lazy route is moved into its own
+/// chunk. The chunk is downloaded the first time the route is rendered and the fallback component
+/// (or nothing) is shown until it has loaded. Without bundle splitting, lazy routes are rendered
+/// like any other route.
+///
+/// ```rust
+/// use dioxus::prelude::*;
+///
+/// #[derive(Clone, Debug, PartialEq, Routable)]
+/// enum Route {
+///     #[route("/")]
+///     Home {},
+///     // The admin panel is only downloaded by visitors that open it
+///     #[route("/admin", AdminPanel, lazy(fallback = Loading))]
+///     Admin {},
+/// }
+/// # #[component]
+/// # fn Home() -> Element { VNode::empty() }
+/// # #[component]
+/// # fn AdminPanel() -> Element { VNode::empty() }
+/// # #[component]
+/// # fn Loading() -> Element { VNode::empty() }
+/// ```
+///
 /// # `#[redirect("path", function)]`
 ///
 /// The `#[redirect]` attribute is used to define a redirect. It takes 2 parameters:
@@ -238,10 +299,93 @@ mod segment;
 /// # #[component]
 /// # fn Home() -> Element { VNode::empty() }
 /// ```
+///
+/// # `#[slot("name", component)]` and named outlets
+///
+/// Layouts can contain named outlets (`Outlet::<Route> { name: "sidebar" }`) next to their default outlet.
+/// The `#[slot]` attribute renders an extra component in a named outlet of the innermost layout when the
+/// route is active. The component must take the same dynamic parameters as the route.
+///
+/// A route can also be rendered in a named outlet instead of the default outlet with the `outlet = "name"`
+/// option of the `#[route]` attribute. This lets a layout render a route in a modal over the rest of its
+/// content while the url reflects the route.
+///
+/// The default outlet and the other named outlets keep rendering the route set with the `background`
+/// option, so the list stays visible behind the modal. The background is either a fixed path like
+/// `background = "/"` or the name of a field that contains the url of the background route. With a
+/// query field, the url encodes both routes, like `/message/1?from=%2Farchive`.
+///
+/// ```rust
+/// use dioxus::prelude::*;
+///
+/// #[derive(Clone, Debug, PartialEq, Routable)]
+/// #[rustfmt::skip]
+/// enum Route {
+///     #[layout(Inbox)]
+///         #[route("/")]
+///         #[slot("sidebar", Folders)]
+///         Messages {},
+///         #[route("/archive")]
+///         Archive {},
+///         // The message is shown in a modal above the folder it was opened from
+///         #[route("/message/:id?:from", MessageModal, outlet = "modal", background = from)]
+///         Message { id: usize, from: String },
+/// }
+///
+/// #[component]
+/// fn Inbox() -> Element {
+///     rsx! {
+///         Outlet::<Route> { name: "sidebar" }
+///         Outlet::<Route> {}
+///         Outlet::<Route> { name: "modal" }
+///     }
+/// }
+/// # #[component]
+/// # fn Messages() -> Element { VNode::empty() }
+/// # #[component]
+/// # fn Folders() -> Element { VNode::empty() }
+/// # #[component]
+/// # fn Archive() -> Element { VNode::empty() }
+/// # #[component]
+/// # fn MessageModal(id: usize, from: String) -> Element { VNode::empty() }
+///
+/// let message = Route::Message { id: 1, from: Route::Archive {}.to_string() };
+/// assert_eq!(message.to_string(), "/message/1?from=%2Farchive");
+/// ```
+///
+/// # `#[sitemap(..)]`
+///
+/// The `#[sitemap]` attribute adds information about a route to the sitemap generated by `dioxus_router::sitemap::Sitemap`. It takes any of these options:
+/// - `lastmod = "2024-01-01"`: The date the page was last modified
+/// - `changefreq = "weekly"`: How often the page changes. One of `always`, `hourly`, `daily`, `weekly`, `monthly`, `yearly` or `never`
+/// - `priority = 0.8`: The priority of the page relative to other pages between `0.0` and `1.0`
+/// - `exclude`: Leave the route out of the sitemap
+///
+/// Routes with a `#[child]` attribute and no `#[sitemap]` attribute use the sitemap information of the child route.
+///
+/// ```rust
+/// use dioxus::prelude::*;
+///
+/// #[derive(Clone, Debug, PartialEq, Routable)]
+/// enum Route {
+///     #[route("/")]
+///     #[sitemap(changefreq = "daily", priority = 1.0)]
+///     Index {},
+///     #[route("/login")]
+///     #[sitemap(exclude)]
+///     Login {},
+/// }
+/// # #[component]
+/// # fn Index() -> Element { VNode::empty() }
+/// # #[component]
+/// # fn Login() -> Element { VNode::empty() }
+/// ```
 #[doc(alias = "route")]
 #[proc_macro_derive(
     Routable,
-    attributes(route, nest, end_nest, layout, end_layout, redirect, child)
+    attributes(
+        route, nest, end_nest, layout, end_layout, redirect, child, sitemap, slot
+    )
 )]
 pub fn routable(input: TokenStream) -> TokenStream {
     let routes_enum = parse_macro_input!(input as syn::ItemEnum);
@@ -255,8 +399,11 @@ pub fn routable(input: TokenStream) -> TokenStream {
     let parse_impl = route_enum.parse_impl();
     let display_impl = route_enum.impl_display();
     let routable_impl = route_enum.routable_impl();
+    let url_builders = route_enum.url_builders();
 
     (quote! {
+        #url_builders
+
         const _: () = {
             #error_type
 
@@ -271,6 +418,7 @@ pub fn routable(input: TokenStream) -> TokenStream {
 }
 
 struct RouteEnum {
+    vis: syn::Visibility,
     name: Ident,
     endpoints: Vec<RouteEndpoint>,
     nests: Vec<Nest>,
@@ -400,7 +548,7 @@ impl RouteEnum {
                         Redirect::parse(input, nest_stack.clone(), endpoints.len())
                     };
                     let redirect = attr.parse_args_with(parser)?;
-                    endpoints.push(RouteEndpoint::Redirect(redirect));
+                    endpoints.push(RouteEndpoint::Redirect(Box::new(redirect)));
                 }
             }
 
@@ -446,7 +594,13 @@ impl RouteEnum {
                 children.push(segment);
             }
 
-            endpoints.push(RouteEndpoint::Route(route));
+            let aliases = route.parse_aliases(variant)?;
+            endpoints.push(RouteEndpoint::Route(Box::new(route)));
+            endpoints.extend(
+                aliases
+                    .into_iter()
+                    .map(|alias| RouteEndpoint::Route(Box::new(alias))),
+            );
         }
 
         // pop any remaining site map segments
@@ -468,6 +622,7 @@ impl RouteEnum {
         }
 
         let myself = Self {
+            vis: data.vis.clone(),
             name: name.clone(),
             endpoints,
             nests,
@@ -483,7 +638,10 @@ impl RouteEnum {
 
         for route in &self.endpoints {
             if let RouteEndpoint::Route(route) = route {
-                display_match.push(route.display_match(&self.nests));
+                // Aliases are always displayed as the canonical route
+                if route.alias.is_none() {
+                    display_match.push(route.display_match(&self.nests));
+                }
             }
         }
 
@@ -570,6 +728,7 @@ impl RouteEnum {
             match endpoint {
                 RouteEndpoint::Route(route) => {
                     let route_name = &route.route_name;
+                    let error_variant = route.error_variant();
 
                     let error_name = route.error_ident();
                     let route_str = &route.route;
@@ -582,9 +741,9 @@ impl RouteEnum {
 
                     error_variants.push(quote! {
                         #[doc = #comment]
-                        #route_name(#error_name)
+                        #error_variant(#error_name)
                     });
-                    display_match.push(quote! { Self::#route_name(err) => write!(f, "Route '{}' ('{}') did not match:\n{}", stringify!(#route_name), #route_str, err)? });
+                    display_match.push(quote! { Self::#error_variant(err) => write!(f, "Route '{}' ('{}') did not match:\n{}", stringify!(#route_name), #route_str, err)? });
                     type_defs.push(route.error_type());
                 }
                 RouteEndpoint::Redirect(redirect) => {
@@ -655,6 +814,27 @@ impl RouteEnum {
         }
     }
 
+    fn url_builders(&self) -> TokenStream2 {
+        let name = &self.name;
+        let vis = &self.vis;
+        let urls_name = format_ident!("{}Urls", name);
+        let builders = self.endpoints.iter().filter_map(|endpoint| match endpoint {
+            RouteEndpoint::Route(route) => route.url_builder(&self.nests, name, vis),
+            RouteEndpoint::Redirect(_) => None,
+        });
+        let doc = format!(" Typed url builders for the routes of [`{name}`].");
+
+        quote! {
+            #[doc = #doc]
+            #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+            #vis struct #urls_name;
+
+            impl #urls_name {
+                #(#builders)*
+            }
+        }
+    }
+
     fn routable_impl(&self) -> TokenStream2 {
         let name = &self.name;
         let site_map = &self.site_map;
@@ -664,16 +844,84 @@ impl RouteEnum {
         // Collect all routes matches
         for route in &self.endpoints {
             if let RouteEndpoint::Route(route) = route {
-                matches.push(route.routable_match(&self.layouts, &self.nests, name));
+                if route.alias.is_none() {
+                    matches.push(route.routable_match(&self.layouts, &self.nests, name));
+                }
             }
         }
 
+        let outlet_matches: Vec<_> = self
+            .endpoints
+            .iter()
+            .flat_map(|endpoint| match endpoint {
+                RouteEndpoint::Route(route) => route.outlet_matches(name),
+                RouteEndpoint::Redirect(_) => Vec::new(),
+            })
+            .collect();
+        let outlet_impl = (!outlet_matches.is_empty()).then(|| {
+            quote! {
+                fn render_outlet(&self, level: usize, __outlet: &str) -> dioxus_core::Element {
+                    let myself = self.clone();
+                    match (level, myself) {
+                        #(#outlet_matches)*
+                        _ => VNode::empty()
+                    }
+                }
+            }
+        });
+
+        let background_matches: Vec<_> = self
+            .endpoints
+            .iter()
+            .filter_map(|endpoint| match endpoint {
+                RouteEndpoint::Route(route) => route.background_match(),
+                RouteEndpoint::Redirect(_) => None,
+            })
+            .collect();
+        let background_impl = (!background_matches.is_empty()).then(|| {
+            quote! {
+                fn background(&self, level: usize) -> Option<Self> {
+                    #[allow(unreachable_patterns)]
+                    match self {
+                        #(#background_matches)*
+                        _ => None,
+                    }
+                }
+            }
+        });
+
+        let sitemap_matches: Vec<_> = self
+            .endpoints
+            .iter()
+            .filter_map(|endpoint| match endpoint {
+                RouteEndpoint::Route(route) => route.sitemap_match(),
+                RouteEndpoint::Redirect(_) => None,
+            })
+            .collect();
+        let sitemap_impl = (!sitemap_matches.is_empty()).then(|| {
+            quote! {
+                fn sitemap_metadata(&self) -> dioxus_router::sitemap::SitemapMetadata {
+                    #[allow(unreachable_patterns)]
+                    match self {
+                        #(#sitemap_matches)*
+                        _ => Default::default(),
+                    }
+                }
+            }
+        });
+
         quote! {
             impl dioxus_router::routable::Routable for #name where Self: Clone {
                 const SITE_MAP: &'static [dioxus_router::routable::SiteMapSegment] = &[
                     #(#site_map,)*
                 ];
 
+                #outlet_impl
+
+                #background_impl
+
+                #sitemap_impl
+
                 fn render(&self, level: usize) -> dioxus_core::Element {
                     let myself = self.clone();
                     match (level, myself) {
@@ -686,9 +934,10 @@ impl RouteEnum {
     }
 }
 
+// Both variants are boxed so the endpoints stay small no matter which of the two grows
 enum RouteEndpoint {
-    Route(Route),
-    Redirect(Redirect),
+    Route(Box<Route>),
+    Redirect(Box<Redirect>),
 }
 
 struct SiteMapSegment {
@@ -18,26 +18,173 @@ use crate::query::QuerySegment;
 use crate::segment::create_error_type;
 use crate::segment::parse_route_segments;
 use crate::segment::RouteSegment;
+use crate::sitemap::SitemapAttribute;
 
 struct RouteArgs {
     route: LitStr,
     comp_name: Option<Path>,
+    lazy: Option<LazyArgs>,
+    outlet: Option<LitStr>,
+    background: Option<Background>,
 }
 
 impl Parse for RouteArgs {
     fn parse(input: ParseStream<'_>) -> syn::Result<Self> {
         let route = input.parse::<LitStr>()?;
+        let mut comp_name = None;
+        let mut lazy = None;
+        let mut outlet = None;
+        let mut background = None;
+
+        while input.parse::<syn::Token![,]>().is_ok() {
+            if input.is_empty() {
+                break;
+            }
+
+            // `lazy` is a keyword here unless it is the start of a longer path like `lazy::Component`
+            let is_lazy = input.peek(Ident)
+                && !input.peek2(syn::Token![::])
+                && input.fork().parse::<Ident>()? == "lazy";
+
+            // `outlet = "name"` renders the route in a named outlet
+            let is_outlet = input.peek(Ident)
+                && input.peek2(syn::Token![=])
+                && input.fork().parse::<Ident>()? == "outlet";
+
+            // `background = "/path"` or `background = field` renders a route behind a route in a named outlet
+            let is_background = input.peek(Ident)
+                && input.peek2(syn::Token![=])
+                && input.fork().parse::<Ident>()? == "background";
+
+            if is_background {
+                let ident = input.parse::<Ident>()?;
+                if background.is_some() {
+                    return Err(syn::Error::new_spanned(
+                        ident,
+                        "Duplicate `background` option",
+                    ));
+                }
+                input.parse::<syn::Token![=]>()?;
+                background = Some(input.parse::<Background>()?);
+            } else if is_outlet {
+                let ident = input.parse::<Ident>()?;
+                if outlet.is_some() {
+                    return Err(syn::Error::new_spanned(ident, "Duplicate `outlet` option"));
+                }
+                input.parse::<syn::Token![=]>()?;
+                outlet = Some(input.parse::<LitStr>()?);
+            } else if is_lazy {
+                let ident = input.parse::<Ident>()?;
+                if lazy.is_some() {
+                    return Err(syn::Error::new_spanned(ident, "Duplicate `lazy` option"));
+                }
+                lazy = Some(if input.peek(syn::token::Paren) {
+                    let content;
+                    syn::parenthesized!(content in input);
+                    content.parse::<LazyArgs>()?
+                } else {
+                    LazyArgs::default()
+                });
+            } else if comp_name.is_none()
+                && lazy.is_none()
+                && outlet.is_none()
+                && background.is_none()
+            {
+                comp_name = Some(input.parse()?);
+            } else {
+                return Err(input.error(
+                    "Expected the route to be followed by an optional component name, an optional `lazy` option and optional `outlet = \"name\"` and `background = ..` options",
+                ));
+            }
+        }
 
         Ok(RouteArgs {
             route,
-            comp_name: {
-                let _ = input.parse::<syn::Token![,]>();
-                input.parse().ok()
-            },
+            comp_name,
+            lazy,
+            outlet,
+            background,
         })
     }
 }
 
+/// The route that is rendered in the default outlets behind a route in a named outlet
+#[derive(Debug)]
+pub(crate) enum Background {
+    /// A fixed route like `background = "/photos"`
+    Path(LitStr),
+    /// A field of the route that contains the url of the background route like `background = from`
+    Field(Ident),
+}
+
+impl Background {
+    fn error(&self, message: &str) -> syn::Error {
+        match self {
+            Background::Path(path) => syn::Error::new_spanned(path, message),
+            Background::Field(field) => syn::Error::new_spanned(field, message),
+        }
+    }
+}
+
+impl Parse for Background {
+    fn parse(input: ParseStream<'_>) -> syn::Result<Self> {
+        if input.peek(LitStr) {
+            input.parse().map(Background::Path)
+        } else {
+            input.parse().map(Background::Field)
+        }
+    }
+}
+
+/// The options of a `#[route("/path", lazy(fallback = Component))]` route
+#[derive(Debug, Default)]
+pub(crate) struct LazyArgs {
+    /// The component to render while the chunk containing the route is loading
+    fallback: Option<Path>,
+}
+
+impl Parse for LazyArgs {
+    fn parse(input: ParseStream<'_>) -> syn::Result<Self> {
+        let mut fallback = None;
+
+        while !input.is_empty() {
+            let ident = input.parse::<Ident>()?;
+            if ident != "fallback" {
+                return Err(syn::Error::new_spanned(
+                    ident,
+                    "Unknown option for lazy routes. Expected `fallback = Component`",
+                ));
+            }
+            input.parse::<syn::Token![=]>()?;
+            fallback = Some(input.parse()?);
+
+            if input.peek(syn::Token![,]) {
+                input.parse::<syn::Token![,]>()?;
+            }
+        }
+
+        Ok(Self { fallback })
+    }
+}
+
+/// A `#[slot("name", Component)]` attribute that renders an extra component in a named outlet
+#[derive(Debug)]
+pub(crate) struct Slot {
+    name: LitStr,
+    component: Path,
+}
+
+impl Parse for Slot {
+    fn parse(input: ParseStream<'_>) -> syn::Result<Self> {
+        let name = input.parse::<LitStr>()?;
+        input.parse::<syn::Token![,]>()?;
+        let component = input.parse::<Path>()?;
+        let _ = input.parse::<syn::Token![,]>();
+
+        Ok(Slot { name, component })
+    }
+}
+
 struct ChildArgs {
     route: LitStr,
 }
@@ -60,6 +207,10 @@ pub(crate) struct Route {
     pub hash: Option<HashFragment>,
     pub nests: Vec<NestId>,
     pub layouts: Vec<LayoutId>,
+    pub sitemap: Option<SitemapAttribute>,
+    pub slots: Vec<Slot>,
+    /// The index of this route in the aliases of the variant. `None` if this is the canonical route
+    pub alias: Option<usize>,
     fields: Vec<(Ident, Type)>,
 }
 
@@ -80,8 +231,24 @@ impl Route {
             Some(attr) => {
                 let args = attr.parse_args::<RouteArgs>()?;
                 let comp_name = args.comp_name.unwrap_or_else(|| parse_quote!(#route_name));
+                if let Some(outlet) = &args.outlet {
+                    if layouts.is_empty() {
+                        return Err(syn::Error::new_spanned(
+                            outlet,
+                            "Routes rendered in a named outlet must be inside of a #[layout] that contains the outlet",
+                        ));
+                    }
+                }
+                if let (Some(background), None) = (&args.background, &args.outlet) {
+                    return Err(background.error(
+                        "Only routes rendered in a named outlet with `outlet = \"name\"` can have a background route",
+                    ));
+                }
                 ty = RouteType::Leaf {
                     component: comp_name,
+                    lazy: args.lazy,
+                    outlet: args.outlet,
+                    background: args.background,
                 };
                 route = args.route.value();
             }
@@ -146,6 +313,47 @@ impl Route {
             _ => Vec::new(),
         };
 
+        if let RouteType::Leaf {
+            background: Some(Background::Field(field)),
+            ..
+        } = &ty
+        {
+            if !fields.iter().any(|(name, _)| name == field) {
+                return Err(syn::Error::new_spanned(
+                    field,
+                    format!(
+                        "Could not find a field with the name '{field}' for the background route"
+                    ),
+                ));
+            }
+        }
+
+        let sitemap = variant
+            .attrs
+            .iter()
+            .find(|attr| attr.path().is_ident("sitemap"))
+            .map(|attr| attr.parse_args::<SitemapAttribute>())
+            .transpose()?;
+
+        let slots = variant
+            .attrs
+            .iter()
+            .filter(|attr| attr.path().is_ident("slot"))
+            .map(|attr| attr.parse_args::<Slot>())
+            .collect::<syn::Result<Vec<_>>>()?;
+        if let (Some(slot), RouteType::Child(_)) = (slots.first(), &ty) {
+            return Err(syn::Error::new_spanned(
+                &slot.name,
+                "#[slot] attributes are not supported on #[child] routes",
+            ));
+        }
+        if let (Some(slot), true) = (slots.first(), layouts.is_empty()) {
+            return Err(syn::Error::new_spanned(
+                &slot.name,
+                "Routes with a #[slot] must be inside of a #[layout] that contains the named outlet",
+            ));
+        }
+
         let (route_segments, query, hash) = {
             parse_route_segments(
                 variant.ident.span(),
@@ -163,10 +371,69 @@ impl Route {
             hash,
             nests,
             layouts,
+            sitemap,
+            slots,
+            alias: None,
             fields,
         })
     }
 
+    /// Parse any additional `#[route]` attributes of the variant as aliases of this route.
+    ///
+    /// Aliases are parsed into the same variant but the variant is always displayed with the canonical route.
+    pub(crate) fn parse_aliases(&self, variant: &syn::Variant) -> syn::Result<Vec<Self>> {
+        let RouteType::Leaf { component, .. } = &self.ty else {
+            return Ok(Vec::new());
+        };
+
+        variant
+            .attrs
+            .iter()
+            .filter(|attr| attr.path().is_ident("route"))
+            .skip(1)
+            .enumerate()
+            .map(|(index, attr)| {
+                let args = attr.parse_args::<RouteArgs>()?;
+                if args.comp_name.is_some()
+                    || args.lazy.is_some()
+                    || args.outlet.is_some()
+                    || args.background.is_some()
+                {
+                    return Err(syn::Error::new_spanned(
+                        attr,
+                        "Route aliases render the component of the first #[route] attribute and can only contain a path",
+                    ));
+                }
+                let route = args.route.value();
+                let (segments, query, hash) = parse_route_segments(
+                    args.route.span(),
+                    self.fields.iter().map(|f| (&f.0, &f.1)),
+                    &route,
+                )?;
+
+                Ok(Self {
+                    route_name: self.route_name.clone(),
+                    ty: RouteType::Leaf {
+                        component: component.clone(),
+                        lazy: None,
+                        outlet: None,
+                        background: None,
+                    },
+                    route,
+                    segments,
+                    query,
+                    hash,
+                    nests: self.nests.clone(),
+                    layouts: self.layouts.clone(),
+                    sitemap: None,
+                    slots: Vec::new(),
+                    alias: Some(index),
+                    fields: self.fields.clone(),
+                })
+            })
+            .collect()
+    }
+
     pub(crate) fn display_match(&self, nests: &[Nest]) -> TokenStream2 {
         let name = &self.route_name;
         let dynamic_segments = self.dynamic_segments();
@@ -267,102 +534,309 @@ impl Route {
                     }
                 }
             }
-            RouteType::Leaf { component } => {
+            RouteType::Leaf { outlet, .. } => {
                 let dynamic_segments = self.dynamic_segments();
-                let dynamic_segments_from_route = self.dynamic_segments();
 
-                /*
-                The implementation of this is pretty gnarly/gross.
-
-                We achieve the bundle splitting by wrapping the incoming function in a new component
-                that suspends based on an internal lazy loader. This lets us use suspense features
-                without breaking the rules of hooks. The router derive is quite complex so this shoves
-                the complexity towards the "leaf" of the codegen rather to its core. In the future though,
-                we should think about restructuring the router macro completely since its codegen
-                makes up nearly 30-40% of the binary size in the dioxus docsite.
-                */
-                use sha2::Digest;
-                let dynamic_segments_receiver = self.dynamic_segments();
-                let dynamic_segments_from_route_ = self.dynamic_segments();
-                let dynamic_segments_from_route__ = self.dynamic_segments();
-                    let unique_identifier = base16::encode_lower(
-                    &sha2::Sha256::digest(format!("{name} {span:?}", span = name.span()))[..16],
-                );
-                let module_name = format_ident!("module{}{unique_identifier}", name).to_string();
-                let comp_name = format_ident!("route{}{unique_identifier}", name);
+                // Routes rendered in a named outlet leave the default outlet empty
+                let render = match outlet {
+                    Some(_) => quote! { VNode::empty() },
+                    None => self.leaf_render(router_name),
+                };
 
                 quote! {
                     #[allow(unused)]
                     (#last_index, Self::#name { #(#dynamic_segments,)* }) => {
-                        dioxus::config_macros::maybe_wasm_split! {
-                            if wasm_split {
-                                {
-                                    fn #comp_name(args: #router_name) -> Element {
-                                        match args {
-                                            #router_name::#name { #(#dynamic_segments_from_route_,)* } => {
-                                                rsx! {
-                                                    #component {
-                                                        #(#dynamic_segments_from_route__: #dynamic_segments_from_route__,)*
-                                                    }
-                                                }
-                                            }
-                                            _ => unreachable!()
-                                        }
-                                    }
+                        #render
+                    }
+                }
+            }
+        });
 
+        tokens
+    }
 
+    /// Render the component of a leaf route
+    fn leaf_render(&self, router_name: &Ident) -> TokenStream2 {
+        let RouteType::Leaf {
+            component, lazy, ..
+        } = &self.ty
+        else {
+            unreachable!("only leaf routes have a component")
+        };
 
-                                    #[component]
-                                    fn LoaderInner(args: NoPartialEq<#router_name>) -> Element {
-                                        static MODULE: wasm_split::LazyLoader<#router_name, Element> =
-                                            wasm_split::lazy_loader!(extern #module_name fn #comp_name(props: #router_name) -> Element);
+        match lazy {
+            Some(lazy) => self.lazy_render(component, lazy, router_name),
+            None => {
+                let dynamic_segments_from_route = self.dynamic_segments();
+                quote! {
+                    rsx! {
+                        #component {
+                            #(#dynamic_segments_from_route: #dynamic_segments_from_route,)*
+                        }
+                    }
+                }
+            }
+        }
+    }
 
-                                        use_resource(|| async move { MODULE.load().await }).suspend()?;
-                                        MODULE.call(args.0).unwrap()
-                                    }
+    /// Get the match arms that render this route in named outlets
+    pub(crate) fn outlet_matches(&self, router_name: &Ident) -> Vec<TokenStream2> {
+        let RouteType::Leaf {
+            outlet, background, ..
+        } = &self.ty
+        else {
+            return Vec::new();
+        };
+        if self.alias.is_some() {
+            return Vec::new();
+        }
 
-                                    struct NoPartialEq<T>(T);
+        let name = &self.route_name;
+        let last_index = self.layouts.len();
+        let mut matches = Vec::new();
 
-                                    impl<T: Clone> Clone for NoPartialEq<T> {
-                                        fn clone(&self) -> Self {
-                                            Self(self.0.clone())
-                                        }
-                                    }
+        if let Some(outlet) = outlet {
+            let dynamic_segments = self.dynamic_segments();
+            let render = self.leaf_render(router_name);
+            matches.push(quote! {
+                #[allow(unused)]
+                (#last_index, Self::#name { #(#dynamic_segments,)* }) if __outlet == #outlet => {
+                    #render
+                }
+            });
+        }
 
-                                    impl<T: std::fmt::Display> std::fmt::Display for NoPartialEq<T> {
-                                        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-                                            self.0.fmt(f)
-                                        }
-                                    }
+        for Slot {
+            name: slot_name,
+            component,
+        } in &self.slots
+        {
+            let dynamic_segments = self.dynamic_segments();
+            let dynamic_segments_from_route = self.dynamic_segments();
+            matches.push(quote! {
+                #[allow(unused)]
+                (#last_index, Self::#name { #(#dynamic_segments,)* }) if __outlet == #slot_name => {
+                    rsx! {
+                        #component {
+                            #(#dynamic_segments_from_route: #dynamic_segments_from_route,)*
+                        }
+                    }
+                }
+            });
+        }
 
-                                    impl<T> PartialEq for NoPartialEq<T> {
-                                        fn eq(&self, _other: &Self) -> bool {
-                                            false
-                                        }
-                                    }
+        // Named outlets the route doesn't render in show the content of the background route
+        if background.is_some() {
+            matches.push(quote! {
+                (__level, Self::#name { .. }) if __level >= #last_index => {
+                    match dioxus_router::routable::Routable::background(self, __level) {
+                        Some(background) => background.render_outlet(__level, __outlet),
+                        None => VNode::empty(),
+                    }
+                }
+            });
+        }
+
+        matches
+    }
 
-                                    rsx! {
-                                        LoaderInner {
-                                            args: NoPartialEq(#router_name::#name { #(#dynamic_segments_receiver,)* } )
+    /// Get the match arm that returns the background route of this route
+    pub(crate) fn background_match(&self) -> Option<TokenStream2> {
+        let RouteType::Leaf {
+            background: Some(background),
+            ..
+        } = &self.ty
+        else {
+            return None;
+        };
+        if self.alias.is_some() {
+            return None;
+        }
+
+        let name = &self.route_name;
+        let last_index = self.layouts.len();
+        Some(match background {
+            Background::Path(path) => quote! {
+                Self::#name { .. } if level >= #last_index => #path.parse().ok(),
+            },
+            Background::Field(field) => quote! {
+                Self::#name { #field, .. } if level >= #last_index => {
+                    std::string::ToString::to_string(#field).parse().ok()
+                }
+            },
+        })
+    }
+
+    /// Render a `#[route(.., lazy)]` route.
+    ///
+    /// The component is wrapped in a suspense boundary that shows the fallback while the chunk that
+    /// contains the route is loading.
+    fn lazy_render(&self, component: &Path, lazy: &LazyArgs, router_name: &Ident) -> TokenStream2 {
+        let name = &self.route_name;
+        let dynamic_segments_receiver = self.dynamic_segments();
+        let dynamic_segments_from_route = self.dynamic_segments();
+        let dynamic_segments_from_route_ = self.dynamic_segments();
+        let dynamic_segments_from_route__ = self.dynamic_segments();
+
+        let fallback = match &lazy.fallback {
+            Some(fallback) => quote! { |_| rsx! { #fallback {} } },
+            None => quote! { |_| VNode::empty() },
+        };
+
+        /*
+        The implementation of this is pretty gnarly/gross.
+
+        We achieve the bundle splitting by wrapping the incoming function in a new component
+        that suspends based on an internal lazy loader. This lets us use suspense features
+        without breaking the rules of hooks. The router derive is quite complex so this shoves
+        the complexity towards the "leaf" of the codegen rather to its core. In the future though,
+        we should think about restructuring the router macro completely since its codegen
+        makes up nearly 30-40% of the binary size in the dioxus docsite.
+
+        Every lazy route gets its own module name so the splitter emits one chunk per route.
+        */
+        use sha2::Digest;
+        let unique_identifier = base16::encode_lower(
+            &sha2::Sha256::digest(format!("{name} {span:?}", span = name.span()))[..16],
+        );
+        let module_name = format_ident!("module{}{unique_identifier}", name).to_string();
+        let comp_name = format_ident!("route{}{unique_identifier}", name);
+
+        quote! {
+            {
+                let __route_body: Element = dioxus::config_macros::maybe_wasm_split! {
+                    if wasm_split {
+                        {
+                            fn #comp_name(args: #router_name) -> Element {
+                                match args {
+                                    #router_name::#name { #(#dynamic_segments_from_route_,)* } => {
+                                        rsx! {
+                                            #component {
+                                                #(#dynamic_segments_from_route__: #dynamic_segments_from_route__,)*
+                                            }
                                         }
                                     }
+                                    _ => unreachable!()
                                 }
-                            } else {
-                                {
-                                    rsx! {
-                                        #component {
-                                            #(#dynamic_segments_from_route: #dynamic_segments_from_route,)*
-                                        }
-                                    }
+                            }
+
+                            #[component]
+                            fn LoaderInner(args: NoPartialEq<#router_name>) -> Element {
+                                static MODULE: wasm_split::LazyLoader<#router_name, Element> =
+                                    wasm_split::lazy_loader!(extern #module_name fn #comp_name(props: #router_name) -> Element);
+
+                                use_resource(|| async move { MODULE.load().await }).suspend()?;
+                                MODULE.call(args.0).unwrap()
+                            }
+
+                            struct NoPartialEq<T>(T);
+
+                            impl<T: Clone> Clone for NoPartialEq<T> {
+                                fn clone(&self) -> Self {
+                                    Self(self.0.clone())
+                                }
+                            }
+
+                            impl<T: std::fmt::Display> std::fmt::Display for NoPartialEq<T> {
+                                fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                                    self.0.fmt(f)
+                                }
+                            }
+
+                            impl<T> PartialEq for NoPartialEq<T> {
+                                fn eq(&self, _other: &Self) -> bool {
+                                    false
+                                }
+                            }
+
+                            rsx! {
+                                LoaderInner {
+                                    args: NoPartialEq(#router_name::#name { #(#dynamic_segments_receiver,)* } )
+                                }
+                            }
+                        }
+                    } else {
+                        {
+                            rsx! {
+                                #component {
+                                    #(#dynamic_segments_from_route: #dynamic_segments_from_route,)*
                                 }
                             }
                         }
                     }
+                };
+
+                rsx! {
+                    SuspenseBoundary {
+                        fallback: #fallback,
+                        {__route_body}
+                    }
                 }
             }
-        });
+        }
+    }
 
-        tokens
+    /// Create a typed url builder function for this route like `RouteUrls::blog_post(id, slug)`
+    pub(crate) fn url_builder(
+        &self,
+        nests: &[Nest],
+        enum_name: &Ident,
+        vis: &syn::Visibility,
+    ) -> Option<TokenStream2> {
+        // Aliases share the url of the canonical route
+        if self.alias.is_some() {
+            return None;
+        }
+
+        let name = &self.route_name;
+        let fn_name = to_snake_case(&name.to_string());
+        let fn_name = match fn_name.as_str() {
+            "self" | "super" | "crate" => format_ident!("{}_", fn_name),
+            // Variants like `Type` become keywords in snake case
+            _ if syn::parse_str::<Ident>(&fn_name).is_err() => format_ident!("r#{}", fn_name),
+            _ => format_ident!("{}", fn_name),
+        };
+        let mut args: Vec<_> = self.fields.iter().map(|(name, ty)| (name, ty)).collect();
+        if let RouteType::Child(field) = &self.ty {
+            args.push((field.ident.as_ref().unwrap(), &field.ty));
+        }
+        let arg_names = args.iter().map(|(name, _)| name);
+        let args = args.iter().map(|(name, ty)| quote! { #name: #ty });
+
+        let full_route: String = self
+            .nests
+            .iter()
+            .map(|id| nests[id.0].route.as_str())
+            .chain(std::iter::once(self.route.as_str()))
+            .collect();
+        let doc = format!(" Create the url for [`{enum_name}::{name}`] (`{full_route}`).");
+
+        Some(quote! {
+            #[doc = #doc]
+            #[allow(clippy::too_many_arguments)]
+            #vis fn #fn_name(#(#args),*) -> String {
+                #enum_name::#name { #(#arg_names),* }.to_string()
+            }
+        })
+    }
+
+    /// Get the match arm that returns the sitemap metadata of this route if it is different from the default
+    pub(crate) fn sitemap_match(&self) -> Option<TokenStream2> {
+        let name = &self.route_name;
+
+        match (&self.sitemap, &self.ty) {
+            (Some(sitemap), _) => Some(quote! {
+                Self::#name { .. } => #sitemap,
+            }),
+            // Child routers inherit the metadata of the child route
+            (None, RouteType::Child(field)) => {
+                let child = field.ident.as_ref().unwrap();
+                Some(quote! {
+                    Self::#name { #child, .. } => dioxus_router::routable::Routable::sitemap_metadata(#child),
+                })
+            }
+            (None, RouteType::Leaf { .. }) => None,
+        }
     }
 
     fn dynamic_segments(&self) -> impl Iterator<Item = TokenStream2> + '_ {
@@ -427,8 +901,16 @@ impl Route {
         }
     }
 
+    /// The name of the variant in the match error enum for this route
+    pub(crate) fn error_variant(&self) -> Ident {
+        match self.alias {
+            Some(index) => format_ident!("{}Alias{}", self.route_name, index),
+            None => self.route_name.clone(),
+        }
+    }
+
     pub(crate) fn error_ident(&self) -> Ident {
-        format_ident!("{}ParseError", self.route_name)
+        format_ident!("{}ParseError", self.error_variant())
     }
 
     pub(crate) fn error_type(&self) -> TokenStream2 {
@@ -459,5 +941,38 @@ impl Route {
 #[derive(Debug)]
 pub(crate) enum RouteType {
     Child(Field),
-    Leaf { component: Path },
+    Leaf {
+        component: Path,
+        lazy: Option<LazyArgs>,
+        outlet: Option<LitStr>,
+        background: Option<Background>,
+    },
+}
+
+/// Convert a `PascalCase` variant name to `snake_case`
+fn to_snake_case(name: &str) -> String {
+    let mut snake = String::with_capacity(name.len() + 4);
+    let mut prev: Option<char> = None;
+    let mut chars = name.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c.is_uppercase() {
+            let next_is_lower = chars.peek().is_some_and(|next| next.is_lowercase());
+            let starts_word = match prev {
+                Some(prev) => {
+                    prev.is_lowercase()
+                        || prev.is_numeric()
+                        || (prev.is_uppercase() && next_is_lower)
+                }
+                None => false,
+            };
+            if starts_word && !snake.ends_with('_') {
+                snake.push('_');
+            }
+            snake.extend(c.to_lowercase());
+        } else {
+            snake.push(c);
+        }
+        prev = Some(c);
+    }
+    snake
 }
@@ -325,7 +325,7 @@ impl RouteTreeSegmentData<'_> {
             RouteTreeSegmentData::Route(route) => {
                 // At this point, we have matched all static segments, so we can just check if the remaining segments match the route
                 let variant_parse_error = route.error_ident();
-                let enum_variant = &route.route_name;
+                let enum_variant = &route.error_variant();
 
                 let route_segments = route
                     .segments
@@ -555,7 +555,7 @@ impl<'a> PathIter<'a> {
             active_nests: &*route.nests,
             segments: &*route.segments,
             error_ident: route.error_ident(),
-            error_variant: route.route_name.clone(),
+            error_variant: route.error_variant(),
             all_nests: nests,
             nest_index: 0,
             static_segment_index: 0,
@@ -0,0 +1,100 @@
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{quote, ToTokens};
+use syn::parse::{Parse, ParseStream};
+use syn::{Ident, LitFloat, LitStr, Token};
+
+/// The options of a `#[sitemap(lastmod = "..", changefreq = "..", priority = 0.5, exclude)]` attribute
+#[derive(Debug, Default)]
+pub(crate) struct SitemapAttribute {
+    lastmod: Option<LitStr>,
+    changefreq: Option<Ident>,
+    priority: Option<LitFloat>,
+    exclude: bool,
+}
+
+impl Parse for SitemapAttribute {
+    fn parse(input: ParseStream<'_>) -> syn::Result<Self> {
+        let mut myself = Self::default();
+
+        while !input.is_empty() {
+            let ident = input.parse::<Ident>()?;
+            match ident.to_string().as_str() {
+                "exclude" => myself.exclude = true,
+                "lastmod" => {
+                    input.parse::<Token![=]>()?;
+                    myself.lastmod = Some(input.parse()?);
+                }
+                "changefreq" => {
+                    input.parse::<Token![=]>()?;
+                    let value = input.parse::<LitStr>()?;
+                    let variant = match value.value().as_str() {
+                        "always" => "Always",
+                        "hourly" => "Hourly",
+                        "daily" => "Daily",
+                        "weekly" => "Weekly",
+                        "monthly" => "Monthly",
+                        "yearly" => "Yearly",
+                        "never" => "Never",
+                        _ => {
+                            return Err(syn::Error::new_spanned(
+                                value,
+                                "Expected one of \"always\", \"hourly\", \"daily\", \"weekly\", \"monthly\", \"yearly\" or \"never\"",
+                            ))
+                        }
+                    };
+                    myself.changefreq = Some(Ident::new(variant, value.span()));
+                }
+                "priority" => {
+                    input.parse::<Token![=]>()?;
+                    let value = input.parse::<LitFloat>()?;
+                    let priority: f32 = value.base10_parse()?;
+                    if !(0.0..=1.0).contains(&priority) {
+                        return Err(syn::Error::new_spanned(
+                            value,
+                            "The sitemap priority must be between 0.0 and 1.0",
+                        ));
+                    }
+                    myself.priority = Some(value);
+                }
+                _ => {
+                    return Err(syn::Error::new_spanned(
+                        ident,
+                        "Unknown sitemap option. Expected `lastmod`, `changefreq`, `priority` or `exclude`",
+                    ))
+                }
+            }
+
+            if input.peek(Token![,]) {
+                input.parse::<Token![,]>()?;
+            }
+        }
+
+        Ok(myself)
+    }
+}
+
+impl ToTokens for SitemapAttribute {
+    fn to_tokens(&self, tokens: &mut TokenStream2) {
+        let option = |value: Option<TokenStream2>| match value {
+            Some(value) => quote! { Some(#value) },
+            None => quote! { None },
+        };
+        let lastmod = option(self.lastmod.as_ref().map(|lit| quote! { #lit }));
+        let changefreq = option(
+            self.changefreq
+                .as_ref()
+                .map(|variant| quote! { dioxus_router::sitemap::ChangeFrequency::#variant }),
+        );
+        let priority = option(self.priority.as_ref().map(|lit| quote! { #lit as f32 }));
+        let exclude = self.exclude;
+
+        tokens.extend(quote! {
+            dioxus_router::sitemap::SitemapMetadata {
+                lastmod: #lastmod,
+                changefreq: #changefreq,
+                priority: #priority,
+                exclude: #exclude,
+            }
+        });
+    }
+}
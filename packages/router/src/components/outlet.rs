@@ -69,6 +69,64 @@ use dioxus_lib::prelude::*;
 /// # vdom.rebuild_in_place();
 /// # assert_eq!(dioxus_ssr::render(&vdom), "<h1>App</h1><p>Child</p>");
 /// ```
-pub fn Outlet<R: Routable + Clone>() -> Element {
-    OutletContext::<R>::render()
+pub fn Outlet<R: Routable + Clone>(props: OutletProps<R>) -> Element {
+    OutletContext::<R>::render(props.name.as_deref())
+}
+
+/// The props for [`Outlet`].
+#[derive(Props)]
+pub struct OutletProps<R: Clone + 'static> {
+    /// The name of the outlet. Named outlets render the routes marked with `#[route(.., outlet = "name")]`
+    /// and the components of `#[slot("name", Component)]` attributes instead of the child route.
+    ///
+    /// ```rust
+    /// # use dioxus::prelude::*;
+    /// # use dioxus_router::prelude::*;
+    /// #[derive(Clone, Routable)]
+    /// #[rustfmt::skip]
+    /// enum Route {
+    ///     #[layout(Photos)]
+    ///         #[route("/")]
+    ///         #[slot("sidebar", Filters)]
+    ///         Index {},
+    ///         // The photo is rendered in a modal over the list of photos
+    ///         #[route("/photo/:id", PhotoModal, outlet = "modal")]
+    ///         Photo { id: usize },
+    /// }
+    ///
+    /// #[component]
+    /// fn Photos() -> Element {
+    ///     rsx! {
+    ///         aside { Outlet::<Route> { name: "sidebar" } }
+    ///         ul { "The list of photos" }
+    ///         Outlet::<Route> {}
+    ///         Outlet::<Route> { name: "modal" }
+    ///     }
+    /// }
+    /// # #[component]
+    /// # fn Index() -> Element { VNode::empty() }
+    /// # #[component]
+    /// # fn Filters() -> Element { VNode::empty() }
+    /// # #[component]
+    /// # fn PhotoModal(id: usize) -> Element { VNode::empty() }
+    /// ```
+    #[props(into)]
+    name: Option<String>,
+    #[props(default)]
+    _marker: std::marker::PhantomData<R>,
+}
+
+impl<R: Clone> Clone for OutletProps<R> {
+    fn clone(&self) -> Self {
+        Self {
+            name: self.name.clone(),
+            _marker: std::marker::PhantomData,
+        }
+    }
+}
+
+impl<R: Clone> PartialEq for OutletProps<R> {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name
+    }
 }
@@ -0,0 +1,251 @@
+use crate::prelude::{outlet::OutletContext, *};
+use crate::utils::use_router_internal::use_router_internal;
+use dioxus_lib::prelude::*;
+
+/// The phase of a route rendered inside of a [`TransitionOutlet`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TransitionPhase {
+    /// The route was just navigated to and is animating in.
+    Entering,
+    /// The route finished animating in.
+    Entered,
+    /// The router navigated away from the route and it is animating out. It will be unmounted once
+    /// the transition finishes.
+    Exiting,
+}
+
+impl TransitionPhase {
+    /// The name of the phase used in css classes and the `data-transition` attribute.
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            TransitionPhase::Entering => "entering",
+            TransitionPhase::Entered => "entered",
+            TransitionPhase::Exiting => "exiting",
+        }
+    }
+}
+
+/// The props for [`TransitionOutlet`].
+#[derive(Props)]
+pub struct TransitionOutletProps<R: Clone + 'static> {
+    /// How long the enter and exit transitions take in milliseconds. The previous route is
+    /// unmounted after this time.
+    #[props(default = 300)]
+    duration: u64,
+    /// The prefix of the css classes added to the wrapper of each route. With the default prefix
+    /// the classes are `route-transition route-transition-entering`, `route-transition route-transition-entered`
+    /// and `route-transition route-transition-exiting`.
+    #[props(default = "route-transition".to_string(), into)]
+    class: String,
+    #[props(default)]
+    _marker: std::marker::PhantomData<R>,
+}
+
+impl<R: Clone> Clone for TransitionOutletProps<R> {
+    fn clone(&self) -> Self {
+        Self {
+            duration: self.duration,
+            class: self.class.clone(),
+            _marker: std::marker::PhantomData,
+        }
+    }
+}
+
+impl<R: Clone> PartialEq for TransitionOutletProps<R> {
+    fn eq(&self, other: &Self) -> bool {
+        self.duration == other.duration && self.class == other.class
+    }
+}
+
+/// An [`Outlet`] that animates between routes.
+///
+/// When the router navigates, the [`TransitionOutlet`] keeps the content of the previous route
+/// mounted in the [`TransitionPhase::Exiting`] phase while the content of the new route is mounted
+/// in the [`TransitionPhase::Entering`] phase. Once the `duration` has passed, the previous route is
+/// unmounted and the new route moves to the [`TransitionPhase::Entered`] phase.
+///
+/// Each route is wrapped in a `div` with the phase in its class and in a `data-transition`
+/// attribute, so the animation itself can be written in css. Components inside of the route can
+/// read the phase with [`use_transition_phase`].
+///
+/// If the user prefers reduced motion (`prefers-reduced-motion: reduce`), the previous route is
+/// unmounted right away. The route that is rendered first is not animated.
+///
+/// The content of the route is rendered with the route it was created for, even while it is
+/// exiting. [`use_route`] always returns the current route of the router, so components that need
+/// the route they were rendered for should take it from their props instead.
+///
+/// # Example
+/// ```rust
+/// # use dioxus::prelude::*;
+/// # use dioxus_router::prelude::*;
+/// #[derive(Clone, Routable)]
+/// #[rustfmt::skip]
+/// enum Route {
+///     #[layout(Wrapper)]
+///         #[route("/")]
+///         Index {},
+/// }
+///
+/// #[component]
+/// fn Wrapper() -> Element {
+///     rsx! {
+///         document::Style {
+///             ".route-transition-entering {{ animation: fade-in 300ms; }}
+///             .route-transition-exiting {{ animation: fade-out 300ms; position: absolute; }}"
+///         }
+///         h1 { "App" }
+///         TransitionOutlet::<Route> { duration: 300 }
+///     }
+/// }
+///
+/// #[component]
+/// fn Index() -> Element {
+///     rsx! { p { "Index" } }
+/// }
+///
+/// # #[component]
+/// # fn App() -> Element {
+/// #     rsx! {
+/// #         dioxus_router::components::HistoryProvider {
+/// #             history:  move |_| std::rc::Rc::new(dioxus_history::MemoryHistory::with_initial_path(Route::Index {}.to_string())) as std::rc::Rc<dyn dioxus_history::History>,
+/// #             Router::<Route> {}
+/// #         }
+/// #     }
+/// # }
+/// #
+/// # let mut vdom = VirtualDom::new(App);
+/// # vdom.rebuild_in_place();
+/// # assert_eq!(
+/// #     dioxus_ssr::render(&vdom),
+/// #     "<h1>App</h1><div class=\"route-transition route-transition-entered\" data-transition=\"entered\"><p>Index</p></div>"
+/// # );
+/// ```
+pub fn TransitionOutlet<R: Routable + Clone>(props: TransitionOutletProps<R>) -> Element {
+    let router = use_router_internal().expect("TransitionOutlet must be inside of a router");
+    let outlet = use_outlet_context::<R>();
+    let mut entries = use_signal(|| {
+        vec![TransitionEntry {
+            id: 0,
+            route: router.current::<R>(),
+            phase: TransitionPhase::Entered,
+        }]
+    });
+    let mut next_id = use_signal(|| 1);
+    let duration = props.duration;
+
+    use_effect(move || {
+        let route = router.current::<R>();
+        let unchanged = entries
+            .peek()
+            .last()
+            .is_some_and(|entry| entry.route.to_string() == route.to_string());
+        if unchanged {
+            return;
+        }
+
+        let id = *next_id.peek();
+        next_id += 1;
+        entries.with_mut(|entries| {
+            for entry in entries.iter_mut() {
+                entry.phase = TransitionPhase::Exiting;
+            }
+            entries.push(TransitionEntry {
+                id,
+                route,
+                phase: TransitionPhase::Entering,
+            });
+        });
+
+        spawn(async move {
+            wait_for_transition(duration).await;
+            entries.with_mut(|entries| {
+                entries.retain(|entry| entry.id >= id || entry.phase != TransitionPhase::Exiting);
+                for entry in entries.iter_mut() {
+                    if entry.id == id && entry.phase == TransitionPhase::Entering {
+                        entry.phase = TransitionPhase::Entered;
+                    }
+                }
+            });
+        });
+    });
+
+    rsx! {
+        for entry in entries.read().iter() {
+            TransitionEntryRenderer::<R> {
+                key: "{entry.id}",
+                outlet: outlet.with_route(entry.route.clone()),
+                phase: entry.phase,
+                class: props.class.clone(),
+            }
+        }
+    }
+}
+
+/// Returns the [`TransitionPhase`] of the route the component was rendered in or `None` if the
+/// component is not rendered inside of a [`TransitionOutlet`].
+///
+/// The component will rerender when the phase changes.
+pub fn use_transition_phase() -> Option<TransitionPhase> {
+    let phase = use_hook(try_consume_context::<Memo<TransitionPhase>>);
+    phase.map(|phase| phase())
+}
+
+struct TransitionEntry<R> {
+    id: usize,
+    route: R,
+    phase: TransitionPhase,
+}
+
+#[derive(Props)]
+struct TransitionEntryRendererProps<R: Clone + 'static> {
+    outlet: OutletContext<R>,
+    phase: TransitionPhase,
+    class: String,
+}
+
+impl<R: Clone> Clone for TransitionEntryRendererProps<R> {
+    fn clone(&self) -> Self {
+        Self {
+            outlet: self.outlet.clone(),
+            phase: self.phase,
+            class: self.class.clone(),
+        }
+    }
+}
+
+impl<R: Clone> PartialEq for TransitionEntryRendererProps<R> {
+    // The route of an entry never changes, only the phase
+    fn eq(&self, other: &Self) -> bool {
+        self.phase == other.phase && self.class == other.class
+    }
+}
+
+fn TransitionEntryRenderer<R: Routable + Clone>(props: TransitionEntryRendererProps<R>) -> Element {
+    let phase = props.phase;
+    let phase = use_memo(use_reactive!(|phase| phase));
+    use_context_provider(|| phase);
+    use_context_provider(|| props.outlet.clone());
+
+    let class = &props.class;
+    let phase = props.phase.as_str();
+    rsx! {
+        div {
+            class: "{class} {class}-{phase}",
+            "data-transition": phase,
+            Outlet::<R> {}
+        }
+    }
+}
+
+/// Wait until the transition is over. This resolves immediately if the user prefers reduced motion
+/// or the renderer can't run javascript.
+async fn wait_for_transition(duration: u64) {
+    let script = format!(
+        "if (!window.matchMedia('(prefers-reduced-motion: reduce)').matches) {{
+            await new Promise((resolve) => setTimeout(resolve, {duration}));
+        }}
+        return true;"
+    );
+    _ = dioxus_lib::document::eval(&script).await;
+}
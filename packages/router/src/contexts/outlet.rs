@@ -1,3 +1,5 @@
+use std::rc::Rc;
+
 use dioxus_lib::prelude::*;
 
 use crate::{routable::Routable, utils::use_router_internal::use_router_internal};
@@ -13,6 +15,8 @@ use crate::{routable::Routable, utils::use_router_internal::use_router_internal}
 #[derive(Clone, Default)]
 pub struct OutletContext<R> {
     current_level: usize,
+    name: Option<String>,
+    route: Option<Rc<R>>,
     _marker: std::marker::PhantomData<R>,
 }
 
@@ -21,6 +25,8 @@ impl<R> OutletContext<R> {
     pub fn new() -> Self {
         Self {
             current_level: 0,
+            name: None,
+            route: None,
             _marker: std::marker::PhantomData,
         }
     }
@@ -29,33 +35,75 @@ impl<R> OutletContext<R> {
     pub fn next(&self) -> Self {
         Self {
             current_level: self.current_level + 1,
+            name: None,
+            route: self.route.clone(),
             _marker: std::marker::PhantomData,
         }
     }
 
+    /// Creates a new outlet context for the next nesting level inside of the named outlet `name`
+    pub fn next_named(&self, name: impl Into<String>) -> Self {
+        Self {
+            name: Some(name.into()),
+            ..self.next()
+        }
+    }
+
     /// Returns the current nesting level of this outlet
     pub fn level(&self) -> usize {
         self.current_level
     }
 
-    pub(crate) fn render() -> Element
+    /// Returns the name of the outlet the current content was rendered in or `None` if it was
+    /// rendered in a default outlet
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// Creates a copy of this outlet context that renders `route` instead of the current route of
+    /// the router. Outlets nested inside of the context will keep rendering `route` even after the
+    /// router navigates somewhere else.
+    pub(crate) fn with_route(&self, route: R) -> Self {
+        Self {
+            current_level: self.current_level,
+            name: self.name.clone(),
+            route: Some(Rc::new(route)),
+            _marker: std::marker::PhantomData,
+        }
+    }
+
+    pub(crate) fn render(name: Option<&str>) -> Element
     where
         R: Routable + Clone,
     {
         let router = use_router_internal().expect("Outlet must be inside of a router");
         let outlet: OutletContext<R> = use_outlet_context();
         let current_level = outlet.level();
-        provide_context(outlet.next());
+        provide_context(match name {
+            Some(name) => outlet.next_named(name),
+            None => outlet.next(),
+        });
 
         if let Some(error) = router.render_error() {
-            return if current_level == 0 {
+            return if current_level == 0 && name.is_none() {
                 error
             } else {
                 VNode::empty()
             };
         }
 
-        router.current::<R>().render(current_level)
+        let route = match outlet.route {
+            Some(route) => R::clone(&route),
+            None => router.current::<R>(),
+        };
+        match name {
+            Some(name) => route.render_outlet(current_level, name),
+            // Default outlets behind a route in a named outlet render the background route
+            None => match route.background(current_level) {
+                Some(background) => background.render(current_level),
+                None => route.render(current_level),
+            },
+        }
     }
 }
 
@@ -7,6 +7,7 @@
 
 pub mod navigation;
 pub mod routable;
+pub mod sitemap;
 
 /// Components interacting with the router.
 pub mod components {
@@ -25,6 +26,9 @@ pub mod components {
     mod router;
     pub use router::*;
 
+    mod transition_outlet;
+    pub use transition_outlet::*;
+
     mod history_provider;
     pub use history_provider::*;
 
@@ -60,9 +64,10 @@ pub use hooks::router;
 
 /// A collection of useful items most applications might need.
 pub mod prelude {
+    pub use crate::components::use_transition_phase;
     pub use crate::components::{
-        GoBackButton, GoForwardButton, HistoryButtonProps, Link, LinkProps, Outlet, Router,
-        RouterProps,
+        GoBackButton, GoForwardButton, HistoryButtonProps, Link, LinkProps, Outlet, OutletProps,
+        Router, RouterProps, TransitionOutlet, TransitionOutletProps, TransitionPhase,
     };
     pub use crate::contexts::*;
     pub use crate::hooks::*;
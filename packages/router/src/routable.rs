@@ -599,6 +599,23 @@ pub trait Routable: FromStr + Display + Clone + 'static {
     /// Render the route at the given level
     fn render(&self, level: usize) -> Element;
 
+    /// Render the content of the named outlet `name` at the given level.
+    ///
+    /// This renders routes with `#[route(.., outlet = "name")]` and `#[slot("name", Component)]` attributes.
+    fn render_outlet(&self, level: usize, name: &str) -> Element {
+        let _ = (level, name);
+        VNode::empty()
+    }
+
+    /// Get the route that default outlets at the given level render behind this route.
+    ///
+    /// Routes with `#[route(.., outlet = "name", background = ..)]` are rendered in a named outlet
+    /// while the default outlet keeps rendering the background route, like a list behind a modal.
+    fn background(&self, level: usize) -> Option<Self> {
+        let _ = level;
+        None
+    }
+
     /// Checks if this route is a child of the given route.
     ///
     /// # Example
@@ -704,6 +721,44 @@ pub trait Routable: FromStr + Display + Clone + 'static {
         Self::from_str(&new_route).ok()
     }
 
+    /// Get the canonical url of this route. This is the url the route is displayed as without the
+    /// hash fragment and can be used in a `<link rel="canonical">` tag.
+    ///
+    /// Routes with multiple `#[route]` attributes are parsed from any of the aliases, but the
+    /// canonical url always uses the first `#[route]` attribute.
+    ///
+    /// # Example
+    /// ```rust
+    /// use dioxus_router::prelude::*;
+    /// use dioxus::prelude::*;
+    ///
+    /// #[component]
+    /// fn Blog(id: usize) -> Element { VNode::empty() }
+    ///
+    /// #[derive(Routable, Clone, PartialEq, Debug)]
+    /// enum Route {
+    ///     #[route("/blog/:id")]
+    ///     #[route("/posts/:id")]
+    ///     Blog { id: usize },
+    /// }
+    ///
+    /// let route: Route = "/posts/1".parse().unwrap();
+    /// assert_eq!(route.canonical_url(), "/blog/1");
+    /// ```
+    fn canonical_url(&self) -> String {
+        let mut url = self.to_string();
+        if let Some(hash) = url.find('#') {
+            url.truncate(hash);
+        }
+        url
+    }
+
+    /// Get the sitemap information for this route. This is set with the `#[sitemap(..)]` attribute
+    /// when deriving [`Routable`] and used by [`Sitemap`](crate::sitemap::Sitemap).
+    fn sitemap_metadata(&self) -> crate::sitemap::SitemapMetadata {
+        crate::sitemap::SitemapMetadata::default()
+    }
+
     /// Returns a flattened version of [`Self::SITE_MAP`].
     fn flatten_site_map<'a>() -> SiteMapFlattened<'a> {
         Self::SITE_MAP.iter().flat_map(SiteMapSegment::flatten)
@@ -0,0 +1,274 @@
+//! Generate `sitemap.xml` and `robots.txt` files from a [`Routable`] enum.
+//!
+//! Every static route in [`Routable::SITE_MAP`] is included automatically. Routes with dynamic
+//! segments can't be enumerated from the route definition alone, so you can provide a function that
+//! lists them with [`Sitemap::static_routes`].
+//!
+//! ```rust
+//! use dioxus::prelude::*;
+//! use dioxus_router::sitemap::Sitemap;
+//!
+//! #[derive(Routable, Clone, PartialEq, Debug)]
+//! enum Route {
+//!     #[route("/")]
+//!     #[sitemap(priority = 1.0, changefreq = "daily")]
+//!     Home {},
+//!     #[route("/blog/:id")]
+//!     Blog { id: usize },
+//! }
+//! # #[component]
+//! # fn Home() -> Element { VNode::empty() }
+//! # #[component]
+//! # fn Blog(id: usize) -> Element { VNode::empty() }
+//!
+//! let sitemap = Sitemap::<Route>::new("https://example.com")
+//!     .static_routes(|| (0..3).map(|id| Route::Blog { id }).collect());
+//!
+//! let xml = sitemap.to_xml();
+//! assert!(xml.contains("<loc>https://example.com/blog/2</loc>"));
+//! ```
+//!
+//! The files are not served automatically. With fullstack, serve them from your server with
+//! `ServeConfigBuilder::sitemap` and `ServeConfigBuilder::robots_txt`. Static generation then
+//! writes them next to the pre-rendered pages.
+
+use std::{
+    collections::HashSet,
+    fmt::{Display, Write},
+    sync::Arc,
+};
+
+use crate::routable::Routable;
+
+/// How often the content of a page is likely to change. This is a hint for crawlers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ChangeFrequency {
+    /// The page changes every time it is accessed.
+    Always,
+    /// The page changes about once an hour.
+    Hourly,
+    /// The page changes about once a day.
+    Daily,
+    /// The page changes about once a week.
+    Weekly,
+    /// The page changes about once a month.
+    Monthly,
+    /// The page changes about once a year.
+    Yearly,
+    /// The page is archived and will not change.
+    Never,
+}
+
+impl ChangeFrequency {
+    /// The value of the `<changefreq>` tag for this frequency.
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            ChangeFrequency::Always => "always",
+            ChangeFrequency::Hourly => "hourly",
+            ChangeFrequency::Daily => "daily",
+            ChangeFrequency::Weekly => "weekly",
+            ChangeFrequency::Monthly => "monthly",
+            ChangeFrequency::Yearly => "yearly",
+            ChangeFrequency::Never => "never",
+        }
+    }
+}
+
+impl Display for ChangeFrequency {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Sitemap information attached to a route with the `#[sitemap(..)]` attribute.
+///
+/// ```rust
+/// use dioxus::prelude::*;
+///
+/// #[derive(Routable, Clone, PartialEq, Debug)]
+/// enum Route {
+///     #[route("/")]
+///     #[sitemap(lastmod = "2024-11-01", changefreq = "weekly", priority = 0.8)]
+///     Home {},
+///     // Routes can be left out of the sitemap entirely
+///     #[route("/admin")]
+///     #[sitemap(exclude)]
+///     Admin {},
+/// }
+/// # #[component]
+/// # fn Home() -> Element { VNode::empty() }
+/// # #[component]
+/// # fn Admin() -> Element { VNode::empty() }
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct SitemapMetadata {
+    /// The date the page was last modified in the [W3C Datetime](https://www.w3.org/TR/NOTE-datetime) format.
+    pub lastmod: Option<&'static str>,
+    /// How often the page is likely to change.
+    pub changefreq: Option<ChangeFrequency>,
+    /// The priority of this page relative to other pages on the site between `0.0` and `1.0`.
+    pub priority: Option<f32>,
+    /// If the route should be left out of the sitemap.
+    pub exclude: bool,
+}
+
+/// A single `<url>` entry in a sitemap.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SitemapUrl {
+    /// The absolute url of the page.
+    pub loc: String,
+    /// The date the page was last modified.
+    pub lastmod: Option<&'static str>,
+    /// How often the page is likely to change.
+    pub changefreq: Option<ChangeFrequency>,
+    /// The priority of this page relative to other pages on the site.
+    pub priority: Option<f32>,
+}
+
+type StaticRoutes<R> = Arc<dyn Fn() -> Vec<R> + Send + Sync>;
+
+/// A sitemap generator for a [`Routable`] enum.
+///
+/// The sitemap contains every route returned by [`Routable::static_routes`] and every route returned
+/// by the function passed to [`Sitemap::static_routes`]. Routes marked with `#[sitemap(exclude)]`
+/// are skipped.
+pub struct Sitemap<R: Routable> {
+    base_url: String,
+    static_routes: Option<StaticRoutes<R>>,
+}
+
+impl<R: Routable> Clone for Sitemap<R> {
+    fn clone(&self) -> Self {
+        Self {
+            base_url: self.base_url.clone(),
+            static_routes: self.static_routes.clone(),
+        }
+    }
+}
+
+impl<R: Routable> Sitemap<R> {
+    /// Create a new sitemap for the site hosted at `base_url` (for example `https://example.com`).
+    pub fn new(base_url: impl Into<String>) -> Self {
+        let mut base_url = base_url.into();
+        while base_url.ends_with('/') {
+            base_url.pop();
+        }
+
+        Self {
+            base_url,
+            static_routes: None,
+        }
+    }
+
+    /// Provide a function that lists routes that can't be derived from the route definitions,
+    /// like routes with dynamic segments. These routes are added to the static routes of the enum.
+    pub fn static_routes(mut self, routes: impl Fn() -> Vec<R> + Send + Sync + 'static) -> Self {
+        self.static_routes = Some(Arc::new(routes));
+        self
+    }
+
+    /// Get all routes that will be included in the sitemap in the order they appear in the file.
+    pub fn routes(&self) -> Vec<R> {
+        let mut seen = HashSet::new();
+        let user_routes = self
+            .static_routes
+            .as_ref()
+            .map(|routes| routes())
+            .unwrap_or_default();
+
+        R::static_routes()
+            .into_iter()
+            .chain(user_routes)
+            .filter(|route| !route.sitemap_metadata().exclude)
+            .filter(|route| seen.insert(route.to_string()))
+            .collect()
+    }
+
+    /// Get the `<url>` entries of the sitemap.
+    pub fn urls(&self) -> Vec<SitemapUrl> {
+        self.routes()
+            .into_iter()
+            .map(|route| {
+                let metadata = route.sitemap_metadata();
+                let route = route.to_string();
+                // Fragments are never sent to the server, so they don't belong in the sitemap
+                let path = route
+                    .split_once('#')
+                    .map(|(path, _)| path)
+                    .unwrap_or(&route);
+                SitemapUrl {
+                    loc: format!("{}{}", self.base_url, path),
+                    lastmod: metadata.lastmod,
+                    changefreq: metadata.changefreq,
+                    priority: metadata.priority,
+                }
+            })
+            .collect()
+    }
+
+    /// Render the sitemap as a `sitemap.xml` document.
+    pub fn to_xml(&self) -> String {
+        let mut xml = String::from(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<urlset xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\">\n",
+        );
+
+        for url in self.urls() {
+            xml.push_str("  <url>\n");
+            let _ = writeln!(xml, "    <loc>{}</loc>", XmlEscaped(&url.loc));
+            if let Some(lastmod) = url.lastmod {
+                let _ = writeln!(xml, "    <lastmod>{}</lastmod>", XmlEscaped(lastmod));
+            }
+            if let Some(changefreq) = url.changefreq {
+                let _ = writeln!(xml, "    <changefreq>{changefreq}</changefreq>");
+            }
+            if let Some(priority) = url.priority.filter(|priority| !priority.is_nan()) {
+                let _ = writeln!(xml, "    <priority>{}</priority>", Priority(priority));
+            }
+            xml.push_str("  </url>\n");
+        }
+
+        xml.push_str("</urlset>\n");
+        xml
+    }
+
+    /// Render a `robots.txt` file that allows all crawlers and points them to the sitemap.
+    pub fn robots_txt(&self) -> String {
+        format!(
+            "User-agent: *\nAllow: /\n\nSitemap: {}/sitemap.xml\n",
+            self.base_url
+        )
+    }
+}
+
+/// A priority clamped to `0.0..=1.0` with up to three decimals, like `0.25` or `1.0`
+struct Priority(f32);
+
+impl Display for Priority {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let priority = format!("{:.3}", self.0.clamp(0.0, 1.0));
+        let priority = priority.trim_end_matches('0');
+        f.write_str(priority)?;
+        if priority.ends_with('.') {
+            f.write_char('0')?;
+        }
+        Ok(())
+    }
+}
+
+struct XmlEscaped<'a>(&'a str);
+
+impl Display for XmlEscaped<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for c in self.0.chars() {
+            match c {
+                '&' => f.write_str("&amp;")?,
+                '<' => f.write_str("&lt;")?,
+                '>' => f.write_str("&gt;")?,
+                '"' => f.write_str("&quot;")?,
+                '\'' => f.write_str("&apos;")?,
+                c => f.write_char(c)?,
+            }
+        }
+        Ok(())
+    }
+}
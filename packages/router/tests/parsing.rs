@@ -75,3 +75,100 @@ fn without_trailing_slashes_parse() {
         RouteWithoutTrailingSlash::Dynamic { id: 123 }
     );
 }
+
+#[component]
+fn BlogPost(id: usize, slug: String) -> Element {
+    unimplemented!()
+}
+
+#[test]
+fn route_aliases_parse_to_the_canonical_route() {
+    #[derive(Routable, Clone, PartialEq, Debug)]
+    enum Route {
+        #[route("/")]
+        #[route("/home")]
+        #[route("/index.html")]
+        Root {},
+        #[route("/blog/:id/:slug")]
+        #[route("/posts/:id/:slug")]
+        BlogPost { id: usize, slug: String },
+    }
+
+    assert_eq!(Route::from_str("/").unwrap(), Route::Root {});
+    assert_eq!(Route::from_str("/home").unwrap(), Route::Root {});
+    assert_eq!(Route::from_str("/index.html").unwrap(), Route::Root {});
+
+    let post = Route::BlogPost {
+        id: 1,
+        slug: "hello".to_string(),
+    };
+    assert_eq!(Route::from_str("/posts/1/hello").unwrap(), post);
+    assert_eq!(post.to_string(), "/blog/1/hello");
+    assert_eq!(
+        Route::from_str("/posts/1/hello").unwrap().canonical_url(),
+        "/blog/1/hello"
+    );
+
+    // Aliases are not part of the site map
+    assert_eq!(Route::static_routes(), vec![Route::Root {}]);
+}
+
+#[component]
+fn Type(name: String) -> Element {
+    unimplemented!()
+}
+
+#[test]
+fn url_builders() {
+    #[derive(Routable, Clone, PartialEq, Debug)]
+    enum Route {
+        #[route("/")]
+        Root {},
+        #[nest("/blog")]
+        #[route("/:id/:slug")]
+        BlogPost { id: usize, slug: String },
+        #[route("/type/:name")]
+        Type { name: String },
+    }
+
+    // The builders don't collide with methods of the enum
+    impl Route {
+        #[allow(unused)]
+        fn root() -> Self {
+            Route::Root {}
+        }
+    }
+
+    assert_eq!(RouteUrls::root(), "/");
+    assert_eq!(RouteUrls::r#type("rust".to_string()), "/blog/type/rust");
+    assert_eq!(
+        RouteUrls::blog_post(42, "hello-world".to_string()),
+        "/blog/42/hello-world"
+    );
+}
+
+#[test]
+fn canonical_url_strips_hash() {
+    #[derive(Routable, Clone, PartialEq, Debug)]
+    enum Route {
+        #[route("/:id?:query#:hash")]
+        Dynamic {
+            id: usize,
+            query: String,
+            hash: String,
+        },
+    }
+
+    #[component]
+    fn Dynamic(id: usize, query: String, hash: String) -> Element {
+        unimplemented!()
+    }
+
+    let route = Route::Dynamic {
+        id: 1,
+        query: "a".to_string(),
+        hash: "top".to_string(),
+    };
+    assert_eq!(route.to_string(), "/1?query=a#top");
+    assert_eq!(route.canonical_url(), "/1?query=a");
+}
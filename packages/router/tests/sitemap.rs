@@ -0,0 +1,165 @@
+use dioxus::prelude::*;
+use dioxus_router::sitemap::{ChangeFrequency, Sitemap, SitemapMetadata};
+
+#[derive(Routable, Clone, PartialEq, Debug)]
+enum ChildRoute {
+    #[route("/")]
+    #[sitemap(priority = 0.3)]
+    ChildRoot {},
+}
+
+#[derive(Routable, Clone, PartialEq, Debug)]
+enum Route {
+    #[route("/")]
+    #[sitemap(lastmod = "2024-11-01", changefreq = "daily", priority = 1.0)]
+    Root {},
+    #[route("/admin")]
+    #[sitemap(exclude)]
+    Admin {},
+    #[route("/blog/:id")]
+    Blog { id: String },
+    #[child("/child")]
+    Nested { child: ChildRoute },
+}
+
+#[component]
+fn Root() -> Element {
+    unimplemented!()
+}
+
+#[component]
+fn Admin() -> Element {
+    unimplemented!()
+}
+
+#[component]
+fn Blog(id: String) -> Element {
+    unimplemented!()
+}
+
+#[component]
+fn ChildRoot() -> Element {
+    unimplemented!()
+}
+
+#[test]
+fn sitemap_metadata_from_attributes() {
+    assert_eq!(
+        Route::Root {}.sitemap_metadata(),
+        SitemapMetadata {
+            lastmod: Some("2024-11-01"),
+            changefreq: Some(ChangeFrequency::Daily),
+            priority: Some(1.0),
+            exclude: false,
+        }
+    );
+    assert!(Route::Admin {}.sitemap_metadata().exclude);
+    assert_eq!(
+        Route::Blog { id: "a".into() }.sitemap_metadata(),
+        SitemapMetadata::default()
+    );
+    // Child routers inherit the metadata of the child route
+    assert_eq!(
+        Route::Nested {
+            child: ChildRoute::ChildRoot {}
+        }
+        .sitemap_metadata()
+        .priority,
+        Some(0.3)
+    );
+}
+
+#[test]
+fn sitemap_includes_static_and_enumerated_routes() {
+    let sitemap = Sitemap::<Route>::new("https://example.com/").static_routes(|| {
+        vec![
+            Route::Blog { id: "a&b".into() },
+            Route::Admin {},
+            // Duplicates of static routes are only included once
+            Route::Root {},
+        ]
+    });
+
+    assert_eq!(
+        sitemap
+            .urls()
+            .into_iter()
+            .map(|url| url.loc)
+            .collect::<Vec<_>>(),
+        vec![
+            "https://example.com/",
+            "https://example.com/child/",
+            "https://example.com/blog/a%26b",
+        ]
+    );
+
+    assert_eq!(
+        sitemap.to_xml(),
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<urlset xmlns="http://www.sitemaps.org/schemas/sitemap/0.9">
+  <url>
+    <loc>https://example.com/</loc>
+    <lastmod>2024-11-01</lastmod>
+    <changefreq>daily</changefreq>
+    <priority>1.0</priority>
+  </url>
+  <url>
+    <loc>https://example.com/child/</loc>
+    <priority>0.3</priority>
+  </url>
+  <url>
+    <loc>https://example.com/blog/a%26b</loc>
+  </url>
+</urlset>
+"#
+    );
+}
+
+#[test]
+fn robots_txt_links_sitemap() {
+    assert_eq!(
+        Sitemap::<Route>::new("https://example.com").robots_txt(),
+        "User-agent: *\nAllow: /\n\nSitemap: https://example.com/sitemap.xml\n"
+    );
+}
+
+#[test]
+fn priorities_keep_their_decimals() {
+    #[derive(Routable, Clone, PartialEq, Debug)]
+    enum Route {
+        #[route("/")]
+        #[sitemap(priority = 0.75)]
+        Root {},
+        #[route("/low")]
+        #[sitemap(priority = 0.125)]
+        Low {},
+        #[route("/hidden")]
+        #[sitemap(priority = 0.0)]
+        Hidden {},
+    }
+
+    #[component]
+    fn Root() -> Element {
+        unimplemented!()
+    }
+
+    #[component]
+    fn Low() -> Element {
+        unimplemented!()
+    }
+
+    #[component]
+    fn Hidden() -> Element {
+        unimplemented!()
+    }
+
+    let xml = Sitemap::<Route>::new("https://example.com").to_xml();
+    let priorities: Vec<_> = xml
+        .lines()
+        .filter_map(|line| line.trim().strip_prefix("<priority>"))
+        .collect();
+    assert_eq!(
+        priorities,
+        ["0.75</priority>", "0.125</priority>", "0.0</priority>"]
+    );
+}
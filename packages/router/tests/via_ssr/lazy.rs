@@ -0,0 +1,80 @@
+use std::rc::Rc;
+
+use dioxus::prelude::*;
+use dioxus_history::{History, MemoryHistory};
+use dioxus_router::components::HistoryProvider;
+
+fn render(path: Route) -> String {
+    let mut vdom = VirtualDom::new_with_props(App, AppProps { path });
+    vdom.rebuild_in_place();
+    dioxus_ssr::render(&vdom)
+}
+
+#[test]
+fn lazy_route_renders_component() {
+    assert_eq!(render(Route::Admin {}), "<h1>Admin</h1>",);
+}
+
+#[test]
+fn lazy_route_with_fallback_renders_component() {
+    assert_eq!(render(Route::Post { id: 42 }), "<h1>Post 42</h1>",);
+}
+
+#[test]
+fn lazy_route_renders_fallback_while_pending() {
+    assert_eq!(render(Route::Settings {}), "Loading...");
+}
+
+#[test]
+fn lazy_routes_do_not_affect_eager_routes() {
+    assert_eq!(render(Route::Home {}), "<h1>Home</h1>");
+}
+
+#[derive(Routable, Clone, PartialEq, Debug)]
+enum Route {
+    #[route("/")]
+    Home {},
+    #[route("/admin", lazy)]
+    Admin {},
+    #[route("/post/:id", BlogPost, lazy(fallback = Loading))]
+    Post { id: usize },
+    #[route("/settings", lazy(fallback = Loading))]
+    Settings {},
+}
+
+#[component]
+fn Home() -> Element {
+    rsx! { h1 { "Home" } }
+}
+
+#[component]
+fn Admin() -> Element {
+    rsx! { h1 { "Admin" } }
+}
+
+#[component]
+fn BlogPost(id: usize) -> Element {
+    rsx! { h1 { "Post {id}" } }
+}
+
+/// A route that never finishes loading, like a chunk that is still being downloaded
+#[component]
+fn Settings() -> Element {
+    use_resource(std::future::pending::<()>).suspend()?;
+    rsx! { h1 { "Settings" } }
+}
+
+#[component]
+fn Loading() -> Element {
+    rsx! { "Loading..." }
+}
+
+#[component]
+fn App(path: Route) -> Element {
+    rsx! {
+        HistoryProvider {
+            history:  move |_| Rc::new(MemoryHistory::with_initial_path(path.clone())) as Rc<dyn History>,
+            Router::<Route> {}
+        }
+    }
+}
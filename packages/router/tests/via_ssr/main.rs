@@ -1,5 +1,8 @@
+mod lazy;
 mod link;
+mod named_outlet;
 mod navigation;
 mod outlet;
 mod redirect;
+mod transition;
 mod without_index;
@@ -0,0 +1,106 @@
+use std::rc::Rc;
+
+use dioxus::prelude::*;
+use dioxus_history::{History, MemoryHistory};
+use dioxus_router::components::HistoryProvider;
+
+fn render(path: Route) -> String {
+    let mut vdom = VirtualDom::new_with_props(App, AppProps { path });
+    vdom.rebuild_in_place();
+    dioxus_ssr::render(&vdom)
+}
+
+#[test]
+fn slots_render_in_named_outlets() {
+    assert_eq!(
+        render(Route::Photos {}),
+        "<aside><p>Filters</p></aside><ul>Photos</ul><h2>Index</h2>"
+    );
+}
+
+#[test]
+fn routes_render_in_named_outlets_over_the_background_route() {
+    // The list keeps rendering in the default outlet while the photo is shown in the modal
+    assert_eq!(
+        render(Route::Photo { id: 1 }),
+        "<aside><p>Filters</p></aside><ul>Photos</ul><h2>Index</h2><dialog>Photo 1 in outlet modal</dialog>"
+    );
+}
+
+#[test]
+fn urls_encode_the_background_route() {
+    let route = Route::Comment {
+        id: 2,
+        from: Route::Photos {}.to_string(),
+    };
+    assert_eq!(route.to_string(), "/comment/2?from=%2F");
+    assert_eq!("/comment/2?from=%2F".parse::<Route>().unwrap(), route);
+    assert_eq!(
+        render(route),
+        "<aside><p>Filters</p></aside><ul>Photos</ul><h2>Index</h2><dialog>Comment 2</dialog>"
+    );
+
+    // Without a background route the default outlet is empty
+    assert_eq!(
+        render(Route::Comment {
+            id: 2,
+            from: String::new()
+        }),
+        "<aside></aside><ul>Photos</ul><dialog>Comment 2</dialog>"
+    );
+}
+
+#[derive(Routable, Clone, PartialEq, Debug)]
+#[rustfmt::skip]
+enum Route {
+    #[layout(Layout)]
+        #[route("/")]
+        #[slot("sidebar", Filters)]
+        Photos {},
+        #[route("/photo/:id", PhotoModal, outlet = "modal", background = "/")]
+        Photo { id: usize },
+        #[route("/comment/:id?:from", CommentModal, outlet = "modal", background = from)]
+        Comment { id: usize, from: String },
+}
+
+#[component]
+fn Layout() -> Element {
+    rsx! {
+        aside { Outlet::<Route> { name: "sidebar" } }
+        ul { "Photos" }
+        Outlet::<Route> {}
+        Outlet::<Route> { name: "modal" }
+    }
+}
+
+#[component]
+fn Photos() -> Element {
+    rsx! { h2 { "Index" } }
+}
+
+#[component]
+fn Filters() -> Element {
+    rsx! { p { "Filters" } }
+}
+
+#[component]
+fn PhotoModal(id: usize) -> Element {
+    let outlet = use_outlet_context::<Route>();
+    let name = outlet.name().unwrap_or_default().to_string();
+    rsx! { dialog { "Photo {id} in outlet {name}" } }
+}
+
+#[component]
+fn CommentModal(id: usize, from: String) -> Element {
+    rsx! { dialog { "Comment {id}" } }
+}
+
+#[component]
+fn App(path: Route) -> Element {
+    rsx! {
+        HistoryProvider {
+            history:  move |_| Rc::new(MemoryHistory::with_initial_path(path.clone())) as Rc<dyn History>,
+            Router::<Route> {}
+        }
+    }
+}
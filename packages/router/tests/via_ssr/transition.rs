@@ -0,0 +1,144 @@
+use dioxus::document::{Document, Eval, EvalError, Evaluator};
+use dioxus::prelude::*;
+use dioxus_core::NoOpMutations;
+use std::{
+    cell::{Cell, RefCell},
+    rc::Rc,
+    task::{Context, Poll, Waker},
+};
+
+thread_local! {
+    static PHASES: RefCell<Vec<String>> = const { RefCell::new(Vec::new()) };
+    static TRANSITION_DONE: Cell<bool> = const { Cell::new(false) };
+    static TRANSITION_WAKER: RefCell<Option<Waker>> = const { RefCell::new(None) };
+}
+
+#[test]
+fn transition_outlet_keeps_the_previous_route_until_the_transition_ends() {
+    let mut vdom = VirtualDom::new(app);
+    vdom.rebuild_in_place();
+    assert_eq!(
+        dioxus_ssr::render(&vdom),
+        "<nav></nav><div class=\"fade fade-entered\" data-transition=\"entered\"><p>Home entered</p></div>"
+    );
+
+    vdom.in_runtime(|| ScopeId::ROOT.in_runtime(|| root_router().unwrap().push(Route::Other {})));
+    for _ in 0..3 {
+        vdom.render_immediate(&mut NoOpMutations);
+        vdom.process_events();
+    }
+    vdom.render_immediate(&mut NoOpMutations);
+
+    // While the transition runs, the old route is exiting next to the new route that is entering
+    assert_eq!(
+        dioxus_ssr::render(&vdom),
+        concat!(
+            "<nav></nav>",
+            "<div class=\"fade fade-exiting\" data-transition=\"exiting\"><p>Home exiting</p></div>",
+            "<div class=\"fade fade-entering\" data-transition=\"entering\"><p>Other entering</p></div>"
+        )
+    );
+
+    finish_transition();
+    for _ in 0..3 {
+        vdom.process_events();
+        vdom.render_immediate(&mut NoOpMutations);
+    }
+    assert_eq!(
+        dioxus_ssr::render(&vdom),
+        "<nav></nav><div class=\"fade fade-entered\" data-transition=\"entered\"><p>Other entered</p></div>"
+    );
+
+    assert_eq!(
+        PHASES.with(|phases| phases.borrow().clone()),
+        [
+            "Home entered",
+            "Other entering",
+            "Home exiting",
+            "Other entered"
+        ]
+    );
+}
+
+fn app() -> Element {
+    use_context_provider(|| Rc::new(TransitionDocument::default()) as Rc<dyn Document>);
+    rsx! {
+        Router::<Route> {}
+    }
+}
+
+/// A document where the javascript that waits for the transition runs until [`finish_transition`] is called
+#[derive(Default)]
+struct TransitionDocument {
+    owner: Owner,
+}
+
+impl Document for TransitionDocument {
+    fn eval(&self, _: String) -> Eval {
+        Eval::new(self.owner.insert(Box::new(TransitionEvaluator)))
+    }
+}
+
+struct TransitionEvaluator;
+
+impl Evaluator for TransitionEvaluator {
+    fn send(&self, _: serde_json::Value) -> Result<(), EvalError> {
+        Err(EvalError::Unsupported)
+    }
+
+    fn poll_recv(&mut self, _: &mut Context<'_>) -> Poll<Result<serde_json::Value, EvalError>> {
+        Poll::Ready(Err(EvalError::Unsupported))
+    }
+
+    fn poll_join(&mut self, cx: &mut Context<'_>) -> Poll<Result<serde_json::Value, EvalError>> {
+        if TRANSITION_DONE.get() {
+            return Poll::Ready(Ok(true.into()));
+        }
+        TRANSITION_WAKER.set(Some(cx.waker().clone()));
+        Poll::Pending
+    }
+}
+
+fn finish_transition() {
+    TRANSITION_DONE.set(true);
+    if let Some(waker) = TRANSITION_WAKER.take() {
+        waker.wake();
+    }
+}
+
+#[derive(Routable, Clone, Debug, PartialEq)]
+#[rustfmt::skip]
+enum Route {
+    #[layout(Layout)]
+        #[route("/")]
+        Home {},
+        #[route("/other")]
+        Other {},
+}
+
+#[component]
+fn Layout() -> Element {
+    rsx! {
+        nav {}
+        TransitionOutlet::<Route> { class: "fade" }
+    }
+}
+
+fn record(name: &str) -> String {
+    let phase = use_transition_phase().unwrap().as_str();
+    let text = format!("{name} {phase}");
+    PHASES.with(|phases| phases.borrow_mut().push(text.clone()));
+    text
+}
+
+#[component]
+fn Home() -> Element {
+    let text = record("Home");
+    rsx! { p { "{text}" } }
+}
+
+#[component]
+fn Other() -> Element {
+    let text = record("Other");
+    rsx! { p { "{text}" } }
+}
@@ -10,6 +10,7 @@ use quote::quote;
 use server_fn_macro::server_macro_impl;
 use syn::{
     __private::ToTokens,
+    ext::IdentExt,
     parse::{Parse, ParseStream},
 };
 
@@ -44,6 +45,8 @@ use syn::{
 /// - `input`: the encoding for the arguments (defaults to `PostUrl`)
 /// - `output`: the encoding for the response (defaults to `Json`)
 /// - `client`: a custom `Client` implementation that will be used for this server fn
+/// - `protocol`: sets the input and output encoding and the client at once. Use `Websocket<In, Out>` to
+///   exchange messages with the client over a websocket
 /// - `encoding`: (legacy, may be deprecated in future) specifies the encoding, which may be one
 ///   of the following (not case sensitive)
 ///     - `"Url"`: `POST` request with URL-encoded arguments and JSON response
@@ -102,6 +105,58 @@ use syn::{
 /// }
 /// ```
 ///
+/// Multiple layers can also be passed to the `middleware` argument of the macro. The layers are
+/// applied in order, so the last layer in the list is the outermost layer and sees the request
+/// first. Layers are only created in the server build.
+///
+/// ```rust,ignore
+/// # use dioxus::prelude::*;
+/// # use std::time::Duration;
+/// #[server(middleware = [
+///     tower_http::timeout::TimeoutLayer::new(Duration::from_secs(5)),
+///     tower_http::trace::TraceLayer::new_for_http(),
+/// ])]
+/// pub async fn my_wacky_server_fn(input: Vec<String>) -> Result<usize, ServerFnError> {
+///     unimplemented!()
+/// }
+/// ```
+///
+/// Middleware runs inside of the server context of the request, so layers can read and modify the
+/// [`DioxusServerContext`](https://docs.rs/dioxus-fullstack/latest/dioxus_fullstack/prelude/struct.DioxusServerContext.html)
+/// with `server_context()`. The easiest way to write a layer that uses the server context is
+/// [`middleware_fn`](https://docs.rs/dioxus-fullstack/latest/dioxus_fullstack/prelude/middleware/fn.middleware_fn.html):
+///
+/// ```rust,ignore
+/// # use dioxus::prelude::*;
+/// use dioxus::fullstack::prelude::middleware::{middleware_fn, Next};
+/// use axum::{body::Body, http::{Request, Response, StatusCode}};
+///
+/// async fn require_auth(
+///     context: DioxusServerContext,
+///     request: Request<Body>,
+///     next: Next,
+/// ) -> Response<Body> {
+///     if request.headers().contains_key("authorization") {
+///         context.insert(UserId(1));
+///         next.run(request).await
+///     } else {
+///         Response::builder()
+///             .status(StatusCode::UNAUTHORIZED)
+///             .body(Body::empty())
+///             .unwrap()
+///     }
+/// }
+///
+/// #[derive(Clone)]
+/// struct UserId(u64);
+///
+/// #[server(middleware = [middleware_fn(require_auth)])]
+/// pub async fn whoami() -> Result<u64, ServerFnError> {
+///     let FromContext(UserId(id)) = extract().await?;
+///     Ok(id)
+/// }
+/// ```
+///
 /// ## Extracting additional data from requests
 ///
 /// Server functions automatically handle serialization and deserialization of arguments and responses.
@@ -119,6 +174,26 @@ use syn::{
 /// }
 /// ```
 ///
+/// ## Injecting dependencies
+///
+/// Parameters marked with `#[inject]` are resolved on the server from the providers set with
+/// `ServeConfigBuilder::providers`. They must have the type `Inject<T>`. They are not part of the
+/// arguments the client sends, so the function below is called with `count_posts(category)` on
+/// the client. If the value can't be resolved, the function returns a server error of its declared
+/// error type:
+///
+/// ```rust,ignore
+/// # use dioxus::prelude::*;
+/// #[derive(Clone)]
+/// struct DatabasePool;
+///
+/// #[server]
+/// pub async fn count_posts(category: String, #[inject] pool: Inject<DatabasePool>) -> Result<usize, ServerFnError> {
+///     let Inject(pool) = pool;
+///     unimplemented!()
+/// }
+/// ```
+///
 /// ## Sharing data with server functions
 ///
 /// You may need to share context with your server functions like a database pool. Server
@@ -147,106 +222,491 @@ use syn::{
 /// ```
 #[proc_macro_attribute]
 pub fn server(args: proc_macro::TokenStream, s: TokenStream) -> TokenStream {
-    // If there is no input codec, use json as the default
-    let args = default_json_codec(args);
+    let args = match syn::parse::<ServerFnArgs>(args) {
+        Ok(args) => args,
+        Err(err) => return err.to_compile_error().into(),
+    };
+
+    let mut function: syn::ItemFn = match syn::parse(s) {
+        Ok(function) => function,
+        Err(err) => return err.to_compile_error().into(),
+    };
+    inject_parameters(&mut function);
+
+    let mut server_fn_args = args.to_server_fn_args();
+    let wrapper = args.protocol.as_ref().map(|protocol| {
+        let (ident, wrapper) = protocol.wrapper(&function);
+        let protocol_args = protocol.server_fn_args(&ident);
+        server_fn_args.extend(quote! { #(#protocol_args,)* });
+        wrapper
+    });
+
+    // The server_fn macro reads middleware from attributes on the function
+    let middleware = args.middleware_attribute();
+    let body = quote! {
+        #middleware
+        #function
+    };
 
     match server_macro_impl(
-        args.into(),
-        s.into(),
+        server_fn_args,
+        body,
         Some(syn::parse_quote!(server_fn)),
         "/api",
         None,
         None,
     ) {
         Err(e) => e.to_compile_error().into(),
-        Ok(s) => s.to_token_stream().into(),
+        Ok(s) => quote! {
+            #wrapper
+            #s
+        }
+        .into(),
     }
 }
 
-fn default_json_codec(args: TokenStream) -> TokenStream {
-    // Try to parse the args
-    if let Err(err) = syn::parse::<ServerFnArgsMetadata>(args.clone()) {
-        return err.to_compile_error().into();
-    }
-    let Ok(args_metadata) = syn::parse::<ServerFnArgsMetadata>(args.clone()) else {
-        // If we fail to parse the args, forward them directly to the macro for diagnostics
-        return args;
-    };
-    let mut new_tokens = args;
+/// Move the `#[inject]` parameters of a server function into its body. They are resolved on the
+/// server, so they are not part of the arguments the client sends.
+fn inject_parameters(function: &mut syn::ItemFn) {
+    let mut injected = Vec::new();
+    function.sig.inputs = std::mem::take(&mut function.sig.inputs)
+        .into_iter()
+        .filter_map(|input| match input {
+            syn::FnArg::Typed(mut typed) => {
+                if take_inject_attribute(&mut typed) {
+                    injected.push(typed);
+                    None
+                } else {
+                    Some(syn::FnArg::Typed(typed))
+                }
+            }
+            input => Some(input),
+        })
+        .collect();
 
-    // Make sure the args always end with a comma
-    if args_metadata.requires_trailing_comma {
-        let default_comma: TokenStream = quote! {,}.into();
-        new_tokens.extend(default_comma);
-    }
+    let custom_error = custom_error(&function.sig.output);
+    let resolve = injected.iter().map(|syn::PatType { pat, ty, .. }| {
+        quote! {
+            let #pat: #ty = match <#ty>::resolve().await {
+                Ok(value) => value,
+                Err(err) => {
+                    return Err(server_fn::ServerFnError::<#custom_error>::ServerError(
+                        err.to_string(),
+                    ))
+                }
+            };
+        }
+    });
+    let stmts = &function.block.stmts;
+    function.block = syn::parse_quote!({
+        #(#resolve)*
+        #(#stmts)*
+    });
+}
 
-    // If the user didn't override the input codec, default to Json
-    if !args_metadata.manual_input {
-        let default_input: TokenStream = quote! {
-            input = server_fn::codec::Json,
+/// Remove the `#[inject]` attribute from a parameter. Returns if the parameter had the attribute.
+fn take_inject_attribute(input: &mut syn::PatType) -> bool {
+    let attributes = input.attrs.len();
+    input.attrs.retain(|attr| !attr.path().is_ident("inject"));
+    input.attrs.len() != attributes
+}
+
+/// The custom error `E` of a server function that returns `Result<T, ServerFnError<E>>`. Errors are
+/// created with the declared error type, so resolving a parameter fails the same way as the
+/// function itself. The type is inferred if the function returns an alias of `Result`.
+fn custom_error(output: &syn::ReturnType) -> proc_macro2::TokenStream {
+    let syn::ReturnType::Type(_, ty) = output else {
+        return quote! { _ };
+    };
+    let error = match &**ty {
+        syn::Type::Path(path) => {
+            path.path
+                .segments
+                .last()
+                .and_then(|segment| match &segment.arguments {
+                    syn::PathArguments::AngleBracketed(args) if segment.ident == "Result" => {
+                        args.args.iter().nth(1)
+                    }
+                    _ => None,
+                })
         }
-        .into();
-        new_tokens.extend(default_input);
+        _ => None,
+    };
+    let Some(syn::GenericArgument::Type(syn::Type::Path(error))) = error else {
+        return quote! { _ };
+    };
+    match error.path.segments.last() {
+        Some(segment) if segment.ident == "ServerFnError" => match &segment.arguments {
+            syn::PathArguments::None => quote! { server_fn::error::NoCustomError },
+            syn::PathArguments::AngleBracketed(args) => args.args.to_token_stream(),
+            _ => quote! { _ },
+        },
+        _ => quote! { _ },
     }
+}
 
-    // If the user didn't override the output codec, default to Json
-    if !args_metadata.manual_output {
-        let default_output: TokenStream = quote! {
-            output = server_fn::codec::Json,
-        }
-        .into();
-        new_tokens.extend(default_output);
+/// A protocol like `Websocket<In, Out>` from the `protocol = ..` argument
+struct Protocol {
+    ty: syn::Type,
+    /// The `{Protocol}Args` trait next to the protocol that encodes the arguments
+    args: syn::Path,
+}
+
+impl Protocol {
+    fn new(ty: syn::Type) -> syn::Result<Self> {
+        let syn::Type::Path(syn::TypePath { qself: None, path }) = &ty else {
+            return Err(syn::Error::new_spanned(
+                ty,
+                "expected a protocol like `Websocket<In, Out>`",
+            ));
+        };
+        let mut args = path.clone();
+        let last = args.segments.last_mut().unwrap();
+        last.ident = quote::format_ident!("{}Args", last.ident);
+        last.arguments = syn::PathArguments::None;
+
+        Ok(Self { ty, args })
+    }
+
+    /// The encodings and client of the server function. `wrapper` is the type the arguments are
+    /// wrapped in.
+    fn server_fn_args(&self, wrapper: &syn::Ident) -> [proc_macro2::TokenStream; 5] {
+        let ty = &self.ty;
+        [
+            quote! { input = #ty },
+            quote! { output = #ty },
+            quote! { client = #ty },
+            quote! { input_derive = () },
+            quote! { custom = #wrapper },
+        ]
     }
 
-    new_tokens
+    /// The type the arguments of the function are wrapped in. The server_fn macro implements
+    /// `ServerFn` for the wrapper, so it must be defined in the crate of the function. The
+    /// encodings of the wrapper are implemented with the `{Protocol}Args` trait.
+    fn wrapper(&self, function: &syn::ItemFn) -> (syn::Ident, proc_macro2::TokenStream) {
+        let name: String = function
+            .sig
+            .ident
+            .unraw()
+            .to_string()
+            .split('_')
+            .flat_map(|word| {
+                let mut chars = word.chars();
+                chars
+                    .next()
+                    .map(|first| first.to_ascii_uppercase())
+                    .into_iter()
+                    .chain(chars)
+            })
+            .collect();
+        let ident = quote::format_ident!("{name}ProtocolArgs", span = function.sig.ident.span());
+        let (ty, args, vis) = (&self.ty, &self.args, &function.vis);
+
+        let wrapper = quote! {
+            #[doc(hidden)]
+            #vis struct #ident<T>(pub T);
+
+            impl<T, Request, CustErr> server_fn::codec::IntoReq<#ty, Request, CustErr> for #ident<T>
+            where
+                #ty: #args<T, Request, CustErr>,
+            {
+                fn into_req(
+                    self,
+                    path: &str,
+                    accepts: &str,
+                ) -> Result<Request, server_fn::ServerFnError<CustErr>> {
+                    <#ty as #args<T, Request, CustErr>>::into_req(self.0, path, accepts)
+                }
+            }
+
+            impl<T, Request, CustErr> server_fn::codec::FromReq<#ty, Request, CustErr> for #ident<T>
+            where
+                #ty: #args<T, Request, CustErr>,
+            {
+                fn from_req(
+                    req: Request,
+                ) -> impl std::future::Future<Output = Result<Self, server_fn::ServerFnError<CustErr>>> + Send {
+                    let args = <#ty as #args<T, Request, CustErr>>::from_req(req);
+                    async move { args.await.map(Self) }
+                }
+            }
+        };
+        (ident, wrapper)
+    }
 }
 
-struct ServerFnArgsMetadata {
+/// The arguments of the server macro. Most arguments are forwarded to the server_fn macro as is.
+struct ServerFnArgs {
+    /// The arguments that are forwarded to the server_fn macro
+    forwarded: Vec<proc_macro2::TokenStream>,
+    /// The layers from the `middleware = [..]` argument
+    middlewares: Vec<syn::Expr>,
+    protocol: Option<Protocol>,
     manual_input: bool,
     manual_output: bool,
-    requires_trailing_comma: bool,
 }
 
-impl Parse for ServerFnArgsMetadata {
+impl ServerFnArgs {
+    fn to_server_fn_args(&self) -> proc_macro2::TokenStream {
+        let forwarded = &self.forwarded;
+        // If there is no input or output codec, use json as the default
+        let input = (!self.manual_input).then(|| quote! { input = server_fn::codec::Json });
+        let output = (!self.manual_output).then(|| quote! { output = server_fn::codec::Json });
+        let args = forwarded.iter().chain(input.iter()).chain(output.iter());
+        quote! { #(#args,)* }
+    }
+
+    /// The `#[middleware]` attribute for the layers of the `middleware = [..]` argument. The server_fn
+    /// macro doesn't separate the layers of multiple attributes with commas, so several layers are
+    /// combined into one layer that applies them in order.
+    fn middleware_attribute(&self) -> Option<proc_macro2::TokenStream> {
+        match self.middlewares.as_slice() {
+            [] => None,
+            [middleware] => Some(quote! { #[middleware(#middleware)] }),
+            middlewares => Some(quote! {
+                #[middleware({
+                    struct Layers<Req, Res>(
+                        Vec<std::sync::Arc<dyn server_fn::middleware::Layer<Req, Res>>>,
+                    );
+                    impl<Req: 'static, Res: 'static> server_fn::middleware::Layer<Req, Res>
+                        for Layers<Req, Res>
+                    {
+                        fn layer(
+                            &self,
+                            inner: server_fn::middleware::BoxedService<Req, Res>,
+                        ) -> server_fn::middleware::BoxedService<Req, Res> {
+                            self.0.iter().fold(inner, |inner, layer| layer.layer(inner))
+                        }
+                    }
+                    Layers(vec![#(
+                        std::sync::Arc::new(#middlewares)
+                            as std::sync::Arc<dyn server_fn::middleware::Layer<_, _>>
+                    ),*])
+                })]
+            }),
+        }
+    }
+}
+
+impl Parse for ServerFnArgs {
     fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut forwarded = Vec::new();
+        let mut middlewares = Vec::new();
+        let mut protocol = None;
         let mut manual_input = false;
         let mut manual_output = false;
-        let mut requires_trailing_comma = false;
-        let mut take_comma = |input: &ParseStream| {
-            let comma: Option<syn::Token![,]> = input.parse().ok();
-            requires_trailing_comma = comma.is_none();
-        };
 
         while !input.is_empty() {
-            // Ignore legacy ident and string args
+            // Forward legacy ident and string args
             if input.peek(syn::Ident) && !input.peek2(syn::Token![=]) {
-                input.parse::<syn::Ident>()?;
-                take_comma(&input);
-                continue;
-            }
-            if input.peek(syn::LitStr) && !input.peek2(syn::Token![=]) {
-                input.parse::<syn::LitStr>()?;
-                take_comma(&input);
-                continue;
-            }
+                forwarded.push(input.parse::<syn::Ident>()?.to_token_stream());
+            } else if input.peek(syn::LitStr) && !input.peek2(syn::Token![=]) {
+                forwarded.push(input.parse::<syn::LitStr>()?.to_token_stream());
+            } else {
+                let ident: syn::Ident = input.parse()?;
+                let _: syn::Token![=] = input.parse()?;
 
-            let ident: syn::Ident = input.parse()?;
-            let _: syn::Token![=] = input.parse()?;
-            let _: syn::Expr = input.parse()?;
+                if ident == "protocol" {
+                    protocol = Some(Protocol::new(input.parse()?)?);
+                    manual_input = true;
+                    manual_output = true;
+                    if !input.is_empty() {
+                        input.parse::<syn::Token![,]>()?;
+                    }
+                    continue;
+                }
 
-            if ident == "input" {
-                manual_input = true;
-            } else if ident == "output" {
-                manual_output = true;
+                let value: syn::Expr = input.parse()?;
+
+                if ident == "middleware" {
+                    match value {
+                        syn::Expr::Array(array) => middlewares.extend(array.elems),
+                        value => middlewares.push(value),
+                    }
+                } else {
+                    if ident == "input" {
+                        manual_input = true;
+                    } else if ident == "output" {
+                        manual_output = true;
+                    }
+                    forwarded.push(quote! { #ident = #value });
+                }
             }
 
-            take_comma(&input);
+            if !input.is_empty() {
+                input.parse::<syn::Token![,]>()?;
+            }
         }
 
         Ok(Self {
+            forwarded,
+            middlewares,
+            protocol,
             manual_input,
             manual_output,
-            requires_trailing_comma,
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &str) -> syn::Result<ServerFnArgs> {
+        syn::parse_str(args)
+    }
+
+    fn strings(tokens: &[impl ToTokens]) -> Vec<String> {
+        tokens
+            .iter()
+            .map(|tokens| tokens.to_token_stream().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn mixed_positional_and_named_args() {
+        let args = parse(
+            r#"AddTodo, "/api", middleware = [TimeoutLayer::new(5), middleware_fn(auth)], input = GetUrl"#,
+        )
+        .unwrap();
+        assert_eq!(
+            strings(&args.forwarded),
+            ["AddTodo", "\"/api\"", "input = GetUrl"]
+        );
+        assert_eq!(
+            strings(&args.middlewares),
+            ["TimeoutLayer :: new (5)", "middleware_fn (auth)"]
+        );
+        assert!(args.manual_input);
+        assert!(!args.manual_output);
+        assert_eq!(
+            args.to_server_fn_args().to_string(),
+            "AddTodo , \"/api\" , input = GetUrl , output = server_fn :: codec :: Json ,"
+        );
+    }
+
+    #[test]
+    fn single_middleware_without_brackets() {
+        let args = parse("middleware = TimeoutLayer::new(5), endpoint = \"todos\"").unwrap();
+        assert_eq!(strings(&args.middlewares), ["TimeoutLayer :: new (5)"]);
+        assert_eq!(strings(&args.forwarded), ["endpoint = \"todos\""]);
+    }
+
+    #[test]
+    fn trailing_commas() {
+        for input in [
+            "middleware = [A, B,],",
+            "middleware = [A, B,], input = GetUrl,",
+            "AddTodo, middleware = [A, B],",
+        ] {
+            let args = parse(input).unwrap();
+            assert_eq!(strings(&args.middlewares), ["A", "B"], "{input}");
+        }
+        assert!(parse("").unwrap().middlewares.is_empty());
+    }
+
+    #[test]
+    fn protocols_set_the_encodings_and_client() {
+        let args = parse("protocol = Websocket<String, u32>,").unwrap();
+        assert!(args.forwarded.is_empty());
+        assert!(args.manual_input && args.manual_output);
+
+        let protocol = args.protocol.unwrap();
+        let function = syn::parse_quote! {
+            async fn r#shout_back(input: WebsocketStream<String>) -> Result<WebsocketStream<u32>, ServerFnError> {}
+        };
+        let (wrapper, tokens) = protocol.wrapper(&function);
+        assert_eq!(wrapper, "ShoutBackProtocolArgs");
+        assert_eq!(
+            strings(&protocol.server_fn_args(&wrapper)),
+            [
+                "input = Websocket < String , u32 >",
+                "output = Websocket < String , u32 >",
+                "client = Websocket < String , u32 >",
+                "input_derive = ()",
+                "custom = ShoutBackProtocolArgs",
+            ]
+        );
+        // The encodings of the wrapper are implemented with the trait next to the protocol
+        assert!(tokens.to_string().contains(
+            "where Websocket < String , u32 > : WebsocketArgs < T , Request , CustErr >"
+        ));
+    }
+
+    #[test]
+    fn only_marked_parameters_are_injected() {
+        let mut function: syn::ItemFn = syn::parse_quote! {
+            async fn count(
+                category: String,
+                #[inject] pool: Inject<Pool>,
+                inject: Inject<String>,
+            ) -> Result<usize, ServerFnError<MyError>> {
+                Ok(0)
+            }
+        };
+        inject_parameters(&mut function);
+        assert_eq!(
+            strings(&function.sig.inputs.iter().collect::<Vec<_>>()),
+            ["category : String", "inject : Inject < String >"]
+        );
+        let body = function.block.to_token_stream().to_string();
+        assert!(
+            body.contains("let pool : Inject < Pool > = match < Inject < Pool > > :: resolve ()")
+        );
+        assert!(body.contains("server_fn :: ServerFnError :: < MyError > :: ServerError"));
+    }
+
+    #[test]
+    fn injection_errors_use_the_declared_error_type() {
+        for (output, error) in [
+            ("-> Result<u8, ServerFnError<MyError>>", "MyError"),
+            (
+                "-> Result<u8, server_fn::ServerFnError>",
+                "server_fn :: error :: NoCustomError",
+            ),
+            ("-> ServerFnResult<u8>", "_"),
+            ("-> Result<u8>", "_"),
+        ] {
+            let output: syn::ReturnType = syn::parse_str(output).unwrap();
+            assert_eq!(custom_error(&output).to_string(), error, "{output:?}");
+        }
+    }
+
+    #[test]
+    fn errors_point_at_the_invalid_tokens() {
+        // Returns the message and the source text the error points at
+        let error = |input: &str| {
+            let error = parse(input).err().unwrap();
+            let span = error.span();
+            let (start, end) = (span.start().column, span.end().column);
+            (
+                error.to_string(),
+                input.get(start..end).unwrap_or_default().to_string(),
+            )
+        };
+
+        for (input, message, at) in [
+            ("middleware = [A B]", "expected `,` or `;`", "B"),
+            ("input = GetUrl output = Json", "expected `,`", "output"),
+            ("= Json", "expected identifier", "="),
+            ("endpoint \"todos\"", "expected `,`", "\"todos\""),
+            (
+                "middleware =",
+                "unexpected end of input, expected an expression",
+                "",
+            ),
+            (
+                "protocol = &str",
+                "expected a protocol like `Websocket<In, Out>`",
+                "&str",
+            ),
+        ] {
+            assert_eq!(
+                error(input),
+                (message.to_string(), at.to_string()),
+                "{input}"
+            );
+        }
+    }
+}
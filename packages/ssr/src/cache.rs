@@ -30,13 +30,23 @@
 //!```
 
 use dioxus_core::prelude::*;
-use std::{fmt::Write, ops::AddAssign};
+use std::{borrow::Cow, fmt::Write, ops::AddAssign};
 
 use crate::renderer::{str_truthy, BOOL_ATTRS};
 
 #[derive(Debug)]
 pub(crate) struct StringCache {
     pub segments: Vec<Segment>,
+    /// The dynamic nodes that are inside an element that preserves whitespace, like `pre`
+    pub preformatted_nodes: Vec<usize>,
+}
+
+/// How the static parts of a template are minified
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) struct Minify {
+    /// If whitespace in text is collapsed. This is false for templates that are rendered inside an element that
+    /// preserves whitespace
+    pub collapse_whitespace: bool,
 }
 
 #[derive(Default)]
@@ -45,6 +55,8 @@ pub struct StringChain {
     // This will be true if the last segment is a static text and the last text isn't part of a hydration only boundary
     add_text_to_last_segment: bool,
     segments: Vec<Segment>,
+    minify: Option<Minify>,
+    preformatted_nodes: Vec<usize>,
 }
 
 impl StringChain {
@@ -96,6 +108,9 @@ pub(crate) enum Segment {
     },
     /// A marker for where to insert a dynamic inner html
     InnerHtmlMarker,
+    /// Inner html that is static in the template. It is kept apart from the pre-rendered text, so the renderer can
+    /// apply its [`InnerHtmlPolicy`](crate::InnerHtmlPolicy)
+    StaticInnerHtml(String),
     /// A marker for where to insert a node id for an attribute
     AttributeNodeMarker,
     /// A marker for where to insert a node id for a root node
@@ -121,28 +136,57 @@ impl std::fmt::Write for StringChain {
 
 impl StringCache {
     /// Create a new string cache from a template. This intentionally does not include any settings about the render mode (hydration or not) so that we can reuse the cache for both hydration and non-hydration renders.
-    pub fn from_template(template: &VNode) -> Result<Self, std::fmt::Error> {
-        let mut chain = StringChain::default();
+    ///
+    /// Minified output is different for every render mode, so minified templates are cached separately.
+    pub fn from_template(
+        template: &VNode,
+        minify: Option<Minify>,
+    ) -> Result<Self, std::fmt::Error> {
+        let mut chain = StringChain {
+            minify,
+            ..Default::default()
+        };
 
         let mut cur_path = vec![];
 
+        // The siblings of the roots are outside of the template, so we don't know what they are
+        let position = Position {
+            is_root: true,
+            parent: None,
+            next: None,
+            preformatted: minify.is_some_and(|minify| !minify.collapse_whitespace),
+        };
         for (root_idx, root) in template.template.roots.iter().enumerate() {
-            from_template_recursive(root, &mut cur_path, root_idx, true, &mut chain)?;
+            from_template_recursive(root, &mut cur_path, root_idx, position, &mut chain)?;
         }
 
         Ok(Self {
             segments: chain.segments,
+            preformatted_nodes: chain.preformatted_nodes,
         })
     }
 }
 
+/// Where a node is in its template
+#[derive(Clone, Copy)]
+struct Position<'a> {
+    is_root: bool,
+    /// The tag of the element the node is in
+    parent: Option<&'a str>,
+    /// The node after this node in the same element. This is `None` if it is the last node in the element
+    next: Option<&'a TemplateNode>,
+    /// If the node is inside an element that preserves whitespace
+    preformatted: bool,
+}
+
 fn from_template_recursive(
     root: &TemplateNode,
     cur_path: &mut Vec<usize>,
     root_idx: usize,
-    is_root: bool,
+    position: Position,
     chain: &mut StringChain,
 ) -> Result<(), std::fmt::Error> {
+    let is_root = position.is_root;
     match root {
         TemplateNode::Element {
             tag,
@@ -170,7 +214,11 @@ fn from_template_recursive(
                         } else if let Some("style") = namespace {
                             styles.push((name, value));
                         } else if BOOL_ATTRS.contains(name) {
-                            if str_truthy(value) {
+                            if !str_truthy(value) {
+                            } else if chain.minify.is_some() {
+                                // The value of a boolean attribute doesn't matter, only if it is present
+                                write!(chain, " {name}")?;
+                            } else {
                                 write!(chain, " {name}=\"{value}\"",)?;
                             }
                         } else {
@@ -221,15 +269,24 @@ fn from_template_recursive(
                 write!(chain, ">")?;
                 // Write the static inner html, or insert a marker if dynamic inner html is possible
                 if let Some(inner_html) = inner_html {
-                    chain.write_str(inner_html)?;
+                    *chain += Segment::StaticInnerHtml(inner_html.to_string());
                 } else if has_dyn_attrs {
                     *chain += Segment::InnerHtmlMarker;
                 }
 
-                for child in *children {
-                    from_template_recursive(child, cur_path, root_idx, false, chain)?;
+                let preformatted = position.preformatted || preserves_whitespace(tag);
+                for (index, child) in children.iter().enumerate() {
+                    let position = Position {
+                        is_root: false,
+                        parent: Some(tag),
+                        next: children.get(index + 1),
+                        preformatted,
+                    };
+                    from_template_recursive(child, cur_path, root_idx, position, chain)?;
+                }
+                if chain.minify.is_none() || is_root || !end_tag_is_optional(tag, position) {
+                    write!(chain, "</{tag}>")?;
                 }
-                write!(chain, "</{tag}>")?;
             }
             cur_path.pop();
         }
@@ -243,22 +300,31 @@ fn from_template_recursive(
                     std::fmt::Result::Ok(())
                 })?;
             }
+            let text = match chain.minify {
+                Some(_) if !position.preformatted => collapse_whitespace(text),
+                _ => Cow::Borrowed(*text),
+            };
             write!(
                 chain,
                 "{}",
-                askama_escape::escape(text, askama_escape::Html)
+                askama_escape::escape(&text, askama_escape::Html)
             )?;
             if is_root {
                 chain.if_hydration_enabled(|chain| write!(chain, "<!--#-->"))?;
             }
         }
-        TemplateNode::Dynamic { id: idx } => *chain += Segment::Node(*idx),
+        TemplateNode::Dynamic { id: idx } => {
+            if position.preformatted {
+                chain.preformatted_nodes.push(*idx);
+            }
+            *chain += Segment::Node(*idx)
+        }
     }
 
     Ok(())
 }
 
-fn tag_is_self_closing(tag: &str) -> bool {
+pub(crate) fn tag_is_self_closing(tag: &str) -> bool {
     matches!(
         tag,
         "area"
@@ -277,3 +343,105 @@ fn tag_is_self_closing(tag: &str) -> bool {
             | "wbr"
     )
 }
+
+/// Elements whose text is rendered with its whitespace
+fn preserves_whitespace(tag: &str) -> bool {
+    matches!(
+        tag,
+        "pre" | "textarea" | "script" | "style" | "listing" | "plaintext" | "xmp"
+    )
+}
+
+/// Collapse every run of whitespace into a single space like browsers do when they render text
+pub(crate) fn collapse_whitespace(text: &str) -> Cow<'_, str> {
+    let mut previous_whitespace = false;
+    let needs_collapse = text.chars().any(|c| {
+        let whitespace = c.is_ascii_whitespace();
+        let collapse = whitespace && (previous_whitespace || c != ' ');
+        previous_whitespace = whitespace;
+        collapse
+    });
+    if !needs_collapse {
+        return Cow::Borrowed(text);
+    }
+
+    let mut collapsed = String::with_capacity(text.len());
+    for c in text.chars() {
+        if !c.is_ascii_whitespace() {
+            collapsed.push(c);
+        } else if !collapsed.ends_with(' ') {
+            collapsed.push(' ');
+        }
+    }
+    Cow::Owned(collapsed)
+}
+
+/// Check if the end tag of an element can be left out because the next node closes the element anyway.
+///
+/// Only the nodes of the template are known, so end tags are only left out if the next node in the template allows
+/// it, or the element is the last node in its parent in the template.
+/// See <https://html.spec.whatwg.org/multipage/syntax.html#optional-tags>
+fn end_tag_is_optional(tag: &str, position: Position) -> bool {
+    let next = match position.next {
+        Some(TemplateNode::Element { tag, .. }) => Some(*tag),
+        // Text or a dynamic node could be anything
+        Some(_) => return false,
+        None => None,
+    };
+    match tag {
+        "li" => matches!(next, None | Some("li")),
+        "dt" => matches!(next, Some("dt" | "dd")),
+        "dd" => matches!(next, None | Some("dt" | "dd")),
+        "td" | "th" => matches!(next, None | Some("td" | "th")),
+        "tr" => matches!(next, None | Some("tr")),
+        "thead" => matches!(next, Some("tbody" | "tfoot")),
+        "tbody" => matches!(next, None | Some("tbody" | "tfoot")),
+        "tfoot" => next.is_none(),
+        "option" => matches!(next, None | Some("option" | "optgroup")),
+        "optgroup" => matches!(next, None | Some("optgroup")),
+        "p" => match next {
+            Some(next) => matches!(
+                next,
+                "address"
+                    | "article"
+                    | "aside"
+                    | "blockquote"
+                    | "details"
+                    | "dialog"
+                    | "div"
+                    | "dl"
+                    | "fieldset"
+                    | "figcaption"
+                    | "figure"
+                    | "footer"
+                    | "form"
+                    | "h1"
+                    | "h2"
+                    | "h3"
+                    | "h4"
+                    | "h5"
+                    | "h6"
+                    | "header"
+                    | "hgroup"
+                    | "hr"
+                    | "main"
+                    | "menu"
+                    | "nav"
+                    | "ol"
+                    | "p"
+                    | "pre"
+                    | "search"
+                    | "section"
+                    | "table"
+                    | "ul"
+            ),
+            // Custom elements don't close paragraphs
+            None => match position.parent {
+                Some("a" | "audio" | "del" | "ins" | "map" | "noscript" | "video") => false,
+                Some(parent) => !parent.contains('-'),
+                None => false,
+            },
+        },
+        _ => false,
+    }
+}
@@ -0,0 +1,534 @@
+//! Render a [`VirtualDom`] to HTML that email clients display the same way browsers do.
+//!
+//! Many email clients drop `<style>` blocks and ignore classes, so the [`EmailRenderer`] moves the rules of every
+//! `<style>` block into the `style` attribute of the elements they match. Rules that can't be inlined, like `@media`
+//! queries or `:hover` styles, are kept in a single `<style>` block for the clients that support them.
+
+use std::fmt::Write;
+
+use dioxus_core::{Element, VirtualDom};
+
+use crate::{
+    cache::tag_is_self_closing,
+    tree::{self, parse_declarations, split_outside_quotes, Node},
+};
+
+/// A renderer for transactional emails.
+///
+/// Compared to the [`Renderer`](crate::Renderer), the email renderer:
+/// - inlines the styles of `<style>` blocks into the elements they match
+/// - never writes hydration markers
+/// - sets `cellpadding`, `cellspacing` and `border` to `0` on tables that don't set them, and mirrors the `width`,
+///   `background-color`, `text-align` and `vertical-align` styles of tables and cells into the attributes older
+///   clients read instead
+///
+/// Selectors made of tags, classes, ids and the descendant and child combinators are inlined.
+///
+/// ```rust
+/// # use dioxus::prelude::*;
+/// let html = dioxus_ssr::EmailRenderer::new().render_element(rsx! {
+///     style { "p.note {{ color: gray }}" }
+///     p { class: "note", font_size: "12px", "Thanks for your order" }
+/// });
+/// assert_eq!(
+///     html,
+///     r#"<p class="note" style="color:gray;font-size:12px">Thanks for your order</p>"#
+/// );
+/// ```
+#[derive(Default)]
+pub struct EmailRenderer {}
+
+impl EmailRenderer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn render(&self, dom: &VirtualDom) -> String {
+        let mut buf = String::new();
+        self.render_to(&mut buf, dom).unwrap();
+        buf
+    }
+
+    pub fn render_to<W: Write + ?Sized>(&self, buf: &mut W, dom: &VirtualDom) -> std::fmt::Result {
+        let mut nodes = tree::build(dom);
+        let mut css = String::new();
+        take_style_blocks(&mut nodes, &mut css);
+
+        let mut writer = EmailWriter {
+            stylesheet: Stylesheet::parse(&css),
+            ancestors: Vec::new(),
+            remaining_written: false,
+        };
+        // Rules that can't be inlined go into the head if there is one
+        if !contains_head(&nodes) {
+            writer.write_remaining(buf)?;
+        }
+        for node in &nodes {
+            writer.write_node(buf, node)?;
+        }
+        Ok(())
+    }
+
+    /// Render an element to a string
+    pub fn render_element(&self, element: Element) -> String {
+        fn lazy_app(props: Element) -> Element {
+            props
+        }
+        let mut dom = VirtualDom::new_with_props(lazy_app, element);
+        dom.rebuild_in_place();
+        self.render(&dom)
+    }
+}
+
+/// Remove every `<style>` element from the tree and collect their css
+fn take_style_blocks(nodes: &mut Vec<Node>, css: &mut String) {
+    nodes.retain_mut(|node| match node {
+        Node::Element(element) if element.tag == "style" => {
+            css.push_str(&element.text());
+            css.push_str(element.inner_html.as_deref().unwrap_or_default());
+            css.push('\n');
+            false
+        }
+        Node::Element(element) => {
+            take_style_blocks(&mut element.children, css);
+            true
+        }
+        Node::Text(_) => true,
+    });
+}
+
+fn contains_head(nodes: &[Node]) -> bool {
+    nodes.iter().any(|node| match node {
+        Node::Element(element) => element.tag == "head" || contains_head(&element.children),
+        Node::Text(_) => false,
+    })
+}
+
+struct EmailWriter<'a> {
+    stylesheet: Stylesheet,
+    ancestors: Vec<&'a tree::Element>,
+    remaining_written: bool,
+}
+
+impl<'a> EmailWriter<'a> {
+    fn write_remaining<W: Write + ?Sized>(&mut self, buf: &mut W) -> std::fmt::Result {
+        if !self.stylesheet.remaining.is_empty() && !self.remaining_written {
+            write!(buf, "<style>{}</style>", self.stylesheet.remaining)?;
+        }
+        self.remaining_written = true;
+        Ok(())
+    }
+
+    fn write_node<W: Write + ?Sized>(&mut self, buf: &mut W, node: &'a Node) -> std::fmt::Result {
+        let element = match node {
+            Node::Text(text) => {
+                return write!(buf, "{}", askama_escape::escape(text, askama_escape::Html))
+            }
+            Node::Element(element) => element,
+        };
+
+        let styles = self.stylesheet.styles(element, &self.ancestors);
+        let mut attrs: Vec<(&str, &str)> = element
+            .attrs
+            .iter()
+            .map(|(name, value)| (*name, value.as_str()))
+            .collect();
+        if matches!(element.tag, "table" | "td" | "th") {
+            table_attributes(element.tag, &styles, &mut attrs);
+        }
+
+        write!(buf, "<{}", element.tag)?;
+        for (name, value) in attrs {
+            write!(
+                buf,
+                " {name}=\"{}\"",
+                askama_escape::escape(value, askama_escape::Html)
+            )?;
+        }
+        if !styles.is_empty() {
+            let styles = styles
+                .iter()
+                .map(|(name, value)| format!("{name}:{value}"))
+                .collect::<Vec<_>>()
+                .join(";");
+            write!(
+                buf,
+                " style=\"{}\"",
+                askama_escape::escape(&styles, askama_escape::Html)
+            )?;
+        }
+
+        if element.children.is_empty()
+            && element.inner_html.is_none()
+            && tag_is_self_closing(element.tag)
+        {
+            return write!(buf, "/>");
+        }
+        write!(buf, ">")?;
+        if element.tag == "head" {
+            self.write_remaining(buf)?;
+        }
+        if let Some(inner_html) = &element.inner_html {
+            write!(buf, "{inner_html}")?;
+        }
+        self.ancestors.push(element);
+        for child in &element.children {
+            self.write_node(buf, child)?;
+        }
+        self.ancestors.pop();
+        write!(buf, "</{}>", element.tag)
+    }
+}
+
+/// Add the presentational attributes older email clients use to lay out tables instead of css
+fn table_attributes<'a>(
+    tag: &str,
+    styles: &'a [(String, String)],
+    attrs: &mut Vec<(&str, &'a str)>,
+) {
+    let style = |name: &str| {
+        styles
+            .iter()
+            .rev()
+            .find(|(style, _)| style == name)
+            .map(|(_, value)| value.as_str())
+    };
+    let mut defaults = Vec::new();
+    if tag == "table" {
+        defaults.extend([("cellpadding", "0"), ("cellspacing", "0"), ("border", "0")]);
+    }
+    if let Some(width) = style("width") {
+        if let Some(pixels) = width.strip_suffix("px") {
+            defaults.push(("width", pixels));
+        } else if width.ends_with('%') {
+            defaults.push(("width", width));
+        }
+    }
+    if let Some(color) = style("background-color") {
+        defaults.push(("bgcolor", color));
+    }
+    if tag != "table" {
+        if let Some(align) = style("text-align") {
+            defaults.push(("align", align));
+        }
+        if let Some(align) = style("vertical-align") {
+            defaults.push(("valign", align));
+        }
+    }
+    for (name, value) in defaults {
+        if !attrs.iter().any(|(attr, _)| *attr == name) {
+            attrs.push((name, value));
+        }
+    }
+}
+
+/// The rules of the `<style>` blocks of an email
+#[derive(Default)]
+struct Stylesheet {
+    rules: Vec<Rule>,
+    /// The css of the rules that can't be inlined
+    remaining: String,
+}
+
+struct Rule {
+    selector: Selector,
+    declarations: Vec<Declaration>,
+}
+
+#[derive(Clone)]
+struct Declaration {
+    name: String,
+    value: String,
+    important: bool,
+}
+
+impl Stylesheet {
+    fn parse(css: &str) -> Self {
+        let css = strip_comments(css);
+        let mut stylesheet = Self::default();
+        let mut rest = css.as_str();
+        loop {
+            rest = rest.trim_start();
+            if rest.is_empty() {
+                break;
+            }
+
+            // At rules like @media are kept as they are
+            if rest.starts_with('@') {
+                let end = at_rule_end(rest);
+                stylesheet.remaining.push_str(rest[..end].trim());
+                rest = &rest[end..];
+                continue;
+            }
+
+            let (Some(open), Some(close)) = (rest.find('{'), rest.find('}')) else {
+                break;
+            };
+            if close < open {
+                // A stray closing brace
+                rest = &rest[close + 1..];
+                continue;
+            }
+            let (selectors, body) = (&rest[..open], &rest[open + 1..close]);
+            rest = &rest[close + 1..];
+
+            let declarations: Vec<_> = parse_declarations(body)
+                .into_iter()
+                .map(|(name, value)| match value.strip_suffix("!important") {
+                    Some(value) => Declaration {
+                        name,
+                        value: value.trim_end().to_string(),
+                        important: true,
+                    },
+                    None => Declaration {
+                        name,
+                        value,
+                        important: false,
+                    },
+                })
+                .collect();
+
+            let mut unsupported = Vec::new();
+            for selector in split_outside_quotes(selectors, ',') {
+                let selector = selector.trim();
+                match Selector::parse(selector) {
+                    Some(parsed) => stylesheet.rules.push(Rule {
+                        selector: parsed,
+                        declarations: declarations.clone(),
+                    }),
+                    None if selector.is_empty() => {}
+                    None => unsupported.push(selector),
+                }
+            }
+            if !unsupported.is_empty() {
+                _ = write!(
+                    stylesheet.remaining,
+                    "{}{{{}}}",
+                    unsupported.join(","),
+                    body.trim()
+                );
+            }
+        }
+        stylesheet
+    }
+
+    /// The styles of an element, in the order the cascade applies them. Rules apply in order of specificity, the
+    /// styles set on the element override them, and `!important` rules override both.
+    fn styles(
+        &self,
+        element: &tree::Element,
+        ancestors: &[&tree::Element],
+    ) -> Vec<(String, String)> {
+        let mut matched: Vec<_> = self
+            .rules
+            .iter()
+            .enumerate()
+            .filter(|(_, rule)| rule.selector.matches(element, ancestors))
+            .collect();
+        matched.sort_by_key(|(order, rule)| (rule.selector.specificity(), *order));
+
+        let mut styles: Vec<(String, String)> = Vec::new();
+        let mut set =
+            |name: &str, value: &str| match styles.iter_mut().find(|(style, _)| style == name) {
+                Some((_, old)) => *old = value.to_string(),
+                None => styles.push((name.to_string(), value.to_string())),
+            };
+        for important in [false, true] {
+            for (_, rule) in &matched {
+                for declaration in &rule.declarations {
+                    if declaration.important == important {
+                        set(&declaration.name, &declaration.value);
+                    }
+                }
+            }
+            if !important {
+                for (name, value) in &element.styles {
+                    set(name, value);
+                }
+            }
+        }
+        styles
+    }
+}
+
+fn strip_comments(css: &str) -> String {
+    let mut stripped = String::with_capacity(css.len());
+    let mut rest = css;
+    while let Some(start) = rest.find("/*") {
+        stripped.push_str(&rest[..start]);
+        rest = match rest[start + 2..].find("*/") {
+            Some(end) => &rest[start + 2 + end + 2..],
+            None => "",
+        };
+    }
+    stripped.push_str(rest);
+    stripped
+}
+
+/// The end of an at rule that either ends with a `;` or a block
+fn at_rule_end(css: &str) -> usize {
+    let mut depth = 0;
+    for (index, c) in css.char_indices() {
+        match c {
+            ';' if depth == 0 => return index + 1,
+            '{' => depth += 1,
+            '}' => {
+                depth -= 1;
+                if depth <= 0 {
+                    return index + 1;
+                }
+            }
+            _ => {}
+        }
+    }
+    css.len()
+}
+
+/// A selector like `table.receipt > tr td`
+struct Selector {
+    compounds: Vec<Compound>,
+    /// The combinators between the compounds
+    combinators: Vec<Combinator>,
+}
+
+#[derive(Default)]
+struct Compound {
+    tag: Option<String>,
+    id: Option<String>,
+    classes: Vec<String>,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Combinator {
+    Descendant,
+    Child,
+}
+
+impl Selector {
+    /// Parse a selector. Returns `None` if the selector uses anything but tags, classes, ids and the descendant and
+    /// child combinators
+    fn parse(selector: &str) -> Option<Self> {
+        let mut compounds = Vec::new();
+        let mut combinators = Vec::new();
+        let mut combinator = None;
+        let mut chars = selector.chars().peekable();
+        while let Some(&c) = chars.peek() {
+            if c.is_whitespace() || c == '>' {
+                chars.next();
+                if c == '>' {
+                    combinator = Some(Combinator::Child);
+                } else if combinator.is_none() {
+                    combinator = Some(Combinator::Descendant);
+                }
+                continue;
+            }
+
+            let mut compound = String::new();
+            while let Some(&c) = chars.peek() {
+                if c.is_whitespace() || c == '>' {
+                    break;
+                }
+                compound.push(c);
+                chars.next();
+            }
+            if !compounds.is_empty() {
+                combinators.push(combinator?);
+            }
+            combinator = None;
+            compounds.push(Compound::parse(&compound)?);
+        }
+        // A selector can't start or end with a combinator
+        if compounds.is_empty() || combinator == Some(Combinator::Child) {
+            return None;
+        }
+        Some(Self {
+            compounds,
+            combinators,
+        })
+    }
+
+    fn specificity(&self) -> (usize, usize, usize) {
+        self.compounds
+            .iter()
+            .fold((0, 0, 0), |(ids, classes, tags), compound| {
+                (
+                    ids + compound.id.is_some() as usize,
+                    classes + compound.classes.len(),
+                    tags + compound.tag.is_some() as usize,
+                )
+            })
+    }
+
+    fn matches(&self, element: &tree::Element, ancestors: &[&tree::Element]) -> bool {
+        let (last, compounds) = self.compounds.split_last().unwrap();
+        last.matches(element) && matches_ancestors(compounds, &self.combinators, ancestors)
+    }
+}
+
+/// Check if the ancestors of an element match the compounds left of the compound that matched the element
+fn matches_ancestors(
+    compounds: &[Compound],
+    combinators: &[Combinator],
+    ancestors: &[&tree::Element],
+) -> bool {
+    let (Some((compound, compounds)), Some((combinator, combinators))) =
+        (compounds.split_last(), combinators.split_last())
+    else {
+        return true;
+    };
+    match combinator {
+        Combinator::Child => match ancestors.split_last() {
+            Some((parent, ancestors)) => {
+                compound.matches(parent) && matches_ancestors(compounds, combinators, ancestors)
+            }
+            None => false,
+        },
+        Combinator::Descendant => (0..ancestors.len()).rev().any(|index| {
+            compound.matches(ancestors[index])
+                && matches_ancestors(compounds, combinators, &ancestors[..index])
+        }),
+    }
+}
+
+impl Compound {
+    fn parse(compound: &str) -> Option<Self> {
+        fn is_name(c: char) -> bool {
+            c.is_alphanumeric() || c == '-' || c == '_'
+        }
+
+        let mut parsed = Self::default();
+        let mut rest = compound.strip_prefix('*').unwrap_or(compound);
+        let tag_end = rest.find(|c| !is_name(c)).unwrap_or(rest.len());
+        if tag_end > 0 {
+            parsed.tag = Some(rest[..tag_end].to_ascii_lowercase());
+        }
+        rest = &rest[tag_end..];
+        while let Some(kind) = rest.chars().next() {
+            let start = kind.len_utf8();
+            let name_end = rest[start..]
+                .find(|c| !is_name(c))
+                .map_or(rest.len(), |end| end + start);
+            let name = rest[start..name_end].to_string();
+            if name.is_empty() {
+                return None;
+            }
+            match kind {
+                '.' => parsed.classes.push(name),
+                '#' => parsed.id = Some(name),
+                // Pseudo classes, attribute selectors and the sibling combinators are not supported
+                _ => return None,
+            }
+            rest = &rest[name_end..];
+        }
+        Some(parsed)
+    }
+
+    fn matches(&self, element: &tree::Element) -> bool {
+        self.tag.iter().all(|tag| element.tag == tag)
+            && self.id.iter().all(|id| element.attr("id") == Some(id))
+            && self
+                .classes
+                .iter()
+                .all(|class| element.classes().any(|other| other == class))
+    }
+}
@@ -4,12 +4,20 @@
 
 mod cache;
 pub mod config;
+pub mod email;
 pub mod renderer;
+pub mod sanitize;
+pub mod stream;
 pub mod template;
+pub mod text;
+mod tree;
 
 use dioxus_core::{Element, VirtualDom};
 
+pub use crate::email::EmailRenderer;
 pub use crate::renderer::Renderer;
+pub use crate::sanitize::InnerHtmlPolicy;
+pub use crate::text::{TextFormat, TextRenderer};
 
 /// A convenience function to render an `rsx!` call to a string
 ///
@@ -1,5 +1,7 @@
 use super::cache::Segment;
-use crate::cache::StringCache;
+use crate::cache::{collapse_whitespace, Minify, StringCache};
+use crate::sanitize::InnerHtmlPolicy;
+use crate::stream::RenderChunks;
 
 use dioxus_core::{prelude::*, AttributeValue, DynamicNode};
 use rustc_hash::FxHashMap;
@@ -16,11 +18,18 @@ pub struct Renderer {
     /// Choose to write ElementIDs into elements so the page can be re-hydrated later on
     pub pre_render: bool,
 
+    /// Choose to make the html smaller: whitespace in text is collapsed outside of elements like `pre`, optional end
+    /// tags are left out and boolean attributes are written without a value
+    pub minify: bool,
+
+    /// How the html of `dangerous_inner_html` attributes is written. Defaults to [`InnerHtmlPolicy::Trusted`]
+    pub inner_html_policy: InnerHtmlPolicy,
+
     /// A callback used to render components. You can set this callback to control what components are rendered and add wrappers around components that are not present in CSR
     render_components: Option<ComponentRenderCallback>,
 
     /// A cache of templates that have been rendered
-    template_cache: FxHashMap<Template, Arc<StringCache>>,
+    template_cache: FxHashMap<(Template, Option<Minify>), Arc<StringCache>>,
 
     /// The current dynamic node id for hydration
     dynamic_node_id: usize,
@@ -62,6 +71,11 @@ impl Renderer {
         self.render_scope(buf, dom, ScopeId::ROOT)
     }
 
+    /// Render the virtual dom in chunks that are only rendered when they are needed. See [`RenderChunks`].
+    pub fn render_chunks<'a>(&'a mut self, dom: &'a VirtualDom) -> RenderChunks<'a> {
+        RenderChunks::new(self, dom)
+    }
+
     /// Render an element to a string
     pub fn render_element(&mut self, element: Element) -> String {
         let mut buf = String::new();
@@ -100,48 +114,95 @@ impl Renderer {
         Ok(())
     }
 
-    fn render_template<W: Write + ?Sized>(
+    fn render_template<'a, W: Write + ?Sized>(
         &mut self,
-        mut buf: &mut W,
-        dom: &VirtualDom,
-        template: &VNode,
+        buf: &mut W,
+        dom: &'a VirtualDom,
+        template: &'a VNode,
     ) -> std::fmt::Result {
+        let mut stack = vec![self.template_frame(template, false)];
+        while !stack.is_empty() {
+            self.render_step(buf, dom, &mut stack)?;
+        }
+        Ok(())
+    }
+
+    /// Start rendering a template. `preformatted` is true if the template is inside an element that preserves
+    /// whitespace
+    pub(crate) fn template_frame<'a>(
+        &mut self,
+        template: &'a VNode,
+        preformatted: bool,
+    ) -> RenderFrame<'a> {
+        let minify = self.minify.then_some(Minify {
+            collapse_whitespace: !preformatted,
+        });
         let entry = self
             .template_cache
-            .entry(template.template)
-            .or_insert_with(move || Arc::new(StringCache::from_template(template).unwrap()))
+            .entry((template.template, minify))
+            .or_insert_with(move || Arc::new(StringCache::from_template(template, minify).unwrap()))
             .clone();
 
-        let mut inner_html = None;
-
-        // We need to keep track of the dynamic styles so we can insert them into the right place
-        let mut accumulated_dynamic_styles = Vec::new();
+        RenderFrame::Template(TemplateFrame {
+            template,
+            entry,
+            preformatted,
+            index: 0,
+            inner_html: None,
+            accumulated_dynamic_styles: Vec::new(),
+            accumulated_listeners: Vec::new(),
+        })
+    }
 
-        // We need to keep track of the listeners so we can insert them into the right place
-        let mut accumulated_listeners = Vec::new();
+    /// Render the frame on top of the stack until it finishes or a child frame is pushed onto the stack
+    ///
+    /// Rendering with an explicit stack instead of recursion lets the streaming renderer stop between any two nodes
+    pub(crate) fn render_step<'a, W: Write + ?Sized>(
+        &mut self,
+        mut buf: &mut W,
+        dom: &'a VirtualDom,
+        stack: &mut Vec<RenderFrame<'a>>,
+    ) -> std::fmt::Result {
+        let frame = match stack.last_mut() {
+            Some(RenderFrame::Template(frame)) => frame,
+            Some(RenderFrame::Fragment(children, preformatted)) => {
+                match children.next() {
+                    Some(child) => {
+                        let child = self.template_frame(child, *preformatted);
+                        stack.push(child);
+                    }
+                    None => _ = stack.pop(),
+                }
+                return Ok(());
+            }
+            None => return Ok(()),
+        };
+        let template = frame.template;
+        let entry = frame.entry.clone();
 
         // We keep track of the index we are on manually so that we can jump forward to a new section quickly without iterating every item
-        let mut index = 0;
-
-        while let Some(segment) = entry.segments.get(index) {
+        while let Some(segment) = entry.segments.get(frame.index) {
+            frame.index += 1;
             match segment {
                 Segment::HydrationOnlySection(jump_to) => {
                     // If we are not prerendering, we don't need to write the content of the hydration only section
                     // Instead we can jump to the next section
                     if !self.pre_render {
-                        index = *jump_to;
-                        continue;
+                        frame.index = *jump_to;
                     }
                 }
                 Segment::Attr(idx) => {
                     let attrs = &*template.dynamic_attrs[*idx];
                     for attr in attrs {
                         if attr.name == "dangerous_inner_html" {
-                            inner_html = Some(attr);
+                            frame.inner_html = Some(attr);
                         } else if attr.namespace == Some("style") {
-                            accumulated_dynamic_styles.push(attr);
+                            frame.accumulated_dynamic_styles.push(attr);
                         } else if BOOL_ATTRS.contains(&attr.name) {
-                            if truthy(&attr.value) {
+                            if !truthy(&attr.value) {
+                            } else if self.minify {
+                                write!(buf, " {}", attr.name)?;
+                            } else {
                                 write_attribute(buf, attr)?;
                             }
                         } else {
@@ -152,64 +213,68 @@ impl Renderer {
                             if let AttributeValue::Listener(_) = &attr.value {
                                 // The onmounted event doesn't need a DOM listener
                                 if attr.name != "onmounted" {
-                                    accumulated_listeners.push(attr.name);
+                                    frame.accumulated_listeners.push(attr.name);
                                 }
                             }
                         }
                     }
                 }
-                Segment::Node(idx) => match &template.dynamic_nodes[*idx] {
-                    DynamicNode::Component(node) => {
-                        if let Some(render_components) = self.render_components.clone() {
-                            let scope_id = node.mounted_scope_id(*idx, template, dom).unwrap();
-
-                            render_components(self, &mut buf, dom, scope_id)?;
-                        } else {
-                            let scope = node.mounted_scope(*idx, template, dom).unwrap();
-                            let node = scope.root_node();
-                            self.render_template(buf, dom, node)?
-                        }
-                    }
-                    DynamicNode::Text(text) => {
-                        // in SSR, we are concerned that we can't hunt down the right text node since they might get merged
-                        if self.pre_render {
-                            write!(buf, "<!--node-id{}-->", self.dynamic_node_id)?;
-                            self.dynamic_node_id += 1;
+                Segment::Node(idx) => {
+                    let preformatted = frame.preformatted || entry.preformatted_nodes.contains(idx);
+                    match &template.dynamic_nodes[*idx] {
+                        DynamicNode::Component(node) => {
+                            if let Some(render_components) = self.render_components.clone() {
+                                let scope_id = node.mounted_scope_id(*idx, template, dom).unwrap();
+
+                                render_components(self, &mut buf, dom, scope_id)?;
+                            } else {
+                                let scope = node.mounted_scope(*idx, template, dom).unwrap();
+                                let child = self.template_frame(scope.root_node(), preformatted);
+                                stack.push(child);
+                                return Ok(());
+                            }
                         }
+                        DynamicNode::Text(text) => {
+                            // in SSR, we are concerned that we can't hunt down the right text node since they might get merged
+                            if self.pre_render {
+                                write!(buf, "<!--node-id{}-->", self.dynamic_node_id)?;
+                                self.dynamic_node_id += 1;
+                            }
 
-                        write!(
-                            buf,
-                            "{}",
-                            askama_escape::escape(&text.value, askama_escape::Html)
-                        )?;
+                            let text = if self.minify && !preformatted {
+                                collapse_whitespace(&text.value)
+                            } else {
+                                text.value.as_str().into()
+                            };
+                            write!(buf, "{}", askama_escape::escape(&text, askama_escape::Html))?;
 
-                        if self.pre_render {
-                            write!(buf, "<!--#-->")?;
+                            if self.pre_render {
+                                write!(buf, "<!--#-->")?;
+                            }
                         }
-                    }
-                    DynamicNode::Fragment(nodes) => {
-                        for child in nodes {
-                            self.render_template(buf, dom, child)?;
+                        DynamicNode::Fragment(nodes) => {
+                            stack.push(RenderFrame::Fragment(nodes.iter(), preformatted));
+                            return Ok(());
                         }
-                    }
 
-                    DynamicNode::Placeholder(_) => {
-                        if self.pre_render {
-                            write!(buf, "<!--placeholder{}-->", self.dynamic_node_id)?;
-                            self.dynamic_node_id += 1;
+                        DynamicNode::Placeholder(_) => {
+                            if self.pre_render {
+                                write!(buf, "<!--placeholder{}-->", self.dynamic_node_id)?;
+                                self.dynamic_node_id += 1;
+                            }
                         }
                     }
-                },
+                }
 
                 Segment::PreRendered(contents) => write!(buf, "{contents}")?,
 
                 Segment::StyleMarker { inside_style_tag } => {
-                    if !accumulated_dynamic_styles.is_empty() {
+                    if !frame.accumulated_dynamic_styles.is_empty() {
                         // if we are inside a style tag, we don't need to write the style attribute
                         if !*inside_style_tag {
                             write!(buf, " style=\"")?;
                         }
-                        for attr in &accumulated_dynamic_styles {
+                        for attr in &frame.accumulated_dynamic_styles {
                             write!(buf, "{}:", attr.name)?;
                             write_value_unquoted(buf, &attr.value)?;
                             write!(buf, ";")?;
@@ -219,29 +284,33 @@ impl Renderer {
                         }
 
                         // clear the accumulated styles
-                        accumulated_dynamic_styles.clear();
+                        frame.accumulated_dynamic_styles.clear();
                     }
                 }
 
                 Segment::InnerHtmlMarker => {
-                    if let Some(inner_html) = inner_html.take() {
-                        let inner_html = &inner_html.value;
-                        match inner_html {
-                            AttributeValue::Text(value) => write!(buf, "{}", value)?,
-                            AttributeValue::Bool(value) => write!(buf, "{}", value)?,
-                            AttributeValue::Float(f) => write!(buf, "{}", f)?,
-                            AttributeValue::Int(i) => write!(buf, "{}", i)?,
-                            _ => {}
-                        }
+                    if let Some(inner_html) = frame.inner_html.take() {
+                        let inner_html = match &inner_html.value {
+                            AttributeValue::Text(value) => value.clone(),
+                            AttributeValue::Bool(value) => value.to_string(),
+                            AttributeValue::Float(f) => f.to_string(),
+                            AttributeValue::Int(i) => i.to_string(),
+                            _ => String::new(),
+                        };
+                        self.inner_html_policy.write(buf, &inner_html)?;
                     }
                 }
 
+                Segment::StaticInnerHtml(inner_html) => {
+                    self.inner_html_policy.write(buf, inner_html)?;
+                }
+
                 Segment::AttributeNodeMarker => {
                     // first write the id
                     write!(buf, "{}", self.dynamic_node_id)?;
                     self.dynamic_node_id += 1;
                     // then write any listeners
-                    for name in accumulated_listeners.drain(..) {
+                    for name in frame.accumulated_listeners.drain(..) {
                         write!(buf, ",{}:", &name[2..])?;
                         write!(
                             buf,
@@ -256,14 +325,34 @@ impl Renderer {
                     self.dynamic_node_id += 1
                 }
             }
-
-            index += 1;
         }
 
+        stack.pop();
         Ok(())
     }
 }
 
+/// A node that is partially rendered
+pub(crate) enum RenderFrame<'a> {
+    Template(TemplateFrame<'a>),
+    /// The nodes of a fragment that are not rendered yet, and if they are inside an element that preserves whitespace
+    Fragment(std::slice::Iter<'a, VNode>, bool),
+}
+
+pub(crate) struct TemplateFrame<'a> {
+    template: &'a VNode,
+    entry: Arc<StringCache>,
+    /// If the template is inside an element that preserves whitespace
+    preformatted: bool,
+    /// The index of the next segment to render
+    index: usize,
+    inner_html: Option<&'a Attribute>,
+    // We need to keep track of the dynamic styles so we can insert them into the right place
+    accumulated_dynamic_styles: Vec<&'a Attribute>,
+    // We need to keep track of the listeners so we can insert them into the right place
+    accumulated_listeners: Vec<&'static str>,
+}
+
 #[test]
 fn to_string_works() {
     use dioxus::prelude::*;
@@ -394,6 +483,85 @@ fn empty_render_works() {
     assert_eq!(out, "");
 }
 
+#[test]
+fn minified_templates() {
+    use dioxus::prelude::*;
+
+    fn app() -> Element {
+        let name = "world";
+        rsx! {
+            ul { class: "list",
+                li { "Hello   \n   {name}" }
+                li { "Static   \n   text" }
+                li { input { r#type: "checkbox", checked: true } }
+            }
+            pre { "  keep\n  this  " }
+            div { dangerous_inner_html: "<b>bold</b>" }
+        }
+    }
+
+    let mut dom = VirtualDom::new(app);
+    dom.rebuild(&mut dioxus_core::NoOpMutations);
+
+    let mut renderer = Renderer::new();
+    renderer.minify = true;
+    let out = renderer.render(&dom);
+
+    for item in renderer.template_cache.iter() {
+        assert_eq!(
+            item.0 .1,
+            Some(Minify {
+                collapse_whitespace: true
+            })
+        );
+        if item.1.segments.len() > 10 {
+            assert_eq!(
+                item.1.segments,
+                vec![
+                    PreRendered("<ul class=\"list\"".to_string()),
+                    HydrationOnlySection(5),
+                    PreRendered(" data-node-hydration=\"".to_string()),
+                    RootNodeMarker,
+                    PreRendered("\"".to_string()),
+                    // The end tags of the list items are left out
+                    PreRendered("><li>".to_string()),
+                    Node(0),
+                    PreRendered("<li>Static text<li><input type=\"checkbox\"".to_string()),
+                    Attr(0),
+                    StyleMarker {
+                        inside_style_tag: false
+                    },
+                    HydrationOnlySection(14),
+                    PreRendered(" data-node-hydration=\"".to_string()),
+                    AttributeNodeMarker,
+                    PreRendered("\"".to_string()),
+                    PreRendered("/></ul><pre".to_string()),
+                    HydrationOnlySection(19),
+                    PreRendered(" data-node-hydration=\"".to_string()),
+                    RootNodeMarker,
+                    PreRendered("\"".to_string()),
+                    // Whitespace in pre is kept
+                    PreRendered(">  keep\n  this  </pre><div".to_string()),
+                    HydrationOnlySection(24),
+                    PreRendered(" data-node-hydration=\"".to_string()),
+                    RootNodeMarker,
+                    PreRendered("\"".to_string()),
+                    PreRendered(">".to_string()),
+                    StaticInnerHtml("<b>bold</b>".to_string()),
+                    PreRendered("</div>".to_string())
+                ]
+            );
+        }
+    }
+
+    use Segment::*;
+
+    assert_eq!(
+        out,
+        "<ul class=\"list\"><li>Hello world<li>Static text<li><input type=\"checkbox\" checked/></ul><pre>  keep\n  this  </pre><div><b>bold</b></div>"
+    );
+}
+
 pub(crate) const BOOL_ATTRS: &[&str] = &[
     "allowfullscreen",
     "allowpaymentrequest",
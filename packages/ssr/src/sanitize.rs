@@ -0,0 +1,362 @@
+//! Policies for the html of `dangerous_inner_html` attributes.
+//!
+//! The renderer writes inner html as it is by default. If the html comes from users, set an [`InnerHtmlPolicy`] on
+//! the [`Renderer`](crate::Renderer) that escapes or sanitizes it.
+
+use std::{fmt::Write, sync::Arc};
+
+/// How the [`Renderer`](crate::Renderer) writes the html of `dangerous_inner_html` attributes
+#[derive(Clone, Default)]
+pub enum InnerHtmlPolicy {
+    /// Write the html as it is. Only use this for html you trust
+    #[default]
+    Trusted,
+    /// Escape the html, so it is displayed as text
+    Escape,
+    /// Only keep formatting elements and attributes that can't run scripts. See [`sanitize_html`]
+    Sanitize,
+    /// Transform the html with a custom function, for example a sanitizer with your own rules
+    Custom(Arc<dyn Fn(&str) -> String + Send + Sync>),
+}
+
+impl InnerHtmlPolicy {
+    pub(crate) fn write<W: Write + ?Sized>(&self, buf: &mut W, html: &str) -> std::fmt::Result {
+        match self {
+            InnerHtmlPolicy::Trusted => write!(buf, "{html}"),
+            InnerHtmlPolicy::Escape => {
+                write!(buf, "{}", askama_escape::escape(html, askama_escape::Html))
+            }
+            InnerHtmlPolicy::Sanitize => write!(buf, "{}", sanitize_html(html)),
+            InnerHtmlPolicy::Custom(transform) => write!(buf, "{}", transform(html)),
+        }
+    }
+}
+
+/// Elements that are kept by [`sanitize_html`]
+const ALLOWED_ELEMENTS: &[&str] = &[
+    "a",
+    "abbr",
+    "b",
+    "blockquote",
+    "br",
+    "caption",
+    "code",
+    "col",
+    "colgroup",
+    "dd",
+    "del",
+    "div",
+    "dl",
+    "dt",
+    "em",
+    "figcaption",
+    "figure",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "hr",
+    "i",
+    "img",
+    "ins",
+    "kbd",
+    "li",
+    "mark",
+    "ol",
+    "p",
+    "pre",
+    "q",
+    "s",
+    "samp",
+    "small",
+    "span",
+    "strong",
+    "sub",
+    "sup",
+    "table",
+    "tbody",
+    "td",
+    "tfoot",
+    "th",
+    "thead",
+    "tr",
+    "u",
+    "ul",
+];
+
+/// Elements that are removed by [`sanitize_html`] together with their content
+const DROPPED_ELEMENTS: &[&str] = &[
+    "embed",
+    "frameset",
+    "iframe",
+    "math",
+    "noembed",
+    "noframes",
+    "noscript",
+    "object",
+    "plaintext",
+    "script",
+    "select",
+    "style",
+    "svg",
+    "template",
+    "textarea",
+    "title",
+    "xmp",
+];
+
+const VOID_ELEMENTS: &[&str] = &["br", "col", "hr", "img"];
+
+/// The url schemes links and images may use
+const ALLOWED_SCHEMES: &[&str] = &["http", "https", "mailto", "tel"];
+
+fn attribute_is_allowed(tag: &str, name: &str) -> bool {
+    match name {
+        "class" | "title" | "lang" | "dir" => true,
+        "href" => tag == "a",
+        "src" | "alt" | "width" | "height" => tag == "img",
+        "colspan" | "rowspan" => matches!(tag, "td" | "th"),
+        "span" => matches!(tag, "col" | "colgroup"),
+        "start" | "reversed" => tag == "ol",
+        "cite" => matches!(tag, "blockquote" | "q" | "del" | "ins"),
+        _ => false,
+    }
+}
+
+/// Sanitize html from an untrusted source.
+///
+/// Only a list of formatting elements like `p`, `a`, `ul` and `table` is kept, together with attributes that can't
+/// run scripts or change the layout of the page, like `href` and `class`. Other elements are removed but their text
+/// is kept, except for elements like `script` and `style` that are removed with their content. Links and images
+/// may only use `http`, `https`, `mailto` and `tel` urls, or relative urls. Elements that are not closed are closed
+/// at the end, so the html can't change the elements around it.
+///
+/// ```rust
+/// let html = dioxus_ssr::sanitize::sanitize_html(
+///     r#"<p onclick="steal()">Hi <a href="javascript:steal()">there</a><script>steal()</script>"#,
+/// );
+/// assert_eq!(html, "<p>Hi <a>there</a></p>");
+/// ```
+pub fn sanitize_html(html: &str) -> String {
+    let mut sanitized = String::with_capacity(html.len());
+    let mut open: Vec<String> = Vec::new();
+    // The dropped element we are in and how many elements with the same tag are open inside of it
+    let mut dropped: Option<(String, usize)> = None;
+    let mut rest = html;
+
+    while !rest.is_empty() {
+        let Some(start) = rest.find('<') else {
+            if dropped.is_none() {
+                sanitized.push_str(&escape_text(rest));
+            }
+            break;
+        };
+        if dropped.is_none() {
+            sanitized.push_str(&escape_text(&rest[..start]));
+        }
+        rest = &rest[start..];
+
+        // Comments, doctypes and processing instructions are removed
+        if let Some(comment) = rest.strip_prefix("<!--") {
+            rest = comment.find("-->").map_or("", |end| &comment[end + 3..]);
+            continue;
+        }
+        if rest.starts_with("<!") || rest.starts_with("<?") {
+            rest = rest.find('>').map_or("", |end| &rest[end + 1..]);
+            continue;
+        }
+
+        let Some(tag) = Tag::parse(rest) else {
+            // Not a tag, so it is text
+            if dropped.is_none() {
+                sanitized.push_str("&lt;");
+            }
+            rest = &rest[1..];
+            continue;
+        };
+        rest = &rest[tag.len..];
+
+        // Skip everything inside dropped elements
+        if let Some((dropped_tag, depth)) = &mut dropped {
+            if tag.name == *dropped_tag {
+                if !tag.closing {
+                    *depth += 1;
+                } else if *depth == 0 {
+                    dropped = None;
+                } else {
+                    *depth -= 1;
+                }
+            }
+            continue;
+        }
+        if DROPPED_ELEMENTS.contains(&tag.name.as_str()) {
+            if !tag.closing && !tag.self_closing {
+                dropped = Some((tag.name, 0));
+            }
+            continue;
+        }
+        if !ALLOWED_ELEMENTS.contains(&tag.name.as_str()) {
+            continue;
+        }
+
+        if tag.closing {
+            // Only close elements that were opened in the html, and close the elements inside of them first
+            if let Some(index) = open.iter().rposition(|name| *name == tag.name) {
+                for name in open.drain(index..).rev() {
+                    _ = write!(sanitized, "</{name}>");
+                }
+            }
+            continue;
+        }
+
+        sanitized.push('<');
+        sanitized.push_str(&tag.name);
+        for (name, value) in &tag.attributes {
+            if !attribute_is_allowed(&tag.name, name) {
+                continue;
+            }
+            if matches!(name.as_str(), "href" | "src" | "cite") && !url_is_allowed(value) {
+                continue;
+            }
+            _ = write!(
+                sanitized,
+                " {name}=\"{}\"",
+                value.replace('"', "&quot;").replace('<', "&lt;")
+            );
+        }
+        sanitized.push('>');
+        if VOID_ELEMENTS.contains(&tag.name.as_str()) {
+        } else if tag.self_closing {
+            _ = write!(sanitized, "</{}>", tag.name);
+        } else {
+            open.push(tag.name);
+        }
+    }
+
+    for name in open.into_iter().rev() {
+        _ = write!(sanitized, "</{name}>");
+    }
+    sanitized
+}
+
+/// Escape the `>` characters in text. Entities are kept, so escaped text stays escaped.
+fn escape_text(text: &str) -> String {
+    text.replace('>', "&gt;")
+}
+
+/// Check if a url in an attribute is relative or uses an allowed scheme
+fn url_is_allowed(url: &str) -> bool {
+    // Entities could hide the scheme of the url, like `javascript&colon;`
+    if url.contains('&') {
+        return false;
+    }
+    // Browsers ignore whitespace and control characters in the scheme
+    let url: String = url
+        .chars()
+        .filter(|c| !c.is_ascii_whitespace() && !c.is_control())
+        .collect();
+    match url.find([':', '/', '?', '#']) {
+        Some(index) if url[index..].starts_with(':') => {
+            let scheme = url[..index].to_ascii_lowercase();
+            ALLOWED_SCHEMES.contains(&scheme.as_str())
+        }
+        _ => true,
+    }
+}
+
+/// A start or end tag
+struct Tag {
+    name: String,
+    closing: bool,
+    self_closing: bool,
+    attributes: Vec<(String, String)>,
+    /// The length of the tag in the html
+    len: usize,
+}
+
+impl Tag {
+    /// Parse the tag at the start of `html`. Returns `None` if `html` doesn't start with a tag
+    fn parse(html: &str) -> Option<Self> {
+        let bytes = html.as_bytes();
+        let mut index = 1;
+        let closing = bytes.get(index) == Some(&b'/');
+        if closing {
+            index += 1;
+        }
+        if !bytes.get(index)?.is_ascii_alphabetic() {
+            return None;
+        }
+        let name_end = index
+            + html[index..]
+                .find(|c: char| c.is_ascii_whitespace() || c == '/' || c == '>')
+                .unwrap_or(html.len() - index);
+        let name = html[index..name_end].to_ascii_lowercase();
+        index = name_end;
+
+        let mut attributes = Vec::new();
+        let mut self_closing = false;
+        loop {
+            // An unterminated tag swallows the rest of the html
+            let c = *bytes.get(index).unwrap_or(&b'>');
+            match c {
+                b'>' => {
+                    index += 1;
+                    break;
+                }
+                b'/' => {
+                    self_closing = bytes.get(index + 1) == Some(&b'>');
+                    index += 1;
+                }
+                c if c.is_ascii_whitespace() => index += 1,
+                _ => {
+                    let attribute_end = index
+                        + html[index..]
+                            .find(|c: char| c.is_ascii_whitespace() || matches!(c, '=' | '>' | '/'))
+                            .unwrap_or(html.len() - index);
+                    let attribute = html[index..attribute_end].to_ascii_lowercase();
+                    index = attribute_end;
+                    while bytes.get(index).is_some_and(u8::is_ascii_whitespace) {
+                        index += 1;
+                    }
+                    let mut value = String::new();
+                    if bytes.get(index) == Some(&b'=') {
+                        index += 1;
+                        while bytes.get(index).is_some_and(u8::is_ascii_whitespace) {
+                            index += 1;
+                        }
+                        let value_end = match bytes.get(index) {
+                            Some(&quote @ (b'"' | b'\'')) => {
+                                index += 1;
+                                let end = html[index..]
+                                    .find(quote as char)
+                                    .map_or(html.len(), |end| index + end);
+                                value = html[index..end].to_string();
+                                end + 1
+                            }
+                            _ => {
+                                let end = index
+                                    + html[index..]
+                                        .find(|c: char| c.is_ascii_whitespace() || c == '>')
+                                        .unwrap_or(html.len() - index);
+                                value = html[index..end].to_string();
+                                end
+                            }
+                        };
+                        index = value_end;
+                    }
+                    attributes.push((attribute, value));
+                }
+            }
+        }
+
+        Some(Self {
+            name,
+            closing,
+            self_closing,
+            attributes,
+            len: index.min(html.len()),
+        })
+    }
+}
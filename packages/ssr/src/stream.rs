@@ -0,0 +1,126 @@
+//! Render a [`VirtualDom`] in chunks, so large pages start arriving at the client before the whole page is rendered.
+//!
+//! The renderer only renders the next chunk when it is asked for it. If the client reads slowly, rendering pauses
+//! and the memory the render uses stays bounded by the chunk size, no matter how large the page is.
+
+use dioxus_core::{ScopeId, VirtualDom};
+
+use crate::renderer::{RenderFrame, Renderer};
+
+/// The size chunks are rendered in by default
+pub const DEFAULT_CHUNK_SIZE: usize = 8 * 1024;
+
+/// An iterator over the chunks of HTML a [`VirtualDom`] renders to. Created with [`Renderer::render_chunks`].
+///
+/// A chunk ends after the first node that fills it up to the chunk size, so chunks can be a bit larger than the
+/// chunk size. Components rendered by a callback set with [`Renderer::set_render_components`] are never split.
+///
+/// ```rust
+/// # use dioxus::prelude::*;
+/// fn app() -> Element {
+///     rsx! {
+///         table {
+///             for i in 0..1000 {
+///                 tr { td { "Row {i}" } }
+///             }
+///         }
+///     }
+/// }
+///
+/// let mut dom = VirtualDom::new(app);
+/// dom.rebuild_in_place();
+///
+/// let mut renderer = dioxus_ssr::Renderer::new();
+/// for chunk in renderer.render_chunks(&dom).with_chunk_size(1024) {
+///     let chunk = chunk.unwrap();
+///     assert!(chunk.len() < 2048);
+/// }
+/// ```
+pub struct RenderChunks<'a> {
+    renderer: &'a mut Renderer,
+    dom: &'a VirtualDom,
+    stack: Vec<RenderFrame<'a>>,
+    chunk_size: usize,
+}
+
+impl<'a> RenderChunks<'a> {
+    pub(crate) fn new(renderer: &'a mut Renderer, dom: &'a VirtualDom) -> Self {
+        renderer.reset_hydration();
+        let root = dom.get_scope(ScopeId::ROOT).unwrap().root_node();
+        let stack = vec![renderer.template_frame(root, false)];
+        Self {
+            renderer,
+            dom,
+            stack,
+            chunk_size: DEFAULT_CHUNK_SIZE,
+        }
+    }
+
+    /// Set the size in bytes chunks are rendered in. Defaults to [`DEFAULT_CHUNK_SIZE`].
+    pub fn with_chunk_size(mut self, chunk_size: usize) -> Self {
+        self.chunk_size = chunk_size.max(1);
+        self
+    }
+
+    /// Turn the chunks into a stream of bytes, for example to use as the body of a response.
+    ///
+    /// The stream borrows the [`VirtualDom`], so it can't be sent to another thread. To serve it from a multithreaded
+    /// server, write the chunks into one half of a [`tokio::io::duplex`] pipe on the thread of the [`VirtualDom`]
+    /// with [`Self::write_to`] and send the other half.
+    #[cfg(feature = "stream")]
+    pub fn into_stream(
+        self,
+    ) -> impl futures_util::Stream<Item = Result<bytes::Bytes, std::fmt::Error>> + 'a {
+        futures_util::stream::iter(self.map(|chunk| chunk.map(bytes::Bytes::from)))
+    }
+
+    /// Write the chunks into `writer` as they are rendered. The next chunk is only rendered once the writer accepted
+    /// the previous one.
+    ///
+    /// ```rust
+    /// # use dioxus::prelude::*;
+    /// # fn app() -> Element { rsx! { div { "Hello world" } } }
+    /// # #[tokio::main(flavor = "current_thread")]
+    /// # async fn main() {
+    /// let mut dom = VirtualDom::new(app);
+    /// dom.rebuild_in_place();
+    ///
+    /// let mut renderer = dioxus_ssr::Renderer::new();
+    /// let mut html = Vec::new();
+    /// renderer.render_chunks(&dom).write_to(&mut html).await.unwrap();
+    /// assert_eq!(html, b"<div>Hello world</div>");
+    /// # }
+    /// ```
+    #[cfg(feature = "stream")]
+    pub async fn write_to<W>(self, writer: &mut W) -> std::io::Result<()>
+    where
+        W: tokio::io::AsyncWrite + Unpin + ?Sized,
+    {
+        use tokio::io::AsyncWriteExt;
+
+        for chunk in self {
+            let chunk = chunk.map_err(std::io::Error::other)?;
+            writer.write_all(chunk.as_bytes()).await?;
+        }
+        writer.flush().await
+    }
+}
+
+impl Iterator for RenderChunks<'_> {
+    type Item = Result<String, std::fmt::Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut chunk = String::new();
+        while !self.stack.is_empty() && chunk.len() < self.chunk_size {
+            if let Err(err) = self
+                .renderer
+                .render_step(&mut chunk, self.dom, &mut self.stack)
+            {
+                // Don't continue a render that failed halfway through a node
+                self.stack.clear();
+                return Some(Err(err));
+            }
+        }
+        (!chunk.is_empty()).then_some(Ok(chunk))
+    }
+}
@@ -0,0 +1,576 @@
+//! Render a [`VirtualDom`] to plain text or Markdown, for example for the text part of an email or a text export.
+//!
+//! Whitespace is collapsed like a browser would, block elements like paragraphs and headings are separated by blank
+//! lines, and lists, links, images and tables are written the way people write them by hand.
+
+use std::fmt::Write;
+
+use dioxus_core::{Element, VirtualDom};
+
+use crate::tree::{self, Node};
+
+/// The text format a [`TextRenderer`] writes
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum TextFormat {
+    /// Plain text without any markup. Links are written as `text (url)` and the headings of the first two levels
+    /// are underlined
+    #[default]
+    Plain,
+    /// CommonMark Markdown. Text is escaped, so characters in the text are never read as markup
+    Markdown,
+}
+
+/// A renderer that writes the text of a page instead of its HTML.
+///
+/// Scripts, styles and the head of the page are skipped. The contents of `dangerous_inner_html` are not rendered.
+///
+/// ```rust
+/// # use dioxus::prelude::*;
+/// let element = rsx! {
+///     h1 { "Your order" }
+///     ul {
+///         li { "1 × Keyboard" }
+///         li { "2 × Mouse" }
+///     }
+///     p { "Track it on " a { href: "https://example.com", "our site" } "." }
+/// };
+///
+/// let text = dioxus_ssr::TextRenderer::markdown().render_element(element);
+/// assert_eq!(
+///     text,
+///     "# Your order\n\n- 1 × Keyboard\n- 2 × Mouse\n\nTrack it on [our site](https://example.com)."
+/// );
+/// ```
+#[derive(Default)]
+pub struct TextRenderer {
+    /// The format the text is written in
+    pub format: TextFormat,
+}
+
+impl TextRenderer {
+    /// Create a renderer that writes plain text
+    pub fn plain_text() -> Self {
+        Self {
+            format: TextFormat::Plain,
+        }
+    }
+
+    /// Create a renderer that writes Markdown
+    pub fn markdown() -> Self {
+        Self {
+            format: TextFormat::Markdown,
+        }
+    }
+
+    pub fn render(&self, dom: &VirtualDom) -> String {
+        let mut buf = String::new();
+        self.render_to(&mut buf, dom).unwrap();
+        buf
+    }
+
+    pub fn render_to<W: Write + ?Sized>(&self, buf: &mut W, dom: &VirtualDom) -> std::fmt::Result {
+        let mut writer = TextWriter::new(self.format, 0);
+        writer.nodes(&tree::build(dom));
+        buf.write_str(&writer.finish())
+    }
+
+    /// Render an element to a string
+    pub fn render_element(&self, element: Element) -> String {
+        fn lazy_app(props: Element) -> Element {
+            props
+        }
+        let mut dom = VirtualDom::new_with_props(lazy_app, element);
+        dom.rebuild_in_place();
+        self.render(&dom)
+    }
+}
+
+/// Elements that start on a new line
+const BLOCK_ELEMENTS: &[&str] = &[
+    "address",
+    "article",
+    "aside",
+    "body",
+    "center",
+    "dd",
+    "details",
+    "div",
+    "dl",
+    "dt",
+    "fieldset",
+    "figcaption",
+    "figure",
+    "footer",
+    "form",
+    "header",
+    "html",
+    "main",
+    "nav",
+    "section",
+    "summary",
+    "tr",
+];
+
+/// Elements that are separated from the content around them by a blank line
+const PARAGRAPH_ELEMENTS: &[&str] = &["p", "blockquote", "pre", "table", "hr"];
+
+/// Elements without any text content
+const SKIPPED_ELEMENTS: &[&str] = &[
+    "head", "script", "style", "template", "title", "input", "select", "textarea",
+];
+
+struct TextWriter {
+    format: TextFormat,
+    /// How deep the lists around the content are nested
+    list_depth: usize,
+    out: String,
+    /// The line breaks that are written before the next content
+    newlines: usize,
+    /// If a space is written before the next content
+    space: bool,
+    /// If the content started with whitespace
+    leading_space: bool,
+}
+
+impl TextWriter {
+    fn new(format: TextFormat, list_depth: usize) -> Self {
+        Self {
+            format,
+            list_depth,
+            out: String::new(),
+            newlines: 0,
+            space: false,
+            leading_space: false,
+        }
+    }
+
+    fn finish(self) -> String {
+        let mut text = self
+            .out
+            .lines()
+            .map(str::trim_end)
+            .collect::<Vec<_>>()
+            .join("\n");
+        text.truncate(text.trim_end().len());
+        text
+    }
+
+    fn markdown(&self) -> bool {
+        self.format == TextFormat::Markdown
+    }
+
+    /// Render nodes into a new writer, to lay out their text before it is written
+    fn sub(&self, nodes: &[Node]) -> TextWriter {
+        let mut writer = TextWriter::new(self.format, self.list_depth);
+        writer.nodes(nodes);
+        writer
+    }
+
+    /// Render nodes on a single line
+    fn inline(&self, nodes: &[Node]) -> String {
+        self.sub(nodes)
+            .finish()
+            .split('\n')
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    /// End the current block and start the next content after `newlines` line breaks
+    fn block(&mut self, newlines: usize) {
+        if !self.out.is_empty() {
+            self.newlines = self.newlines.max(newlines);
+        }
+        self.space = false;
+    }
+
+    fn at_line_start(&self) -> bool {
+        self.out.is_empty() || self.newlines > 0 || self.out.ends_with('\n')
+    }
+
+    /// Write content that is already laid out
+    fn push(&mut self, content: &str) {
+        if content.is_empty() {
+            return;
+        }
+        if self.newlines > 0 {
+            self.out.push_str(&"\n".repeat(self.newlines));
+            self.newlines = 0;
+        } else if self.space && !self.at_line_start() {
+            self.out.push(' ');
+        }
+        self.space = false;
+        self.out.push_str(content);
+    }
+
+    /// Write a block of lines, like a list item or a table
+    fn push_lines(&mut self, lines: impl IntoIterator<Item = String>) {
+        for (index, line) in lines.into_iter().enumerate() {
+            if index == 0 {
+                self.push(&line);
+            } else {
+                self.out.push('\n');
+                self.out.push_str(&line);
+            }
+        }
+    }
+
+    /// Write text with its whitespace collapsed
+    fn text(&mut self, text: &str) {
+        if text.starts_with(char::is_whitespace) {
+            if self.out.is_empty() {
+                self.leading_space = true;
+            }
+            self.space = true;
+        }
+        for (index, word) in text.split_whitespace().enumerate() {
+            if index > 0 {
+                self.space = true;
+            }
+            if self.markdown() {
+                let escaped = escape_markdown(word, self.at_line_start());
+                self.push(&escaped);
+            } else {
+                self.push(word);
+            }
+        }
+        if text.ends_with(char::is_whitespace) {
+            self.space = true;
+        }
+    }
+
+    /// Write inline content wrapped in markup, like `**bold**`
+    fn wrapped(&mut self, nodes: &[Node], before: &str, after: &str) {
+        let sub = self.sub(nodes);
+        if sub.leading_space {
+            self.space = true;
+        }
+        let trailing_space = sub.space;
+        let content = sub.finish();
+        if !content.is_empty() {
+            self.push(&format!("{before}{content}{after}"));
+        }
+        if trailing_space {
+            self.space = true;
+        }
+    }
+
+    fn nodes(&mut self, nodes: &[Node]) {
+        for node in nodes {
+            match node {
+                Node::Text(text) => self.text(text),
+                Node::Element(element) => self.element(element),
+            }
+        }
+    }
+
+    fn element(&mut self, element: &tree::Element) {
+        let markdown = self.markdown();
+        match element.tag {
+            tag if SKIPPED_ELEMENTS.contains(&tag) => {}
+            "br" => {
+                if !self.out.is_empty() {
+                    self.newlines += 1;
+                }
+            }
+            "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+                let level = (element.tag.as_bytes()[1] - b'0') as usize;
+                let heading = self.inline(&element.children);
+                self.block(2);
+                if markdown {
+                    self.push(&format!("{} {heading}", "#".repeat(level)));
+                } else if level <= 2 {
+                    let underline = if level == 1 { "=" } else { "-" };
+                    let underline = underline.repeat(heading.chars().count());
+                    self.push_lines([heading, underline]);
+                } else {
+                    self.push(&heading);
+                }
+                self.block(2);
+            }
+            "ul" | "ol" => self.list(element),
+            "li" => self.list_item("- ", &element.children),
+            "blockquote" => {
+                let quote = self.sub(&element.children).finish();
+                self.block(2);
+                self.push_lines(
+                    quote
+                        .lines()
+                        .map(|line| format!("> {line}").trim_end().to_string()),
+                );
+                self.block(2);
+            }
+            "pre" => {
+                let code = element.text();
+                let code = code.trim_matches('\n');
+                self.block(2);
+                if markdown {
+                    let fence = code_fence(code, '`', 3);
+                    self.push_lines(
+                        std::iter::once(fence.clone())
+                            .chain(code.lines().map(str::to_string))
+                            .chain(std::iter::once(fence)),
+                    );
+                } else {
+                    self.push_lines(code.lines().map(str::to_string));
+                }
+                self.block(2);
+            }
+            "table" => self.table(element),
+            "hr" => {
+                self.block(2);
+                self.push("---");
+                self.block(2);
+            }
+            "a" => {
+                let text = self.inline(&element.children);
+                let href = element.attr("href").unwrap_or_default();
+                if href.is_empty() || href.starts_with('#') {
+                    self.wrapped(&element.children, "", "");
+                } else if markdown && text.is_empty() {
+                    self.push(&format!("<{href}>"));
+                } else if markdown {
+                    let href = if href.contains([' ', '(', ')']) {
+                        format!("<{href}>")
+                    } else {
+                        href.to_string()
+                    };
+                    self.wrapped(&element.children, "[", &format!("]({href})"));
+                } else if text.is_empty() || text == href {
+                    self.push(href);
+                } else {
+                    self.wrapped(&element.children, "", &format!(" ({href})"));
+                }
+            }
+            "img" => {
+                let alt = element.attr("alt").unwrap_or_default();
+                let src = element.attr("src").unwrap_or_default();
+                if markdown {
+                    self.push(&format!("![{}]({src})", escape_markdown(alt, false)));
+                } else {
+                    self.push(alt);
+                }
+            }
+            "strong" | "b" if markdown => self.wrapped(&element.children, "**", "**"),
+            "em" | "i" if markdown => self.wrapped(&element.children, "*", "*"),
+            "s" | "del" if markdown => self.wrapped(&element.children, "~~", "~~"),
+            "code" if markdown => {
+                let code = element.text();
+                let fence = code_fence(&code, '`', 1);
+                // Code that starts or ends with a backtick needs a space between it and the fence
+                let padding = if code.starts_with('`') || code.ends_with('`') {
+                    " "
+                } else {
+                    ""
+                };
+                self.push(&format!("{fence}{padding}{code}{padding}{fence}"));
+            }
+            tag if PARAGRAPH_ELEMENTS.contains(&tag) => {
+                self.block(2);
+                self.nodes(&element.children);
+                self.block(2);
+            }
+            tag if BLOCK_ELEMENTS.contains(&tag) => {
+                self.block(1);
+                self.nodes(&element.children);
+                self.block(1);
+            }
+            _ => self.nodes(&element.children),
+        }
+    }
+
+    fn list(&mut self, list: &tree::Element) {
+        // Nested lists are not separated from their item by a blank line
+        let separation = if self.list_depth == 0 { 2 } else { 1 };
+        self.block(separation);
+        let mut number: i64 = list
+            .attr("start")
+            .and_then(|start| start.parse().ok())
+            .unwrap_or(1);
+        for child in &list.children {
+            match child {
+                Node::Element(item) if item.tag == "li" => {
+                    let marker = if list.tag == "ol" {
+                        format!("{number}. ")
+                    } else {
+                        "- ".to_string()
+                    };
+                    number += 1;
+                    self.list_item(&marker, &item.children);
+                }
+                _ => self.nodes(std::slice::from_ref(child)),
+            }
+        }
+        self.block(separation);
+    }
+
+    fn list_item(&mut self, marker: &str, children: &[Node]) {
+        let mut item = TextWriter::new(self.format, self.list_depth + 1);
+        item.nodes(children);
+        let item = item.finish();
+        let indent = " ".repeat(marker.chars().count());
+        self.block(1);
+        self.push_lines(item.lines().enumerate().map(|(index, line)| {
+            if index == 0 {
+                format!("{marker}{line}")
+            } else if line.is_empty() {
+                String::new()
+            } else {
+                format!("{indent}{line}")
+            }
+        }));
+        self.block(1);
+    }
+
+    fn table(&mut self, table: &tree::Element) {
+        let markdown = self.markdown();
+        let mut caption = String::new();
+        let mut rows = Vec::new();
+        let mut header = false;
+        collect_rows(self, table, &mut caption, &mut rows, &mut header);
+
+        self.block(2);
+        if !caption.is_empty() {
+            self.push(&caption);
+            self.block(1);
+        }
+        let columns = rows.iter().map(Vec::len).max().unwrap_or_default();
+        if columns == 0 {
+            self.block(2);
+            return;
+        }
+        for row in &mut rows {
+            row.resize(columns, String::new());
+        }
+        let widths: Vec<usize> = (0..columns)
+            .map(|column| {
+                let width = rows
+                    .iter()
+                    .map(|row| row[column].chars().count())
+                    .max()
+                    .unwrap_or_default();
+                if markdown {
+                    width.max(3)
+                } else {
+                    width
+                }
+            })
+            .collect();
+        let pad = |cell: &str, width: usize| {
+            let padding = width.saturating_sub(cell.chars().count());
+            format!("{cell}{}", " ".repeat(padding))
+        };
+        let line = |row: &[String]| {
+            let cells = row
+                .iter()
+                .zip(&widths)
+                .map(|(cell, width)| pad(cell, *width));
+            if markdown {
+                format!("| {} |", cells.collect::<Vec<_>>().join(" | "))
+            } else {
+                cells.collect::<Vec<_>>().join("  ")
+            }
+        };
+        let rule = |fill: &str| {
+            let cells = widths.iter().map(|width| fill.repeat(*width));
+            if markdown {
+                format!("| {} |", cells.collect::<Vec<_>>().join(" | "))
+            } else {
+                cells.collect::<Vec<_>>().join("  ")
+            }
+        };
+
+        // Markdown tables always have a header row
+        let mut lines = Vec::new();
+        for (index, row) in rows.iter().enumerate() {
+            lines.push(line(row));
+            if index == 0 && (markdown || header) {
+                lines.push(rule("-"));
+            }
+        }
+        self.push_lines(lines);
+        self.block(2);
+    }
+}
+
+/// Collect the caption and the text of the cells of a table, without the rows of nested tables
+fn collect_rows(
+    writer: &TextWriter,
+    element: &tree::Element,
+    caption: &mut String,
+    rows: &mut Vec<Vec<String>>,
+    header: &mut bool,
+) {
+    for child in &element.children {
+        let Node::Element(child) = child else {
+            continue;
+        };
+        match child.tag {
+            "caption" => *caption = writer.inline(&child.children),
+            "tr" => {
+                let cells: Vec<_> = child
+                    .children
+                    .iter()
+                    .filter_map(|cell| match cell {
+                        Node::Element(cell) if matches!(cell.tag, "td" | "th") => Some(cell),
+                        _ => None,
+                    })
+                    .collect();
+                // The first row is the header if it only has header cells or it is in the head of the table
+                if rows.is_empty() {
+                    *header = element.tag == "thead"
+                        || (!cells.is_empty() && cells.iter().all(|cell| cell.tag == "th"));
+                }
+                rows.push(
+                    cells
+                        .into_iter()
+                        .map(|cell| writer.inline(&cell.children))
+                        .collect(),
+                );
+            }
+            "thead" | "tbody" | "tfoot" => collect_rows(writer, child, caption, rows, header),
+            _ => {}
+        }
+    }
+}
+
+/// Escape the characters of a word that Markdown would read as markup
+fn escape_markdown(word: &str, at_line_start: bool) -> String {
+    let mut escaped = String::with_capacity(word.len());
+    // Characters that only start a heading, quote or list at the start of a line
+    if at_line_start && word.starts_with(['#', '>', '-', '+', '=']) {
+        escaped.push('\\');
+    }
+    // The `.` or `)` after the number of an ordered list item
+    let digits = word.len() - word.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+    let list_marker =
+        (at_line_start && digits > 0 && word[digits..].starts_with(['.', ')'])).then_some(digits);
+    for (index, c) in word.char_indices() {
+        // `&` would start an entity like `&amp;`
+        if matches!(
+            c,
+            '\\' | '`' | '*' | '_' | '[' | ']' | '<' | '|' | '~' | '&'
+        ) || list_marker == Some(index)
+        {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// A fence of `fill` characters that is longer than any run of them in `code`
+fn code_fence(code: &str, fill: char, min: usize) -> String {
+    let mut longest = 0;
+    let mut current = 0;
+    for c in code.chars() {
+        if c == fill {
+            current += 1;
+            longest = longest.max(current);
+        } else {
+            current = 0;
+        }
+    }
+    fill.to_string().repeat(min.max(longest + 1))
+}
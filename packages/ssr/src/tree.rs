@@ -0,0 +1,191 @@
+//! A plain tree of the elements a [`VirtualDom`] renders, for renderers that need to look at a whole element before
+//! they write it.
+//!
+//! The [`Renderer`](crate::Renderer) writes templates out of cached strings and never sees the structure of the
+//! page. The email and text renderers need to match styles against elements and lay out lists and tables, so they
+//! work on this tree instead.
+
+use dioxus_core::{prelude::*, AttributeValue, DynamicNode};
+
+use crate::renderer::{str_truthy, truthy, BOOL_ATTRS};
+
+pub(crate) enum Node {
+    Element(Element),
+    Text(String),
+}
+
+pub(crate) struct Element {
+    pub tag: &'static str,
+    /// The attributes of the element in the order they were set, except for styles and inner html
+    pub attrs: Vec<(&'static str, String)>,
+    /// The style properties set on the element in the order they were set, including the properties of the `style`
+    /// attribute
+    pub styles: Vec<(String, String)>,
+    pub inner_html: Option<String>,
+    pub children: Vec<Node>,
+}
+
+impl Element {
+    fn push_attr(&mut self, name: &'static str, namespace: Option<&str>, value: String) {
+        if name == "dangerous_inner_html" {
+            self.inner_html = Some(value);
+        } else if namespace == Some("style") {
+            self.styles.push((name.to_string(), value));
+        } else if name == "style" {
+            self.styles.extend(parse_declarations(&value));
+        } else {
+            self.attrs.push((name, value));
+        }
+    }
+
+    pub fn attr(&self, name: &str) -> Option<&str> {
+        self.attrs
+            .iter()
+            .rev()
+            .find(|(attr, _)| *attr == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    pub fn classes(&self) -> impl Iterator<Item = &str> {
+        self.attrs
+            .iter()
+            .filter(|(name, _)| *name == "class")
+            .flat_map(|(_, value)| value.split_whitespace())
+    }
+
+    /// The text of every text node in the element
+    pub fn text(&self) -> String {
+        fn collect(nodes: &[Node], text: &mut String) {
+            for node in nodes {
+                match node {
+                    Node::Text(value) => text.push_str(value),
+                    Node::Element(element) => collect(&element.children, text),
+                }
+            }
+        }
+        let mut text = String::new();
+        collect(&self.children, &mut text);
+        text
+    }
+}
+
+/// Build the tree of the elements the root scope of the dom renders
+pub(crate) fn build(dom: &VirtualDom) -> Vec<Node> {
+    let mut nodes = Vec::new();
+    let root = dom.get_scope(ScopeId::ROOT).unwrap().root_node();
+    push_vnode(dom, root, &mut nodes);
+    nodes
+}
+
+fn push_vnode(dom: &VirtualDom, vnode: &VNode, nodes: &mut Vec<Node>) {
+    for root in vnode.template.roots {
+        push_template_node(dom, vnode, root, nodes);
+    }
+}
+
+fn push_template_node(dom: &VirtualDom, vnode: &VNode, node: &TemplateNode, nodes: &mut Vec<Node>) {
+    match node {
+        TemplateNode::Element {
+            tag,
+            attrs,
+            children,
+            ..
+        } => {
+            let mut element = Element {
+                tag,
+                attrs: Vec::new(),
+                styles: Vec::new(),
+                inner_html: None,
+                children: Vec::new(),
+            };
+            for attr in *attrs {
+                match attr {
+                    TemplateAttribute::Static {
+                        name,
+                        value,
+                        namespace,
+                    } => {
+                        if !BOOL_ATTRS.contains(name) || str_truthy(value) {
+                            element.push_attr(name, *namespace, value.to_string());
+                        }
+                    }
+                    TemplateAttribute::Dynamic { id } => {
+                        for attr in &*vnode.dynamic_attrs[*id] {
+                            if BOOL_ATTRS.contains(&attr.name) && !truthy(&attr.value) {
+                                continue;
+                            }
+                            if let Some(value) = attribute_text(&attr.value) {
+                                element.push_attr(attr.name, attr.namespace, value);
+                            }
+                        }
+                    }
+                }
+            }
+            for child in *children {
+                push_template_node(dom, vnode, child, &mut element.children);
+            }
+            nodes.push(Node::Element(element));
+        }
+        TemplateNode::Text { text } => nodes.push(Node::Text(text.to_string())),
+        TemplateNode::Dynamic { id } => match &vnode.dynamic_nodes[*id] {
+            DynamicNode::Component(component) => {
+                let scope = component.mounted_scope(*id, vnode, dom).unwrap();
+                push_vnode(dom, scope.root_node(), nodes);
+            }
+            DynamicNode::Text(text) => nodes.push(Node::Text(text.value.clone())),
+            DynamicNode::Fragment(children) => {
+                for child in children {
+                    push_vnode(dom, child, nodes);
+                }
+            }
+            DynamicNode::Placeholder(_) => {}
+        },
+    }
+}
+
+fn attribute_text(value: &AttributeValue) -> Option<String> {
+    match value {
+        AttributeValue::Text(value) => Some(value.clone()),
+        AttributeValue::Bool(value) => Some(value.to_string()),
+        AttributeValue::Int(value) => Some(value.to_string()),
+        AttributeValue::Float(value) => Some(value.to_string()),
+        _ => None,
+    }
+}
+
+/// Parse the declarations of a style attribute or a css rule, like `color: red; margin: 0`
+pub(crate) fn parse_declarations(declarations: &str) -> Vec<(String, String)> {
+    split_outside_quotes(declarations, ';')
+        .into_iter()
+        .filter_map(|declaration| {
+            let (name, value) = declaration.split_once(':')?;
+            let (name, value) = (name.trim(), value.trim());
+            (!name.is_empty() && !value.is_empty())
+                .then(|| (name.to_ascii_lowercase(), value.to_string()))
+        })
+        .collect()
+}
+
+/// Split `text` at every `separator` that is not inside quotes or parentheses, like the `;` in `url("a;b")`
+pub(crate) fn split_outside_quotes(text: &str, separator: char) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut quote = None;
+    let mut depth = 0usize;
+    let mut start = 0;
+    for (index, c) in text.char_indices() {
+        match (quote, c) {
+            (Some(open), c) if c == open => quote = None,
+            (Some(_), _) => {}
+            (None, '"' | '\'') => quote = Some(c),
+            (None, '(') => depth += 1,
+            (None, ')') => depth = depth.saturating_sub(1),
+            (None, c) if c == separator && depth == 0 => {
+                parts.push(&text[start..index]);
+                start = index + c.len_utf8();
+            }
+            _ => {}
+        }
+    }
+    parts.push(&text[start..]);
+    parts
+}
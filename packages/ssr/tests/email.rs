@@ -0,0 +1,124 @@
+#![allow(non_snake_case)]
+
+use dioxus::prelude::*;
+use dioxus_ssr::EmailRenderer;
+
+#[test]
+fn style_blocks_are_inlined() {
+    let html = EmailRenderer::new().render_element(rsx! {
+        style {
+            "/* the colors of the receipt */
+            p {{ color: black; margin: 0 }}
+            .total {{ font-weight: bold }}
+            p.total {{ color: green }}
+            #footer p {{ color: gray !important }}
+            div > span {{ font-size: 12px }}"
+        }
+        p { "Your order" }
+        p { class: "total", color: "red", "Total: $10" }
+        div { id: "footer",
+            p { style: "color: blue", "Questions?" }
+            span { "Direct child" }
+            b { span { "Grandchild" } }
+        }
+    });
+    assert_eq!(
+        html,
+        concat!(
+            r#"<p style="color:black;margin:0">Your order</p>"#,
+            r#"<p class="total" style="color:red;margin:0;font-weight:bold">Total: $10</p>"#,
+            r#"<div id="footer">"#,
+            r#"<p style="color:gray;margin:0">Questions?</p>"#,
+            r#"<span style="font-size:12px">Direct child</span>"#,
+            r#"<b><span>Grandchild</span></b>"#,
+            r#"</div>"#
+        )
+    );
+}
+
+#[test]
+fn rules_that_cant_be_inlined_are_kept() {
+    let html = EmailRenderer::new().render_element(rsx! {
+        style {
+            "a, a:hover {{ color: red }}
+            @media (max-width: 600px) {{ p {{ font-size: 18px }} }}"
+        }
+        a { href: "https://example.com", "Link" }
+    });
+    assert_eq!(
+        html,
+        concat!(
+            r#"<style>a:hover{color: red}@media (max-width: 600px) { p { font-size: 18px } }</style>"#,
+            r#"<a href="https://example.com" style="color:red">Link</a>"#
+        )
+    );
+
+    // If the email has a head, the rules go there
+    let html = EmailRenderer::new().render_element(rsx! {
+        head {
+            title { "Receipt" }
+            style { "a:hover {{ color: red }}" }
+        }
+        body { "Hello" }
+    });
+    assert_eq!(
+        html,
+        "<head><style>a:hover{color: red}</style><title>Receipt</title></head><body>Hello</body>"
+    );
+}
+
+#[test]
+fn no_hydration_markers() {
+    #[component]
+    fn Item(name: String) -> Element {
+        rsx! { li { class: "item-{name}", onclick: |_| {}, "{name}" } }
+    }
+
+    let html = EmailRenderer::new().render_element(rsx! {
+        ul {
+            for name in ["a", "b"] {
+                Item { name }
+            }
+        }
+    });
+    assert_eq!(
+        html,
+        r#"<ul><li class="item-a">a</li><li class="item-b">b</li></ul>"#
+    );
+}
+
+#[test]
+fn tables_get_presentational_attributes() {
+    let html = EmailRenderer::new().render_element(rsx! {
+        style { "td.price {{ text-align: right; width: 80px }}" }
+        table { width: "100%", background_color: "#ffffff",
+            tr {
+                td { "Keyboard" }
+                td { class: "price", vertical_align: "top", "$10" }
+            }
+        }
+        table { "cellpadding": "4" }
+    });
+    assert_eq!(
+        html,
+        concat!(
+            r##"<table cellpadding="0" cellspacing="0" border="0" width="100%" bgcolor="#ffffff" style="width:100%;background-color:#ffffff">"##,
+            r#"<tr><td>Keyboard</td><td class="price" width="80" align="right" valign="top" style="text-align:right;width:80px;vertical-align:top">$10</td></tr>"#,
+            r#"</table>"#,
+            r#"<table cellpadding="4" cellspacing="0" border="0"></table>"#
+        )
+    );
+}
+
+#[test]
+fn text_and_attributes_are_escaped() {
+    let name = "<script>\"Bobby\"</script>";
+    let html = EmailRenderer::new().render_element(rsx! {
+        p { title: "{name}", "Hi {name}" }
+        br {}
+    });
+    assert_eq!(
+        html,
+        r#"<p title="&lt;script&gt;&quot;Bobby&quot;&lt;/script&gt;">Hi &lt;script&gt;&quot;Bobby&quot;&lt;/script&gt;</p><br/>"#
+    );
+}
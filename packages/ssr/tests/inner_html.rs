@@ -24,3 +24,75 @@ fn dynamic_inner_html() {
 
     assert_eq!(dioxus_ssr::render(&dom), r#"<div><div>1234</div></div>"#);
 }
+
+fn render_with_policy(policy: dioxus_ssr::InnerHtmlPolicy) -> String {
+    fn app() -> Element {
+        let comment = r#"<p onclick="steal()">Nice <a href="javascript:steal()">post</a><script>steal()</script>"#;
+        rsx! {
+            div { dangerous_inner_html: "<b>static</b><img src=x onerror=steal()>" }
+            div { dangerous_inner_html: "{comment}" }
+        }
+    }
+
+    let mut dom = VirtualDom::new(app);
+    dom.rebuild(&mut dioxus_core::NoOpMutations);
+
+    let mut renderer = dioxus_ssr::Renderer::new();
+    renderer.inner_html_policy = policy;
+    renderer.render(&dom)
+}
+
+#[test]
+fn escaped_inner_html() {
+    assert_eq!(
+        render_with_policy(dioxus_ssr::InnerHtmlPolicy::Escape),
+        concat!(
+            "<div>&lt;b&gt;static&lt;/b&gt;&lt;img src=x onerror=steal()&gt;</div>",
+            "<div>&lt;p onclick=&quot;steal()&quot;&gt;Nice &lt;a href=&quot;javascript:steal()&quot;&gt;post&lt;/a&gt;&lt;script&gt;steal()&lt;/script&gt;</div>"
+        )
+    );
+}
+
+#[test]
+fn sanitized_inner_html() {
+    assert_eq!(
+        render_with_policy(dioxus_ssr::InnerHtmlPolicy::Sanitize),
+        r#"<div><b>static</b><img src="x"></div><div><p>Nice <a>post</a></p></div>"#
+    );
+}
+
+#[test]
+fn custom_inner_html_policy() {
+    let policy = dioxus_ssr::InnerHtmlPolicy::Custom(std::sync::Arc::new(|html: &str| {
+        format!("<!-- {} bytes removed -->", html.len())
+    }));
+    assert_eq!(
+        render_with_policy(policy),
+        "<div><!-- 40 bytes removed --></div><div><!-- 87 bytes removed --></div>"
+    );
+}
+
+#[test]
+fn sanitizer_edge_cases() {
+    use dioxus_ssr::sanitize::sanitize_html;
+
+    // Unclosed elements are closed and stray end tags are removed
+    assert_eq!(
+        sanitize_html("<ul><li><em>one</li></div>"),
+        "<ul><li><em>one</em></li></ul>"
+    );
+    // Dropped elements are removed with their content, even if they are nested
+    assert_eq!(
+        sanitize_html("a<style><style></style>b{}</style>c<!-- comment -->d"),
+        "acd"
+    );
+    // Hidden schemes are caught
+    assert_eq!(
+        sanitize_html(
+            r#"<a href=" java&#x09;script:x()">1</a><a href="JAVASCRIPT:x()">2</a><a href="/page?a:b">3</a><a href="https://example.com">4</a>"#
+        ),
+        r#"<a>1</a><a>2</a><a href="/page?a:b">3</a><a href="https://example.com">4</a>"#
+    );
+    // Text that isn't a tag stays text
+    assert_eq!(sanitize_html("1 < 2 > 0"), "1 &lt; 2 &gt; 0");
+}
@@ -0,0 +1,125 @@
+#![allow(non_snake_case)]
+
+use dioxus::prelude::*;
+use dioxus_ssr::Renderer;
+
+fn minified() -> Renderer {
+    let mut renderer = Renderer::new();
+    renderer.minify = true;
+    renderer
+}
+
+#[test]
+fn large_pages_get_smaller() {
+    fn app() -> Element {
+        rsx! {
+            table {
+                for i in 0..100 {
+                    tr {
+                        td { "Row   {i}" }
+                        td {
+                            "
+                            A cell with text over
+                            several lines
+                            "
+                        }
+                        td { input { r#type: "checkbox", checked: i % 2 == 0 } }
+                    }
+                }
+            }
+        }
+    }
+
+    let mut dom = VirtualDom::new(app);
+    dom.rebuild(&mut dioxus_core::NoOpMutations);
+
+    let html = Renderer::new().render(&dom);
+    let minified = minified().render(&dom);
+    assert!(minified.len() * 2 < html.len());
+    assert!(minified.starts_with(concat!(
+        "<table><tr><td>Row 0<td> A cell with text over several lines ",
+        r#"<td><input type="checkbox" checked/></tr><tr><td>Row 1"#
+    )));
+    // The end tag of a template root is kept, because the next node isn't known when the template is cached
+    assert!(minified.ends_with(r#"<td><input type="checkbox"/></tr></table>"#));
+}
+
+#[test]
+fn end_tags_are_only_left_out_when_it_is_safe() {
+    let html = minified().render_element(rsx! {
+        div {
+            p { "Closed by the next paragraph" }
+            p { "Closed by the end of the div" }
+        }
+        a { p { "A link cannot close a paragraph" } }
+        p { "Followed by a link" }
+        a { "Link" }
+        dl {
+            dt { "Term" }
+            dd { "Description" }
+        }
+        select {
+            option { "One" }
+            option { "Two" }
+        }
+    });
+    assert_eq!(
+        html,
+        concat!(
+            "<div><p>Closed by the next paragraph<p>Closed by the end of the div</div>",
+            "<a><p>A link cannot close a paragraph</p></a>",
+            "<p>Followed by a link</p><a>Link</a>",
+            "<dl><dt>Term<dd>Description</dl>",
+            "<select><option>One<option>Two</select>"
+        )
+    );
+}
+
+#[test]
+fn whitespace_is_kept_where_it_matters() {
+    #[component]
+    fn Code(code: String) -> Element {
+        rsx! { span { class: "code", "{code}" } }
+    }
+
+    let html = minified().render_element(rsx! {
+        p { "Some   code:" }
+        pre {
+            Code { code: "fn main() {{\n    run();\n}}" }
+        }
+        textarea { "  two\n  lines" }
+        Code { code: "not   preformatted" }
+    });
+    assert_eq!(
+        html,
+        concat!(
+            "<p>Some code:</p>",
+            "<pre><span class=\"code\">fn main() {\n    run();\n}</span></pre>",
+            "<textarea>  two\n  lines</textarea>",
+            "<span class=\"code\">not preformatted</span>"
+        )
+    );
+}
+
+#[test]
+fn hydration_still_works() {
+    fn app() -> Element {
+        let count = 1;
+        rsx! {
+            ul {
+                li { onclick: |_| {}, "Count   {count}" }
+                li { "Static" }
+            }
+        }
+    }
+
+    let mut dom = VirtualDom::new(app);
+    dom.rebuild(&mut dioxus_core::NoOpMutations);
+
+    let mut renderer = minified();
+    renderer.pre_render = true;
+    assert_eq!(
+        renderer.render(&dom),
+        r#"<ul data-node-hydration="0"><li data-node-hydration="1,click:1"><!--node-id2-->Count 1<!--#--><li>Static</ul>"#
+    );
+}
@@ -0,0 +1,113 @@
+#![allow(non_snake_case)]
+
+use dioxus::prelude::*;
+use dioxus_ssr::Renderer;
+
+fn app() -> Element {
+    rsx! {
+        h1 { "Report" }
+        table {
+            for i in 0..1000 {
+                Row { i }
+            }
+        }
+        "{1000} rows"
+    }
+}
+
+#[component]
+fn Row(i: i32) -> Element {
+    rsx! {
+        tr { class: "row-{i}",
+            td { "Row {i}" }
+            if i % 2 == 0 {
+                td { "even" }
+            }
+        }
+    }
+}
+
+fn dom() -> VirtualDom {
+    let mut dom = VirtualDom::new(app);
+    dom.rebuild_in_place();
+    dom
+}
+
+#[test]
+fn chunks_match_the_full_render() {
+    let dom = dom();
+    for pre_render in [false, true] {
+        let mut renderer = Renderer::new();
+        renderer.pre_render = pre_render;
+        let full = renderer.render(&dom);
+
+        let chunks = renderer
+            .render_chunks(&dom)
+            .with_chunk_size(256)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert!(chunks.len() > 100);
+        // A chunk ends after the node that fills it, so it is never much larger than the chunk size
+        assert!(chunks.iter().all(|chunk| chunk.len() < 512));
+        assert_eq!(chunks.concat(), full);
+    }
+}
+
+#[test]
+fn chunks_are_rendered_lazily() {
+    let dom = dom();
+    let mut renderer = Renderer::new();
+    let full = renderer.render(&dom);
+
+    let mut chunks = renderer.render_chunks(&dom).with_chunk_size(64);
+    let first = chunks.next().unwrap().unwrap();
+    assert!(first.starts_with("<h1>Report</h1><table>"));
+    assert!(full.starts_with(&first));
+    assert!(first.len() < full.len() / 100);
+}
+
+#[tokio::test]
+async fn write_to_waits_for_the_reader() {
+    use tokio::io::AsyncReadExt;
+
+    let dom = dom();
+    let mut renderer = Renderer::new();
+    let full = renderer.render(&dom);
+
+    // The pipe only buffers 64 bytes, so the renderer has to wait for the reader to make progress
+    let (mut writer, mut reader) = tokio::io::duplex(64);
+    let write = async {
+        renderer
+            .render_chunks(&dom)
+            .with_chunk_size(128)
+            .write_to(&mut writer)
+            .await
+            .unwrap();
+        drop(writer);
+    };
+    let read = async {
+        let mut html = String::new();
+        reader.read_to_string(&mut html).await.unwrap();
+        html
+    };
+    let ((), html) = tokio::join!(write, read);
+    assert_eq!(html, full);
+}
+
+#[tokio::test]
+async fn into_stream_yields_every_chunk() {
+    use futures_util::TryStreamExt;
+
+    let dom = dom();
+    let mut renderer = Renderer::new();
+    let full = renderer.render(&dom);
+
+    let chunks: Vec<_> = renderer
+        .render_chunks(&dom)
+        .into_stream()
+        .try_collect()
+        .await
+        .unwrap();
+    assert!(chunks.len() > 1);
+    assert_eq!(chunks.concat(), full.as_bytes());
+}
@@ -0,0 +1,167 @@
+use dioxus::prelude::*;
+use dioxus_ssr::TextRenderer;
+
+fn receipt() -> Element {
+    let items = [("Keyboard", 1, "$10"), ("Mouse | wireless", 2, "$20")];
+    rsx! {
+        style { "p {{ color: red }}" }
+        h1 { "Receipt" }
+        p {
+            "Thanks   for your
+            order, "
+            b { "Alice" }
+            "!"
+        }
+        h3 { "Items" }
+        table {
+            thead {
+                tr {
+                    th { "Item" }
+                    th { "Quantity" }
+                    th { "Price" }
+                }
+            }
+            tbody {
+                for (name , quantity , price) in items {
+                    tr {
+                        td { "{name}" }
+                        td { "{quantity}" }
+                        td { "{price}" }
+                    }
+                }
+            }
+        }
+        p {
+            "Questions? "
+            a { href: "mailto:help@example.com", "Contact us" }
+        }
+    }
+}
+
+#[test]
+fn plain_text() {
+    let text = TextRenderer::plain_text().render_element(receipt());
+    assert_eq!(
+        text,
+        "Receipt
+=======
+
+Thanks for your order, Alice!
+
+Items
+
+Item              Quantity  Price
+----------------  --------  -----
+Keyboard          1         $10
+Mouse | wireless  2         $20
+
+Questions? Contact us (mailto:help@example.com)"
+    );
+}
+
+#[test]
+fn markdown() {
+    let text = TextRenderer::markdown().render_element(receipt());
+    assert_eq!(
+        text,
+        r"# Receipt
+
+Thanks for your order, **Alice**!
+
+### Items
+
+| Item              | Quantity | Price |
+| ----------------- | -------- | ----- |
+| Keyboard          | 1        | $10   |
+| Mouse \| wireless | 2        | $20   |
+
+Questions? [Contact us](mailto:help@example.com)"
+    );
+}
+
+#[test]
+fn nested_lists() {
+    let element = rsx! {
+        ol { start: "3",
+            li { "Three" }
+            li {
+                "Four"
+                ul {
+                    li { "Four and a half" }
+                    li { p { "A long" } p { "item" } }
+                }
+            }
+        }
+        p { "After the list" }
+    };
+    assert_eq!(
+        TextRenderer::markdown().render_element(element),
+        "3. Three
+4. Four
+   - Four and a half
+   - A long
+
+     item
+
+After the list"
+    );
+}
+
+#[test]
+fn markdown_is_escaped() {
+    let element = rsx! {
+        p { "# Not a heading with *stars* and [brackets]" }
+        p { "- not a list" }
+        p { "1. not an ordered list" }
+        p { "2) or this one, but 3. is fine" }
+        p { "Tom &amp; Jerry" }
+        p { code { "let `x` = 1;" } }
+        pre { "fn main() {{\n    println!(\"```\");\n}}" }
+        blockquote { p { "Quoted" } p { "twice" } }
+        img { alt: "Logo", src: "/logo.png" }
+    };
+    assert_eq!(
+        TextRenderer::markdown().render_element(element),
+        r#"\# Not a heading with \*stars\* and \[brackets\]
+
+\- not a list
+
+1\. not an ordered list
+
+2\) or this one, but 3. is fine
+
+Tom \&amp; Jerry
+
+``let `x` = 1;``
+
+````
+fn main() {
+    println!("```");
+}
+````
+
+> Quoted
+>
+> twice
+
+![Logo](/logo.png)"#
+    );
+}
+
+#[test]
+fn whitespace_and_line_breaks() {
+    let element = rsx! {
+        div { "  One" br {} "Two  " }
+        div {
+            span { "Three" }
+            " "
+            span { "Four" }
+        }
+        hr {}
+        "Five"
+    };
+    assert_eq!(
+        TextRenderer::plain_text().render_element(element),
+        "One\nTwo\nThree Four\n\n---\n\nFive"
+    );
+}